@@ -0,0 +1,36 @@
+//! The stable part of the library.
+//!
+//! Every module of the crate is public so the binary can be built on top of
+//! it, but most of them change as the tool does. What wallets and other front
+//! ends can depend on is re-exported in [`prelude`]:
+//!
+//! - Items in the prelude keep their names and signatures across minor and
+//!   patch releases. Removing one, or changing it in a way that breaks
+//!   callers, waits for the next major release (or minor release, while the
+//!   version is 0.x).
+//! - Items may be added to the prelude, and fields may be added to the
+//!   structs it returns, in any release.
+//! - Types from other crates that appear in prelude signatures, like
+//!   `bip39::Language`, are as stable as the versions of those crates this
+//!   one depends on.
+//! - Anything reached through another module path is not covered, and may
+//!   change in any release.
+//!
+//! The prelude is checked against `tests/snapshots/public_api__prelude.snap`
+//! by `cargo test --features public-api --test public_api`, which needs a
+//! nightly toolchain. CI also runs `cargo semver-checks` against the last
+//! release; it looks at every module, so what it finds outside the prelude
+//! only needs a note in the release, not a major version.
+
+pub mod prelude {
+    pub use crate::limits::{
+        is_valid_secret_len, is_valid_share_len, max_groups, max_members, secret_len, MAX_GROUPS,
+        MAX_MEMBERS, MAX_SECRET_LEN, METADATA_SIZE_BYTES, MIN_SECRET_LEN,
+    };
+    pub use crate::output::ShareMetadataOutput;
+    pub use crate::recover::{decode_share, recover_secret, recover_shares};
+    pub use crate::share_format::{decode, encode, EncodedShare, ShareFormat};
+    pub use crate::split::{split_raw, split_shares, ShareGroups};
+    pub use crate::validate::{validate_share, ShareDiagnostics};
+    pub use crate::verify::{verify, GroupOutcome, GroupVerification, Verification};
+}
@@ -0,0 +1,323 @@
+use crate::hash::HashAlgorithm;
+use crate::hint::hint_cards;
+use crate::manifest::{ArtifactHash, Manifest, SeedManifest};
+use crate::naming::{ensure_unique_names, NameTemplate, ShareName};
+use crate::policy::{self, Channel, Field};
+use crate::recover::recover_secret;
+use crate::share_format::decode;
+use crate::sskr_shares::share_metadata;
+use anyhow::{bail, Context, Error};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+pub struct Artifact {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WriteOutcome {
+    Written,
+    Verified,
+}
+
+// What to do when the output directory already holds shares
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExistingShares {
+    Refuse,
+    // Check that the shares already there are this split's layout and
+    // recover this split's secret, instead of writing anything
+    Verify,
+    // Delete every file holding a share first, whichever split it is from
+    Replace,
+}
+
+// One artifact per share, named by the template and filled in by `contents`
+// from the (zero-based) group and share numbers and the share itself
+pub fn share_artifacts(
+    dir: &Path,
+    seed: &SeedManifest,
+    groups: &[Vec<String>],
+    name_template: &NameTemplate,
+    contents: impl Fn(usize, usize, &str) -> String,
+) -> Result<Vec<Artifact>, Error> {
+    let mut artifacts = vec![];
+    let mut names = vec![];
+
+    for (group_num, group) in groups.iter().enumerate() {
+        for (share_num, share) in group.iter().enumerate() {
+            let name = name_template.render(&ShareName {
+                seed,
+                group: group_num + 1,
+                member: share_num + 1,
+                members: group.len(),
+            })?;
+            artifacts.push(Artifact {
+                path: dir.join(format!("{}.txt", name)),
+                contents: contents(group_num, share_num, share),
+            });
+            names.push(name);
+        }
+    }
+
+    ensure_unique_names(&names)?;
+    Ok(artifacts)
+}
+
+pub fn paths(artifacts: &[Artifact]) -> Vec<PathBuf> {
+    artifacts
+        .iter()
+        .map(|artifact| artifact.path.clone())
+        .collect()
+}
+
+// Writes the artifacts of one split into `dir`. A directory that already holds
+// shares is never written to, so two different splits can't end up mixed
+// together; the existing shares can instead be checked against this split,
+// which splitting again can't reproduce byte for byte (every split has its
+// own identifier and randomness), but which they must match in layout and
+// in the secret they recover.
+pub fn write_artifacts(
+    dir: &Path,
+    identifier: &str,
+    artifacts: &[Artifact],
+    existing: ExistingShares,
+) -> Result<WriteOutcome, Error> {
+    let found = existing_shares(dir)?;
+    // Checking shares already written writes nothing
+    if existing == ExistingShares::Verify && !found.is_empty() {
+        verify_existing(dir, &found, artifacts)?;
+        return Ok(WriteOutcome::Verified);
+    }
+    policy::check(Field::Shares, Channel::Disk)?;
+
+    if existing == ExistingShares::Replace {
+        for path in found.keys() {
+            fs::remove_file(path)
+                .with_context(|| format!("Unable to delete \"{}\"", path.display()))?;
+        }
+    } else if !found.is_empty() {
+        if let Some(other) = found.values().flatten().find(|id| *id != identifier) {
+            bail!(
+                "Directory \"{}\" already contains shares from a different split (identifier {}), use an empty directory",
+                dir.display(),
+                other
+            );
+        }
+        bail!(
+            "Directory \"{}\" already contains shares for identifier {}, pass --verify-existing to check them instead",
+            dir.display(),
+            identifier
+        );
+    }
+
+    for artifact in artifacts {
+        if let Some(parent) = artifact.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Unable to create directory \"{}\"", parent.display()))?;
+        }
+        fs::write(&artifact.path, &artifact.contents)
+            .with_context(|| format!("Unable to write file \"{}\"", artifact.path.display()))?;
+    }
+
+    Ok(WriteOutcome::Written)
+}
+
+// The shares in `found` must all be from one split, hold a share for every
+// place the artifacts do and none other, and recover the same secret
+fn verify_existing(
+    dir: &Path,
+    found: &BTreeMap<PathBuf, BTreeSet<String>>,
+    artifacts: &[Artifact],
+) -> Result<(), Error> {
+    let identifiers: BTreeSet<&String> = found.values().flatten().collect();
+    if identifiers.len() > 1 {
+        bail!(
+            "Directory \"{}\" holds shares from more than one split (identifiers {})",
+            dir.display(),
+            identifiers
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let mut existing = Zeroizing::new(vec![]);
+    for path in found.keys() {
+        let contents = Zeroizing::new(
+            fs::read_to_string(path)
+                .with_context(|| format!("Unable to read \"{}\"", path.display()))?,
+        );
+        existing.extend(share_lines(&contents));
+    }
+    let expected: Zeroizing<Vec<String>> = Zeroizing::new(
+        artifacts
+            .iter()
+            .flat_map(|artifact| share_lines(&artifact.contents))
+            .collect(),
+    );
+
+    if layout(&existing)? != layout(&expected)? {
+        bail!(
+            "The shares in \"{}\" are not laid out as this split is (groups, thresholds or shares differ)",
+            dir.display()
+        );
+    }
+    let secret = recover_secret(existing.to_vec(), &false).with_context(|| {
+        format!(
+            "The shares in \"{}\" don't combine to recover a secret",
+            dir.display()
+        )
+    })?;
+    if secret != recover_secret(expected.to_vec(), &false)? {
+        bail!(
+            "The shares in \"{}\" recover a different secret than this split",
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+// The lines of `contents` that are shares
+fn share_lines(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .filter(|line| decode(line).is_ok())
+        .map(str::to_string)
+        .collect()
+}
+
+// The place of each share (group and member, with the thresholds and counts),
+// whichever split it is from
+fn layout(lines: &[String]) -> Result<BTreeSet<[usize; 5]>, Error> {
+    lines
+        .iter()
+        .map(|line| Ok(share_metadata(&decode(line)?.1, &false)?.1))
+        .collect()
+}
+
+pub struct ShareFiles {
+    pub manifest: PathBuf,
+    pub shares: Vec<PathBuf>,
+    pub hint_cards: Vec<PathBuf>,
+    pub outcome: WriteOutcome,
+}
+
+// Each share on its own in a file, ready to be handed to a custodian, plus a
+// manifest describing the split and, when asked for, a hint card for each
+// share under hints/, named as the share is
+pub fn write_share_files(
+    dir: &Path,
+    seed: &SeedManifest,
+    groups: &[Vec<String>],
+    name_template: &NameTemplate,
+    existing: ExistingShares,
+    algorithm: HashAlgorithm,
+    with_hint_cards: bool,
+) -> Result<ShareFiles, Error> {
+    let shares = share_artifacts(dir, seed, groups, name_template, |_, _, share| {
+        format!("{}\n", share)
+    })?;
+    let hints = if with_hint_cards {
+        let cards = hint_cards(seed);
+        share_artifacts(
+            &dir.join("hints"),
+            seed,
+            groups,
+            name_template,
+            |group_num, share_num, _| cards[group_num][share_num].render(),
+        )?
+    } else {
+        vec![]
+    };
+    let mut written = artifact_hashes(dir, &shares, algorithm)?;
+    written.extend(artifact_hashes(dir, &hints, algorithm)?);
+    let recorded = SeedManifest {
+        artifacts: written,
+        ..seed.clone()
+    };
+    let manifest = Artifact {
+        path: dir.join("manifest.json"),
+        contents: Manifest::new(recorded).to_json()? + "\n",
+    };
+
+    let (share_paths, hint_paths) = (paths(&shares), paths(&hints));
+    let manifest_path = manifest.path.clone();
+    let mut artifacts = vec![manifest];
+    artifacts.extend(shares);
+    artifacts.extend(hints);
+    let outcome = write_artifacts(dir, &seed.identifier, &artifacts, existing)?;
+
+    Ok(ShareFiles {
+        manifest: manifest_path,
+        shares: share_paths,
+        hint_cards: hint_paths,
+        outcome,
+    })
+}
+
+// The hashes of the artifacts, by their paths relative to `dir`
+pub fn artifact_hashes(
+    dir: &Path,
+    artifacts: &[Artifact],
+    algorithm: HashAlgorithm,
+) -> Result<Vec<ArtifactHash>, Error> {
+    artifacts
+        .iter()
+        .map(|artifact| {
+            let path = artifact.path.strip_prefix(dir).with_context(|| {
+                format!(
+                    "\"{}\" is not inside \"{}\"",
+                    artifact.path.display(),
+                    dir.display()
+                )
+            })?;
+            Ok(ArtifactHash {
+                path: path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                hash: algorithm.hash(artifact.contents.as_bytes()),
+            })
+        })
+        .collect()
+}
+
+// Every file anywhere below `dir` that holds shares, with the identifiers of
+// the shares in it (one share per line)
+pub fn existing_shares(dir: &Path) -> Result<BTreeMap<PathBuf, BTreeSet<String>>, Error> {
+    let mut shares = BTreeMap::new();
+
+    if !dir.is_dir() {
+        return Ok(shares);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            shares.extend(existing_shares(&path)?);
+            continue;
+        }
+
+        // Anything that isn't text can't hold a share
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let identifiers: BTreeSet<String> = contents
+            .lines()
+            .filter_map(|line| decode(line).ok())
+            .filter_map(|(_format, share)| share_metadata(&share, &false).ok())
+            .map(|(id, _meta)| format!("{:04x}", id))
+            .collect();
+        if !identifiers.is_empty() {
+            shares.insert(path, identifiers);
+        }
+    }
+
+    Ok(shares)
+}
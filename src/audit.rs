@@ -0,0 +1,146 @@
+use crate::input::ShareLine;
+use crate::manifest::{Manifest, SeedManifest};
+use crate::output::ShareMetadataOutput;
+use crate::recover::decode_share;
+use crate::seed_ur::format_date;
+use anyhow::Error;
+use serde::Serialize;
+use std::fmt;
+use zeroize::Zeroizing;
+
+const SECONDS_PER_DAY: u64 = 86400;
+
+// What the manifest says about a share presented years after the split. The
+// commitments (share checks) are salted digests of every share the split
+// made, so a share that matches one is exactly as the split made it, and no
+// other shares are needed to tell.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    // Matches a commitment of the manifest
+    Genuine,
+    // Claims to be from a split in the manifest but matches none of its
+    // commitments, or doesn't fit its groups: altered, or not from that split
+    Tampered,
+    // Fits a split in the manifest, which has no commitments to check it by
+    Unchecked,
+    // From no split the manifest describes
+    Unknown,
+    Unreadable,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::Genuine => "genuine",
+            Verdict::Tampered => "tampered",
+            Verdict::Unchecked => "unchecked",
+            Verdict::Unknown => "unknown",
+            Verdict::Unreadable => "unreadable",
+        })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ShareAudit {
+    pub location: String,
+    pub verdict: Verdict,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    // Numbered from 1, like the groups
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<usize>,
+    // When the split was made, as a Unix time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+}
+
+impl ShareAudit {
+    pub fn is_genuine(&self) -> bool {
+        self.verdict == Verdict::Genuine
+    }
+}
+
+impl fmt::Display for ShareAudit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.verdict)?;
+        match (&self.seed, &self.identifier) {
+            (Some(seed), _) => write!(f, "\n  {}", seed)?,
+            (None, Some(identifier)) => write!(f, "\n  Identifier {}", identifier)?,
+            (None, None) => {}
+        }
+        if let (Some(group), Some(member)) = (&self.group, self.member) {
+            write!(f, "\n  {}, share {}", group, member)?;
+        }
+        if let Some(created) = self.created {
+            write!(
+                f,
+                "\n  Split on {}",
+                format_date((created / SECONDS_PER_DAY) as i64)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn audit(manifest: &Manifest, lines: &[ShareLine]) -> Result<Vec<ShareAudit>, Error> {
+    lines
+        .iter()
+        .map(|line| audit_share(manifest, line))
+        .collect()
+}
+
+fn audit_share(manifest: &Manifest, line: &ShareLine) -> Result<ShareAudit, Error> {
+    let mut audit = ShareAudit {
+        location: line.to_string(),
+        verdict: Verdict::Unreadable,
+        identifier: None,
+        seed: None,
+        group: None,
+        member: None,
+        created: None,
+    };
+    let Ok(share) = decode_share(&line.text).map(Zeroizing::new) else {
+        return Ok(audit);
+    };
+    let Ok(metadata) = ShareMetadataOutput::new(&share) else {
+        return Ok(audit);
+    };
+    audit.identifier = Some(metadata.identifier.clone());
+
+    let Some(seed) = manifest
+        .seeds
+        .iter()
+        .find(|seed| seed.identifier == metadata.identifier)
+    else {
+        audit.verdict = Verdict::Unknown;
+        return Ok(audit);
+    };
+    audit.seed = Some(seed.title());
+    audit.group = Some(seed.group_title(metadata.group_index + 1));
+    audit.member = Some(metadata.member_index + 1);
+    audit.created = seed.created;
+
+    audit.verdict = match &seed.share_checks {
+        Some(checks) if checks.matches(&share)? => Verdict::Genuine,
+        Some(_) => Verdict::Tampered,
+        None if fits(seed, &metadata) => Verdict::Unchecked,
+        None => Verdict::Tampered,
+    };
+    Ok(audit)
+}
+
+// Whether the share's group and member are ones the split made
+fn fits(seed: &SeedManifest, metadata: &ShareMetadataOutput) -> bool {
+    metadata.group_threshold == seed.group_threshold
+        && metadata.group_count == seed.groups.len()
+        && seed.groups.iter().any(|group| {
+            group.group == metadata.group_index + 1
+                && group.member_threshold == metadata.member_threshold
+                && metadata.member_index < group.member_count
+        })
+}
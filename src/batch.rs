@@ -0,0 +1,130 @@
+use crate::split::{default_group_threshold, parse_spec};
+use anyhow::{anyhow, bail, Context, Error};
+use bip39::MnemonicType;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+// A batch file lists the seeds to split in one run, each as a [[seed]] table:
+//
+//     [[seed]]
+//     name = "Alice"
+//     spec = "family=2of3,lawyers=1of2"
+//     group_threshold = 1
+//     out_dir = "alice"
+//
+// A seed without a mnemonic is generated, `count` of them if given, each in
+// its own numbered output directory.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Batch {
+    #[serde(rename = "seed")]
+    pub seeds: Vec<BatchSeed>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BatchSeed {
+    pub name: Option<String>,
+    pub purpose: Option<String>,
+    pub spec: String,
+    pub group_threshold: Option<usize>,
+    pub mnemonic: Option<String>,
+    pub words: Option<usize>,
+    pub count: Option<usize>,
+    // Relative to the directory of the batch file
+    pub out_dir: String,
+}
+
+// One share set to make
+pub struct BatchJob {
+    pub name: Option<String>,
+    pub purpose: Option<String>,
+    pub spec: String,
+    pub group_threshold: usize,
+    pub mnemonic: Option<Zeroizing<String>>,
+    pub words: MnemonicType,
+    pub out_dir: PathBuf,
+}
+
+impl Batch {
+    pub fn load(path: &Path) -> Result<Batch, Error> {
+        let contents = Zeroizing::new(
+            fs::read_to_string(path)
+                .with_context(|| format!("Unable to read batch file \"{}\"", path.display()))?,
+        );
+        toml::from_str(&contents)
+            .with_context(|| format!("Invalid batch file \"{}\"", path.display()))
+    }
+
+    // Every share set the batch asks for, checked as a whole before any of
+    // them is made
+    pub fn jobs(self, base: &Path) -> Result<Vec<BatchJob>, Error> {
+        if self.seeds.is_empty() {
+            bail!("The batch lists no seeds");
+        }
+        let mut jobs = vec![];
+
+        for (index, seed) in self.seeds.into_iter().enumerate() {
+            let which = match &seed.name {
+                Some(name) => format!("seed \"{}\"", name),
+                None => format!("seed {}", index + 1),
+            };
+            let group_threshold = match seed.group_threshold {
+                Some(threshold) => threshold,
+                None => default_group_threshold(&seed.spec).with_context(|| which.clone())?,
+            };
+            parse_spec(&seed.spec, group_threshold).with_context(|| which.clone())?;
+            let words = match seed.words {
+                Some(words) => MnemonicType::for_word_count(words)
+                    .map_err(|_| anyhow!("{}: mnemonics have 12, 15, 18, 21 or 24 words", which))?,
+                None => MnemonicType::Words12,
+            };
+
+            let count = seed.count.unwrap_or(1);
+            match (&seed.mnemonic, seed.count) {
+                (Some(_), Some(_)) => bail!("{}: a mnemonic is given, so count can't be", which),
+                (Some(_), _) if seed.words.is_some() => {
+                    bail!("{}: a mnemonic is given, so words can't be", which)
+                }
+                (None, Some(0)) => bail!("{}: count has to be at least 1", which),
+                _ => {}
+            }
+
+            for number in 1..=count {
+                let (name, out_dir) = match seed.count {
+                    Some(_) => (
+                        seed.name
+                            .as_ref()
+                            .map(|name| format!("{} {}", name, number)),
+                        format!("{}-{}", seed.out_dir, number),
+                    ),
+                    None => (seed.name.clone(), seed.out_dir.clone()),
+                };
+                jobs.push(BatchJob {
+                    name,
+                    purpose: seed.purpose.clone(),
+                    spec: seed.spec.clone(),
+                    group_threshold,
+                    mnemonic: seed.mnemonic.clone().map(Zeroizing::new),
+                    words,
+                    out_dir: base.join(out_dir),
+                });
+            }
+        }
+
+        for (index, job) in jobs.iter().enumerate() {
+            if jobs[..index]
+                .iter()
+                .any(|other| other.out_dir == job.out_dir)
+            {
+                bail!(
+                    "Two seeds would be written to \"{}\"",
+                    job.out_dir.display()
+                );
+            }
+        }
+        Ok(jobs)
+    }
+}
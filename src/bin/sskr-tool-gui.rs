@@ -0,0 +1,7 @@
+// The graphical recovery front end, built with `--features gui`
+fn main() {
+    if let Err(error) = sskr_tool::gui::run() {
+        eprintln!("{:#}", error);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,11 @@
+// The custodians' checking tool, built with `--features verify-only`
+fn main() {
+    match sskr_tool::verify_only::run(std::env::args_os()) {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(error) => {
+            eprintln!("{:#}", error);
+            std::process::exit(sskr_tool::error::exit_code(&error));
+        }
+    }
+}
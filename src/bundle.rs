@@ -0,0 +1,183 @@
+use crate::hash::{Hash, HashAlgorithm};
+use crate::manifest::Manifest;
+use crate::output::ShareMetadataOutput;
+use crate::policy::{self, Channel, Field};
+use crate::recover::decode_share;
+use crate::split::share_position;
+use anyhow::{bail, Context, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const BUNDLE_FORMAT: &str = "sskr-tool archival bundle v1";
+pub const SSKR_SPEC: &str = "BCR-2020-011 Sharded Secret Key Reconstruction (SSKR), https://github.com/BlockchainCommons/Research/blob/master/papers/bcr-2020-011-sskr.md";
+
+// Everything someone recovering the secret decades from now needs besides the
+// shares themselves: which software to get, how to check they got the right
+// release, and where the format is specified in case they have to write their
+// own. The bundle is sealed by a hash over all of its other fields, made with
+// the same algorithm as the hash of the release.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bundle {
+    pub format: String,
+    pub created: u64,
+    pub software: Software,
+    pub spec: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<Manifest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shares: Vec<String>,
+    #[serde(flatten)]
+    pub seal: Hash,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Software {
+    pub name: String,
+    pub version: String,
+    // The file of the release that was hashed, e.g. the downloaded archive
+    pub file: String,
+    #[serde(flatten)]
+    pub hash: Hash,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BundleVerification {
+    pub identifiers: Vec<String>,
+    pub shares: usize,
+    pub software: Software,
+    // Whether the release given to compare against is the recorded one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_matches: Option<bool>,
+}
+
+impl Bundle {
+    pub fn new(
+        manifest: Option<Manifest>,
+        shares: Vec<String>,
+        release: &Path,
+        algorithm: HashAlgorithm,
+    ) -> Result<Bundle, Error> {
+        if manifest.is_none() && shares.is_empty() {
+            bail!("A bundle needs shares, a manifest or both");
+        }
+        let mut bundle = Bundle {
+            format: BUNDLE_FORMAT.to_string(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            software: Software {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                file: release
+                    .file_name()
+                    .map_or_else(String::new, |name| name.to_string_lossy().to_string()),
+                hash: hash_file(release, algorithm)?,
+            },
+            spec: SSKR_SPEC.to_string(),
+            manifest,
+            shares,
+            seal: algorithm.unset(),
+        };
+        bundle.check_contents()?;
+        // Shares are kept in group order whatever order they were given in,
+        // so the same shares always make the same bundle
+        bundle.shares.sort_by_cached_key(|share| {
+            decode_share(share)
+                .and_then(|share| share_position(&share))
+                .ok()
+        });
+        bundle.seal = bundle.content_hash()?;
+        Ok(bundle)
+    }
+
+    pub fn load(path: &Path) -> Result<Bundle, Error> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read bundle \"{}\"", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("\"{}\" is not an archival bundle", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if !self.shares.is_empty() {
+            policy::check(Field::Shares, Channel::Disk)?;
+        }
+        if path.exists() {
+            bail!(
+                "\"{}\" already exists, refusing to overwrite it",
+                path.display()
+            );
+        }
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Unable to write bundle \"{}\"", path.display()))
+    }
+
+    // Checks the seal and that the shares and manifest still go together, and
+    // with a release at hand, whether it is the one recorded
+    pub fn verify(&self, release: Option<&Path>) -> Result<BundleVerification, Error> {
+        if self.format != BUNDLE_FORMAT {
+            bail!("Unsupported bundle format \"{}\"", self.format);
+        }
+        if self.content_hash()? != self.seal {
+            bail!("The bundle doesn't match its hash, it was edited or has rotted");
+        }
+        let identifiers = self.check_contents()?;
+
+        Ok(BundleVerification {
+            identifiers: identifiers.into_iter().collect(),
+            shares: self.shares.len(),
+            software: self.software.clone(),
+            release_matches: match release {
+                Some(release) => {
+                    Some(hash_file(release, self.software.hash.algorithm())? == self.software.hash)
+                }
+                None => None,
+            },
+        })
+    }
+
+    fn content_hash(&self) -> Result<Hash, Error> {
+        let algorithm = self.seal.algorithm();
+        let unsealed = Bundle {
+            seal: algorithm.unset(),
+            ..self.clone()
+        };
+        Ok(algorithm.hash(serde_json::to_string(&unsealed)?.as_bytes()))
+    }
+
+    // Every share has to read, and the manifest has to describe their splits
+    fn check_contents(&self) -> Result<BTreeSet<String>, Error> {
+        let mut identifiers = BTreeSet::new();
+        for (share_num, share) in self.shares.iter().enumerate() {
+            let metadata = decode_share(share)
+                .and_then(|share| ShareMetadataOutput::new(&share))
+                .with_context(|| format!("Share {} of the bundle is invalid", share_num + 1))?;
+            identifiers.insert(metadata.identifier);
+        }
+
+        if let Some(manifest) = &self.manifest {
+            let described = manifest
+                .seeds
+                .iter()
+                .map(|seed| seed.identifier.clone())
+                .collect::<BTreeSet<_>>();
+            if let Some(identifier) = identifiers.difference(&described).next() {
+                bail!(
+                    "The manifest doesn't describe shares with identifier {}",
+                    identifier
+                );
+            }
+            identifiers.extend(described);
+        }
+
+        Ok(identifiers)
+    }
+}
+
+fn hash_file(path: &Path, algorithm: HashAlgorithm) -> Result<Hash, Error> {
+    let contents =
+        fs::read(path).with_context(|| format!("Unable to read \"{}\"", path.display()))?;
+    Ok(algorithm.hash(&contents))
+}
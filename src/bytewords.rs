@@ -1,10 +1,11 @@
-use anyhow::{anyhow, Error};
+use crate::error::SskrToolError;
+use anyhow::{bail, Error};
+use clap::ValueEnum;
 use crc::{Crc, CRC_32_ISO_HDLC};
-use lazy_static::lazy_static;
-use std::collections::HashMap;
+use serde::Serialize;
 
 #[rustfmt::skip]
-static WORDS: &'static str =
+const WORDS: &str =
     "ableacidalsoapexaquaarchatomauntawayaxisbackbaldbarnbeltbetabias\
      bluebodybragbrewbulbbuzzcalmcashcatschefcityclawcodecolacookcost\
      cruxcurlcuspcyandarkdatadaysdelidicedietdoordowndrawdropdrumdull\
@@ -22,115 +23,334 @@ static WORDS: &'static str =
      vastveryvetovialvibeviewvisavoidvowswallwandwarmwaspwavewaxywebs\
      whatwhenwhizwolfworkyankyawnyellyogayurtzapszerozestzinczonezoom";
 
-lazy_static! {
-    static ref WORD_TO_INDEX_LOOKUP: HashMap<&'static str, u8> = {
-        let mut lookup = HashMap::new();
-        for i in 0..=255 {
-            lookup.insert(index_to_byteword(i), i);
+// No two bytewords share both their first and last letter, so those two
+// letters are enough to find a word's index. The table is built at compile
+// time, indexed by the letters' places in the alphabet, with NOT_A_WORD where
+// no byteword has the pair.
+const NOT_A_WORD: u16 = 256;
+const INDEX_BY_LETTERS: [u16; 26 * 26] = index_by_letters();
+
+const fn index_by_letters() -> [u16; 26 * 26] {
+    let words = WORDS.as_bytes();
+    let mut lookup = [NOT_A_WORD; 26 * 26];
+    let mut i = 0;
+    while i < 256 {
+        let slot = (words[i * 4] - b'a') as usize * 26 + (words[i * 4 + 3] - b'a') as usize;
+        assert!(
+            lookup[slot] == NOT_A_WORD,
+            "two bytewords with the same first and last letters"
+        );
+        lookup[slot] = i as u16;
+        i += 1;
+    }
+    lookup
+}
+
+const CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+// The three ways the Bytewords spec writes words
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BytewordStyle {
+    /// Whole words separated by spaces
+    Standard,
+    /// Whole words separated by hyphens, as in URIs
+    Uri,
+    /// The first and last letter of each word, not separated
+    Minimal,
+}
+
+impl BytewordStyle {
+    pub fn from_minimal(minimal: &bool) -> BytewordStyle {
+        if *minimal {
+            BytewordStyle::Minimal
+        } else {
+            BytewordStyle::Standard
         }
-        lookup
-    };
+    }
 
-    static ref MINIMAL_WORD_TO_WORD_LOOKUP: HashMap<String, &'static str> = {
-        let mut lookup = HashMap::new();
-        for i in 0..=255 {
-            let word = index_to_byteword(i);
-            let minimal = byteword_to_minimal_string(word);
-            lookup.insert(minimal, word);
+    pub fn separator(&self) -> &'static str {
+        match self {
+            BytewordStyle::Standard => " ",
+            BytewordStyle::Uri => "-",
+            BytewordStyle::Minimal => "",
         }
-        lookup
-    };
+    }
 }
 
 fn index_to_byteword(i: u8) -> &'static str {
     let begin: usize = (i as u16 * 4) as usize;
-    let end: usize = (begin + 4) as usize;
+    let end: usize = begin + 4;
     &WORDS[begin..end]
 }
 
-fn byteword_to_index(word: &str) -> u8 {
-    WORD_TO_INDEX_LOOKUP[word]
+// The index of the byteword with this first and last letter, in either case
+fn index_by_minimal(first: char, last: char) -> Option<u8> {
+    let (first, last) = (first.to_ascii_lowercase(), last.to_ascii_lowercase());
+    if !first.is_ascii_lowercase() || !last.is_ascii_lowercase() {
+        return None;
+    }
+    match INDEX_BY_LETTERS[(first as u8 - b'a') as usize * 26 + (last as u8 - b'a') as usize] {
+        NOT_A_WORD => None,
+        index => Some(index as u8),
+    }
 }
 
-fn byteword_checksum(bytes: &[u8]) -> [u8; 4] {
-    Crc::<u32>::new(&CRC_32_ISO_HDLC)
-        .checksum(bytes)
-        .to_be_bytes()
+pub fn byteword_to_index(word: &str) -> Option<u8> {
+    let mut chars = word.chars();
+    let (first, last) = (chars.next()?, chars.next_back()?);
+    index_by_minimal(first, last)
+        .filter(|index| index_to_byteword(*index).eq_ignore_ascii_case(word))
 }
 
-fn byteword_minimal_string_to_byteword(input: &str) -> Result<Vec<&str>, Error> {
-    let chars = input.chars().collect::<Vec<char>>();
-    let chunks= chars
-        .chunks(2)
-        .map(|x| x.iter().collect::<String>());
+pub fn is_byteword(word: &str) -> bool {
+    byteword_to_index(word).is_some()
+}
 
-    let words = chunks.map(|x| {
-        match MINIMAL_WORD_TO_WORD_LOOKUP.get(&x) {
-            Some(word) => Ok(*word),
-            None => return Err(anyhow!("Not a valid byteword: \"{}\"", x)),
-        }
-    }).collect();
+pub fn byteword_checksum(bytes: &[u8]) -> [u8; 4] {
+    CRC.checksum(bytes).to_be_bytes()
+}
 
-    words
+// Minimal bytewords, with whitespace allowed anywhere in them
+fn minimal_string_to_bytes(input: &str) -> Result<Vec<u8>, Error> {
+    let mut letters = input.chars().filter(|c| !c.is_whitespace());
+    let mut bytes = Vec::with_capacity(input.len() / 2);
+    while let Some(first) = letters.next() {
+        let last = letters.next();
+        match last.and_then(|last| index_by_minimal(first, last)) {
+            Some(index) => bytes.push(index),
+            None => {
+                let word = first.to_string() + &last.map(String::from).unwrap_or_default();
+                return Err(SskrToolError::InvalidByteword {
+                    word,
+                    closest: vec![],
+                }
+                .into());
+            }
+        }
+    }
+    Ok(bytes)
 }
 
-fn byteword_to_minimal_string(word: &str) -> String {
-    let first = word.chars().next().unwrap();
-    let last = word.chars().last().unwrap();
-    format!("{}{}", first, last)
+fn words_to_bytes(input: &str) -> Result<Vec<u8>, Error> {
+    split_words(input)
+        .map(|word| {
+            byteword_to_index(word).ok_or_else(|| {
+                SskrToolError::InvalidByteword {
+                    word: word.to_string(),
+                    closest: closest_bytewords(&word.to_ascii_lowercase())
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                }
+                .into()
+            })
+        })
+        .collect()
 }
 
-pub fn byteword_string(bytes: &[u8], minimal: &bool) -> String {
+pub fn byteword_string(bytes: &[u8], style: BytewordStyle) -> String {
     let checksum = byteword_checksum(bytes);
     let data_with_checksum = [bytes, &checksum].concat();
-    byteword_string_no_checksum(&data_with_checksum, minimal)
+    byteword_string_no_checksum(&data_with_checksum, style)
 }
 
-pub fn byteword_string_no_checksum(bytes: &[u8], minimal: &bool) -> String {
-    let words = bytes
-        .iter()
-        .map(|i| {
-            let btw = index_to_byteword(*i);
-            if *minimal {
-                byteword_to_minimal_string(btw)
-            } else {
-                btw.to_string()
-            }
-        })
-        .collect::<Vec<String>>();
+pub fn byteword_string_no_checksum(bytes: &[u8], style: BytewordStyle) -> String {
+    let mut string = String::with_capacity(bytes.len() * 5);
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            string.push_str(style.separator());
+        }
+        let word = index_to_byteword(*byte);
+        if style == BytewordStyle::Minimal {
+            // Bytewords are four ASCII letters, minimal ones the first and last
+            string.push_str(&word[..1]);
+            string.push_str(&word[3..]);
+        } else {
+            string.push_str(word);
+        }
+    }
+    string
+}
+
+pub fn byteword_string_to_bytes(input: &str, style: BytewordStyle) -> Result<Vec<u8>, Error> {
+    let (bytes, checksum_ok) = byteword_string_to_bytes_unchecked(input, style)?;
+    if !checksum_ok {
+        return Err(SskrToolError::InvalidChecksum {
+            line: input.to_string(),
+        }
+        .into());
+    }
+    Ok(bytes)
+}
 
-    if *minimal {
-        words.join("")
+// Parses the words and splits off the checksum, reporting whether it matches
+// instead of failing. Words transcribed from paper come in any case and with
+// any run of spaces or tabs between them, so neither matters.
+pub fn byteword_string_to_bytes_unchecked(
+    input: &str,
+    style: BytewordStyle,
+) -> Result<(Vec<u8>, bool), Error> {
+    let mut bytes = if style == BytewordStyle::Minimal {
+        minimal_string_to_bytes(input)?
     } else {
-        words.join(" ")
+        words_to_bytes(input)?
+    };
+    if bytes.len() < 5 {
+        return Err(SskrToolError::TooShort {
+            line: input.to_string(),
+        }
+        .into());
     }
+    let checksum_start = bytes.len() - 4;
+    let checksum_ok = bytes[checksum_start..] == byteword_checksum(&bytes[..checksum_start]);
+    bytes.truncate(checksum_start);
+    Ok((bytes, checksum_ok))
 }
 
-pub fn byteword_string_to_bytes(input: &str, minimal: &bool) -> Result<Vec<u8>, Error> {
-    let words: Vec<&str> = if *minimal {
-        byteword_minimal_string_to_byteword(input)?
+// Whole words can be separated by spaces, hyphens or line breaks, whatever
+// the style, as printing templates and engravings differ
+pub fn split_words(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|word| !word.is_empty())
+}
+
+// Whether every word of the input is a byteword, however few there are
+pub fn is_byteword_string(input: &str, style: BytewordStyle) -> bool {
+    if style == BytewordStyle::Minimal {
+        !input.trim().is_empty() && minimal_string_to_bytes(input).is_ok()
     } else {
-        input.split(" ").collect()
+        let mut words = split_words(input).peekable();
+        words.peek().is_some() && words.all(is_byteword)
+    }
+}
+
+// Words further than this from every byteword are not guessed at
+const MAX_CORRECTION_DISTANCE: usize = 2;
+// Limit on the combinations of guesses tried when several words are unreadable
+const MAX_CORRECTIONS_TRIED: usize = 4096;
+
+// Edit distance counting a swap of neighbouring letters as one edit, which is
+// how handwritten words are most often misread or miscopied
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+// The bytewords closest to a word that isn't one, if any are close enough
+pub fn closest_bytewords(word: &str) -> Vec<&'static str> {
+    let distances = (0..=255)
+        .map(|i| {
+            (
+                edit_distance(word, index_to_byteword(i)),
+                index_to_byteword(i),
+            )
+        })
+        .collect::<Vec<(usize, &str)>>();
+    let best = distances
+        .iter()
+        .map(|(distance, _)| *distance)
+        .min()
+        .unwrap();
+    if best > MAX_CORRECTION_DISTANCE {
+        return vec![];
+    }
+    distances
+        .into_iter()
+        .filter(|(distance, _)| *distance == best)
+        .map(|(_, word)| word)
+        .collect()
+}
+
+// A word as read and the byteword it was corrected to
+pub type Correction = (String, &'static str);
+
+// Replaces every word of a (non-minimal) byteword string that isn't a byteword
+// by one of its closest bytewords. Only a correction that is the single one to
+// pass the checksum is returned, written out in the given style, along with the
+// (word, replacement) pairs.
+pub fn correct_byteword_string(
+    input: &str,
+    style: BytewordStyle,
+) -> Result<(String, Vec<Correction>), Error> {
+    let separator = match style {
+        BytewordStyle::Standard => " ",
+        BytewordStyle::Uri => "-",
+        BytewordStyle::Minimal => bail!("Minimal bytewords can't be corrected word by word"),
     };
+    let words: Vec<&str> = split_words(input).collect();
+    let mut guesses: Vec<(usize, Vec<&'static str>)> = vec![];
+    let mut combinations: usize = 1;
 
-    for word in words.clone().into_iter() {
-        if !WORD_TO_INDEX_LOOKUP.contains_key(word) {
-            return Err(anyhow!("Not a valid byteword: \"{}\"", word));
+    for (i, word) in words.iter().enumerate() {
+        if is_byteword(word) {
+            continue;
         }
+        let closest = closest_bytewords(word);
+        if closest.is_empty() {
+            return Err(SskrToolError::UncorrectableByteword {
+                word: word.to_string(),
+            }
+            .into());
+        }
+        combinations = combinations.saturating_mul(closest.len());
+        guesses.push((i, closest));
+    }
+    if combinations > MAX_CORRECTIONS_TRIED {
+        return Err(SskrToolError::TooManyCorrections {
+            line: input.to_string(),
+        }
+        .into());
     }
-    let all_bytes = words.into_iter().map(byteword_to_index).collect::<Vec<u8>>();
-    if all_bytes.len() < 5 {
-        return Err(anyhow!(
-            "Byteword string too short (must include checksum): \"{}\"",
-            input
-        ));
-    }
-    let (bytes, checksum) = all_bytes.split_at(all_bytes.len() - 4);
-    if checksum != byteword_checksum(bytes) {
-        return Err(anyhow!(
-            "Invalid checksum (last 4 words) for byteword string \"{}\"",
-            input
-        ));
-    }
-    Ok(bytes.to_vec())
-}
\ No newline at end of file
+
+    let mut passing = vec![];
+    for n in 0..combinations {
+        let mut corrected = words.clone();
+        let mut corrections = vec![];
+        let mut choice = n;
+        for (i, closest) in &guesses {
+            let replacement = closest[choice % closest.len()];
+            corrected[*i] = replacement;
+            corrections.push((words[*i].to_string(), replacement));
+            choice /= closest.len();
+        }
+        let corrected = corrected.join(separator);
+        if let Ok((_, true)) = byteword_string_to_bytes_unchecked(&corrected, style) {
+            passing.push((corrected, corrections));
+        }
+    }
+
+    match passing.len() {
+        0 => Err(SskrToolError::NoCorrection {
+            line: input.to_string(),
+        }
+        .into()),
+        1 => Ok(passing.remove(0)),
+        ways => Err(SskrToolError::AmbiguousCorrection {
+            line: input.to_string(),
+            ways,
+        }
+        .into()),
+    }
+}
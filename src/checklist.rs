@@ -0,0 +1,160 @@
+use anyhow::{bail, Context, Error};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// A ceremony checklist has a step per line, confirmed one after another
+// before the ceremony starts, so every ceremony follows the same written
+// procedure:
+//
+//   # Before anyone sees a share
+//   Check the room has no cameras and the laptop no network
+//   Confirm each custodian's identity against their ID
+//
+// Blank lines and `#` comments are skipped.
+#[derive(Debug, Clone)]
+pub struct Checklist {
+    steps: Vec<Step>,
+    current: usize,
+    step_started: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    text: String,
+    took: Option<Duration>,
+}
+
+impl Checklist {
+    pub fn load(path: &Path) -> Result<Checklist, Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read checklist \"{}\"", path.display()))?;
+        Checklist::parse(&contents)
+            .with_context(|| format!("Invalid checklist \"{}\"", path.display()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Checklist, Error> {
+        let steps = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|text| Step {
+                text: text.to_string(),
+                took: None,
+            })
+            .collect::<Vec<_>>();
+        if steps.is_empty() {
+            bail!("The checklist has no steps");
+        }
+        Ok(Checklist {
+            steps,
+            current: 0,
+            step_started: Instant::now(),
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current == self.steps.len()
+    }
+
+    // Confirms the current step, timing it from when the one before it was
+    pub fn confirm(&mut self) {
+        if let Some(step) = self.steps.get_mut(self.current) {
+            step.took = Some(self.step_started.elapsed());
+            self.current += 1;
+            self.step_started = Instant::now();
+        }
+    }
+
+    // A line per step, ticked with the time it took once confirmed, and with
+    // the time so far for the current one
+    pub fn lines(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let (mark, took) = match step.took {
+                    Some(took) => ("[x]", format!("  {}", elapsed(took))),
+                    None if index == self.current => {
+                        ("[ ]", format!("  {}", elapsed(self.step_started.elapsed())))
+                    }
+                    None => ("[ ]", String::new()),
+                };
+                format!(
+                    "{} {} {}{}",
+                    if index == self.current { ">" } else { " " },
+                    mark,
+                    step.text,
+                    took
+                )
+            })
+            .collect()
+    }
+
+    pub fn checked(&self) -> Vec<CheckedStep> {
+        self.steps
+            .iter()
+            .filter_map(|step| {
+                step.took.map(|took| CheckedStep {
+                    step: step.text.clone(),
+                    seconds: took.as_secs(),
+                })
+            })
+            .collect()
+    }
+}
+
+// "4:05", or "1:04:05" past an hour
+pub fn elapsed(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CheckedStep {
+    pub step: String,
+    pub seconds: u64,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    // Every share was handed out
+    Split,
+    Recovered,
+    // Quit before either
+    Abandoned,
+}
+
+// What the audit log keeps of a ceremony: when it ran, how it ended and the
+// checklist steps confirmed, but nothing of the secret or the shares
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub started: u64,
+    pub finished: u64,
+    pub outcome: Outcome,
+    pub checklist: Vec<CheckedStep>,
+}
+
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+// Appends the record to the audit log as a line of JSON, creating the log if
+// needed
+pub fn append_audit(path: &Path, record: &AuditRecord) -> Result<(), Error> {
+    let line = format!("{}\n", serde_json::to_string(record)?);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Unable to append to audit log \"{}\"", path.display()))
+}
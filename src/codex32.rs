@@ -0,0 +1,369 @@
+use anyhow::{bail, ensure, Error};
+use rand::Rng;
+use zeroize::Zeroizing;
+
+// Codex32 (BIP-93) strings, for exchanging secrets with codex32 backups: the
+// secret on its own as "ms10...", or split k of n into shares "ms1k..." that
+// codex32 recovers by hand with its volvelles. The shares are Shamir shares
+// of their own, over GF(32) rather than SSKR's GF(256), so SSKR shares can't
+// be converted to codex32 shares one by one; only the secret goes between
+// the two.
+
+pub const HRP: &str = "ms";
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+// The index of the share that is the secret itself
+const SECRET_INDEX: char = 's';
+// The indices shares are given, in order, after the secret's
+const SHARE_INDICES: &str = "acdefghjklmnpqrtuvwxyz023456789";
+const IDENTIFIER_LENGTH: usize = 4;
+const CHECKSUM_LENGTH: usize = 13;
+// Longer strings need the long checksum, which isn't supported
+const MAX_DATA_LENGTH: usize = 93;
+pub const MIN_SECRET_LEN: usize = 16;
+const MAX_THRESHOLD: usize = 9;
+
+// Copied from BIP-93
+const GENERATOR: [u128; 5] = [
+    0x19dc500ce73fde210,
+    0x1bfae00def77fe529,
+    0x1fbd920fffe7bee52,
+    0x1739640bdeee3fdad,
+    0x07729a039cfc75f5a,
+];
+const RESIDUE: u128 = 0x10ce0795c2fd1e62a;
+
+// A codex32 string taken apart, its data as values from 0 to 31
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    // 0 for a secret that isn't split
+    pub threshold: usize,
+    pub identifier: String,
+    pub index: char,
+    // The data after the "ms1" and before the checksum
+    data: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    // The bytes of the payload, the padding bits at the end dropped
+    pub fn payload(&self) -> Zeroizing<Vec<u8>> {
+        let values = &self.data[IDENTIFIER_LENGTH + 2..];
+        let mut bytes = Zeroizing::new(Vec::with_capacity(values.len() * 5 / 8));
+        let (mut accumulator, mut bits) = (0u32, 0);
+        for value in values.iter() {
+            accumulator = (accumulator << 5) | *value as u32;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                bytes.push((accumulator >> bits) as u8);
+                accumulator &= (1 << bits) - 1;
+            }
+        }
+        bytes
+    }
+
+    fn encode(&self) -> String {
+        let mut data = self.data.to_vec();
+        data.extend(checksum(&self.data));
+        let encoded: String = data
+            .iter()
+            .map(|value| CHARSET[*value as usize] as char)
+            .collect();
+        format!("{}1{}", HRP, encoded)
+    }
+}
+
+pub fn is_codex32(line: &str) -> bool {
+    line.trim().to_lowercase().starts_with("ms1")
+}
+
+// Reads a codex32 string, in lower or upper case but not both
+pub fn parse(line: &str) -> Result<Share, Error> {
+    let line = line.trim();
+    ensure!(
+        line == line.to_lowercase() || line == line.to_uppercase(),
+        "A codex32 string is in lower or upper case, not both"
+    );
+    let line = line.to_lowercase();
+    let Some(encoded) = line.strip_prefix("ms1") else {
+        bail!("A codex32 string starts with \"ms1\"");
+    };
+    let mut data = Zeroizing::new(vec![]);
+    for (position, c) in encoded.chars().enumerate() {
+        let Some(value) = CHARSET.iter().position(|b| *b as char == c) else {
+            bail!(
+                "Character {} of the codex32 string, \"{}\", isn't a bech32 character",
+                position + 4,
+                c
+            );
+        };
+        data.push(value as u8);
+    }
+    ensure!(
+        data.len() <= MAX_DATA_LENGTH,
+        "The codex32 string is too long, long codex32 strings aren't supported"
+    );
+    ensure!(
+        data.len() >= IDENTIFIER_LENGTH + 2 + CHECKSUM_LENGTH + MIN_SECRET_LEN * 8 / 5,
+        "The codex32 string is too short"
+    );
+    ensure!(
+        polymod(&data) == RESIDUE,
+        "The codex32 checksum doesn't match"
+    );
+
+    let characters: Vec<char> = encoded.chars().collect();
+    let threshold = match characters[0] {
+        '0' => 0,
+        c @ '2'..='9' => c as usize - '0' as usize,
+        c => bail!("Invalid codex32 threshold \"{}\"", c),
+    };
+    let index = characters[IDENTIFIER_LENGTH + 1];
+    ensure!(
+        threshold != 0 || index == SECRET_INDEX,
+        "A codex32 secret with threshold 0 must have share index \"s\""
+    );
+    let length = data.len() - CHECKSUM_LENGTH;
+    data.truncate(length);
+    Ok(Share {
+        threshold,
+        identifier: characters[1..=IDENTIFIER_LENGTH].iter().collect(),
+        index,
+        data,
+    })
+}
+
+// The secret on its own, as codex32 writes a secret that isn't split
+pub fn encode_secret(secret: &[u8], identifier: &str) -> Result<String, Error> {
+    Ok(secret_share(secret, 0, identifier)?.encode())
+}
+
+// Splits the secret k of n the way BIP-93 does: the first k - 1 shares are
+// random, and the others are interpolated from them and the secret
+pub fn split(
+    secret: &[u8],
+    threshold: usize,
+    count: usize,
+    identifier: &str,
+) -> Result<Vec<String>, Error> {
+    ensure!(
+        (2..=MAX_THRESHOLD).contains(&threshold),
+        "A codex32 threshold is from 2 to {}",
+        MAX_THRESHOLD
+    );
+    ensure!(
+        (threshold..=SHARE_INDICES.len()).contains(&count),
+        "Codex32 makes from the threshold to {} shares",
+        SHARE_INDICES.len()
+    );
+    let secret = secret_share(secret, threshold, identifier)?;
+
+    let mut rng = rand::thread_rng();
+    let mut known = vec![secret.clone()];
+    for index in SHARE_INDICES.chars().take(threshold - 1) {
+        let mut data = secret.data.clone();
+        data[IDENTIFIER_LENGTH + 1] = value(index);
+        for value in data[IDENTIFIER_LENGTH + 2..].iter_mut() {
+            *value = rng.gen_range(0..32);
+        }
+        known.push(Share {
+            index,
+            data,
+            ..secret.clone()
+        });
+    }
+
+    let shares = SHARE_INDICES
+        .chars()
+        .take(count)
+        .map(
+            |index| match known.iter().find(|share| share.index == index) {
+                Some(share) => share.encode(),
+                None => interpolate(&known, index).encode(),
+            },
+        )
+        .collect();
+    Ok(shares)
+}
+
+// The secret and its identifier from enough shares, or from the secret's own
+// string
+pub fn recover(lines: &[String]) -> Result<(Zeroizing<Vec<u8>>, String), Error> {
+    let mut shares: Vec<Share> = vec![];
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        ensure!(
+            is_codex32(line),
+            "Line {} isn't a codex32 string, codex32 and SSKR shares can't be combined",
+            number + 1
+        );
+        let share = parse(line)?;
+        if let Some(first) = shares.first() {
+            ensure!(
+                share.identifier == first.identifier
+                    && share.threshold == first.threshold
+                    && share.data.len() == first.data.len(),
+                "Codex32 shares \"{}\" and \"{}\" are from different splits",
+                first.identifier,
+                share.identifier
+            );
+        }
+        match shares.iter().find(|other| other.index == share.index) {
+            Some(other) if *other == share => continue,
+            Some(_) => bail!(
+                "Conflicting codex32 shares: two different shares have index \"{}\"",
+                share.index
+            ),
+            None => shares.push(share),
+        }
+    }
+
+    let Some(first) = shares.first() else {
+        bail!("No shares to recover from");
+    };
+    let identifier = first.identifier.clone();
+    if let Some(secret) = shares.iter().find(|share| share.index == SECRET_INDEX) {
+        return Ok((secret.payload(), identifier));
+    }
+    let threshold = first.threshold;
+    ensure!(
+        shares.len() >= threshold,
+        "Not enough codex32 shares, need {} but only {} are present",
+        threshold,
+        shares.len()
+    );
+    Ok((
+        interpolate(&shares[..threshold], SECRET_INDEX).payload(),
+        identifier,
+    ))
+}
+
+// A new identifier, four random bech32 characters
+pub fn random_identifier() -> String {
+    let mut rng = rand::thread_rng();
+    (0..IDENTIFIER_LENGTH)
+        .map(|_| CHARSET[rng.gen_range(0..32)] as char)
+        .collect()
+}
+
+fn secret_share(secret: &[u8], threshold: usize, identifier: &str) -> Result<Share, Error> {
+    ensure!(
+        secret.len() >= MIN_SECRET_LEN,
+        "Codex32 holds secrets of at least {} bytes",
+        MIN_SECRET_LEN
+    );
+    let identifier = identifier.to_lowercase();
+    ensure!(
+        identifier.len() == IDENTIFIER_LENGTH && identifier.bytes().all(|c| CHARSET.contains(&c)),
+        "A codex32 identifier is {} bech32 characters",
+        IDENTIFIER_LENGTH
+    );
+
+    let mut data = Zeroizing::new(vec![value(
+        char::from_digit(threshold as u32, 10).unwrap_or('0'),
+    )]);
+    data.extend(identifier.chars().map(value));
+    data.push(value(SECRET_INDEX));
+    // The bytes as 5 bit values, the last one padded with zero bits
+    let (mut accumulator, mut bits) = (0u32, 0);
+    for byte in secret {
+        accumulator = (accumulator << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            data.push(((accumulator >> bits) & 31) as u8);
+        }
+        accumulator &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        data.push(((accumulator << (5 - bits)) & 31) as u8);
+    }
+    ensure!(
+        data.len() + CHECKSUM_LENGTH <= MAX_DATA_LENGTH,
+        "The secret is too long for a codex32 string"
+    );
+    Ok(Share {
+        threshold,
+        identifier,
+        index: SECRET_INDEX,
+        data,
+    })
+}
+
+// The share at `index` through the given shares, value by value. The checksum
+// is linear, so the shares' checksums interpolate to the new share's.
+fn interpolate(shares: &[Share], index: char) -> Share {
+    let x = value(index);
+    let mut data = Zeroizing::new(vec![0u8; shares[0].data.len()]);
+    for (i, share) in shares.iter().enumerate() {
+        let xi = value(share.index);
+        let mut basis = 1;
+        for (j, other) in shares.iter().enumerate() {
+            if i != j {
+                let xj = value(other.index);
+                basis = multiply(basis, multiply(x ^ xj, inverse(xi ^ xj)));
+            }
+        }
+        for (result, value) in data.iter_mut().zip(share.data.iter()) {
+            *result ^= multiply(basis, *value);
+        }
+    }
+    Share {
+        index,
+        data,
+        ..shares[0].clone()
+    }
+}
+
+fn value(c: char) -> u8 {
+    CHARSET.iter().position(|b| *b as char == c).unwrap_or(0) as u8
+}
+
+fn polymod(values: &[u8]) -> u128 {
+    let mut residue: u128 = 0x23181b3;
+    for value in values {
+        let top = residue >> 60;
+        residue = ((residue & 0x0fffffffffffffff) << 5) ^ *value as u128;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                residue ^= generator;
+            }
+        }
+    }
+    residue
+}
+
+fn checksum(data: &[u8]) -> Vec<u8> {
+    let mut values = data.to_vec();
+    values.extend([0; CHECKSUM_LENGTH]);
+    let residue = polymod(&values) ^ RESIDUE;
+    (0..CHECKSUM_LENGTH)
+        .map(|i| ((residue >> (5 * (CHECKSUM_LENGTH - 1 - i))) & 31) as u8)
+        .collect()
+}
+
+// Multiplication in GF(32), modulo x^5 + x^3 + 1 like bech32
+fn multiply(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a <<= 1;
+        if a & 32 != 0 {
+            a ^= 0b101001;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+// a^30, which is 1/a for every a but 0
+fn inverse(a: u8) -> u8 {
+    let mut result = 1;
+    for _ in 0..30 {
+        result = multiply(result, a);
+    }
+    result
+}
@@ -0,0 +1,38 @@
+use anyhow::{bail, Context, Error};
+use std::io::{BufRead, Write};
+
+pub const DEFAULT_CONFIRMATION_WORD: &str = "confirm";
+
+// Shortest confirmation word accepted, anything shorter is as easy to type by
+// accident as "y"
+const MIN_WORD_LENGTH: usize = 4;
+
+// Destructive actions only go ahead once the confirmation word has been typed
+// exactly, so a stray "y" or Enter during a ceremony can't trigger them
+pub fn confirm(
+    action: &str,
+    word: &str,
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<(), Error> {
+    if word.trim().chars().count() < MIN_WORD_LENGTH {
+        bail!(
+            "Confirmation word \"{}\" is too short, use at least {} characters",
+            word,
+            MIN_WORD_LENGTH
+        );
+    }
+
+    write!(prompt, "{}\nType \"{}\" to continue: ", action, word.trim())?;
+    prompt.flush()?;
+
+    let mut answer = String::new();
+    input
+        .read_line(&mut answer)
+        .context("Unable to read confirmation")?;
+
+    if answer.trim() != word.trim() {
+        bail!("Not confirmed, nothing was changed");
+    }
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use crate::bytewords::{byteword_string_no_checksum, is_byteword, BytewordStyle};
+use crate::sanitize::sanitize;
+use crate::share_format::{checksum_matches, ShareFormat};
+use crate::status::SetStatus;
+use serde::Serialize;
+
+// What stands between a set of shares and the secret, with shares given by
+// their position among the shares at hand
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum Problem {
+    // Words that aren't bytewords, by position from 1
+    NotBytewords {
+        share: usize,
+        words: Vec<usize>,
+    },
+    // Every word is a byteword but the checksum doesn't match. The suspects
+    // are the words that, replaced by another byteword, make it match.
+    ChecksumMismatch {
+        share: usize,
+        suspects: Vec<usize>,
+    },
+    Unreadable {
+        share: usize,
+    },
+    OtherSet {
+        share: usize,
+    },
+    // Same identifier, but thresholds that don't match the other shares
+    OtherSplit {
+        share: usize,
+    },
+    Conflict {
+        share: usize,
+        other: usize,
+        group: usize,
+        member: usize,
+    },
+    // Unsatisfied groups as (group, shares missing), the threshold of a group
+    // none of whose shares are at hand being unknown
+    NotEnoughShares {
+        groups_missing: usize,
+        groups: Vec<(usize, Option<usize>)>,
+    },
+}
+
+// A line that couldn't be decoded, looked at closely enough to say where it
+// went wrong
+pub fn unreadable(share: usize, line: &str) -> Problem {
+    let cleaned = sanitize(line).to_lowercase();
+    let format = ShareFormat::detect(&cleaned);
+    let (words, style) = match format {
+        ShareFormat::Bytewords => (
+            cleaned.split(' ').map(str::to_string).collect(),
+            BytewordStyle::Standard,
+        ),
+        ShareFormat::Hyphenated => (
+            cleaned.split('-').map(str::to_string).collect(),
+            BytewordStyle::Uri,
+        ),
+        ShareFormat::Minimal => (
+            cleaned
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().collect())
+                .collect::<Vec<String>>(),
+            BytewordStyle::Minimal,
+        ),
+        _ => return Problem::Unreadable { share },
+    };
+
+    let readable = |word: &str| match style {
+        BytewordStyle::Minimal => {
+            (0..=255).any(|byte| byteword_string_no_checksum(&[byte], style) == word)
+        }
+        _ => is_byteword(word),
+    };
+    let not_bytewords: Vec<usize> = words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| !readable(word))
+        .map(|(position, _)| position + 1)
+        .collect();
+    // Mostly words that aren't bytewords, it isn't a share in bytewords at all
+    if not_bytewords.len() * 2 > words.len() {
+        return Problem::Unreadable { share };
+    }
+    if !not_bytewords.is_empty() {
+        return Problem::NotBytewords {
+            share,
+            words: not_bytewords,
+        };
+    }
+    if checksum_matches(&cleaned, format).unwrap_or(true) {
+        return Problem::Unreadable { share };
+    }
+
+    let separator = match style {
+        BytewordStyle::Standard => " ",
+        BytewordStyle::Uri => "-",
+        BytewordStyle::Minimal => "",
+    };
+    let suspects = (0..words.len())
+        .filter(|&position| {
+            (0..=255).any(|byte| {
+                let mut candidate = words.clone();
+                candidate[position] = byteword_string_no_checksum(&[byte], style);
+                candidate[position] != words[position]
+                    && checksum_matches(&candidate.join(separator), format).unwrap_or(false)
+            })
+        })
+        .map(|position| position + 1)
+        .collect();
+    Problem::ChecksumMismatch { share, suspects }
+}
+
+// Why a set of shares can't be recovered yet, if it can't
+pub fn shortfall(set: &SetStatus) -> Option<Problem> {
+    let groups_missing = set.groups_missing.filter(|missing| *missing > 0)?;
+    let mut groups: Vec<(usize, Option<usize>)> = set
+        .groups
+        .iter()
+        .filter(|group| !group.satisfied)
+        .map(|group| (group.group, group.missing))
+        .collect();
+    // The closest groups first, those of unknown threshold last
+    groups.sort_by_key(|(group, missing)| (missing.unwrap_or(usize::MAX), *group));
+    Some(Problem::NotEnoughShares {
+        groups_missing,
+        groups,
+    })
+}
+
+// The next steps for a problem, `names` naming the shares at hand, as "share
+// 2" or "the share on shares.txt:3"
+pub fn suggestions(problem: &Problem, names: &[String]) -> Vec<String> {
+    let name = |share: &usize| {
+        names
+            .get(*share)
+            .cloned()
+            .unwrap_or_else(|| format!("share {}", share + 1))
+    };
+    match problem {
+        Problem::NotBytewords { share, words } => vec![format!(
+            "Re-check {} {} of {}, {} a byteword",
+            plural(words.len(), "word", "words"),
+            list(words),
+            name(share),
+            plural(words.len(), "it isn't", "they aren't")
+        )],
+        Problem::ChecksumMismatch { share, suspects } => match &suspects[..] {
+            [word] => vec![format!(
+                "Re-check word {} of {}, the checksum matches with another word there",
+                word,
+                name(share)
+            )],
+            _ => vec![format!(
+                "Re-check {} against its copy word by word, more than one word is off",
+                name(share)
+            )],
+        },
+        Problem::Unreadable { share } => vec![format!(
+            "Check {} against its copy, it can't be read as bytewords, ur:sskr or hex",
+            name(share)
+        )],
+        Problem::OtherSet { share } => vec![format!(
+            "Set {} aside, it is from another share set",
+            name(share)
+        )],
+        Problem::OtherSplit { share } => vec![format!(
+            "Set {} aside, it is from another split of the same secret",
+            name(share)
+        )],
+        Problem::Conflict {
+            share,
+            other,
+            group,
+            member,
+        } => vec![format!(
+            "Check {} and {} against their copies, both claim to be share {} of group {}",
+            name(other),
+            name(share),
+            member,
+            group
+        )],
+        Problem::NotEnoughShares {
+            groups_missing,
+            groups,
+        } => {
+            let (needed, alternatives) = groups.split_at((*groups_missing).min(groups.len()));
+            let mut suggestions: Vec<String> = needed
+                .iter()
+                .map(|(group, missing)| collect(*group, *missing))
+                .collect();
+            suggestions.extend(alternatives.iter().map(|(group, missing)| {
+                format!("Or instead, {}", uncapitalized(&collect(*group, *missing)))
+            }));
+            suggestions
+        }
+    }
+}
+
+fn collect(group: usize, missing: Option<usize>) -> String {
+    match missing {
+        Some(missing) => format!(
+            "Collect {} more {} from group {}",
+            missing,
+            plural(missing, "share", "shares"),
+            group
+        ),
+        None => format!(
+            "Collect shares from group {}, the first one tells how many it needs",
+            group
+        ),
+    }
+}
+
+fn uncapitalized(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn plural<'a>(count: usize, one: &'a str, many: &'a str) -> &'a str {
+    if count == 1 {
+        one
+    } else {
+        many
+    }
+}
+
+fn list(numbers: &[usize]) -> String {
+    match numbers {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [rest @ .., last] => format!(
+            "{} and {}",
+            rest.iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            last
+        ),
+    }
+}
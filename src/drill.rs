@@ -0,0 +1,310 @@
+use crate::bytewords::{byteword_to_index, closest_bytewords, is_byteword};
+use crate::hash::HashAlgorithm;
+use crate::limits::METADATA_SIZE_BYTES;
+use crate::output::ShareMetadataOutput;
+use crate::share_format::{bytewords_header_len, decode_as, ShareFormat};
+use crate::sskr_shares::share_metadata;
+use anyhow::{anyhow, bail, Context, Error};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use zeroize::Zeroizing;
+
+// Shares have at least 29 words (a 16 byte secret) and at most 46 (32 bytes)
+const MIN_WORDS: usize = 29;
+const MAX_WORDS: usize = 46;
+// Tries at a word that isn't a byteword before the drill gives up
+const MAX_TRIES: usize = 3;
+// The four words of a share's checksum
+const CHECKSUM_WORDS: usize = 4;
+// Words of each share the manifest keeps a check of on their own
+const DRILL_WORDS: usize = 4;
+
+// A salted digest of every share of a split, kept in the manifest so a
+// custodian can check their copy of a share against it without any other
+// share around. Shares are random well beyond brute force, so the digests
+// reveal nothing about them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ShareChecks {
+    pub salt: String,
+    pub digests: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_default")]
+    pub algorithm: HashAlgorithm,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<WordChecks>,
+}
+
+// Checks of a few words of one share, picked out by the words that name the
+// share (its identifier and place in the split), so a drill can ask for just
+// those words. A word on its own is one of 256, which its check gives away,
+// so the words checked are spread over a group's shares such that no place is
+// checked on as many shares as it takes to recover the group: the checks then
+// tell nothing about the secret. Checking no more than a few words of a share
+// also leaves it far beyond brute force for its digest above.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WordChecks {
+    pub identifier: String,
+    pub group: usize,
+    pub member: usize,
+    pub checks: Vec<WordCheck>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WordCheck {
+    // Counted from 1, as the words are printed
+    pub word: usize,
+    pub digest: String,
+}
+
+impl ShareChecks {
+    pub fn new(groups: &[Vec<Vec<u8>>], algorithm: HashAlgorithm) -> ShareChecks {
+        let salt = rand::random::<[u8; 16]>();
+        ShareChecks {
+            salt: hex::encode(salt),
+            digests: groups
+                .iter()
+                .flatten()
+                .map(|share| digest(algorithm, &salt, share))
+                .collect(),
+            algorithm,
+            words: word_checks(groups, algorithm, &salt),
+        }
+    }
+
+    pub fn matches(&self, share: &[u8]) -> Result<bool, Error> {
+        let salt = hex::decode(&self.salt).context("Invalid share check salt")?;
+        Ok(self.digests.contains(&digest(self.algorithm, &salt, share)))
+    }
+}
+
+fn digest(algorithm: HashAlgorithm, salt: &[u8], share: &[u8]) -> String {
+    let salted = Zeroizing::new([salt, share].concat());
+    algorithm.hash(&salted).hex().to_string()
+}
+
+// A word's check covers the share's metadata and where the word is, so the
+// same word in two places or two shares checks differently
+fn word_digest(
+    algorithm: HashAlgorithm,
+    salt: &[u8],
+    metadata: &[u8],
+    word: usize,
+    byte: u8,
+) -> String {
+    digest(algorithm, salt, &[metadata, &[word as u8, byte]].concat())
+}
+
+fn word_checks(groups: &[Vec<Vec<u8>>], algorithm: HashAlgorithm, salt: &[u8]) -> Vec<WordChecks> {
+    let mut rng = rand::thread_rng();
+    let mut word_checks = vec![];
+    for group in groups {
+        // How many of the group's shares each place is checked on
+        let mut checked = vec![0; group.iter().map(Vec::len).max().unwrap_or(0)];
+        for share in group {
+            let Ok((identifier, meta)) = share_metadata(share, &false) else {
+                continue;
+            };
+            let mut places = (METADATA_SIZE_BYTES..share.len())
+                .filter(|place| checked[*place] + 1 < meta[4])
+                .collect::<Vec<_>>();
+            places.shuffle(&mut rng);
+            places.truncate(DRILL_WORDS);
+            if places.is_empty() {
+                continue;
+            }
+            places.sort_unstable();
+
+            let header = bytewords_header_len(share.len());
+            let metadata = &share[..METADATA_SIZE_BYTES];
+            word_checks.push(WordChecks {
+                identifier: format!("{:04x}", identifier),
+                group: meta[0] + 1,
+                member: meta[3] + 1,
+                checks: places
+                    .into_iter()
+                    .map(|place| {
+                        checked[place] += 1;
+                        let word = header + place + 1;
+                        WordCheck {
+                            word,
+                            digest: word_digest(algorithm, salt, metadata, word, share[place]),
+                        }
+                    })
+                    .collect(),
+            });
+        }
+    }
+    word_checks
+}
+
+#[derive(Serialize, Debug)]
+pub struct DrillResult {
+    #[serde(flatten)]
+    pub metadata: ShareMetadataOutput,
+    // Whether the share is one the manifest has checks for; None without checks
+    pub matches_manifest: Option<bool>,
+    // The words asked on their own that don't match their checks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mismatched_words: Vec<usize>,
+}
+
+// Quizzes a custodian on the words of their share. The words naming the share
+// come first; when the manifest checks words of that share on their own, only
+// those are asked, in an order of `shuffle`'s choosing, and each is checked
+// against its check. Otherwise every other word is asked in `shuffle`'s
+// order, so the whole copy is read rather than recited, and the words are put
+// back in place and checked against the share's own checksum, then against
+// the checks of the manifest, if any.
+pub fn run(
+    checks: &[&ShareChecks],
+    shuffle: impl FnOnce(&mut [usize]),
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<DrillResult, Error> {
+    let count = read_line("How many words does your share have? ", input, prompt)?;
+    let Ok(count) = count.trim().parse::<usize>() else {
+        bail!("\"{}\" is not a number of words", count.trim());
+    };
+    if !(MIN_WORDS..=MAX_WORDS).contains(&count) {
+        bail!(
+            "Shares have {} to {} words, not {}",
+            MIN_WORDS,
+            MAX_WORDS,
+            count
+        );
+    }
+
+    // The words up to the end of the metadata name the share
+    let share_len = (0..count)
+        .find(|len| len + bytewords_header_len(*len) + CHECKSUM_WORDS == count)
+        .unwrap_or(count);
+    let naming = (bytewords_header_len(share_len) + METADATA_SIZE_BYTES).min(count);
+    let mut words = Zeroizing::new(vec![String::new(); count]);
+    for position in 0..naming {
+        words[position] = read_word(position + 1, input, prompt)?;
+    }
+    let metadata = words[naming - METADATA_SIZE_BYTES..naming]
+        .iter()
+        .filter_map(|word| byteword_to_index(word))
+        .collect::<Vec<u8>>();
+    if let Ok(named) = ShareMetadataOutput::new(&metadata) {
+        let found = checks.iter().find_map(|checks| {
+            checks
+                .words
+                .iter()
+                .find(|words| {
+                    words.identifier == named.identifier
+                        && words.group == named.group_index + 1
+                        && words.member == named.member_index + 1
+                })
+                .map(|words| (checks, words))
+        });
+        if let Some((checks, words)) = found {
+            let mismatched_words = quiz(checks, words, &metadata, shuffle, input, prompt)?;
+            return Ok(DrillResult {
+                metadata: ShareMetadataOutput {
+                    secret_length: share_len.saturating_sub(METADATA_SIZE_BYTES),
+                    ..named
+                },
+                matches_manifest: Some(mismatched_words.is_empty()),
+                mismatched_words,
+            });
+        }
+    }
+
+    let mut order = (naming..count).collect::<Vec<_>>();
+    shuffle(&mut order);
+    for position in order {
+        words[position] = read_word(position + 1, input, prompt)?;
+    }
+
+    let words = Zeroizing::new(words.join(" "));
+    let share = Zeroizing::new(decode_as(&words, ShareFormat::Bytewords).map_err(|_| {
+        anyhow!("The words typed don't make up a valid share, check them against your copy")
+    })?);
+    let metadata = ShareMetadataOutput::new(&share)?;
+
+    let matches_manifest = if checks.is_empty() {
+        None
+    } else {
+        let mut matches = false;
+        for checks in checks {
+            matches |= checks.matches(&share)?;
+        }
+        Some(matches)
+    };
+    Ok(DrillResult {
+        metadata,
+        matches_manifest,
+        mismatched_words: vec![],
+    })
+}
+
+// Asks for the words `words` checks, giving the ones that don't match
+fn quiz(
+    checks: &ShareChecks,
+    words: &WordChecks,
+    metadata: &[u8],
+    shuffle: impl FnOnce(&mut [usize]),
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<Vec<usize>, Error> {
+    let salt = hex::decode(&checks.salt).context("Invalid share check salt")?;
+    let mut order = (0..words.checks.len()).collect::<Vec<_>>();
+    shuffle(&mut order);
+    let mut mismatched = vec![];
+    for index in order {
+        let check = &words.checks[index];
+        let word = Zeroizing::new(read_word(check.word, input, prompt)?);
+        let byte = byteword_to_index(&word).context("Not a byteword")?;
+        if word_digest(checks.algorithm, &salt, metadata, check.word, byte) != check.digest {
+            mismatched.push(check.word);
+        }
+    }
+    mismatched.sort_unstable();
+    Ok(mismatched)
+}
+
+fn read_word(
+    position: usize,
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<String, Error> {
+    for _ in 0..MAX_TRIES {
+        let word = read_line(&format!("Word {}: ", position), input, prompt)?
+            .trim()
+            .to_lowercase();
+        if is_byteword(&word) {
+            return Ok(word);
+        }
+        match closest_bytewords(&word).as_slice() {
+            [] => writeln!(prompt, "\"{}\" is not a byteword, try again", word)?,
+            closest => writeln!(
+                prompt,
+                "\"{}\" is not a byteword (closest: {}), try again",
+                word,
+                closest.join(", ")
+            )?,
+        }
+    }
+    bail!("Word {} is not a byteword, check your copy", position)
+}
+
+fn read_line(
+    message: &str,
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<String, Error> {
+    write!(prompt, "{}", message)?;
+    prompt.flush()?;
+
+    let mut line = String::new();
+    if input
+        .read_line(&mut line)
+        .context("Unable to read answer")?
+        == 0
+    {
+        bail!("The drill was ended before the last word");
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,92 @@
+use anyhow::{bail, Error};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+pub const OPERATORS: usize = 2;
+const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+// A two-person rule for recovery: two operators each choose a passphrase when
+// the seed is split, and the entropy is encrypted under both of them together
+// (see encryption), so enough shares are of no use without both operators.
+// Nothing of either passphrase is kept; the manifest only records that the
+// seed is under dual control, along with the check words of the encryption.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DualControl {
+    pub operators: usize,
+}
+
+impl Default for DualControl {
+    fn default() -> Self {
+        DualControl {
+            operators: OPERATORS,
+        }
+    }
+}
+
+impl DualControl {
+    // Asks every operator to choose a passphrase, and gives the passphrase
+    // the entropy is to be encrypted under
+    pub fn enroll(
+        read: &mut impl FnMut(&str) -> Result<Zeroizing<String>, Error>,
+    ) -> Result<(DualControl, Zeroizing<String>), Error> {
+        let mut passphrases: Vec<Zeroizing<String>> = vec![];
+
+        for operator in 1..=OPERATORS {
+            let passphrase = read(&format!("Operator {}, choose a passphrase", operator))?;
+            if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+                bail!(
+                    "Passphrase of operator {} is too short, use at least {} characters",
+                    operator,
+                    MIN_PASSPHRASE_LENGTH
+                );
+            }
+            if read(&format!("Operator {}, repeat the passphrase", operator))? != passphrase {
+                bail!("Passphrases of operator {} don't match", operator);
+            }
+            if passphrases.contains(&passphrase) {
+                bail!("Each operator needs their own passphrase");
+            }
+            passphrases.push(passphrase);
+        }
+
+        Ok((DualControl::default(), combined(&passphrases)))
+    }
+
+    // Asks every operator for their passphrase, in turn, and gives the
+    // passphrase the entropy was encrypted under
+    pub fn passphrase(
+        &self,
+        read: &mut impl FnMut(&str) -> Result<Zeroizing<String>, Error>,
+    ) -> Result<Zeroizing<String>, Error> {
+        if self.operators != OPERATORS {
+            bail!(
+                "Dual control needs {} operators, the manifest lists {}",
+                OPERATORS,
+                self.operators
+            );
+        }
+        let passphrases = (1..=OPERATORS)
+            .map(|operator| read(&format!("Operator {}, enter your passphrase", operator)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(combined(&passphrases))
+    }
+}
+
+// Each passphrase is prefixed with its length, so no other pair of
+// passphrases gives the same one, and they can't be entered the other way
+// round
+fn combined(passphrases: &[Zeroizing<String>]) -> Zeroizing<String> {
+    // With room for the length prefixes, so the passphrases are never copied
+    // by growing the string
+    let length = passphrases
+        .iter()
+        .map(|passphrase| passphrase.len() + 8)
+        .sum();
+    let mut combined = Zeroizing::new(String::with_capacity(length));
+    for passphrase in passphrases {
+        combined.push_str(&passphrase.len().to_string());
+        combined.push(':');
+        combined.push_str(passphrase);
+    }
+    combined
+}
@@ -0,0 +1,186 @@
+use anyhow::{bail, Error};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::str::FromStr;
+
+// Pressed in a full-screen mode (review and the ceremony), this sequence wipes
+// every share and secret held in memory, clears the screen and exits at once,
+// for an operator interrupted mid-ceremony. The exit looks like an interrupt.
+pub const DEFAULT_KEYS: &str = "ctrl-x ctrl-x";
+pub const EXIT_CODE: i32 = 130;
+
+// A key of the sequence: the key and the modifiers held with it
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+// Keys separated by spaces, each a character, f1 to f12, esc, tab, enter,
+// backspace or delete, with any of ctrl-, alt- and shift- before it. "none"
+// turns the sequence off.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DuressKeys {
+    keys: Vec<Key>,
+}
+
+impl FromStr for DuressKeys {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<DuressKeys, Error> {
+        let input = input.trim().to_lowercase();
+        if input == "none" {
+            return Ok(DuressKeys::default());
+        }
+        let keys = input
+            .split_whitespace()
+            .map(parse_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        if keys.is_empty() {
+            bail!("No keys given for the duress sequence, \"none\" turns it off");
+        }
+        Ok(DuressKeys { keys })
+    }
+}
+
+fn parse_key(input: &str) -> Result<Key, Error> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = input;
+    loop {
+        let (modifier, after) = match rest.split_once('-') {
+            Some((modifier, after)) if !after.is_empty() => (modifier, after),
+            _ => break,
+        };
+        modifiers |= match modifier {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => bail!(
+                "Unknown modifier \"{}\" in duress key \"{}\"",
+                modifier,
+                input
+            ),
+        };
+        rest = after;
+    }
+    let code = match rest {
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "enter" => KeyCode::Enter,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        _ if rest.chars().count() == 1 => KeyCode::Char(rest.chars().next().unwrap_or(' ')),
+        _ => match rest
+            .strip_prefix('f')
+            .and_then(|number| number.parse().ok())
+        {
+            Some(number @ 1..=12) => KeyCode::F(number),
+            _ => bail!("Unknown duress key \"{}\"", input),
+        },
+    };
+    Ok(Key { code, modifiers })
+}
+
+impl fmt::Display for DuressKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.keys.is_empty() {
+            return f.write_str("none");
+        }
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                let mut name = String::new();
+                for (modifier, prefix) in [
+                    (KeyModifiers::CONTROL, "ctrl-"),
+                    (KeyModifiers::ALT, "alt-"),
+                    (KeyModifiers::SHIFT, "shift-"),
+                ] {
+                    if key.modifiers.contains(modifier) {
+                        name.push_str(prefix);
+                    }
+                }
+                match key.code {
+                    KeyCode::Char(c) => name.push(c),
+                    KeyCode::F(number) => name.push_str(&format!("f{}", number)),
+                    code => name.push_str(&format!("{:?}", code).to_lowercase()),
+                }
+                name
+            })
+            .collect::<Vec<_>>();
+        f.write_str(&keys.join(" "))
+    }
+}
+
+// How a full-screen mode ended
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ending {
+    Done,
+    // The duress sequence was pressed and everything in it wiped
+    Wiped,
+}
+
+// Follows the keys as they are pressed. Keys that continue the sequence are
+// swallowed, so a control key doesn't end up typed as a letter.
+#[derive(Debug)]
+pub struct Watch<'a> {
+    keys: &'a DuressKeys,
+    matched: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Press {
+    // The key isn't part of the sequence, and goes to the screen as usual
+    Pass,
+    Swallowed,
+    Wipe,
+}
+
+impl DuressKeys {
+    pub fn watch(&self) -> Watch<'_> {
+        Watch {
+            keys: self,
+            matched: 0,
+        }
+    }
+}
+
+impl Watch<'_> {
+    pub fn press(&mut self, event: &KeyEvent) -> Press {
+        let Some(next) = self.keys.keys.get(self.matched) else {
+            return Press::Pass;
+        };
+        if matches(next, event) {
+            self.matched += 1;
+            if self.matched == self.keys.keys.len() {
+                self.matched = 0;
+                return Press::Wipe;
+            }
+            return Press::Swallowed;
+        }
+        // A wrong key starts over, and may itself start the sequence again
+        self.matched = 0;
+        if self
+            .keys
+            .keys
+            .first()
+            .is_some_and(|first| matches(first, event))
+        {
+            return self.press(event);
+        }
+        Press::Pass
+    }
+}
+
+// Letters come in upper case with shift held, so case is left to the modifiers
+fn matches(key: &Key, event: &KeyEvent) -> bool {
+    let ignored = KeyModifiers::SHIFT;
+    match (key.code, event.code) {
+        (KeyCode::Char(expected), KeyCode::Char(pressed)) if expected.is_alphabetic() => {
+            expected.eq_ignore_ascii_case(&pressed)
+                && key.modifiers - ignored == event.modifiers - ignored
+                && (!key.modifiers.contains(ignored) || event.modifiers.contains(ignored))
+        }
+        (expected, pressed) => expected == pressed && key.modifiers == event.modifiers,
+    }
+}
@@ -0,0 +1,115 @@
+use crate::bytewords::{
+    byteword_string, byteword_string_no_checksum, byteword_string_to_bytes, BytewordStyle,
+};
+use anyhow::{anyhow, bail, Context, Error};
+use rand::RngCore;
+use scrypt::{scrypt, Params};
+use zeroize::Zeroizing;
+
+const MIN_PASSPHRASE_LENGTH: usize = 8;
+// 128 MiB and around a second per guess
+const LOG_N: u8 = 17;
+const R: u32 = 8;
+const P: u32 = 1;
+// Bytes of the stretched passphrase that are shown as check words
+const CHECK_SIZE: usize = 2;
+const SALT_SIZE: usize = 16;
+// The most an SSKR share holds
+const MAX_SECRET_SIZE: usize = 32;
+
+// A secret encrypted under a passphrase before it is split, so that enough
+// shares alone don't recover it. SSKR holds at most 32 bytes, leaving no room
+// for a nonce or tag, so the entropy is XORed with the passphrase stretched by
+// scrypt to its length, which keeps the length. Every split gets a random salt,
+// so guesses can't be computed once for every split. The salt goes in the
+// shares in front of the entropy when both fit, which only 16 byte entropy
+// does, and is given back to be kept with the check words otherwise.
+pub struct Encrypted {
+    // What gets split
+    pub secret: Zeroizing<Vec<u8>>,
+    // Bytewords that only the right passphrase gives, there being no tag to
+    // tell a mistyped passphrase by
+    pub check: String,
+    // The salt in bytewords, when it didn't fit in the shares
+    pub salt: Option<String>,
+}
+
+pub struct Decrypted {
+    pub secret: Zeroizing<Vec<u8>>,
+    pub check: String,
+}
+
+pub fn encrypt(secret: &[u8], passphrase: &str) -> Result<Encrypted, Error> {
+    let mut salt = [0u8; SALT_SIZE];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let (encrypted, check) = apply(secret, passphrase, &salt)?;
+    if secret.len() + SALT_SIZE <= MAX_SECRET_SIZE {
+        Ok(Encrypted {
+            secret: Zeroizing::new([&salt, encrypted.as_slice()].concat()),
+            check,
+            salt: None,
+        })
+    } else {
+        Ok(Encrypted {
+            secret: encrypted,
+            check,
+            salt: Some(byteword_string(&salt, BytewordStyle::Standard)),
+        })
+    }
+}
+
+// Decrypts a combined secret with the salt kept apart from the shares or,
+// without one, the salt in front of it
+pub fn decrypt(combined: &[u8], passphrase: &str, salt: Option<&str>) -> Result<Decrypted, Error> {
+    let (salt, encrypted) = match salt {
+        Some(salt) => (
+            byteword_string_to_bytes(salt, BytewordStyle::Standard)
+                .context("Invalid passphrase salt")?,
+            combined,
+        ),
+        None if combined.len() == MAX_SECRET_SIZE => {
+            let (salt, encrypted) = combined.split_at(SALT_SIZE);
+            (salt.to_vec(), encrypted)
+        }
+        None => bail!(
+            "The shares don't hold the passphrase salt, give the one shown when they were split"
+        ),
+    };
+    if salt.len() != SALT_SIZE {
+        bail!("The passphrase salt must be {} bytes", SALT_SIZE);
+    }
+    let (secret, check) = apply(encrypted, passphrase, &salt)?;
+    Ok(Decrypted { secret, check })
+}
+
+// Encrypting and decrypting are the same XOR
+fn apply(
+    secret: &[u8],
+    passphrase: &str,
+    salt: &[u8],
+) -> Result<(Zeroizing<Vec<u8>>, String), Error> {
+    let params = Params::new(LOG_N, R, P, secret.len() + CHECK_SIZE)
+        .map_err(|error| anyhow!("Invalid scrypt parameters: {}", error))?;
+    let mut stretched = Zeroizing::new(vec![0u8; secret.len() + CHECK_SIZE]);
+    scrypt(passphrase.as_bytes(), salt, &params, &mut stretched)
+        .map_err(|error| anyhow!("Unable to stretch passphrase: {}", error))?;
+
+    let (pad, check) = stretched.split_at(secret.len());
+    Ok((
+        Zeroizing::new(secret.iter().zip(pad).map(|(a, b)| a ^ b).collect()),
+        byteword_string_no_checksum(check, BytewordStyle::Standard),
+    ))
+}
+
+pub fn check_passphrase(passphrase: &str, repeated: &str) -> Result<(), Error> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        bail!(
+            "Passphrase is too short, use at least {} characters",
+            MIN_PASSPHRASE_LENGTH
+        );
+    }
+    if passphrase != repeated {
+        bail!("Passphrases don't match");
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::{anyhow, bail, Error};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+// Physical entropy supplied by the user, as the sequence of outcomes
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntropySource {
+    Dice(String),
+    Coin(String),
+}
+
+impl FromStr for EntropySource {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<EntropySource, Error> {
+        let (kind, outcomes) = input
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected dice:<rolls> or coin:<flips>"))?;
+
+        // Spaces and dashes make long sequences easier to type, but don't count
+        let outcomes: String = outcomes
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect::<String>()
+            .to_lowercase();
+
+        let (source, valid) = match kind.to_lowercase().as_str() {
+            "dice" => (EntropySource::Dice(outcomes.clone()), "123456"),
+            "coin" => (EntropySource::Coin(outcomes.clone()), "ht01"),
+            _ => bail!("Unknown entropy source \"{}\", use dice or coin", kind),
+        };
+        if let Some(c) = outcomes.chars().find(|c| !valid.contains(*c)) {
+            bail!(
+                "Invalid {} outcome '{}', expected one of {}",
+                kind,
+                c,
+                valid
+            );
+        }
+
+        Ok(source)
+    }
+}
+
+impl EntropySource {
+    pub fn name(&self) -> &str {
+        match self {
+            EntropySource::Dice(_) => "dice rolls",
+            EntropySource::Coin(_) => "coin flips",
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        match self {
+            EntropySource::Dice(rolls) => rolls.len(),
+            EntropySource::Coin(flips) => flips.len(),
+        }
+    }
+
+    // Outcomes needed to provide at least `bits` bits
+    pub fn required(&self, bits: usize) -> usize {
+        match self {
+            EntropySource::Dice(_) => (bits as f64 / 6f64.log2()).ceil() as usize,
+            EntropySource::Coin(_) => bits,
+        }
+    }
+
+    // The outcomes are hashed rather than used directly, so the result is
+    // uniform no matter how the outcomes were written down
+    pub fn entropy(&self, bytes: usize) -> Result<Zeroizing<Vec<u8>>, Error> {
+        let required = self.required(bytes * 8);
+        if self.count() < required {
+            bail!(
+                "Not enough {}: {} needed for {} bits of entropy, got {}",
+                self.name(),
+                required,
+                bytes * 8,
+                self.count()
+            );
+        }
+
+        let outcomes = Zeroizing::new(match self {
+            EntropySource::Dice(rolls) => rolls.clone(),
+            EntropySource::Coin(flips) => flips.replace('h', "1").replace('t', "0"),
+        });
+        let digest = Zeroizing::new(Sha256::digest(outcomes.as_bytes()).to_vec());
+        Ok(Zeroizing::new(digest[..bytes].to_vec()))
+    }
+}
+
+// XORing in OS randomness means the result is at least as strong as either
+// source on its own
+pub fn mix_os_entropy(entropy: &mut [u8]) {
+    let mut os = Zeroizing::new(vec![0u8; entropy.len()]);
+    rand::thread_rng().fill_bytes(&mut os);
+    for (byte, os_byte) in entropy.iter_mut().zip(os.iter()) {
+        *byte ^= os_byte;
+    }
+}
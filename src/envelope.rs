@@ -0,0 +1,215 @@
+use crate::bytewords::{byteword_string, byteword_string_to_bytes, BytewordStyle};
+use anyhow::{anyhow, bail, Error};
+use dcbor::{CBOREncodable, Map, CBOR};
+use sha2::{Digest, Sha256};
+
+pub const UR_PREFIX: &str = "ur:envelope/";
+
+const ENVELOPE_TAG: u64 = 200;
+const LEAF_TAG: u64 = 201;
+const KNOWN_VALUE_TAG: u64 = 40000;
+const ENCRYPTED_TAG: u64 = 40002;
+const SSKR_SHARE_TAG: u64 = 40309;
+const DATE_TAG: u64 = 1;
+
+// Known values, the registered predicates of Gordian Envelope
+const SSKR_SHARE: u64 = 6;
+const HAS_NAME: u64 = 11;
+const DATE: u64 = 16;
+
+// An SSKR share wrapped in a Gordian Envelope: the share is the subject, and
+// metadata about it is asserted on it. This is not the layout SeedTool gives
+// the shares it exports (an encrypted seed as the subject, with a share of
+// the key it is encrypted with asserted on it), which this tool neither
+// writes nor reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareEnvelope {
+    pub share: Vec<u8>,
+    pub label: Option<String>,
+    // Seconds since the Unix epoch
+    pub date: Option<i64>,
+}
+
+// The parts of an envelope this tool builds, with the digest each one
+// contributes to the envelope's digest tree
+struct Part {
+    cbor: CBOR,
+    digest: [u8; 32],
+}
+
+fn leaf(value: CBOR) -> Part {
+    Part {
+        digest: Sha256::digest(value.cbor_data()).into(),
+        cbor: CBOR::tagged_value(LEAF_TAG, value),
+    }
+}
+
+fn known_value(value: u64) -> Part {
+    Part {
+        digest: Sha256::digest(CBOR::tagged_value(KNOWN_VALUE_TAG, value).cbor_data()).into(),
+        cbor: CBOR::Unsigned(value),
+    }
+}
+
+fn digest_of(digests: &[&[u8; 32]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for digest in digests {
+        hasher.update(digest);
+    }
+    hasher.finalize().into()
+}
+
+fn assertion(predicate: Part, object: Part) -> Part {
+    let mut map = Map::new();
+    map.insert(predicate.cbor, object.cbor);
+    Part {
+        digest: digest_of(&[&predicate.digest, &object.digest]),
+        cbor: CBOR::Map(map),
+    }
+}
+
+impl ShareEnvelope {
+    pub fn new(share: &[u8]) -> ShareEnvelope {
+        ShareEnvelope {
+            share: share.to_vec(),
+            label: None,
+            date: None,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let subject = leaf(CBOR::tagged_value(
+            SSKR_SHARE_TAG,
+            CBOR::byte_string(&self.share),
+        ));
+
+        let mut assertions = vec![];
+        if let Some(label) = &self.label {
+            assertions.push(assertion(
+                known_value(HAS_NAME),
+                leaf(CBOR::Text(label.clone())),
+            ));
+        }
+        if let Some(date) = self.date {
+            let seconds = match u64::try_from(date) {
+                Ok(seconds) => CBOR::Unsigned(seconds),
+                Err(_) => CBOR::Negative(date),
+            };
+            assertions.push(assertion(
+                known_value(DATE),
+                leaf(CBOR::tagged_value(DATE_TAG, seconds)),
+            ));
+        }
+
+        // Assertions are ordered by digest, so the same metadata always gives
+        // the same envelope
+        let content = if assertions.is_empty() {
+            subject.cbor
+        } else {
+            assertions.sort_by_key(|assertion| assertion.digest);
+            let mut node = vec![subject.cbor];
+            node.extend(assertions.into_iter().map(|assertion| assertion.cbor));
+            CBOR::Array(node)
+        };
+
+        // The UR type already identifies the payload, so the CBOR is left untagged
+        format!(
+            "{}{}",
+            UR_PREFIX,
+            byteword_string(content.cbor_data().as_slice(), BytewordStyle::Minimal)
+        )
+    }
+
+    pub fn decode(input: &str) -> Result<ShareEnvelope, Error> {
+        let input = input.trim();
+        if !input.to_lowercase().starts_with(UR_PREFIX) {
+            bail!("Not a ur:envelope string: \"{}\"", input);
+        }
+        let body = input[UR_PREFIX.len()..].to_lowercase();
+        let bytes = byteword_string_to_bytes(&body, BytewordStyle::Minimal)?;
+        let cbor = CBOR::from_data(bytes.as_slice())?;
+        let content = match cbor.expect_tagged_value(ENVELOPE_TAG) {
+            Ok(content) => content,
+            Err(_) => &cbor,
+        };
+
+        let (subject, assertions) = match content {
+            CBOR::Array(node) if !node.is_empty() => (&node[0], &node[1..]),
+            _ => (content, &[][..]),
+        };
+
+        let mut envelope = ShareEnvelope {
+            share: vec![],
+            label: None,
+            date: None,
+        };
+        let mut share = subject_share(subject)?;
+        for assertion in assertions {
+            let Some((predicate, object)) = assertion.as_map().and_then(|map| map.iter().next())
+            else {
+                bail!("Envelope assertion is not a predicate and object");
+            };
+            match known(predicate) {
+                Some(SSKR_SHARE) => share = Some(share_leaf(object)?),
+                Some(HAS_NAME) => {
+                    envelope.label = Some(leaf_value(object)?.expect_text()?.to_string())
+                }
+                Some(DATE) => envelope.date = Some(date(leaf_value(object)?)?),
+                // Metadata this tool has no use for
+                _ => {}
+            }
+        }
+
+        envelope.share = share.ok_or_else(|| anyhow!("The envelope holds no SSKR share"))?;
+        Ok(envelope)
+    }
+}
+
+// Envelopes with an encrypted subject, as SeedTool exports, hold a share of
+// the key the subject is encrypted with rather than of the secret
+fn subject_share(subject: &CBOR) -> Result<Option<Vec<u8>>, Error> {
+    match subject.as_tagged_value() {
+        Some((tag, _)) if tag.value() == ENCRYPTED_TAG => bail!(
+            "The envelope holds an encrypted secret and a share of its key, which this tool \
+             can't decrypt; recover it with the tool that made it"
+        ),
+        Some((tag, value)) if tag.value() == LEAF_TAG => match value.as_tagged_value() {
+            Some((tag, share)) if tag.value() == SSKR_SHARE_TAG => {
+                Ok(Some(share.expect_byte_string()?.to_vec()))
+            }
+            _ => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+fn share_leaf(object: &CBOR) -> Result<Vec<u8>, Error> {
+    Ok(leaf_value(object)?
+        .expect_tagged_value(SSKR_SHARE_TAG)?
+        .expect_byte_string()?
+        .to_vec())
+}
+
+fn leaf_value(cbor: &CBOR) -> Result<&CBOR, Error> {
+    Ok(cbor.expect_tagged_value(LEAF_TAG)?)
+}
+
+// Known values are plain integers in envelopes, though older encoders tag them
+fn known(cbor: &CBOR) -> Option<u64> {
+    match cbor {
+        CBOR::Unsigned(value) => Some(*value),
+        CBOR::Tagged(tag, value) if tag.value() == KNOWN_VALUE_TAG => match **value {
+            CBOR::Unsigned(value) => Some(value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn date(cbor: &CBOR) -> Result<i64, Error> {
+    match cbor.expect_tagged_value(DATE_TAG)? {
+        CBOR::Unsigned(seconds) => Ok(i64::try_from(*seconds)?),
+        CBOR::Negative(seconds) => Ok(*seconds),
+        _ => bail!("Envelope date is not in whole seconds"),
+    }
+}
@@ -0,0 +1,200 @@
+use thiserror::Error;
+
+// Exit codes, so scripts can tell why a command failed without matching on
+// messages. Anything else that fails exits with 1, and bad arguments with 2.
+pub const UNREADABLE_SHARE: i32 = 3;
+pub const INVALID_CHECKSUM: i32 = 4;
+pub const MISMATCHED_SHARES: i32 = 5;
+pub const NOT_ENOUGH_SHARES: i32 = 6;
+pub const INVALID_SPLIT: i32 = 7;
+pub const COMBINATION_FAILED: i32 = 8;
+
+// The errors of reading shares, splitting and recovering. They are returned
+// as anyhow errors like the rest, and can be found again with downcast_ref.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SskrToolError {
+    // Reading shares
+    #[error("Not a valid byteword: \"{word}\"{}", closest_hint(.closest))]
+    InvalidByteword { word: String, closest: Vec<String> },
+    #[error("Not a valid byteword: \"{word}\", and too far from any byteword to correct")]
+    UncorrectableByteword { word: String },
+    #[error("Invalid checksum (last 4 words) for byteword string \"{line}\"")]
+    InvalidChecksum { line: String },
+    #[error("Byteword string too short (must include checksum): \"{line}\"")]
+    TooShort { line: String },
+    #[error("Too many unreadable words to correct: \"{line}\"")]
+    TooManyCorrections { line: String },
+    #[error("No correction of \"{line}\" passes the checksum")]
+    NoCorrection { line: String },
+    #[error("\"{line}\" can be corrected in {ways} ways that all pass the checksum, check the words by hand")]
+    AmbiguousCorrection { line: String, ways: usize },
+    #[error("Share is too short: \"{share}\"")]
+    ShareTooShort { share: String },
+    #[error("Share has invalid group threshold: \"{share}\"")]
+    InvalidGroupThreshold { share: String },
+    #[error("Share has invalid reserved bits, --lenient ignores them: \"{share}\"")]
+    ReservedBits { share: String },
+    #[error("The share holds {length} bytes of secret, which SSKR can't combine (even lengths from {min} to {max})")]
+    UncombinableSecret {
+        length: usize,
+        min: usize,
+        max: usize,
+    },
+
+    // Recovering, with groups and members numbered from 1
+    #[error("No shares to recover from")]
+    NoShares,
+    #[error("Conflicting shares for group {group}, member {member}: two different shares have the same index")]
+    ConflictingShares { group: usize, member: usize },
+    #[error("Mismatched identifiers, shares don't go together")]
+    MismatchedIdentifier { expected: u16, found: u16 },
+    #[error("Mismatched group threshold or count, shares don't go together")]
+    MismatchedGroups,
+    #[error("Shares of different splits can't be grouped together")]
+    MixedSplits,
+    #[error("Mismatched share member thresholds in group {group}, shares don't go together")]
+    MismatchedMemberThreshold { group: usize },
+    // The groups that have enough shares are in `have`
+    #[error(
+        "Not enough groups, need to satisfy at least {needed} but only {} are satisfied ({})",
+        .have.len(),
+        join_groups(.have)
+    )]
+    InsufficientGroups { needed: usize, have: Vec<usize> },
+    #[error("Error during SSKR combination: {0}")]
+    CombinationFailed(String),
+
+    // Splitting
+    // The problem is with the bytes from start to end of the spec
+    #[error(
+        "Invalid group spec at column {}, {problem}:\n  {spec}\n  {}",
+        column(.spec, *.start),
+        underline(.spec, *.start, *.end)
+    )]
+    InvalidSpec {
+        spec: String,
+        start: usize,
+        end: usize,
+        problem: String,
+    },
+    #[error("Invalid group spec: group \"{group}\" has an empty name")]
+    EmptyGroupName { group: String },
+    #[error("Invalid group spec: group name \"{name}\" is used twice")]
+    DuplicateGroupName { name: String },
+    #[error("Invalid group \"{group}\" in spec ({threshold} is greater than {count})")]
+    ThresholdAboveCount {
+        group: String,
+        threshold: usize,
+        count: usize,
+    },
+    #[error("Invalid group \"{group}\" in spec: 1 of N groups (where N > 1) not supported")]
+    OneOfMany { group: String },
+    #[error("The spec has {groups} groups, so a group threshold is needed")]
+    GroupThresholdNeeded { groups: usize },
+    #[error("The secret is {length} bytes, SSKR secrets are {min} to {max} bytes")]
+    SecretLength {
+        length: usize,
+        min: usize,
+        max: usize,
+    },
+    #[error("The secret is {length} bytes, SSKR secrets have an even number of bytes")]
+    OddSecretLength { length: usize },
+    #[error("Invalid identifier \"{input}\", expected 4 hex digits such as 3f1a")]
+    InvalidIdentifier { input: String },
+    #[error("The spec has {groups} groups, but only one can be used here")]
+    SingleGroupNeeded { groups: usize },
+    #[error("Error making group spec for group \"{group}\": {reason}")]
+    UnsupportedGroup { group: String, reason: String },
+    #[error("Invalid entropy \"{input}\", expected hex")]
+    InvalidEntropy { input: String },
+    #[error(
+        "The entropy is {length} bytes, mnemonics are made from 16, 20, 24, 28 or 32 \
+         (--raw-secret splits other lengths)"
+    )]
+    EntropyLength { length: usize },
+    #[error("Unable to make mnemonic from entropy: {0}")]
+    MnemonicFromEntropy(String),
+    #[error("Expected a number of words")]
+    InvalidWordCount,
+    #[error("Mnemonics have 12, 15, 18, 21 or 24 words")]
+    UnsupportedWordCount { count: usize },
+}
+
+impl SskrToolError {
+    pub fn exit_code(&self) -> i32 {
+        use SskrToolError::*;
+        match self {
+            InvalidByteword { .. }
+            | UncorrectableByteword { .. }
+            | TooShort { .. }
+            | TooManyCorrections { .. }
+            | AmbiguousCorrection { .. }
+            | ShareTooShort { .. }
+            | InvalidGroupThreshold { .. }
+            | ReservedBits { .. }
+            | UncombinableSecret { .. } => UNREADABLE_SHARE,
+            InvalidChecksum { .. } | NoCorrection { .. } => INVALID_CHECKSUM,
+            ConflictingShares { .. }
+            | MismatchedIdentifier { .. }
+            | MismatchedGroups
+            | MismatchedMemberThreshold { .. }
+            | MixedSplits => MISMATCHED_SHARES,
+            NoShares | InsufficientGroups { .. } => NOT_ENOUGH_SHARES,
+            CombinationFailed(_) => COMBINATION_FAILED,
+            InvalidSpec { .. }
+            | EmptyGroupName { .. }
+            | DuplicateGroupName { .. }
+            | ThresholdAboveCount { .. }
+            | OneOfMany { .. }
+            | GroupThresholdNeeded { .. }
+            | SecretLength { .. }
+            | OddSecretLength { .. }
+            | InvalidIdentifier { .. }
+            | SingleGroupNeeded { .. }
+            | UnsupportedGroup { .. }
+            | InvalidEntropy { .. }
+            | EntropyLength { .. }
+            | MnemonicFromEntropy(_)
+            | InvalidWordCount
+            | UnsupportedWordCount { .. } => INVALID_SPLIT,
+        }
+    }
+}
+
+// The exit code for an error, from the first SskrToolError among its causes
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<SskrToolError>())
+        .map_or(1, SskrToolError::exit_code)
+}
+
+fn closest_hint(closest: &[String]) -> String {
+    if closest.is_empty() {
+        return String::new();
+    }
+    format!(" (closest: {})", closest.join(", "))
+}
+
+fn column(text: &str, at: usize) -> usize {
+    text[..at].chars().count() + 1
+}
+
+// Carets under the part of the text from start to end, at least one wide so
+// the end of the text can be pointed at
+fn underline(text: &str, start: usize, end: usize) -> String {
+    let width = text[start..end].chars().count().max(1);
+    format!(
+        "{}{}",
+        " ".repeat(column(text, start) - 1),
+        "^".repeat(width)
+    )
+}
+
+fn join_groups(groups: &[usize]) -> String {
+    groups
+        .iter()
+        .map(|group| group.to_string())
+        .collect::<Vec<String>>()
+        .join(" and ")
+}
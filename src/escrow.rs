@@ -0,0 +1,218 @@
+use crate::checklist::unix_time;
+use crate::hash::{Hash, HashAlgorithm};
+use crate::output::ShareMetadataOutput;
+use crate::recover::decode_share;
+use crate::share_format::{self, ShareFormat};
+use age::x25519::Recipient;
+use anyhow::{anyhow, bail, Context, Error};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+pub const ESCROW_VERSION: u32 = 1;
+pub const PAYLOAD_FILE: &str = "shares.age";
+pub const MANIFEST_FILE: &str = "escrow.json";
+pub const SIGNATURE_FILE: &str = "escrow.json.sig";
+
+// A lodgement of shares with a professional custodian, laid out the way
+// escrow agents take them in: the shares encrypted with age to the agent's
+// key, and a manifest of what was lodged signed by the depositor, so the
+// agent can log the intake without decrypting anything.
+//
+//   shares.age       the shares, a line of bytewords each
+//   escrow.json      the manifest
+//   escrow.json.sig  the depositor's Ed25519 signature of escrow.json, in hex
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EscrowManifest {
+    pub version: u32,
+    pub identifier: String,
+    pub group_threshold: usize,
+    pub group_count: usize,
+    pub groups: Vec<LodgedGroup>,
+    // The agent's age public key the shares are encrypted to
+    pub recipient: String,
+    pub payload: String,
+    pub payload_hash: Hash,
+    // The depositor's Ed25519 public key, in hex
+    pub signer: String,
+    // When the shares were lodged, as a Unix time
+    pub created: u64,
+}
+
+// Groups and members are numbered from 1
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LodgedGroup {
+    pub group: usize,
+    pub member_threshold: usize,
+    pub members: Vec<usize>,
+}
+
+// Writes the lodgement of the shares of the groups, given in any format, into
+// `dir`
+pub fn export(
+    lines: &[String],
+    groups: &[usize],
+    recipient: &str,
+    signing_key: &SigningKey,
+    hash: HashAlgorithm,
+    dir: &Path,
+) -> Result<EscrowManifest, Error> {
+    let recipient: Recipient = recipient
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Invalid age recipient \"{}\": {}", recipient, e))?;
+
+    if groups.is_empty() {
+        bail!("No groups to lodge");
+    }
+    let mut lodged: Vec<(ShareMetadataOutput, Zeroizing<Vec<u8>>)> = vec![];
+    for line in lines {
+        let share = Zeroizing::new(decode_share(line)?);
+        let metadata = ShareMetadataOutput::new(&share)?;
+        if groups.contains(&(metadata.group_index + 1)) {
+            lodged.push((metadata, share));
+        }
+    }
+    for group in groups {
+        if !lodged
+            .iter()
+            .any(|(meta, _)| meta.group_index + 1 == *group)
+        {
+            bail!("There are no shares of group {} to lodge", group);
+        }
+    }
+    // The same share given twice is lodged once
+    lodged.sort_by_key(|(meta, _)| (meta.group_index, meta.member_index));
+    lodged.dedup_by(|(_, share), (_, other)| share == other);
+    let (first, _) = &lodged[0];
+    if lodged
+        .iter()
+        .any(|(meta, _)| meta.identifier != first.identifier)
+    {
+        bail!("The shares are from different splits, lodge each split on its own");
+    }
+
+    let mut lodged_groups: Vec<LodgedGroup> = vec![];
+    for (meta, _) in &lodged {
+        match lodged_groups.last_mut() {
+            Some(group) if group.group == meta.group_index + 1 => {
+                group.members.push(meta.member_index + 1)
+            }
+            _ => lodged_groups.push(LodgedGroup {
+                group: meta.group_index + 1,
+                member_threshold: meta.member_threshold,
+                members: vec![meta.member_index + 1],
+            }),
+        }
+    }
+
+    let plaintext = Zeroizing::new(
+        lodged
+            .iter()
+            .map(|(_, share)| share_format::encode(share, ShareFormat::Bytewords) + "\n")
+            .collect::<String>(),
+    );
+    let payload = age::encrypt(&recipient, plaintext.as_bytes())?;
+
+    let manifest = EscrowManifest {
+        version: ESCROW_VERSION,
+        identifier: first.identifier.clone(),
+        group_threshold: first.group_threshold,
+        group_count: first.group_count,
+        groups: lodged_groups,
+        recipient: recipient.to_string(),
+        payload: PAYLOAD_FILE.to_string(),
+        payload_hash: hash.hash(&payload),
+        signer: hex::encode(signing_key.verifying_key().as_bytes()),
+        created: unix_time(),
+    };
+    let json = serde_json::to_string_pretty(&manifest)? + "\n";
+    let signature = signing_key.sign(json.as_bytes());
+
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Unable to create directory \"{}\"", dir.display()))?;
+    for (name, contents) in [
+        (PAYLOAD_FILE, payload),
+        (MANIFEST_FILE, json.into_bytes()),
+        (
+            SIGNATURE_FILE,
+            (hex::encode(signature.to_bytes()) + "\n").into_bytes(),
+        ),
+    ] {
+        let path = dir.join(name);
+        fs::write(&path, contents)
+            .with_context(|| format!("Unable to write \"{}\"", path.display()))?;
+    }
+    Ok(manifest)
+}
+
+// Checks a lodgement the way an agent would at intake: the manifest is signed
+// by its signer, or by `signer` when the depositor's key is known, and the
+// payload is the one it describes. Nothing is decrypted.
+pub fn verify(dir: &Path, signer: Option<&str>) -> Result<EscrowManifest, Error> {
+    let read = |name: &str| {
+        let path = dir.join(name);
+        fs::read(&path).with_context(|| format!("Unable to read \"{}\"", path.display()))
+    };
+    let json = read(MANIFEST_FILE)?;
+    let manifest: EscrowManifest = serde_json::from_slice(&json)
+        .with_context(|| format!("Invalid escrow manifest in \"{}\"", dir.display()))?;
+    if manifest.version > ESCROW_VERSION {
+        bail!(
+            "The escrow manifest is version {}, this release reads up to version {}",
+            manifest.version,
+            ESCROW_VERSION
+        );
+    }
+
+    let key = parse_verifying_key(signer.unwrap_or(&manifest.signer))?;
+    if hex::encode(key.as_bytes()) != manifest.signer.trim().to_lowercase() {
+        bail!("The manifest was signed with a different key than the depositor's");
+    }
+    let signature = String::from_utf8(read(SIGNATURE_FILE)?)?;
+    let signature: [u8; 64] = hex::decode(signature.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .context("Invalid signature, expected 128 hex digits")?;
+    key.verify_strict(&json, &Signature::from_bytes(&signature))
+        .map_err(|_| anyhow!("The manifest's signature doesn't match, it was altered"))?;
+
+    if !manifest.payload_hash.matches(&read(&manifest.payload)?) {
+        bail!(
+            "\"{}\" doesn't match the {} hash in the manifest, it was altered",
+            manifest.payload,
+            manifest.payload_hash.algorithm().name()
+        );
+    }
+    Ok(manifest)
+}
+
+// A new signing key for a depositor, as the hex the key file holds
+pub fn generate_signing_key() -> Zeroizing<String> {
+    Zeroizing::new(hex::encode(rand::random::<[u8; 32]>()))
+}
+
+pub fn load_signing_key(path: &Path) -> Result<SigningKey, Error> {
+    let contents = Zeroizing::new(
+        fs::read_to_string(path)
+            .with_context(|| format!("Unable to read signing key \"{}\"", path.display()))?,
+    );
+    let bytes = Zeroizing::new(hex::decode(contents.trim()).unwrap_or_default());
+    let Ok(seed) = <[u8; 32]>::try_from(bytes.as_slice()) else {
+        bail!(
+            "Invalid signing key \"{}\", expected 64 hex digits",
+            path.display()
+        );
+    };
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+fn parse_verifying_key(input: &str) -> Result<VerifyingKey, Error> {
+    let bytes: [u8; 32] = hex::decode(input.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| format!("Invalid public key \"{}\", expected 64 hex digits", input))?;
+    Ok(VerifyingKey::from_bytes(&bytes)?)
+}
@@ -0,0 +1,138 @@
+use crate::artifacts::{
+    paths, share_artifacts, write_artifacts, Artifact, ExistingShares, WriteOutcome,
+};
+use crate::manifest::{Manifest, SeedManifest};
+use crate::naming::NameTemplate;
+use anyhow::{bail, Error};
+use std::path::{Path, PathBuf};
+
+// The estate profile writes two artifact sets that must never overlap: the
+// custodian packets enable recovery (they hold the shares) but don't explain
+// it, and the executor packet explains recovery but never holds a share.
+pub struct EstatePackets {
+    pub executor: Vec<PathBuf>,
+    pub custodians: Vec<PathBuf>,
+    pub outcome: WriteOutcome,
+}
+
+pub fn write_estate(
+    dir: &Path,
+    seed: &SeedManifest,
+    groups: &[Vec<String>],
+    name_template: &NameTemplate,
+    existing: ExistingShares,
+) -> Result<EstatePackets, Error> {
+    let manifest = Manifest::new(seed.clone());
+    let manifest_json = manifest.to_json()?;
+    let runbook = runbook(&manifest);
+
+    for share in groups.iter().flatten() {
+        if manifest_json.contains(share.as_str()) || runbook.contains(share.as_str()) {
+            bail!("Refusing to write an executor packet that contains a share");
+        }
+    }
+
+    let executor_dir = dir.join("executor");
+    let custodians_dir = dir.join("custodians");
+
+    let mut executor = vec![
+        Artifact {
+            path: executor_dir.join("manifest.json"),
+            contents: manifest_json,
+        },
+        Artifact {
+            path: executor_dir.join("runbook.txt"),
+            contents: runbook,
+        },
+    ];
+
+    let mut custodians = share_artifacts(
+        &custodians_dir,
+        seed,
+        groups,
+        name_template,
+        |group_num, share_num, share| custodian_packet(seed, group_num, share_num, share),
+    )?;
+    let (executor_paths, custodian_paths) = (paths(&executor), paths(&custodians));
+
+    executor.append(&mut custodians);
+    let outcome = write_artifacts(dir, &seed.identifier, &executor, existing)?;
+
+    Ok(EstatePackets {
+        executor: executor_paths,
+        custodians: custodian_paths,
+        outcome,
+    })
+}
+
+// Deliberately carries no thresholds or instructions, only what a custodian
+// needs to identify and hand over their share.
+fn custodian_packet(
+    seed: &SeedManifest,
+    group_num: usize,
+    share_num: usize,
+    share: &str,
+) -> String {
+    format!(
+        "SSKR SHARE - CUSTODIAN PACKET\n\
+         \n\
+         Share set identifier: {}\n\
+         {}, share {}\n\
+         \n\
+         {}\n\
+         \n\
+         Keep this share safe and private. Hand it over only to the executor\n\
+         of the estate when they ask for it.\n",
+        seed.identifier,
+        seed.group_title(group_num + 1),
+        share_num + 1,
+        share
+    )
+}
+
+pub fn runbook(manifest: &Manifest) -> String {
+    let mut runbook = String::new();
+
+    runbook.push_str("SSKR RECOVERY RUNBOOK - EXECUTOR PACKET\n\n");
+    runbook.push_str("This packet explains how to recover the secret, but contains no shares.\n");
+    runbook.push_str("The shares are held by the custodians, one share per custodian packet.\n");
+
+    for seed in &manifest.seeds {
+        runbook.push_str(&format!("\n{}\n", seed.title()));
+        if let Some(purpose) = &seed.purpose {
+            runbook.push_str(&format!("Purpose: {}\n", purpose));
+        }
+        if let Some(fingerprint) = &seed.fingerprint {
+            runbook.push_str(&format!("Master fingerprint: {}\n", fingerprint));
+        }
+        runbook.push_str(&format!(
+            "\nRecovery needs shares from at least {} of the following {} group(s):\n\n",
+            seed.group_threshold,
+            seed.groups.len()
+        ));
+        for group in &seed.groups {
+            runbook.push_str(&format!(
+                "  {}: any {} of its {} shares\n",
+                seed.group_title(group.group),
+                group.member_threshold,
+                group.member_count
+            ));
+        }
+        runbook.push_str(&format!("\nCommand: {}\n", seed.recover_command()));
+    }
+
+    runbook.push_str(
+        "\n\
+         Steps:\n\
+         \n\
+         1. Use a secure, offline computer with sskr-tool installed.\n\
+         2. Collect enough custodian packets, checking that each one shows\n\
+         \x20  the share set identifier of the seed being recovered.\n\
+         3. Copy the share from each packet into a file such as shares.txt,\n\
+         \x20  one share per line.\n\
+         4. Run the command listed above for that seed.\n\
+         5. Restore the wallet from the recovered mnemonic and move the funds.\n",
+    );
+
+    runbook
+}
@@ -0,0 +1,43 @@
+use crate::split::{parse_spec, to_bytewords, ShareGroups};
+use anyhow::Error;
+use bc_crypto::make_fake_random_number_generator;
+use bip39::{Language, Mnemonic};
+use sskr::{sskr_generate_using, Secret};
+
+// A demonstration for new users, on the seed every BIP-39 test vector starts
+// with. The shares come from a fixed random number generator, so they are
+// the same on every run and `recover --example` recovers what
+// `split --example` printed.
+pub const MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+pub const SPEC: &str = "2of3";
+pub const GROUP_THRESHOLD: usize = 1;
+// The shares `recover --example` uses, as members of the group
+pub const RECOVERED_FROM: [usize; 2] = [1, 3];
+
+pub const NOTICE: &str = "EXAMPLE ONLY: this seed is published in every BIP-39 test suite and \
+    these shares are the same for everyone. Never use them for real funds.";
+
+pub fn mnemonic() -> Result<Mnemonic, Error> {
+    Mnemonic::from_phrase(MNEMONIC, Language::English)
+}
+
+pub fn shares() -> Result<ShareGroups, Error> {
+    let mnemonic = mnemonic()?;
+    let secret = Secret::new(mnemonic.entropy())?;
+    let spec = parse_spec(SPEC, GROUP_THRESHOLD)?;
+    Ok(sskr_generate_using(
+        &spec,
+        &secret,
+        &mut make_fake_random_number_generator(),
+    )?)
+}
+
+// Enough of the example shares to recover it, as bytewords
+pub fn recovery_lines() -> Result<Vec<String>, Error> {
+    let shares = to_bytewords(&shares()?, &false);
+    Ok(RECOVERED_FROM
+        .iter()
+        .map(|member| shares[0][member - 1].clone())
+        .collect())
+}
@@ -0,0 +1,238 @@
+use crate::diagnostics::suggestions;
+use crate::policy::{self, Channel, Field, Policy};
+use crate::recover::{diagnose_lines, recover_secret};
+use crate::status::status;
+use anyhow::{anyhow, Error};
+use bip39::{Language, Mnemonic};
+use eframe::egui::{self, Color32, RichText, Sense, TextEdit};
+use qrcode::{Color, QrCode};
+use zeroize::Zeroizing;
+
+// Pixels per module of the QR code, and the modules of white around it that
+// scanners need
+const QR_MODULE_SIZE: f32 = 6.0;
+const QR_QUIET_ZONE: usize = 4;
+const WORDS_PER_ROW: usize = 4;
+
+// What the shares entered so far amount to, worked out again whenever they
+// change
+#[derive(Debug, Default, PartialEq)]
+pub struct Recovery {
+    // Lines that can't be used, with what to do about them
+    pub problems: Vec<(String, Vec<String>)>,
+    pub progress: Vec<String>,
+    // The mnemonic, or the secret as hex when it isn't one, [redacted] where
+    // the policy keeps it off the screen
+    pub recovered: Option<Zeroizing<String>>,
+}
+
+impl Recovery {
+    // Shares one per line, blank lines and `#` comments ignored as in files
+    pub fn of(text: &str, policy: &Policy) -> Recovery {
+        let lines: Vec<String> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        let names: Vec<String> = (1..=lines.len())
+            .map(|line| format!("the share on line {}", line))
+            .collect();
+        let line_problems = diagnose_lines(&lines);
+        let problems = line_problems
+            .iter()
+            .map(|problem| {
+                (
+                    format!("Line {}: {}", problem.index + 1, problem.message),
+                    suggestions(&problem.problem, &names),
+                )
+            })
+            .collect();
+        let usable: Vec<String> = lines
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| line_problems.iter().all(|problem| problem.index != *index))
+            .map(|(_, line)| line)
+            .collect();
+
+        let mut recovery = Recovery {
+            problems,
+            ..Recovery::default()
+        };
+        if usable.is_empty() {
+            return recovery;
+        }
+        let Ok(sets) = status(usable.clone(), &false, None) else {
+            return recovery;
+        };
+        recovery
+            .progress
+            .push(format!("{} usable share(s)", usable.len()));
+        for set in &sets {
+            for group in &set.groups {
+                recovery.progress.push(format!(
+                    "Group {}: {} of {} shares{}",
+                    group.group,
+                    group.present,
+                    group
+                        .member_threshold
+                        .map_or("?".to_string(), |threshold| threshold.to_string()),
+                    if group.satisfied { ", complete" } else { "" }
+                ));
+            }
+            if let Some(missing) = set.groups_missing.filter(|missing| *missing > 0) {
+                recovery
+                    .progress
+                    .push(format!("{} more group(s) needed", missing));
+            }
+            recovery.progress.extend(set.suggestions.iter().cloned());
+        }
+        if sets.iter().any(|set| set.recoverable) {
+            match recover_secret(usable, &false) {
+                Ok(secret) => {
+                    recovery.recovered = Some(Zeroizing::new(
+                        match Mnemonic::from_entropy(&secret, Language::English) {
+                            Ok(mnemonic) => {
+                                policy.shown(Field::Mnemonic, Channel::Display, mnemonic.phrase())
+                            }
+                            Err(_) => policy.shown(
+                                Field::Entropy,
+                                Channel::Display,
+                                format_args!("0x{}", hex::encode(secret.as_slice())),
+                            ),
+                        },
+                    ))
+                }
+                Err(error) => recovery.progress.push(error.to_string()),
+            }
+        }
+        recovery
+    }
+}
+
+// The window: shares go in on the left, what they amount to shows on the
+// right. Nothing is saved, and everything is wiped on Clear and on exit.
+#[derive(Default)]
+struct App {
+    input: Zeroizing<String>,
+    hide_shares: bool,
+    show_qr: bool,
+    recovery: Recovery,
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::SidePanel::left("shares")
+            .resizable(true)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.heading("Shares");
+                ui.label("Type or paste the shares, one per line, in any format.");
+                ui.checkbox(&mut self.hide_shares, "Hide the shares as they are typed");
+                let edit = TextEdit::multiline(&mut *self.input)
+                    .password(self.hide_shares)
+                    .desired_rows(16)
+                    .desired_width(f32::INFINITY)
+                    .font(egui::TextStyle::Monospace);
+                if ui.add(edit).changed() {
+                    self.recovery = Recovery::of(&self.input, policy::current());
+                }
+                if ui.button("Clear everything").clicked() {
+                    *self = App {
+                        hide_shares: self.hide_shares,
+                        ..App::default()
+                    };
+                }
+            });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| self.results(ui));
+        });
+    }
+}
+
+impl App {
+    fn results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Progress");
+        if self.recovery.progress.is_empty() && self.recovery.problems.is_empty() {
+            ui.label("No shares yet.");
+        }
+        for line in &self.recovery.progress {
+            ui.label(line);
+        }
+        for (problem, suggestions) in &self.recovery.problems {
+            ui.colored_label(Color32::from_rgb(200, 80, 0), problem);
+            for suggestion in suggestions {
+                ui.label(format!("  - {}", suggestion));
+            }
+        }
+
+        let Some(recovered) = &self.recovery.recovered else {
+            return;
+        };
+        ui.separator();
+        ui.heading("Recovered");
+        if recovered.as_str() == policy::REDACTED {
+            ui.label(policy::REDACTED);
+            return;
+        }
+        let words: Vec<&str> = recovered.split(' ').collect();
+        egui::Grid::new("words").striped(true).show(ui, |ui| {
+            for (row, chunk) in words.chunks(WORDS_PER_ROW).enumerate() {
+                for (column, word) in chunk.iter().enumerate() {
+                    let number = row * WORDS_PER_ROW + column + 1;
+                    ui.label(
+                        RichText::new(format!("{:>2}. {}", number, word))
+                            .monospace()
+                            .size(18.0),
+                    );
+                }
+                ui.end_row();
+            }
+        });
+        ui.checkbox(
+            &mut self.show_qr,
+            "Show as a QR code, to scan into a wallet",
+        );
+        if self.show_qr {
+            if let Err(error) = qr_code(ui, recovered) {
+                ui.colored_label(Color32::RED, error.to_string());
+            }
+        }
+    }
+}
+
+fn qr_code(ui: &mut egui::Ui, text: &str) -> Result<(), Error> {
+    let code = QrCode::new(text.as_bytes()).map_err(|error| anyhow!("No QR code: {}", error))?;
+    let width = code.width();
+    let size = (width + 2 * QR_QUIET_ZONE) as f32 * QR_MODULE_SIZE;
+    let (response, painter) = ui.allocate_painter(egui::vec2(size, size), Sense::hover());
+    let origin = response.rect.min;
+    painter.rect_filled(response.rect, 0.0, Color32::WHITE);
+    for (index, color) in code.to_colors().iter().enumerate() {
+        if *color == Color::Dark {
+            let (x, y) = (index % width + QR_QUIET_ZONE, index / width + QR_QUIET_ZONE);
+            let min = origin + egui::vec2(x as f32, y as f32) * QR_MODULE_SIZE;
+            painter.rect_filled(
+                egui::Rect::from_min_size(min, egui::vec2(QR_MODULE_SIZE, QR_MODULE_SIZE)),
+                0.0,
+                Color32::BLACK,
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn run() -> Result<(), Error> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_title("SSKR recovery")
+            .with_inner_size([1100.0, 700.0]),
+        ..eframe::NativeOptions::default()
+    };
+    eframe::run_native(
+        "SSKR recovery",
+        options,
+        Box::new(|_creation| Ok(Box::<App>::default())),
+    )
+    .map_err(|error| anyhow!("Unable to open the window: {}", error))
+}
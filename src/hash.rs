@@ -0,0 +1,72 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// The algorithms the hashes this tool records can be made with, for
+// organizations whose crypto policy prescribes one. SHA-256 is the default,
+// and the only one earlier releases wrote.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Blake3 => "BLAKE3",
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == HashAlgorithm::default()
+    }
+
+    pub fn hash(&self, data: &[u8]) -> Hash {
+        match self {
+            HashAlgorithm::Sha256 => Hash::Sha256(hex::encode(Sha256::digest(data))),
+            HashAlgorithm::Blake3 => Hash::Blake3(blake3::hash(data).to_hex().to_string()),
+        }
+    }
+
+    // A hash with no value yet, in place of one over the document it is in
+    pub fn unset(&self) -> Hash {
+        match self {
+            HashAlgorithm::Sha256 => Hash::Sha256(String::new()),
+            HashAlgorithm::Blake3 => Hash::Blake3(String::new()),
+        }
+    }
+}
+
+// A hash as manifests and bundles record it, under the name of its algorithm,
+// as in "sha256": "ab12...", so checking it needs no option to say which one
+// it is
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Hash {
+    Sha256(String),
+    Blake3(String),
+}
+
+impl Hash {
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            Hash::Sha256(_) => HashAlgorithm::Sha256,
+            Hash::Blake3(_) => HashAlgorithm::Blake3,
+        }
+    }
+
+    pub fn hex(&self) -> &str {
+        match self {
+            Hash::Sha256(hex) | Hash::Blake3(hex) => hex,
+        }
+    }
+
+    // Whether `data` has this hash, made with the same algorithm
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.algorithm().hash(data) == *self
+    }
+}
@@ -0,0 +1,95 @@
+use crate::manifest::SeedManifest;
+use serde::Serialize;
+
+// A card for each custodian saying what their share is for and how it is used,
+// so that knowing how to recover doesn't rest with whoever made the split. It
+// holds nothing of the share or the secret and can be kept with the share or
+// apart from it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HintCard {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_name: Option<String>,
+    pub identifier: String,
+    pub group: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    pub member: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custodian: Option<String>,
+    // The other holders of the group's shares needed along with this one
+    pub co_custodians: usize,
+    pub member_count: usize,
+    pub group_threshold: usize,
+    pub group_count: usize,
+    pub command: String,
+}
+
+// The cards of every share, by group, in the order of the shares
+pub fn hint_cards(seed: &SeedManifest) -> Vec<Vec<HintCard>> {
+    seed.groups
+        .iter()
+        .map(|group| {
+            (0..group.member_count)
+                .map(|member| HintCard {
+                    seed_name: seed.name.clone(),
+                    identifier: seed.identifier.clone(),
+                    group: group.group,
+                    group_name: group.label.clone(),
+                    member: member + 1,
+                    custodian: group.custodians.get(member).cloned(),
+                    co_custodians: group.member_threshold.saturating_sub(1),
+                    member_count: group.member_count,
+                    group_threshold: seed.group_threshold,
+                    group_count: seed.groups.len(),
+                    command: seed.recover_command(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+impl HintCard {
+    pub fn render(&self) -> String {
+        let mut card = String::from("SSKR RECOVERY HINT CARD - HOLDS NO SHARE OR SECRET\n\n");
+        match &self.seed_name {
+            Some(name) => card.push_str(&format!(
+                "Seed:       \"{}\" (share set {})\n",
+                name, self.identifier
+            )),
+            None => card.push_str(&format!("Seed:       share set {}\n", self.identifier)),
+        }
+        let group = match &self.group_name {
+            Some(name) => format!("{} (group {})", name, self.group),
+            None => format!("group {}", self.group),
+        };
+        card.push_str(&format!(
+            "Share:      {} of {} in {}\n",
+            self.member, self.member_count, group
+        ));
+        if let Some(custodian) = &self.custodian {
+            card.push_str(&format!("Held by:    {}\n", custodian));
+        }
+
+        card.push('\n');
+        card.push_str(&match self.co_custodians {
+            0 => "This share recovers its group on its own.\n".to_string(),
+            1 => "Recovery needs this share and 1 other share of the same group.\n".to_string(),
+            count => format!(
+                "Recovery needs this share and {} other shares of the same group.\n",
+                count
+            ),
+        });
+        if self.group_count > 1 {
+            card.push_str(&format!(
+                "Shares of {} of the {} groups are needed in all.\n",
+                self.group_threshold, self.group_count
+            ));
+        }
+        card.push_str(&format!(
+            "\nTool:       sskr-tool, version {} or later\n",
+            env!("CARGO_PKG_VERSION")
+        ));
+        card.push_str(&format!("Command:    {}\n", self.command));
+        card
+    }
+}
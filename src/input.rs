@@ -0,0 +1,158 @@
+use crate::share_format::{join_segments, join_wrapped};
+use anyhow::{Context, Error};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use zeroize::Zeroizing;
+
+pub const STDIN: &str = "-";
+
+// A share line and where it was read, to point at it when it has a problem
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareLine {
+    pub source: String,
+    // Numbered from 1, counting blank lines and comments
+    pub line: usize,
+    pub text: String,
+}
+
+impl fmt::Display for ShareLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.source, self.line)
+    }
+}
+
+// Collects share lines from every source in order. A source is a file, `-` for
+// stdin, or a directory whose .txt files are read in name order (so manifests
+// and other artifacts written next to the shares are left alone). Blank lines
+// and `#` comments are dropped, and the segments of a share are joined.
+pub fn read_share_lines(sources: &[String]) -> Result<Vec<String>, Error> {
+    Ok(read_located_share_lines(sources)?
+        .into_iter()
+        .map(|line| line.text)
+        .collect())
+}
+
+pub fn read_located_share_lines(sources: &[String]) -> Result<Vec<ShareLine>, Error> {
+    let mut lines = vec![];
+
+    for source in sources {
+        for (name, contents) in read_source(source)? {
+            lines.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .enumerate()
+                    .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+                    .map(|(line_num, line)| ShareLine {
+                        source: name.clone(),
+                        line: line_num + 1,
+                        text: strip_numbering(line).to_string(),
+                    }),
+            );
+        }
+    }
+
+    // Shares wrapped across lines or split into segments count as one share,
+    // at their first line
+    let texts = lines
+        .iter()
+        .map(|line| line.text.as_str())
+        .collect::<Vec<_>>();
+    Ok(join_lines(&texts)
+        .into_iter()
+        .map(|(index, text)| ShareLine {
+            text,
+            ..lines[index].clone()
+        })
+        .collect())
+}
+
+// The shares in text laid out like a share file, as pasted into a form
+// rather than read from a file
+pub fn share_lines(contents: &str) -> Vec<String> {
+    let lines = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(strip_numbering)
+        .collect::<Vec<_>>();
+    join_lines(&lines)
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect()
+}
+
+// The numbering split puts before each share ("3: "), copied along with it
+fn strip_numbering(line: &str) -> &str {
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == line.len() {
+        return line;
+    }
+    match rest.strip_prefix([':', '.', ')']) {
+        Some(share) => share.trim_start(),
+        None => line,
+    }
+}
+
+// Joins wrapped shares, then segments, keeping the index of the line each
+// result starts at
+fn join_lines(lines: &[&str]) -> Vec<(usize, String)> {
+    let wrapped = join_wrapped(lines);
+    let texts = wrapped
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>();
+    join_segments(&texts)
+        .into_iter()
+        .map(|(index, text)| (wrapped[index].0, text))
+        .collect()
+}
+
+// The contents of every file of a source, by file name
+fn read_source(source: &str) -> Result<Vec<(String, String)>, Error> {
+    if source == STDIN {
+        let mut contents = String::new();
+        io::stdin()
+            .read_to_string(&mut contents)
+            .context("Unable to read shares from stdin")?;
+        return Ok(vec![("stdin".to_string(), contents)]);
+    }
+
+    let path = Path::new(source);
+    if !path.is_dir() {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read file \"{}\"", source))?;
+        return Ok(vec![(source.to_string(), contents)]);
+    }
+
+    let mut files = vec![];
+    for entry in
+        fs::read_dir(path).with_context(|| format!("Unable to read directory \"{}\"", source))?
+    {
+        let file = entry?.path();
+        if file.is_file() && file.extension().is_some_and(|ext| ext == "txt") {
+            files.push(file);
+        }
+    }
+    files.sort();
+
+    files
+        .iter()
+        .map(|file| {
+            let contents = fs::read_to_string(file)
+                .with_context(|| format!("Unable to read file \"{}\"", file.display()))?;
+            Ok((file.display().to_string(), contents))
+        })
+        .collect()
+}
+
+// Reads a secret from the terminal without echoing it, so it ends up neither
+// on screen nor in the shell history like a command line argument would
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_hidden(prompt: &str) -> Result<Zeroizing<String>, Error> {
+    let input = rpassword::prompt_password(prompt).context("Unable to read from the terminal")?;
+    Ok(Zeroizing::new(input.trim().to_string()))
+}
@@ -0,0 +1,101 @@
+use crate::manifest::SeedManifest;
+use crate::output::ShareMetadataOutput;
+use crate::share_format::{share_checksum, EncodedShare, ShareChecksum, ShareFormat};
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, Write};
+
+// What inspect says about a single share: its metadata, the names a manifest
+// records for it, and its checksum. The checksum's payload is the share
+// itself, so a public inspection, safe to read out or paste anywhere, leaves
+// the checksum out.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Inspection {
+    pub format: ShareFormat,
+    #[serde(flatten)]
+    pub metadata: ShareMetadataOutput,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<ShareChecksum>,
+}
+
+impl Inspection {
+    pub fn new(share: &EncodedShare) -> Inspection {
+        Inspection {
+            format: share.format(),
+            metadata: ShareMetadataOutput::from(share),
+            name: None,
+            label: None,
+            checksum: Some(share_checksum(share.share())),
+        }
+    }
+
+    // The names the split's manifest records for the share's seed and group
+    pub fn with_seed(self, seed: &SeedManifest) -> Inspection {
+        let label = seed
+            .groups
+            .iter()
+            .find(|group| group.group == self.metadata.group_index + 1)
+            .and_then(|group| group.label.clone());
+        Inspection {
+            name: seed.name.clone(),
+            label,
+            ..self
+        }
+    }
+
+    pub fn public(self) -> Inspection {
+        Inspection {
+            checksum: None,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let metadata = &self.metadata;
+        writeln!(f, "Format:           {}", self.format)?;
+        writeln!(f, "Identifier:       {}", metadata.identifier)?;
+        if let Some(name) = &self.name {
+            writeln!(f, "Seed name:        {}", name)?;
+        }
+        match &self.label {
+            Some(label) => writeln!(
+                f,
+                "Group:            {} ({})",
+                metadata.group_index + 1,
+                label
+            )?,
+            None => writeln!(f, "Group:            {}", metadata.group_index + 1)?,
+        }
+        writeln!(f, "Group threshold:  {}", metadata.group_threshold)?;
+        writeln!(f, "Group count:      {}", metadata.group_count)?;
+        writeln!(f, "Member:           {}", metadata.member_index + 1)?;
+        writeln!(f, "Member threshold: {}", metadata.member_threshold)?;
+        writeln!(f, "Secret length:    {} bytes", metadata.secret_length)?;
+        if let Some(checksum) = &self.checksum {
+            let mut text = vec![];
+            write_checksum(&mut text, checksum, "").map_err(|_| fmt::Error)?;
+            f.write_str(&String::from_utf8_lossy(&text))?;
+        }
+        Ok(())
+    }
+}
+
+// Lined up with the inspect fields, or indented under a share of a split
+pub fn write_checksum(
+    out: &mut impl Write,
+    checksum: &ShareChecksum,
+    indent: &str,
+) -> io::Result<()> {
+    writeln!(out, "{}Payload:          {}", indent, checksum.payload)?;
+    writeln!(
+        out,
+        "{}CRC-32:           {} ({})",
+        indent, checksum.crc32, checksum.bytewords
+    )
+}
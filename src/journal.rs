@@ -0,0 +1,186 @@
+use crate::policy::{self, Channel, Field};
+use crate::recover::decode_share;
+use crate::share_format::{encode, ShareFormat};
+use anyhow::{bail, Context, Error};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A recovery session is collected into a journal, which is a plain share file
+// (recover and status read it like any other, the rest is `#` comments) where
+// every share is preceded by an entry line chaining it to the one before:
+//
+//   # entry <number> <unix time> <hash>
+//   <share>
+//
+// The hash covers the previous entry's hash, the number, the time and the
+// share, so editing, reordering or removing an entry breaks every hash after
+// it. Cutting entries off the end leaves a valid chain, which is why each
+// addition reports the new head to be written down outside the journal.
+pub const HEADER: &str = "# sskr-tool session journal v1";
+const ENTRY_PREFIX: &str = "# entry ";
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub number: usize,
+    pub time: u64,
+    pub share: String,
+    pub hash: String,
+}
+
+fn entry_hash(previous: &str, number: usize, time: u64, share: &str) -> String {
+    let data = format!("{}\n{}\n{}\n{}", previous, number, time, share);
+    hex::encode(Sha256::digest(data.as_bytes()))
+}
+
+// Reads the journal and checks its whole chain, failing at the first entry
+// that doesn't match
+pub fn read_journal(path: &Path) -> Result<Vec<JournalEntry>, Error> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Unable to read journal \"{}\"", path.display()))?;
+    let mut lines = contents.lines().enumerate();
+
+    match lines.next() {
+        Some((_, HEADER)) => (),
+        _ => bail!("\"{}\" is not a session journal", path.display()),
+    }
+
+    let mut entries: Vec<JournalEntry> = vec![];
+    while let Some((line_num, line)) = lines.next() {
+        let line_num = line_num + 1;
+        let Some(fields) = line.strip_prefix(ENTRY_PREFIX) else {
+            bail!("Line {}: expected an entry, found \"{}\"", line_num, line);
+        };
+        let (number, time, hash) = match fields.split(' ').collect::<Vec<_>>()[..] {
+            [number, time, hash] => (number.parse::<usize>(), time.parse::<u64>(), hash),
+            _ => bail!("Line {}: malformed entry \"{}\"", line_num, line),
+        };
+        let (Ok(number), Ok(time)) = (number, time) else {
+            bail!("Line {}: malformed entry \"{}\"", line_num, line);
+        };
+        let Some((_, share)) = lines.next() else {
+            bail!(
+                "Line {}: entry {} has no share, the journal was cut off",
+                line_num,
+                number
+            );
+        };
+
+        if number != entries.len() + 1 {
+            bail!(
+                "Line {}: expected entry {}, found entry {}, entries were removed or reordered",
+                line_num,
+                entries.len() + 1,
+                number
+            );
+        }
+        let previous = entries.last().map_or(GENESIS, |entry| &entry.hash);
+        if entry_hash(previous, number, time, share) != hash {
+            bail!(
+                "Line {}: entry {} doesn't match its hash, it or an entry before it was edited",
+                line_num,
+                number
+            );
+        }
+
+        entries.push(JournalEntry {
+            number,
+            time,
+            share: share.to_string(),
+            hash: hash.to_string(),
+        });
+    }
+
+    Ok(entries)
+}
+
+// Checks the journal and, given a head written down earlier, that the journal
+// still reaches it with the same entry number
+pub fn verify_journal(path: &Path, head: Option<&str>) -> Result<Vec<JournalEntry>, Error> {
+    let entries = read_journal(path)?;
+
+    if let Some(head) = head {
+        let head = head.trim().to_lowercase();
+        let (number, hash) = match head.split_once(':') {
+            Some((number, hash)) => (Some(number.trim_start_matches('#')), hash),
+            None => (None, head.as_str()),
+        };
+        let Some(entry) = entries.iter().find(|entry| entry.hash == hash) else {
+            bail!(
+                "Head {} is not in the journal, which has {} entries; it was cut off or replaced",
+                head,
+                entries.len()
+            );
+        };
+        if number.is_some_and(|number| number != entry.number.to_string()) {
+            bail!("Head {} is entry {} of the journal", head, entry.number);
+        }
+    }
+
+    Ok(entries)
+}
+
+// Appends the shares that aren't in the journal yet, creating it if needed.
+// A journal that fails verification is never appended to. Returns the new
+// entries.
+pub fn append_shares(
+    path: &Path,
+    lines: &[String],
+    minimal: &bool,
+) -> Result<Vec<JournalEntry>, Error> {
+    let format = if *minimal {
+        ShareFormat::Minimal
+    } else {
+        ShareFormat::Bytewords
+    };
+    policy::check(Field::Shares, Channel::Log)?;
+    let shares = lines
+        .iter()
+        .map(|line| Ok(encode(&decode_share(line)?, format)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut entries = if path.exists() {
+        read_journal(path)?
+    } else {
+        fs::write(path, format!("{}\n", HEADER))
+            .with_context(|| format!("Unable to create journal \"{}\"", path.display()))?;
+        vec![]
+    };
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let first_new = entries.len();
+    let mut appended = String::new();
+
+    for share in shares {
+        if entries.iter().any(|entry| entry.share == share) {
+            continue;
+        }
+        let number = entries.len() + 1;
+        let previous = entries.last().map_or(GENESIS, |entry| &entry.hash);
+        let hash = entry_hash(previous, number, time, &share);
+        appended.push_str(&format!(
+            "{}{} {} {}\n{}\n",
+            ENTRY_PREFIX, number, time, hash, share
+        ));
+        entries.push(JournalEntry {
+            number,
+            time,
+            share,
+            hash,
+        });
+    }
+
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(appended.as_bytes()))
+        .with_context(|| format!("Unable to append to journal \"{}\"", path.display()))?;
+
+    Ok(entries.split_off(first_new))
+}
@@ -0,0 +1,93 @@
+use anyhow::{bail, Error};
+use bip39::{Language, Mnemonic};
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum MnemonicLanguage {
+    #[value(alias = "en")]
+    English,
+    #[value(alias = "zh-hans")]
+    ChineseSimplified,
+    #[value(alias = "zh-hant")]
+    ChineseTraditional,
+    #[value(alias = "fr")]
+    French,
+    #[value(alias = "it")]
+    Italian,
+    #[value(alias = "ja")]
+    Japanese,
+    #[value(alias = "ko")]
+    Korean,
+    #[value(alias = "es")]
+    Spanish,
+}
+
+static ALL_LANGUAGES: [MnemonicLanguage; 8] = [
+    MnemonicLanguage::English,
+    MnemonicLanguage::ChineseSimplified,
+    MnemonicLanguage::ChineseTraditional,
+    MnemonicLanguage::French,
+    MnemonicLanguage::Italian,
+    MnemonicLanguage::Japanese,
+    MnemonicLanguage::Korean,
+    MnemonicLanguage::Spanish,
+];
+
+impl MnemonicLanguage {
+    pub fn bip39(self) -> Language {
+        match self {
+            MnemonicLanguage::English => Language::English,
+            MnemonicLanguage::ChineseSimplified => Language::ChineseSimplified,
+            MnemonicLanguage::ChineseTraditional => Language::ChineseTraditional,
+            MnemonicLanguage::French => Language::French,
+            MnemonicLanguage::Italian => Language::Italian,
+            MnemonicLanguage::Japanese => Language::Japanese,
+            MnemonicLanguage::Korean => Language::Korean,
+            MnemonicLanguage::Spanish => Language::Spanish,
+        }
+    }
+
+    pub fn from_bip39(language: Language) -> MnemonicLanguage {
+        *ALL_LANGUAGES
+            .iter()
+            .find(|candidate| candidate.bip39() == language)
+            .unwrap()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MnemonicLanguage::English => "english",
+            MnemonicLanguage::ChineseSimplified => "chinese-simplified",
+            MnemonicLanguage::ChineseTraditional => "chinese-traditional",
+            MnemonicLanguage::French => "french",
+            MnemonicLanguage::Italian => "italian",
+            MnemonicLanguage::Japanese => "japanese",
+            MnemonicLanguage::Korean => "korean",
+            MnemonicLanguage::Spanish => "spanish",
+        }
+    }
+}
+
+// Word lists overlap between some languages (most notably the two Chinese
+// lists), so a phrase is only auto-detected when exactly one language accepts
+// it, checksum included.
+pub fn detect_language(phrase: &str) -> Result<Language, Error> {
+    let candidates: Vec<MnemonicLanguage> = ALL_LANGUAGES
+        .iter()
+        .copied()
+        .filter(|language| Mnemonic::from_phrase(phrase, language.bip39()).is_ok())
+        .collect();
+
+    match candidates.as_slice() {
+        [language] => Ok(language.bip39()),
+        [] => bail!("Not a valid BIP-39 mnemonic in any supported language"),
+        _ => bail!(
+            "Mnemonic is valid in several languages ({}), specify one with --language",
+            candidates
+                .iter()
+                .map(|language| language.name())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ),
+    }
+}
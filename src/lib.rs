@@ -0,0 +1,77 @@
+pub mod api;
+pub mod artifacts;
+pub mod audit;
+pub mod batch;
+pub mod bundle;
+mod bytewords;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod checklist;
+pub mod codex32;
+pub mod confirm;
+pub mod diagnostics;
+pub mod drill;
+pub mod dual_control;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod duress;
+pub mod encryption;
+pub mod entropy;
+pub mod envelope;
+pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod escrow;
+pub mod estate;
+pub mod example;
+#[cfg(feature = "gui")]
+pub mod gui;
+pub mod hash;
+pub mod hint;
+pub mod input;
+pub mod inspect;
+pub mod journal;
+pub mod language;
+pub mod limits;
+pub mod locale;
+pub mod manifest;
+pub mod media;
+pub mod migrate;
+pub mod mirror;
+pub mod naming;
+#[cfg(feature = "smartcard")]
+pub mod nfc;
+pub mod output;
+pub mod paper;
+pub mod paranoid;
+#[cfg(feature = "smartcard")]
+pub mod pcsc;
+pub mod policy;
+pub mod profile;
+pub mod readback;
+pub mod recover;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod review;
+mod sanitize;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scan;
+pub mod sealed;
+pub mod seed_ur;
+pub mod selftest;
+pub mod share_format;
+#[cfg(feature = "smartcard")]
+pub mod smartcard;
+pub mod spec;
+pub mod split;
+mod sskr_shares;
+pub mod status;
+pub mod testdata;
+pub mod tones;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tui;
+pub mod validate;
+pub mod verify;
+#[cfg(feature = "verify-only")]
+pub mod verify_only;
+pub mod wallet;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use validate::{validate_share, ShareDiagnostics};
@@ -0,0 +1,38 @@
+// The limits of SSKR, as the sskr crate sets them, so front ends can check
+// what a user enters before splitting without depending on that crate or
+// repeating its numbers
+
+// The most groups a split can have, and the most shares in a group
+pub const MAX_GROUPS: usize = sskr::MAX_GROUPS_COUNT;
+pub const MAX_MEMBERS: usize = sskr::MAX_SHARE_COUNT;
+
+// Secrets are an even number of bytes between these two
+pub const MIN_SECRET_LEN: usize = sskr::MIN_SECRET_LEN;
+pub const MAX_SECRET_LEN: usize = sskr::MAX_SECRET_LEN;
+
+// The identifier, group and member fields at the start of every share
+pub const METADATA_SIZE_BYTES: usize = sskr::METADATA_SIZE_BYTES;
+
+pub const fn max_groups() -> usize {
+    MAX_GROUPS
+}
+
+pub const fn max_members() -> usize {
+    MAX_MEMBERS
+}
+
+pub const fn is_valid_secret_len(len: usize) -> bool {
+    len >= MIN_SECRET_LEN && len <= MAX_SECRET_LEN && len.is_multiple_of(2)
+}
+
+// The length of the secret a share of this length holds, when it can hold one
+pub const fn secret_len(share_len: usize) -> Option<usize> {
+    match share_len.checked_sub(METADATA_SIZE_BYTES) {
+        Some(len) if is_valid_secret_len(len) => Some(len),
+        _ => None,
+    }
+}
+
+pub const fn is_valid_share_len(share_len: usize) -> bool {
+    secret_len(share_len).is_some()
+}
@@ -0,0 +1,229 @@
+use clap::ValueEnum;
+use std::fmt::Display;
+
+// Wording of the thresholds and group numbers in text output. Numbers,
+// identifiers and shares are always left-to-right, so in right-to-left
+// locales they are wrapped in directional isolates to keep their order.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// German
+    De,
+    /// Spanish
+    Es,
+    /// French
+    Fr,
+    /// Arabic (right-to-left)
+    Ar,
+}
+
+const LEFT_TO_RIGHT_ISOLATE: char = '\u{2066}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+impl Locale {
+    pub fn is_rtl(self) -> bool {
+        self == Locale::Ar
+    }
+
+    pub fn ltr(self, text: impl Display) -> String {
+        if self.is_rtl() {
+            format!(
+                "{}{}{}",
+                LEFT_TO_RIGHT_ISOLATE, text, POP_DIRECTIONAL_ISOLATE
+            )
+        } else {
+            text.to_string()
+        }
+    }
+
+    // "2 of 3"
+    pub fn threshold(self, present: impl Display, of: impl Display) -> String {
+        let (present, of) = (self.ltr(present), self.ltr(of));
+        match self {
+            Locale::En => format!("{} of {}", present, of),
+            Locale::De => format!("{} von {}", present, of),
+            Locale::Es => format!("{} de {}", present, of),
+            Locale::Fr => format!("{} sur {}", present, of),
+            Locale::Ar => format!("{} من {}", present, of),
+        }
+    }
+
+    // "Group 2", groups are numbered from 1
+    pub fn group(self, group: impl Display) -> String {
+        let group = self.ltr(group);
+        match self {
+            Locale::En => format!("Group {}", group),
+            Locale::De => format!("Gruppe {}", group),
+            Locale::Es => format!("Grupo {}", group),
+            Locale::Fr => format!("Groupe {}", group),
+            Locale::Ar => format!("المجموعة {}", group),
+        }
+    }
+
+    pub fn share_set(self, identifier: &str) -> String {
+        let identifier = self.ltr(identifier);
+        match self {
+            Locale::En => format!("Share set {}", identifier),
+            Locale::De => format!("Anteilssatz {}", identifier),
+            Locale::Es => format!("Conjunto de partes {}", identifier),
+            Locale::Fr => format!("Jeu de parts {}", identifier),
+            Locale::Ar => format!("مجموعة الحصص {}", identifier),
+        }
+    }
+
+    // The BIP-32 fingerprint of the seed
+    pub fn fingerprint(self, fingerprint: &str) -> String {
+        let fingerprint = self.ltr(fingerprint);
+        match self {
+            Locale::En => format!("Fingerprint {}", fingerprint),
+            Locale::De => format!("Fingerabdruck {}", fingerprint),
+            Locale::Es => format!("Huella {}", fingerprint),
+            Locale::Fr => format!("Empreinte {}", fingerprint),
+            Locale::Ar => format!("البصمة {}", fingerprint),
+        }
+    }
+
+    pub fn split_header(self, group_threshold: usize) -> String {
+        let groups = self.ltr(group_threshold);
+        match self {
+            Locale::En => format!(
+                "SSKR shares - need to recover at least {} group(s) to recover mnemonic",
+                groups
+            ),
+            Locale::De => format!(
+                "SSKR-Anteile - mindestens {} Gruppe(n) nötig, um die Mnemonic wiederherzustellen",
+                groups
+            ),
+            Locale::Es => format!(
+                "Partes SSKR - se necesitan al menos {} grupo(s) para recuperar la mnemotécnica",
+                groups
+            ),
+            Locale::Fr => format!(
+                "Parts SSKR - il faut au moins {} groupe(s) pour récupérer la phrase mnémonique",
+                groups
+            ),
+            Locale::Ar => format!(
+                "حصص SSKR - يلزم استعادة {} مجموعة على الأقل لاستعادة العبارة",
+                groups
+            ),
+        }
+    }
+
+    pub fn group_needs(self, group: impl Display, threshold: usize, count: usize) -> String {
+        let (group, threshold) = (self.group(group), self.threshold(threshold, count));
+        match self {
+            Locale::En => format!("{} - need {} shares to recover group", group, threshold),
+            Locale::De => format!("{} - {} Anteilen nötig", group, threshold),
+            Locale::Es => format!("{} - se necesitan {} partes", group, threshold),
+            Locale::Fr => format!("{} - il faut {} parts", group, threshold),
+            Locale::Ar => format!("{} - يلزم {} حصص لاستعادة المجموعة", group, threshold),
+        }
+    }
+
+    pub fn group_progress(
+        self,
+        group: usize,
+        present: usize,
+        threshold: Option<usize>,
+        missing: Option<usize>,
+    ) -> String {
+        let threshold = self.threshold(
+            present,
+            threshold.map_or("?".to_string(), |threshold| threshold.to_string()),
+        );
+        let progress = match (self, missing) {
+            (Locale::En, Some(0)) => "satisfied".to_string(),
+            (Locale::En, Some(missing)) => format!("needs {} more", missing),
+            (Locale::En, None) => "threshold unknown".to_string(),
+            (Locale::De, Some(0)) => "erfüllt".to_string(),
+            (Locale::De, Some(missing)) => format!("{} fehlen noch", missing),
+            (Locale::De, None) => "Schwelle unbekannt".to_string(),
+            (Locale::Es, Some(0)) => "completo".to_string(),
+            (Locale::Es, Some(missing)) => format!("faltan {}", missing),
+            (Locale::Es, None) => "umbral desconocido".to_string(),
+            (Locale::Fr, Some(0)) => "atteint".to_string(),
+            (Locale::Fr, Some(missing)) => format!("il en manque {}", missing),
+            (Locale::Fr, None) => "seuil inconnu".to_string(),
+            (Locale::Ar, Some(0)) => "مكتملة".to_string(),
+            (Locale::Ar, Some(missing)) => format!("ينقصها {}", self.ltr(missing)),
+            (Locale::Ar, None) => "الحد غير معروف".to_string(),
+        };
+        let group = self.group(group);
+        match self {
+            Locale::En => format!(
+                "{}: {} needed shares present - {}",
+                group, threshold, progress
+            ),
+            Locale::De => format!(
+                "{}: {} nötigen Anteilen vorhanden - {}",
+                group, threshold, progress
+            ),
+            Locale::Es => format!(
+                "{}: {} partes necesarias presentes - {}",
+                group, threshold, progress
+            ),
+            Locale::Fr => format!(
+                "{} : {} parts nécessaires présentes - {}",
+                group, threshold, progress
+            ),
+            Locale::Ar => format!(
+                "{}: {} من الحصص اللازمة متوفرة - {}",
+                group, threshold, progress
+            ),
+        }
+    }
+
+    pub fn groups_progress(
+        self,
+        satisfied: usize,
+        group_threshold: Option<usize>,
+        missing: Option<usize>,
+    ) -> String {
+        let count = self.ltr(satisfied);
+        match (self, group_threshold, missing) {
+            (Locale::En, _, Some(0)) => format!("{} group(s) satisfied - enough to recover", count),
+            (Locale::En, Some(threshold), Some(missing)) => format!(
+                "{} required group(s) satisfied - need {} more group(s)",
+                self.threshold(satisfied, threshold),
+                missing
+            ),
+            (Locale::En, _, _) => format!("{} group(s) satisfied", count),
+            (Locale::De, _, Some(0)) => {
+                format!("{} Gruppe(n) erfüllt - genug zur Wiederherstellung", count)
+            }
+            (Locale::De, Some(threshold), Some(missing)) => format!(
+                "{} nötigen Gruppe(n) erfüllt - {} weitere Gruppe(n) nötig",
+                self.threshold(satisfied, threshold),
+                missing
+            ),
+            (Locale::De, _, _) => format!("{} Gruppe(n) erfüllt", count),
+            (Locale::Es, _, Some(0)) => {
+                format!("{} grupo(s) completo(s) - suficiente para recuperar", count)
+            }
+            (Locale::Es, Some(threshold), Some(missing)) => format!(
+                "{} grupo(s) necesarios completos - faltan {} grupo(s)",
+                self.threshold(satisfied, threshold),
+                missing
+            ),
+            (Locale::Es, _, _) => format!("{} grupo(s) completo(s)", count),
+            (Locale::Fr, _, Some(0)) => {
+                format!("{} groupe(s) atteint(s) - suffisant pour récupérer", count)
+            }
+            (Locale::Fr, Some(threshold), Some(missing)) => format!(
+                "{} groupe(s) requis atteint(s) - il manque {} groupe(s)",
+                self.threshold(satisfied, threshold),
+                missing
+            ),
+            (Locale::Fr, _, _) => format!("{} groupe(s) atteint(s)", count),
+            (Locale::Ar, _, Some(0)) => format!("{} مجموعة مكتملة - يكفي للاستعادة", count),
+            (Locale::Ar, Some(threshold), Some(missing)) => format!(
+                "{} من المجموعات المطلوبة مكتملة - يلزم {} مجموعة أخرى",
+                self.threshold(satisfied, threshold),
+                self.ltr(missing)
+            ),
+            (Locale::Ar, _, _) => format!("{} مجموعة مكتملة", count),
+        }
+    }
+}
@@ -1,12 +1,42 @@
-mod bytewords;
-mod recover;
-mod split;
-mod sskr_shares;
-
-use bip39::Mnemonic;
-use clap::{Parser, Subcommand};
-use std::fs::read_to_string;
+use anyhow::{bail, Context};
+use artifacts::{ExistingShares, WriteOutcome};
+use bip39::{Language, Mnemonic, MnemonicType};
+use checklist::{AuditRecord, Checklist};
+use clap::parser::ValueSource;
+use clap::{ArgGroup, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
+use dual_control::DualControl;
+use duress::{DuressKeys, Ending};
+use entropy::EntropySource;
+use hash::HashAlgorithm;
+use inspect::{write_checksum, Inspection};
+use language::MnemonicLanguage;
+use locale::Locale;
+use manifest::{Manifest, SeedManifest};
+use output::{OutputFormat, SecretOutput, ShareMetadataOutput, SplitOutput};
+use policy::{Channel, Field, Policy};
+use profile::profiles_dir;
+use rand::seq::SliceRandom;
+use seed_ur::{SeedMetadata, SeedUrType};
+use serde::Serialize;
+use share_format::{BytewordStyle, EncodedShare, ShareFormat, SplitFormat, WordSeparator};
+use sskr_tool::{
+    artifacts, audit, batch, bundle, checklist, codex32, confirm, diagnostics, drill, dual_control,
+    duress, encryption, entropy, error, escrow, estate, example, hash, hint, input, inspect,
+    journal, language, limits, locale, manifest, media, migrate, mirror, naming, output, paper,
+    paranoid, policy, profile, readback, recover, review, scan, sealed, seed_ur, selftest,
+    share_format, split, status, testdata, tones, tui, validate, verify, wallet,
+};
+#[cfg(feature = "smartcard")]
+use sskr_tool::{nfc::NfcTag, pcsc::PcscReader};
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+use validate::BlindCheck;
+use wallet::WalletPreview;
+use zeroize::{Zeroize, Zeroizing};
 
 /// ╭───────────────────────────────────────────────────────────────────────────────────────╮
 /// │                   ONLY USE THIS TOOL ON A SECURE, OFFLINE COMPUTER!                   │
@@ -19,7 +49,49 @@ use std::process;
 /// ╰───────────────────────────────────────────────────────────────────────────────────────╯
 #[derive(Parser, Debug)]
 #[clap(verbatim_doc_comment)]
-struct CLI {
+struct Cli {
+    /// How to format the output; "json" prints a single structured document
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// The language of text output
+    #[clap(long, global = true, value_enum, default_value_t = Locale::En)]
+    locale: Locale,
+
+    /// The word to type to confirm destructive actions, such as deleting files
+    #[clap(long, global = true, default_value = confirm::DEFAULT_CONFIRMATION_WORD)]
+    confirmation_word: String,
+
+    /// Re-verify splits and recoveries on an independent code path
+    #[clap(
+        long,
+        global = true,
+        long_help = "Re-verify everything before declaring success: every share decodes back, \
+            and every share is used in a combination that recovers the secret on two \
+            independent code paths. Slower, for real ceremonies"
+    )]
+    paranoid: bool,
+
+    /// A policy file saying what may be shown, written to disk or logged
+    #[clap(
+        long,
+        global = true,
+        env = "SSKR_TOOL_POLICY",
+        long_help = "A policy file saying which of the mnemonic, entropy, shares and \
+            fingerprints may be shown, written to disk or logged"
+    )]
+    policy: Option<PathBuf>,
+
+    /// Keys that wipe memory and exit at once in review and the ceremony
+    #[clap(
+        long,
+        global = true,
+        default_value = duress::DEFAULT_KEYS,
+        long_help = "Keys that, pressed in review or the ceremony, wipe everything in memory \
+            and exit at once, as \"ctrl-x ctrl-x\" or \"f12\"; \"none\" turns them off"
+    )]
+    duress_keys: DuressKeys,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -27,365 +99,8604 @@ struct CLI {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Splits a BIP-39 mnemonic into SSKR shares according to the spec.
-    Split {
-        /// Comma-separated list of M-of-N groups specifications. There can only be
-        /// a maximum of 16 groups, and a maximum of 16 shares in any one group.
-        ///
-        /// Example: "2of3,4of9,3of5" would create three groups:
-        ///     Group 1 = 2 of 3
-        ///     Group 2 = 4 of 9
-        ///     Group 3 = 3 of 5
-        #[clap(verbatim_doc_comment)]
-        spec: String,
-
-        /// The number of groups that need to be satisfied in order recover the seed
-        group_threshold: usize,
-
-        /// A valid BIP-39 seed phrase mnemonic (12 or 24 words); random if not specified
-        mnemonic: Option<String>,
-
-        #[clap(long, short)]
-        minimal: bool,
-    },
+    Split(Box<SplitArgs>),
 
     /// Recovers the original BIP-39 mnemonic from SSKR shares.
-    Recover {
-        /// The name of a file containing the SSKR shares as bytewords, one per line
-        filename: String,
+    Recover(RecoverArgs),
+
+    /// Converts a single SSKR share between bytewords, minimal bytewords and UR.
+    Convert(ConvertArgs),
+
+    /// Validates a single SSKR share and prints its metadata, without recovering anything.
+    Inspect(InspectArgs),
+
+    /// Checks shares typed in without echo, answering only valid or invalid, each
+    /// after the same delay, for checking a share somewhere not fully trusted.
+    Check(CheckArgs),
+
+    /// Checks shares presented years after the split against the commitments
+    /// its manifest records, telling genuine ones from altered or foreign ones
+    /// without needing enough shares to recover.
+    Audit(AuditArgs),
+
+    /// Reports which shares and groups are still missing for recovery, without
+    /// revealing the secret.
+    Status(StatusArgs),
+
+    /// Splits the secret of existing SSKR shares again under a new spec, without
+    /// revealing it, so that lost or withdrawn shares can be rotated out.
+    Reshare(ReshareArgs),
+
+    /// Collects the shares of a multi-day recovery into a tamper-evident journal.
+    Session(SessionArgs),
+
+    /// Checks that SSKR shares recover a known mnemonic, reporting which groups
+    /// do, without printing the secret.
+    Verify(VerifyArgs),
+
+    /// Re-reads the share files on a backup medium and checks them against the
+    /// hashes in its manifest, reporting files that have rotted.
+    VerifyMedia(VerifyMediaArgs),
+
+    /// Creates or checks an archival bundle: shares and manifest together with
+    /// the hash of the tool release and the SSKR spec to recover them with.
+    Bundle(BundleArgs),
+
+    /// Lodges the shares of one or more groups with a professional custodian:
+    /// encrypted with age to the escrow agent's key, with a manifest signed by
+    /// the depositor.
+    Escrow(EscrowArgs),
+
+    /// Upgrades manifests and split documents written by earlier releases to the
+    /// current format, and checks that journals and bundles are ones it reads.
+    Migrate(MigrateArgs),
+
+    /// Checks this build against the Blockchain Commons SSKR, Shamir and
+    /// Bytewords reference vectors bundled in it, before trusting it with a seed.
+    Selftest,
+
+    /// Runs a corpus of mistyped, mixed up and altered shares through the
+    /// diagnostics recover gives, checking each mistake is named as expected.
+    Diagnose(DiagnoseArgs),
+
+    /// Shows shares one at a time full-screen in large letters, to photograph or
+    /// copy them by hand. Nothing is written to disk or left on screen.
+    Review(ReviewArgs),
+
+    /// Quizzes a custodian on the words of their share, in random order, and
+    /// checks them against the share's checksum and the split's manifest.
+    Drill(DrillArgs),
+
+    /// Walks through a split or recovery ceremony full-screen, showing each
+    /// share on its own as its custodian comes to the screen.
+    Tui(TuiArgs),
+
+    /// Experimental: moves a share across an air gap as audio tones, played
+    /// from a WAV file on one machine and recorded on the other.
+    Tones(TonesArgs),
+
+    /// Saves the options of a split as a named profile, so a ceremony repeated
+    /// every quarter runs the same way with `split --profile NAME`.
+    Profile(ProfileArgs),
+
+    /// Prints the shell completion script for the given shell, to source from
+    /// the shell's startup file or install where it looks for completions.
+    Completions(CompletionsArgs),
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("record").multiple(true).args(["estate", "out_dir", "manifest"])))]
+struct SplitArgs {
+    /// Comma-separated list of M-of-N groups specifications. There can only be
+    /// a maximum of 16 groups, and a maximum of 16 shares in any one group.
+    ///
+    /// Example: "2of3,4of9,3of5" (or "2/3, 4/9, 3/5") would create three groups:
+    ///     Group 1 = 2 of 3
+    ///     Group 2 = 4 of 9
+    ///     Group 3 = 3 of 5
+    #[clap(verbatim_doc_comment, required_unless_present_any = ["batch", "example", "profile"])]
+    spec: Option<String>,
+
+    /// The number of groups that need to be satisfied in order recover the seed;
+    /// may be left out when the spec has a single group
+    group_threshold: Option<String>,
+
+    /// A valid BIP-39 seed phrase mnemonic (12 to 24 words); random if not specified
+    mnemonic: Option<String>,
+
+    /// The number of words of a newly generated mnemonic
+    #[clap(
+        long,
+        value_name = "12|15|18|21|24",
+        default_value = "12",
+        value_parser = split::parse_word_count,
+        conflicts_with_all = ["mnemonic", "entropy_hex"]
+    )]
+    words: MnemonicType,
+
+    /// Generate the new mnemonic from physical entropy instead of the OS, given
+    /// as dice:<rolls> (1-6) or coin:<flips> (h/t or 1/0). 12 words need at
+    /// least 50 dice rolls or 128 coin flips, 24 words at least 100 or 256
+    #[clap(long, value_name = "SOURCE", conflicts_with = "mnemonic")]
+    entropy: Option<EntropySource>,
+
+    /// Split the mnemonic for this entropy, given as hex (16, 20, 24, 28 or 32
+    /// bytes), as another tool may have given the seed. Both are printed with
+    /// --echo-secret
+    #[clap(
+        long,
+        value_name = "HEX",
+        conflicts_with_all = ["mnemonic", "entropy", "no_echo"]
+    )]
+    entropy_hex: Option<String>,
+
+    /// Split a raw secret given as hex instead of a mnemonic, of any even length
+    /// from 16 to 32 bytes. Shares of lengths no mnemonic has recover as hex
+    #[clap(
+        long,
+        value_name = "HEX",
+        conflicts_with_all = ["mnemonic", "entropy", "entropy_hex", "language", "estate", "out_dir", "paper", "manifest", "mirror"]
+    )]
+    raw_secret: Option<String>,
+
+    /// Split a file of any size instead of a mnemonic: it is encrypted with a
+    /// random key into a sealed file, and the shares split the key. Recover it
+    /// with `recover --sealed-file ... --output-file ...`
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["mnemonic", "entropy", "entropy_hex", "language", "raw_secret", "estate", "out_dir", "paper", "manifest", "mirror"]
+    )]
+    input_file: Option<String>,
+
+    /// Split every seed listed in this TOML file, each into its own output
+    /// directory, instead of one seed given on the command line
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["spec", "group_threshold", "mnemonic", "entropy", "entropy_hex", "raw_secret", "input_file", "no_echo", "identifier", "identifier_seed", "estate", "out_dir", "paper", "manifest", "mirror", "seed_name", "purpose", "fingerprint", "dual_control", "segments"]
+    )]
+    batch: Option<String>,
+
+    /// Take the spec, group threshold, custodian names, output options and
+    /// hardening flags from a profile saved with `profile save`; options given
+    /// here take precedence over the profile's
+    #[clap(long, value_name = "NAME", conflicts_with_all = ["batch", "example"])]
+    profile: Option<String>,
+
+    // The custodians of each group's members, which only profiles name
+    #[clap(skip)]
+    custodians: Vec<Vec<String>>,
+
+    /// Where to write the sealed file; the input file with ".sealed" added if
+    /// not given
+    #[clap(long, value_name = "FILE", requires = "input_file")]
+    sealed_file: Option<String>,
+
+    /// The identifier of the new shares, as 4 hex digits, instead of a random
+    /// one; to tell a replacement set apart from the old one, or match it
+    #[clap(long, value_name = "HEX", value_parser = split::parse_identifier)]
+    identifier: Option<u16>,
+
+    /// Derive the identifier from this string (the first two bytes of its
+    /// SHA-256), so the same string always gives the same identifier
+    #[clap(long, value_name = "STRING", conflicts_with = "identifier")]
+    identifier_seed: Option<String>,
+
+    /// Print the CRC-32 checksum of each share's payload under it, as hex and
+    /// as the four bytewords it ends with, along with the payload as hex
+    #[clap(long)]
+    checksums: bool,
+
+    /// Prompt for the mnemonic without echoing it, instead of taking it on the
+    /// command line where it ends up in the shell history
+    #[clap(long, conflicts_with_all = ["mnemonic", "entropy", "raw_secret"])]
+    no_echo: bool,
+
+    /// Print the mnemonic and entropy even though they were given rather than
+    /// generated; they are only printed by default when generated
+    #[clap(long)]
+    echo_secret: bool,
+
+    /// Mix OS randomness into the --entropy outcomes, so the mnemonic is safe as
+    /// long as either source is
+    #[clap(long, requires = "entropy")]
+    mix_os_entropy: bool,
+
+    #[clap(long, short)]
+    minimal: bool,
+
+    /// How to write the words of each share: separated by spaces (standard),
+    /// by hyphens (uri) or as two letters each (minimal, the same as --minimal)
+    #[clap(long, value_enum, conflicts_with = "minimal")]
+    style: Option<BytewordStyle>,
+
+    /// What to separate the words of standard bytewords with, for printing
+    /// templates and engraving layouts. Shares are read back with any of them
+    #[clap(long, value_enum, conflicts_with_all = ["minimal", "envelope", "paper"])]
+    separator: Option<WordSeparator>,
+
+    /// What to write shares as: bytewords, hex of the share, or hex of the
+    /// tagged CBOR the bytewords encode. Hex carries no checksum
+    #[clap(long, value_enum, default_value_t = SplitFormat::Bytewords)]
+    share_format: SplitFormat,
+
+    /// Wrap each share in a Gordian Envelope (ur:envelope) as its subject; not
+    /// the layout SeedTool exports its shares in
+    #[clap(long, conflicts_with_all = ["minimal", "style", "paper"])]
+    envelope: bool,
+
+    /// Assert this label on each --envelope share, e.g. the name of the seed
+    #[clap(long, value_name = "LABEL", requires = "envelope")]
+    envelope_label: Option<String>,
+
+    /// Assert the date of the split on each --envelope share
+    #[clap(long, requires = "envelope")]
+    envelope_date: bool,
+
+    /// Also print each share split into this many ur:sskr segments, for QR
+    /// codes, NFC tags or engraving areas too small for a whole share. Every
+    /// command that reads shares joins the segments back
+    #[clap(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u8).range(2..=16),
+        conflicts_with_all = ["envelope", "paper", "estate", "out_dir"]
+    )]
+    segments: Option<u8>,
+
+    /// After the shares are printed or written, ask for each one to be typed
+    /// back from the copy made of it, and fail unless every one matches
+    #[clap(long, conflicts_with_all = ["batch", "segments"])]
+    confirm: bool,
+
+    /// With --confirm, ask for this many words of each share, at random,
+    /// instead of the whole share
+    #[clap(
+        long,
+        value_name = "N",
+        requires = "confirm",
+        value_parser = clap::value_parser!(u8).range(1..)
+    )]
+    confirm_words: Option<u8>,
+
+    /// Also write every share to an NFC tag (NTAG213 or larger) on a PC/SC
+    /// reader, one tag after another, reading each back to compare
+    #[cfg(feature = "smartcard")]
+    #[clap(long, conflicts_with = "codex32")]
+    nfc: bool,
+
+    /// The card reader to use for --nfc, by part of its name, instead of the
+    /// first one
+    #[cfg(feature = "smartcard")]
+    #[clap(long, value_name = "NAME", requires = "nfc")]
+    reader: Option<String>,
+
+    /// The language of the mnemonic; detected from the mnemonic if not specified,
+    /// and English for random mnemonics
+    #[clap(long, value_enum)]
+    language: Option<MnemonicLanguage>,
+
+    /// Write custodian share packets and an executor instruction packet
+    /// (manifest and runbook, no shares) to this directory instead of
+    /// printing the shares
+    #[clap(long, value_name = "DIR", conflicts_with = "out_dir")]
+    estate: Option<String>,
+
+    /// Write each share to its own file in this directory, along with a manifest
+    /// of the split, instead of printing the shares
+    #[clap(long, value_name = "DIR")]
+    out_dir: Option<String>,
+
+    /// Render each share on its own printable page, with blank lines to copy
+    /// it by hand, to this .pdf or .html file instead of printing the shares
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["estate", "out_dir"])]
+    paper: Option<String>,
+
+    /// Also give each custodian a hint card, holding no share or secret, with
+    /// the seed's name, their group, how many other shares recovery needs and
+    /// the command to run. Printed after the shares, or written under hints/
+    /// with --out-dir
+    #[clap(long, conflicts_with_all = ["estate", "paper", "raw_secret", "input_file", "codex32", "check", "example"])]
+    hint_cards: bool,
+
+    /// Add a QR code of each share (as a ur:sskr) to its --paper page
+    #[clap(long, requires = "paper")]
+    paper_qr: bool,
+
+    /// File name template for per-share artifacts. Placeholders: {identifier},
+    /// {name}, {fingerprint}, {group}, {member}, {members}, {threshold}, {custodian}
+    #[clap(long, default_value = naming::DEFAULT_SHARE_TEMPLATE)]
+    name_template: String,
+
+    /// Keep file names to ASCII, spelling accented and other letters without
+    /// them (Zoë becomes Zoe), for archives restored on older systems
+    #[clap(long)]
+    ascii_names: bool,
+
+    /// If the output directory already holds shares, check that they have this
+    /// split's layout and recover its secret instead of writing anything
+    #[clap(long)]
+    verify_existing: bool,
+
+    /// If the output directory already holds shares, delete every file holding
+    /// a share before writing, after typing the confirmation word
+    #[clap(long, conflicts_with = "verify_existing")]
+    replace_existing: bool,
+
+    /// Add this seed to the custody manifest in this file, creating it if needed,
+    /// so that several seeds held by the same custodians are described together
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+
+    /// Also write the record of the split (its manifest, without shares) to
+    /// each of these mounted removable devices, reading every copy back
+    #[clap(long, value_name = "DIR,DIR", value_delimiter = ',', num_args = 1..)]
+    mirror: Vec<String>,
+
+    /// A name for the seed, recorded in the manifest
+    #[clap(long)]
+    seed_name: Option<String>,
+
+    /// What the seed is used for, recorded in the manifest
+    #[clap(long)]
+    purpose: Option<String>,
+
+    /// The seed's BIP-32 master fingerprint (8 hex digits), recorded in the manifest
+    #[clap(long)]
+    fingerprint: Option<String>,
+
+    /// Put recovery under a two-person rule: two operators each choose a
+    /// passphrase (not echoed), and the entropy is encrypted under both, so
+    /// `recover --manifest` needs both to give the mnemonic
+    #[clap(long, requires = "record", conflicts_with = "encrypt")]
+    dual_control: bool,
+
+    /// The algorithm to hash the share files and share checks the manifest
+    /// records with, for crypto policies that prescribe one. `verify-media`
+    /// and `drill` tell which one a manifest used
+    #[clap(long, value_enum, default_value_t = HashAlgorithm::Sha256)]
+    hash: HashAlgorithm,
+
+    /// Encrypt the entropy under a passphrase (asked for twice, not echoed)
+    /// before splitting, so enough shares alone don't recover the mnemonic.
+    /// `recover --decrypt` asks for the passphrase again
+    #[clap(
+        long,
+        conflicts_with_all = ["raw_secret", "input_file", "batch"],
+        long_help = "Encrypt the entropy under a passphrase (asked for twice, not echoed) \
+            before splitting, so enough shares alone don't recover the mnemonic. \
+            `recover --decrypt` asks for the passphrase again.\n\n\
+            The passphrase is stretched with scrypt under a random salt, which is \
+            stored in the shares for a 12 word mnemonic and shown to keep with them \
+            otherwise. There is no room for an authentication tag: the two check \
+            words let whoever holds enough shares tell a right guess offline, at \
+            one scrypt run per guess, so the passphrase has to be long and not \
+            used elsewhere."
+    )]
+    encrypt: bool,
+
+    /// Split into codex32 (BIP-93) shares instead of SSKR shares, for a spec
+    /// of a single group of up to 9 of 31. "1of1" gives the secret as a
+    /// single codex32 string
+    #[clap(long, conflicts_with_all = ["input_file", "batch", "encrypt", "record", "paper", "envelope", "segments", "confirm", "identifier", "identifier_seed"])]
+    codex32: bool,
+
+    /// Only check the spec, group threshold and mnemonic, and print the groups
+    /// and the combinations of them that recover, without generating shares
+    #[clap(long, conflicts_with_all = ["batch", "raw_secret", "input_file", "codex32", "entropy_hex"])]
+    check: bool,
+
+    /// Split a well-known example seed, to see what a split looks like before
+    /// splitting a real one. The seed is public, never use it for funds
+    #[clap(
+        long,
+        conflicts_with_all = ["spec", "group_threshold", "mnemonic", "entropy", "entropy_hex", "raw_secret", "input_file", "batch", "no_echo", "record", "paper", "mirror", "codex32", "check", "encrypt", "identifier", "identifier_seed", "dual_control"]
+    )]
+    example: bool,
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("wallet").multiple(true).args(["fingerprint", "derive"])))]
+struct RecoverArgs {
+    /// Files containing the SSKR shares as bytewords, one per line. `-` reads
+    /// from stdin and a directory reads every .txt file in it
+    #[cfg_attr(
+        feature = "smartcard",
+        clap(value_name = "FILE", required_unless_present_any = ["serial", "example", "nfc"])
+    )]
+    #[cfg_attr(
+        not(feature = "smartcard"),
+        clap(value_name = "FILE", required_unless_present_any = ["serial", "example"])
+    )]
+    files: Vec<String>,
+
+    /// Read shares as they are scanned from a serial port, checking each one as
+    /// it arrives and stopping once enough are present. `-` reads from stdin,
+    /// which is where keyboard wedge (USB HID) scanners type
+    #[clap(long, value_name = "DEVICE", conflicts_with = "files")]
+    serial: Option<String>,
+
+    /// Recover the example seed from shares of `split --example`, to see what
+    /// a recovery looks like. The seed is public, never use it for funds
+    #[clap(long, conflicts_with_all = ["files", "serial", "manifest", "sealed_file"])]
+    example: bool,
+
+    /// Read shares from NFC tags on a PC/SC reader, one tag after another,
+    /// checking each one as it is read and stopping once enough are present
+    #[cfg(feature = "smartcard")]
+    #[clap(long, conflicts_with_all = ["files", "serial", "example"])]
+    nfc: bool,
+
+    /// The card reader to use for --nfc, by part of its name, instead of the
+    /// first one
+    #[cfg(feature = "smartcard")]
+    #[clap(long, value_name = "NAME", requires = "nfc")]
+    reader: Option<String>,
+
+    /// The baud rate of the serial port
+    #[clap(long, default_value_t = 9600, requires = "serial")]
+    baud: u32,
+
+    /// Show shares in error messages as minimal bytewords (the format of each
+    /// share is detected on its own)
+    #[clap(long, short)]
+    minimal: bool,
+
+    /// The language to output the recovered mnemonic in
+    #[clap(long, value_enum, default_value_t = MnemonicLanguage::English)]
+    language: MnemonicLanguage,
+
+    /// Replace words that aren't bytewords by the closest bytewords, as long as
+    /// only one such correction passes the share's checksum
+    #[clap(long)]
+    auto_correct: bool,
+
+    /// Warn about reserved bits set by newer SSKR versions or other tools
+    /// instead of refusing the share
+    #[clap(long)]
+    lenient: bool,
+
+    /// Stop if any line can't be used, instead of reporting it and recovering
+    /// from the other lines
+    #[clap(long)]
+    strict: bool,
+
+    /// The custody manifest of the shares; if it puts them under dual control,
+    /// both operators' passphrases are asked for to decrypt the mnemonic
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+
+    /// Ask both operators for their passphrase to decrypt shares split with
+    /// `--dual-control`, for recovering them without their manifest
+    #[clap(long)]
+    dual_control: bool,
+
+    /// Decrypt the combined secret with the passphrase it was split with
+    /// `--encrypt` (asked for, not echoed). A manifest that recorded the
+    /// passphrase check asks for it without this
+    #[clap(long)]
+    decrypt: bool,
+
+    /// The passphrase salt `split --encrypt` showed, for encrypted shares of
+    /// more than 12 words recovered without their manifest
+    #[clap(long, value_name = "WORDS")]
+    passphrase_salt: Option<String>,
+
+    /// Also print the fingerprint of the BIP-32 master key, to match against
+    /// the one a hardware wallet shows
+    #[clap(long)]
+    fingerprint: bool,
+
+    /// The BIP-39 passphrase to derive the master key with
+    #[clap(long, requires = "wallet")]
+    passphrase: Option<String>,
+
+    /// Also print the xpub at this derivation path, and for BIP-44, 49, 84 and
+    /// 86 paths the first receive addresses, e.g. "m/84'/0'/0'"
+    #[clap(long, value_name = "PATH")]
+    derive: Option<String>,
+
+    /// A file sealed by `split --input-file`, to open with the recovered key
+    #[clap(
+        long,
+        value_name = "FILE",
+        requires = "output_file",
+        conflicts_with = "wallet"
+    )]
+    sealed_file: Option<String>,
+
+    /// Where to write the contents of the --sealed-file; an existing file is
+    /// never overwritten
+    #[clap(long, value_name = "FILE", requires = "sealed_file")]
+    output_file: Option<String>,
+
+    /// Also print the recovered secret as a codex32 (BIP-93) string. Codex32
+    /// shares ("ms1...") are read without this
+    #[clap(long, conflicts_with = "sealed_file")]
+    codex32: bool,
+
+    /// Also print the recovered secret as a ur:seed, to import into Gordian
+    /// SeedTool or a compatible wallet by QR code
+    #[clap(long, conflicts_with = "sealed_file")]
+    seed_ur: bool,
+
+    /// Write the --seed-ur as a ur:crypto-seed, for wallets from before ur:seed
+    #[clap(long, requires = "seed_ur")]
+    crypto_seed: bool,
+
+    /// The name to give the seed in the --seed-ur
+    #[clap(long, value_name = "NAME", requires = "seed_ur")]
+    seed_name: Option<String>,
+
+    /// A note to add to the seed in the --seed-ur
+    #[clap(long, requires = "seed_ur")]
+    note: Option<String>,
+
+    /// The date the seed was created, as YYYY-MM-DD, for the --seed-ur
+    #[clap(long, value_name = "DATE", requires = "seed_ur", value_parser = seed_ur::parse_date)]
+    creation_date: Option<i64>,
+
+    /// Also show the --seed-ur as a QR code, to scan off the screen
+    #[clap(long, requires = "seed_ur")]
+    qr: bool,
+}
+
+#[derive(Args, Debug)]
+struct ConvertArgs {
+    /// The share to convert, in any supported format (detected automatically)
+    share: String,
+
+    /// The format to convert the share to
+    #[clap(long, value_enum)]
+    to: ShareFormat,
+}
+
+#[derive(Args, Debug)]
+struct ReviewArgs {
+    /// Files containing the SSKR shares, one per line. A directory reads every
+    /// .txt file in it
+    #[clap(value_name = "FILE", required = true)]
+    files: Vec<String>,
+
+    /// Show the shares in this format instead of the one they were read in
+    #[clap(long, value_enum)]
+    to: Option<ShareFormat>,
+}
+
+#[derive(Args, Debug)]
+struct AuditArgs {
+    /// The manifest written by the split
+    #[clap(long, value_name = "FILE")]
+    manifest: String,
+
+    /// Files with the shares to check, or `-` for stdin; directories are read
+    /// like `recover` reads them
+    #[clap(required = true)]
+    files: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct DrillArgs {
+    /// The manifest of the split, to check the share is one of it and not only
+    /// a share that passes its checksum
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct TuiArgs {
+    /// A file with a step per line (checking the room, the custodians'
+    /// identities, ...) to confirm one by one before the ceremony starts
+    #[clap(long, value_name = "FILE")]
+    checklist: Option<PathBuf>,
+
+    /// Appends a line of JSON when the ceremony ends, with its times, how it
+    /// ended and how long each checklist step took, but no shares or secret
+    #[clap(long, value_name = "FILE")]
+    audit_log: Option<PathBuf>,
+
+    /// The language of the mnemonic; detected from the mnemonic typed in if
+    /// not specified, and English for random and recovered mnemonics
+    #[clap(long, value_enum)]
+    language: Option<MnemonicLanguage>,
+}
+
+#[derive(Args, Debug)]
+struct TonesArgs {
+    #[command(subcommand)]
+    command: TonesCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum TonesCommands {
+    /// Writes the share as a WAV file of tones, to play on the sending machine
+    Encode(TonesEncodeArgs),
+
+    /// Reads a share from a WAV recording of the tones and prints it
+    Decode(TonesDecodeArgs),
+}
+
+#[derive(Args, Debug)]
+struct TonesEncodeArgs {
+    /// The share to send, in any supported format (detected automatically)
+    share: String,
+
+    /// The WAV file to write
+    #[clap(long, value_name = "FILE")]
+    output_file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct TonesDecodeArgs {
+    /// A 16-bit PCM WAV recording, at any sample rate
+    file: PathBuf,
+
+    /// The format to print the share in
+    #[clap(long, value_enum, default_value_t = ShareFormat::Bytewords)]
+    to: ShareFormat,
+}
+
+#[derive(Args, Debug)]
+struct ProfileArgs {
+    #[command(subcommand)]
+    command: ProfileCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileCommands {
+    /// Checks a TOML profile file and saves it under the name, replacing any
+    /// profile of that name. Profiles are kept in $SSKR_TOOL_PROFILES, or in
+    /// sskr-tool/profiles in the configuration directory
+    Save(ProfileSaveArgs),
+
+    /// Prints a saved profile, to copy it elsewhere or edit and save it again
+    Load(ProfileLoadArgs),
+
+    /// Lists the saved profiles
+    List,
+}
+
+#[derive(Args, Debug)]
+struct ProfileSaveArgs {
+    /// The name to save the profile under, of letters, digits, - and _
+    name: String,
+
+    /// The profile file to save
+    file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ProfileLoadArgs {
+    /// The name of the profile
+    name: String,
+}
+
+#[derive(Args, Debug)]
+struct CheckArgs {
+    /// The manifest of the split, so only its own shares are valid
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+
+    /// The number of shares that can be tried before the check stops
+    #[clap(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=10))]
+    attempts: u8,
+}
+
+#[derive(Args, Debug)]
+struct InspectArgs {
+    /// The share to inspect, in any supported format (detected automatically)
+    share: String,
+
+    /// A manifest of the split, to show the seed name and group name it records
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+
+    /// Leave out the checksum, whose payload is the share itself, so what is
+    /// shown can be read out or pasted anywhere
+    #[clap(long)]
+    public: bool,
+}
+
+#[derive(Args, Debug)]
+struct StatusArgs {
+    /// Files containing the SSKR shares as bytewords, one per line. `-` reads
+    /// from stdin and a directory reads every .txt file in it
+    #[clap(value_name = "FILE", required = true)]
+    files: Vec<String>,
+
+    #[clap(long, short)]
+    minimal: bool,
+
+    /// A custody manifest describing the seeds, so that groups and seeds without
+    /// any shares present yet are reported too
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct ReshareArgs {
+    /// The new spec, see split
+    spec: String,
+
+    /// The number of groups of the new spec that need to be satisfied; may be
+    /// left out when the spec has a single group
+    group_threshold: Option<usize>,
+
+    /// Files containing the existing SSKR shares, one per line. `-` reads from
+    /// stdin and a directory reads every .txt file in it
+    #[clap(long, value_name = "FILE", required = true, num_args = 1..)]
+    shares: Vec<String>,
+
+    /// Read and write minimal bytewords
+    #[clap(long, short)]
+    minimal: bool,
+
+    /// Also print the entropy and mnemonic of the secret
+    #[clap(long)]
+    show_secret: bool,
+
+    /// The language of the mnemonic printed by --show-secret
+    #[clap(long, value_enum, default_value_t = MnemonicLanguage::English)]
+    language: MnemonicLanguage,
+}
+
+#[derive(Args, Debug)]
+struct SessionArgs {
+    #[command(subcommand)]
+    command: SessionCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum SessionCommands {
+    /// Appends the shares in the files to the journal, creating it if needed.
+    /// Shares already in the journal are skipped
+    Add(SessionAddArgs),
+
+    /// Checks that no entry of the journal was edited, reordered or removed
+    Verify(SessionVerifyArgs),
+}
+
+#[derive(Args, Debug)]
+struct SessionAddArgs {
+    /// The journal, which recover and status read like any other shares file
+    journal: String,
+
+    /// Files containing the SSKR shares to add, one per line. `-` reads from
+    /// stdin and a directory reads every .txt file in it
+    #[clap(value_name = "FILE", required = true)]
+    files: Vec<String>,
+
+    #[clap(long, short)]
+    minimal: bool,
+}
+
+#[derive(Args, Debug)]
+struct SessionVerifyArgs {
+    journal: String,
+
+    /// A head reported by `session add` and written down at the time; the
+    /// journal has to still reach it, which catches entries cut off the end
+    #[clap(long)]
+    head: Option<String>,
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("secret").required(true).args(["mnemonic", "entropy_hex", "no_echo"])))]
+struct VerifyArgs {
+    /// Files containing the SSKR shares, one per line. `-` reads from stdin and
+    /// a directory reads every .txt file in it
+    #[clap(value_name = "FILE", required = true)]
+    files: Vec<String>,
+
+    /// The mnemonic the shares should recover
+    #[clap(long)]
+    mnemonic: Option<String>,
+
+    /// The entropy the shares should recover, as hex
+    #[clap(long, value_name = "HEX")]
+    entropy_hex: Option<String>,
+
+    /// Prompt for the mnemonic the shares should recover without echoing it
+    #[clap(long)]
+    no_echo: bool,
+
+    #[clap(long, short)]
+    minimal: bool,
+
+    /// Replace words that aren't bytewords by the closest bytewords, as long as
+    /// only one such correction passes the share's checksum
+    #[clap(long)]
+    auto_correct: bool,
+
+    /// Warn about reserved bits set by newer SSKR versions or other tools
+    /// instead of refusing the share
+    #[clap(long)]
+    lenient: bool,
+}
+
+#[derive(Args, Debug)]
+struct MigrateArgs {
+    /// The artifacts to upgrade: manifests, `--output json` split documents,
+    /// session journals and bundles. Each upgraded file keeps its original
+    /// next to it as <FILE>.orig
+    #[clap(value_name = "FILE", required = true)]
+    files: Vec<String>,
+
+    /// Only report what would change, failing if anything would
+    #[clap(long)]
+    check: bool,
+}
+
+#[derive(Args, Debug)]
+struct CompletionsArgs {
+    #[clap(value_enum)]
+    shell: Shell,
+}
+
+#[derive(Args, Debug)]
+struct VerifyMediaArgs {
+    /// The directory the shares were written to with --out-dir, or where the
+    /// medium holding them is mounted
+    #[clap(value_name = "PATH")]
+    path: String,
+
+    /// The manifest recording the files, if not the manifest.json in PATH
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct DiagnoseArgs {
+    /// A corpus of cases in TOML, each with its shares and the problems
+    /// expected in them; the corpus built into this version if not given
+    #[clap(value_name = "FILE")]
+    file: Option<String>,
+
+    /// Print the built-in corpus as TOML, to start a corpus of your own from
+    #[clap(long, conflicts_with = "file")]
+    export: bool,
+}
+
+#[derive(Args, Debug)]
+struct BundleArgs {
+    #[command(subcommand)]
+    command: BundleCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum BundleCommands {
+    /// Writes a new bundle of the shares and/or manifest
+    Create(BundleCreateArgs),
+
+    /// Checks that the bundle is intact, and optionally that a release is the
+    /// one it records
+    Verify(BundleVerifyArgs),
+}
+
+#[derive(Args, Debug)]
+struct EscrowArgs {
+    #[command(subcommand)]
+    command: EscrowCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum EscrowCommands {
+    /// Writes the lodgement of the shares of the given groups into a directory
+    Export(EscrowExportArgs),
+
+    /// Checks a lodgement's signature and payload, as the agent does at intake
+    Verify(EscrowVerifyArgs),
+
+    /// Writes a new signing key for the depositor and prints its public key
+    Keygen(EscrowKeygenArgs),
+}
+
+#[derive(Args, Debug)]
+struct EscrowExportArgs {
+    /// Files containing the SSKR shares, one per line. `-` reads from stdin
+    /// and a directory reads every .txt file in it
+    #[clap(value_name = "FILE", required = true)]
+    files: Vec<String>,
+
+    /// A group to lodge, numbered from 1; repeat it to lodge several
+    #[clap(long, required = true, value_parser = clap::value_parser!(u8).range(1..=limits::MAX_GROUPS as i64))]
+    group: Vec<u8>,
+
+    /// The escrow agent's age public key (age1...)
+    #[clap(long)]
+    recipient: String,
+
+    /// The depositor's key to sign the manifest with, as `escrow keygen` writes
+    #[clap(long, value_name = "FILE")]
+    signing_key: PathBuf,
+
+    /// The directory to write the lodgement into
+    #[clap(long, value_name = "DIR")]
+    out_dir: PathBuf,
+
+    /// The algorithm to hash the encrypted shares with in the manifest
+    #[clap(long, value_enum, default_value_t = HashAlgorithm::Sha256)]
+    hash: HashAlgorithm,
+}
+
+#[derive(Args, Debug)]
+struct EscrowVerifyArgs {
+    /// The directory of the lodgement
+    dir: PathBuf,
+
+    /// The depositor's public key, to check the manifest was signed by them
+    /// and not only by the key it names
+    #[clap(long, value_name = "HEX")]
+    signer: Option<String>,
+}
 
-        #[clap(long, short)]
-        minimal: bool,
-    },
+#[derive(Args, Debug)]
+struct EscrowKeygenArgs {
+    /// The file to write the signing key to; it is never overwritten
+    #[clap(long, value_name = "FILE")]
+    output_file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct BundleCreateArgs {
+    /// The bundle file to write
+    bundle: String,
+
+    /// Files containing the SSKR shares to include, one per line. `-` reads
+    /// from stdin and a directory reads every .txt file in it
+    #[clap(long, value_name = "FILE", num_args = 1.., required_unless_present = "manifest")]
+    shares: Vec<String>,
+
+    /// The custody manifest to include
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+
+    /// The release to record the hash of, such as the downloaded archive;
+    /// the running executable if not given
+    #[clap(long, value_name = "FILE")]
+    release: Option<String>,
+
+    /// The algorithm to hash the release and seal the bundle with; `bundle
+    /// verify` tells which one a bundle used
+    #[clap(long, value_enum, default_value_t = HashAlgorithm::Sha256)]
+    hash: HashAlgorithm,
+}
+
+#[derive(Args, Debug)]
+struct BundleVerifyArgs {
+    bundle: String,
+
+    /// A release obtained to recover with, to compare against the recorded hash
+    #[clap(long, value_name = "FILE")]
+    release: Option<String>,
 }
 
 fn main() {
-    match &CLI::parse().command {
-        Commands::Split {
-            spec,
-            group_threshold,
-            mnemonic,
-            minimal
-        } => split(spec, group_threshold, mnemonic, minimal),
-        Commands::Recover { filename, minimal } => recover(filename, minimal),
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    exit_on_error(
+        with_profile(&mut cli, &matches, profiles_dir),
+        "Error reading profile",
+    );
+    let mut policy = match &cli.policy {
+        Some(path) => exit_on_error(Policy::load(path), "Error reading policy"),
+        None => Policy::default(),
+    };
+    // A mnemonic the user gave isn't printed back unless asked for, while a
+    // generated one is, since the user has no other copy
+    if let Commands::Split(args) = &cli.command {
+        if secret_given(args) && !args.echo_secret {
+            policy = policy.without_secret_display();
+        }
+    }
+    exit_on_error(policy::install(policy), "Error reading policy");
+    match &cli.command {
+        Commands::Split(args) => split(args, &cli),
+        Commands::Recover(args) => recover(args, &cli),
+        Commands::Convert(args) => convert(args, &cli.output),
+        Commands::Inspect(args) => inspect(args, &cli.output),
+        Commands::Check(args) => check(args, &cli.output),
+        Commands::Audit(args) => audit(args, &cli.output),
+        Commands::Status(args) => status(args, &cli.output, cli.locale),
+        Commands::Reshare(args) => reshare(args, &cli),
+        Commands::Session(args) => session(args, &cli.output),
+        Commands::Verify(args) => verify(args, &cli.output, cli.locale),
+        Commands::VerifyMedia(args) => verify_media(args, &cli.output),
+        Commands::Bundle(args) => bundle(args, &cli.output),
+        Commands::Escrow(args) => escrow(args, &cli.output),
+        Commands::Migrate(args) => migrate(args, &cli.output),
+        Commands::Selftest => selftest(&cli.output),
+        Commands::Diagnose(args) => diagnose(args, &cli.output),
+        Commands::Review(args) => review(args, &cli.duress_keys),
+        Commands::Drill(args) => drill(args, &cli.output),
+        Commands::Tui(args) => run_tui(args, &cli.duress_keys),
+        Commands::Tones(args) => tones(args),
+        Commands::Profile(args) => profiles(args),
+        Commands::Completions(args) => completions(args),
     }
 }
 
-fn split(spec: &String, group_threshold: &usize, mnemonic: &Option<String>, minimal: &bool) {
-    let result = match mnemonic {
-        Some(phrase) => split::split(spec, *group_threshold, &phrase, minimal),
-        None => split::split_random_phrase(spec, *group_threshold, minimal),
+fn split(args: &SplitArgs, cli: &Cli) {
+    let (output, locale) = (&cli.output, cli.locale);
+    if let Some(path) = &args.batch {
+        return split_batch(Path::new(path), args, cli);
+    }
+    if args.example {
+        return split_example(args, cli);
+    }
+    let (group_threshold, phrase) =
+        exit_on_error(split_parameters(args), "Error splitting mnemonic");
+    let hidden = args.no_echo.then(|| {
+        exit_on_error(
+            input::read_hidden("Mnemonic (not echoed): "),
+            "Error reading mnemonic",
+        )
+    });
+    let phrase = hidden.as_ref().map(|phrase| phrase.as_str()).or(phrase);
+    if args.check {
+        return split_check(group_threshold, phrase, args, output);
+    }
+    if let Some(secret) = &args.raw_secret {
+        return split_raw_secret(secret, group_threshold, args, cli);
+    }
+    if let Some(path) = &args.input_file {
+        return split_file(Path::new(path), group_threshold, args, cli);
+    }
+    let language = args.language.map(MnemonicLanguage::bip39);
+    let result = match phrase {
+        Some(phrase) => split::split_shares(args.spec(), group_threshold, phrase, language),
+        None => {
+            let language = language.unwrap_or(Language::English);
+            let mnemonic = match (&args.entropy, &args.entropy_hex) {
+                (Some(source), _) => {
+                    let mnemonic = exit_on_error(
+                        split::entropy_mnemonic(source, &args.mix_os_entropy, args.words, language),
+                        "Error generating mnemonic",
+                    );
+                    eprintln!(
+                        "Derived entropy from {} {}{}: {}",
+                        source.count(),
+                        source.name(),
+                        if args.mix_os_entropy {
+                            ", mixed with OS randomness"
+                        } else {
+                            ""
+                        },
+                        policy::logged(
+                            Field::Entropy,
+                            format_args!("0x{}", hex::encode(mnemonic.entropy()))
+                        )
+                    );
+                    mnemonic
+                }
+                (None, Some(entropy)) => exit_on_error(
+                    split::entropy_hex_mnemonic(entropy, language),
+                    "Error reading entropy",
+                ),
+                (None, None) => split::random_mnemonic(args.words, language),
+            };
+            split::split_shares(
+                args.spec(),
+                group_threshold,
+                mnemonic.phrase(),
+                Some(mnemonic.language()),
+            )
+        }
+    };
+
+    let (mnemonic, mut shares) = match result {
+        Ok(result) => result,
+        Err(error) => {
+            eprintln!("Error splitting mnemonic: {:?}", error);
+            process::exit(error::exit_code(&error));
+        }
+    };
+    if args.codex32 {
+        return split_codex32(mnemonic.entropy(), Some(&mnemonic), args, output);
+    }
+    if let Some(identifier) = chosen_identifier(args) {
+        split::set_identifier(&mut shares, identifier);
+    }
+    let encrypted = if args.dual_control {
+        let (_, passphrase) = exit_on_error(
+            DualControl::enroll(&mut read_operator_passphrase),
+            "Error setting up dual control",
+        );
+        Some(encrypt_shares(
+            &mnemonic,
+            &mut shares,
+            group_threshold,
+            args,
+            &passphrase,
+        ))
+    } else if args.encrypt {
+        let passphrase = read_encryption_passphrase();
+        Some(encrypt_shares(
+            &mnemonic,
+            &mut shares,
+            group_threshold,
+            args,
+            &passphrase,
+        ))
+    } else {
+        None
     };
 
-    match result {
-        Ok((mnemonic, groups)) => split_success(spec, group_threshold, mnemonic, groups),
-        Err(error) => {
-            eprintln!("Error splitting mnemonic: {:?}", error);
-            process::exit(1);
+    let groups = encode_shares(&shares, args);
+    if cli.paranoid {
+        let secret = match &encrypted {
+            Some(encrypted) => encrypted.secret.as_slice(),
+            None => mnemonic.entropy(),
+        };
+        paranoid_check(&shares, &groups, secret);
+    }
+    let labels = exit_on_error(split::spec_labels(args.spec()), "Invalid spec");
+
+    let describe = args.estate.is_some()
+        || args.out_dir.is_some()
+        || args.manifest.is_some()
+        || !args.mirror.is_empty()
+        || args.hint_cards;
+    let seed = describe.then(|| {
+        let mut seed = exit_on_error(
+            SeedManifest::from_shares(&groups, &args.minimal, mnemonic.language()).and_then(
+                |seed| {
+                    seed.with_details(&args.seed_name, &args.purpose, &args.fingerprint)
+                        .map(|seed| {
+                            seed.with_group_labels(&labels)
+                                .with_custodians(&args.custodians)
+                                .with_share_checks(&shares, args.hash)
+                                .with_creation_time()
+                        })
+                },
+            ),
+            "Error describing split",
+        );
+        seed.passphrase_check = encrypted.as_ref().map(|encrypted| encrypted.check.clone());
+        seed.passphrase_salt = encrypted
+            .as_ref()
+            .and_then(|encrypted| encrypted.salt.clone());
+        seed.dual_control = args.dual_control.then(DualControl::default);
+        seed
+    });
+
+    if let (Some(path), Some(seed)) = (&args.manifest, &seed) {
+        exit_on_error(
+            add_to_manifest(Path::new(path), seed),
+            "Error updating manifest",
+        );
+    }
+
+    if let (false, Some(seed)) = (args.mirror.is_empty(), &seed) {
+        let paths = exit_on_error(
+            mirror::mirror_record(&args.mirror, seed),
+            "Error mirroring split record",
+        );
+        for path in paths {
+            eprintln!("Split record written and read back: {}", path.display());
+        }
+    }
+
+    if let Some(path) = &args.paper {
+        paper_success(path, mnemonic, &shares, args, output);
+        let printed = shares
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|share| share_format::encode(share, ShareFormat::Bytewords))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        write_nfc_tags(&shares, args);
+        return read_back(&shares, &printed, args);
+    }
+
+    let printed = if args.confirm { groups.clone() } else { vec![] };
+    let hint_seed = seed.as_ref().filter(|_| args.hint_cards);
+    match (&args.estate, &args.out_dir, &seed, output) {
+        (Some(dir), _, Some(seed), _) => {
+            let existing = existing_shares(dir, args, &cli.confirmation_word);
+            estate_success(dir, mnemonic, groups, seed, args, existing, output)
+        }
+        (_, Some(dir), Some(seed), _) => {
+            let existing = existing_shares(dir, args, &cli.confirmation_word);
+            out_dir_success(dir, mnemonic, groups, seed, args, existing, output)
+        }
+        (_, _, _, OutputFormat::Text) => split_success(
+            group_threshold,
+            Some(&mnemonic),
+            groups,
+            &labels,
+            ShareDetails::of(args).with_hint_cards(hint_seed),
+            locale,
+        ),
+        (_, _, _, OutputFormat::Json) => exit_on_error(
+            SplitOutput::new(args.spec(), group_threshold, &mnemonic, &shares)
+                .map(|document| {
+                    if args.envelope {
+                        document.with_envelopes(&groups)
+                    } else {
+                        document
+                    }
+                })
+                .map(|document| match hint_seed {
+                    Some(seed) => document.with_hint_cards(&hint::hint_cards(seed)),
+                    None => document,
+                })
+                .and_then(|document| with_segments(document, &shares, args))
+                .and_then(|document| output::print_json(&document)),
+            "Error formatting output",
+        ),
+    }
+    write_nfc_tags(&shares, args);
+    read_back(&shares, &printed, args);
+}
+
+fn read_encryption_passphrase() -> Zeroizing<String> {
+    let passphrase = exit_on_error(
+        input::read_hidden("Encryption passphrase (not echoed): "),
+        "Error reading passphrase",
+    );
+    let repeated = exit_on_error(
+        input::read_hidden("Repeat the passphrase: "),
+        "Error reading passphrase",
+    );
+    exit_on_error(
+        encryption::check_passphrase(&passphrase, &repeated),
+        "Error encrypting mnemonic",
+    );
+    passphrase
+}
+
+fn read_operator_passphrase(message: &str) -> Result<Zeroizing<String>, anyhow::Error> {
+    input::read_hidden(&format!("{} (not echoed): ", message))
+}
+
+// Splits the entropy again, encrypted under a passphrase, keeping the
+// identifier already chosen, and gives the passphrase's check words
+fn encrypt_shares(
+    mnemonic: &Mnemonic,
+    shares: &mut split::ShareGroups,
+    group_threshold: usize,
+    args: &SplitArgs,
+    passphrase: &str,
+) -> encryption::Encrypted {
+    let encrypted = exit_on_error(
+        encryption::encrypt(mnemonic.entropy(), passphrase),
+        "Error encrypting mnemonic",
+    );
+    let (identifier, _, _) = exit_on_error(
+        split::share_position(&shares[0][0]),
+        "Error encrypting mnemonic",
+    );
+    *shares = exit_on_error(
+        split::split_raw(args.spec(), group_threshold, &encrypted.secret),
+        "Error splitting mnemonic",
+    );
+    split::set_identifier(shares, identifier);
+    eprintln!(
+        "Passphrase check: {} (recovering shows the same words for the right passphrase)",
+        encrypted.check
+    );
+    if let Some(salt) = &encrypted.salt {
+        eprintln!(
+            "Passphrase salt: {} (doesn't fit in the shares, keep it with them: recovering needs it)",
+            salt
+        );
+    }
+    encrypted
+}
+
+// Runs before the shares are shown or written, so a split that fails a check
+// never leaves the machine
+fn paranoid_check(shares: &[Vec<Vec<u8>>], encoded: &[Vec<String>], secret: &[u8]) {
+    let checked = exit_on_error(
+        paranoid::check_split(shares, encoded, secret),
+        "Paranoid check failed",
+    );
+    eprintln!(
+        "Paranoid checks passed: {} shares decode back, {} combinations recover the secret on two code paths",
+        checked.shares, checked.combinations
+    );
+}
+
+#[derive(Serialize)]
+struct Codex32SplitOutput {
+    #[serde(flatten)]
+    secret: SecretOutput,
+    spec: String,
+    identifier: String,
+    threshold: usize,
+    shares: Vec<String>,
+}
+
+// Codex32 shares in place of SSKR shares. They hold the same secret as SSKR
+// shares do, the entropy of a mnemonic rather than the seed derived from it.
+fn split_codex32(
+    secret: &[u8],
+    mnemonic: Option<&Mnemonic>,
+    args: &SplitArgs,
+    output: &OutputFormat,
+) {
+    let (threshold, count) = exit_on_error(
+        split::single_group(args.spec()),
+        "Error splitting into codex32",
+    );
+    let identifier = codex32::random_identifier();
+    let shares = exit_on_error(
+        if threshold == 1 {
+            codex32::encode_secret(secret, &identifier).map(|secret| vec![secret])
+        } else {
+            codex32::split(secret, threshold, count, &identifier)
+        },
+        "Error splitting into codex32",
+    );
+
+    match output {
+        OutputFormat::Text => {
+            match mnemonic {
+                Some(mnemonic) => {
+                    print_secret(mnemonic);
+                }
+                None => println!(
+                    "Secret:   {}",
+                    policy::shown(Field::Entropy, format_args!("0x{}", hex::encode(secret)))
+                ),
+            }
+            println!();
+            println!("Codex32 identifier: {}", identifier);
+            if threshold == 1 {
+                println!("The secret as a single codex32 string:");
+            } else {
+                println!("{} of {} shares are needed to recover:", threshold, count);
+            }
+            for share in &shares {
+                println!("  {}", policy::shown(Field::Shares, share));
+            }
+        }
+        OutputFormat::Json => exit_on_error(
+            output::print_json(&Codex32SplitOutput {
+                secret: mnemonic.map_or_else(|| SecretOutput::raw(secret), SecretOutput::new),
+                spec: args.spec().to_string(),
+                identifier,
+                threshold,
+                shares: shares
+                    .iter()
+                    .map(|share| policy::shown(Field::Shares, share))
+                    .collect(),
+            }),
+            "Error formatting output",
+        ),
+    }
+}
+
+// The example split, shown like any other but marked on both ends
+fn split_example(args: &SplitArgs, cli: &Cli) {
+    eprintln!("{}", example::NOTICE);
+    let (mnemonic, shares) = exit_on_error(
+        example::mnemonic().and_then(|mnemonic| Ok((mnemonic, example::shares()?))),
+        "Error splitting example",
+    );
+    match cli.output {
+        OutputFormat::Text => split_success(
+            example::GROUP_THRESHOLD,
+            Some(&mnemonic),
+            encode_shares(&shares, args),
+            &[],
+            ShareDetails::of(args),
+            cli.locale,
+        ),
+        OutputFormat::Json => exit_on_error(
+            SplitOutput::new(example::SPEC, example::GROUP_THRESHOLD, &mnemonic, &shares)
+                .and_then(|document| with_segments(document, &shares, args))
+                .and_then(|document| output::print_json(&document)),
+            "Error formatting output",
+        ),
+    }
+    eprintln!("Any two of these shares recover the seed, see it done with `recover --example`");
+    eprintln!("{}", example::NOTICE);
+}
+
+fn example_share_lines() -> Vec<input::ShareLine> {
+    eprintln!("{}", example::NOTICE);
+    let lines = exit_on_error(example::recovery_lines(), "Error reading example shares");
+    eprintln!(
+        "Recovering from shares {} and {} of `split --example`:",
+        example::RECOVERED_FROM[0],
+        example::RECOVERED_FROM[1]
+    );
+    for line in &lines {
+        eprintln!("  {}", policy::shown(Field::Shares, line));
+    }
+    lines
+        .into_iter()
+        .zip(example::RECOVERED_FROM)
+        .map(|(text, member)| input::ShareLine {
+            source: "example".to_string(),
+            line: member,
+            text,
+        })
+        .collect()
+}
+
+// The shares have been shown or written by now, and are asked back from the
+// copies made of them
+fn read_back(shares: &[Vec<Vec<u8>>], printed: &[Vec<String>], args: &SplitArgs) {
+    if !args.confirm {
+        return;
+    }
+    let mode = match args.confirm_words {
+        Some(count) => readback::ReadBack::Sample(count as usize),
+        None => readback::ReadBack::Full,
+    };
+    eprintln!("Type each share back from the copy you made of it, not from the screen.");
+    let confirmed = exit_on_error(
+        readback::run(
+            shares,
+            printed,
+            mode,
+            |words, count| {
+                rand::seq::index::sample(&mut rand::thread_rng(), words, count).into_vec()
+            },
+            &mut std::io::stdin().lock(),
+            &mut std::io::stderr(),
+        ),
+        "Read-back failed",
+    );
+    eprintln!("All {} shares match their copies", confirmed);
+}
+
+// Every share goes on a tag of its own, which is read back after writing; a
+// tag that can't be written is asked for again
+#[cfg(feature = "smartcard")]
+fn write_nfc_tags(shares: &[Vec<Vec<u8>>], args: &SplitArgs) {
+    if !args.nfc {
+        return;
+    }
+    for (group, members) in shares.iter().enumerate() {
+        for (member, share) in members.iter().enumerate() {
+            loop {
+                if !wait_for_tag(&format!(
+                    "Place the tag for group {}, share {} on the reader and press Enter",
+                    group + 1,
+                    member + 1
+                )) {
+                    exit_on_error::<()>(
+                        Err(anyhow::anyhow!(
+                            "The input ended before every share was written"
+                        )),
+                        "Error writing tags",
+                    );
+                }
+                match PcscReader::connect(args.reader.as_deref())
+                    .and_then(|reader| NfcTag::new(reader).write_share(share))
+                {
+                    Ok(()) => {
+                        eprintln!(
+                            "Written and read back: group {}, share {}",
+                            group + 1,
+                            member + 1
+                        );
+                        break;
+                    }
+                    Err(error) => eprintln!("Unable to write the tag: {:#}", error),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "smartcard"))]
+fn write_nfc_tags(_shares: &[Vec<Vec<u8>>], _args: &SplitArgs) {}
+
+// Tags are read one at a time, as the custodians bring them, until the
+// shares are enough or the input ends
+#[cfg(feature = "smartcard")]
+fn read_nfc_tags(args: &RecoverArgs) -> Vec<input::ShareLine> {
+    let mut collector = scan::Collector::new(&args.minimal);
+    let mut report = |message: &str| eprintln!("{}", message);
+    while wait_for_tag("Place a share's tag on the reader and press Enter") {
+        match PcscReader::connect(args.reader.as_deref())
+            .and_then(|reader| NfcTag::new(reader).read_share())
+        {
+            Ok(share) => {
+                let text = share_format::encode(&share, ShareFormat::Ur);
+                if exit_on_error(collector.offer(text, &mut report), "Error reading tags") {
+                    break;
+                }
+            }
+            Err(error) => eprintln!("Unable to read the tag: {:#}", error),
+        }
+    }
+    collector
+        .into_shares()
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| input::ShareLine {
+            source: "NFC tag".to_string(),
+            line: index + 1,
+            text,
+        })
+        .collect()
+}
+
+// Whether Enter was pressed, rather than the input ending
+#[cfg(feature = "smartcard")]
+fn wait_for_tag(message: &str) -> bool {
+    eprint!("{}: ", message);
+    let mut line = String::new();
+    matches!(std::io::stdin().read_line(&mut line), Ok(read) if read > 0)
+}
+
+// The raw secret as the split prints it, which a secret given with
+// --raw-secret is left out of as a given mnemonic is
+fn raw_secret_line(secret: &[u8], policy: &Policy) -> String {
+    format!(
+        "Secret:   {}",
+        policy.shown(
+            Field::Entropy,
+            Channel::Display,
+            format_args!("0x{}", hex::encode(secret))
+        )
+    )
+}
+
+fn split_raw_secret(secret: &str, group_threshold: usize, args: &SplitArgs, cli: &Cli) {
+    let secret = secret.trim();
+    let secret = Zeroizing::new(exit_on_error(
+        hex::decode(secret.strip_prefix("0x").unwrap_or(secret)).map_err(anyhow::Error::from),
+        "Invalid --raw-secret, expected hex",
+    ));
+    if args.codex32 {
+        return split_codex32(&secret, None, args, &cli.output);
+    }
+    let mut shares = exit_on_error(
+        split::split_raw(args.spec(), group_threshold, &secret),
+        "Error splitting secret",
+    );
+    if let Some(identifier) = chosen_identifier(args) {
+        split::set_identifier(&mut shares, identifier);
+    }
+    if !split::BIP39_ENTROPY_LENGTHS.contains(&secret.len()) {
+        eprintln!(
+            "The secret is {} bytes, which no BIP-39 mnemonic has; the shares recover it as hex",
+            secret.len()
+        );
+    }
+    if cli.paranoid {
+        paranoid_check(&shares, &encode_shares(&shares, args), &secret);
+    }
+
+    let (output, locale) = (&cli.output, cli.locale);
+    match output {
+        OutputFormat::Text => {
+            println!("{}", raw_secret_line(&secret, policy::current()));
+            println!();
+            let groups = encode_shares(&shares, args);
+            split_success(
+                group_threshold,
+                None,
+                groups,
+                &exit_on_error(split::spec_labels(args.spec()), "Invalid spec"),
+                ShareDetails::of(args),
+                locale,
+            );
+        }
+        OutputFormat::Json => exit_on_error(
+            SplitOutput::raw(args.spec(), group_threshold, &secret, &shares)
+                .map(|document| {
+                    if args.envelope {
+                        document.with_envelopes(&encode_shares(&shares, args))
+                    } else {
+                        document
+                    }
+                })
+                .and_then(|document| with_segments(document, &shares, args))
+                .and_then(|document| output::print_json(&document)),
+            "Error formatting output",
+        ),
+    }
+    write_nfc_tags(&shares, args);
+    read_back(&shares, &encode_shares(&shares, args), args);
+}
+
+// The key of the sealed file is never shown, the shares are the only way back
+// to the file
+fn split_file(path: &Path, group_threshold: usize, args: &SplitArgs, cli: &Cli) {
+    let contents = Zeroizing::new(exit_on_error(
+        fs::read(path).with_context(|| format!("Unable to read \"{}\"", path.display())),
+        "Error splitting file",
+    ));
+    let (key, sealed_contents) = sealed::seal(&contents);
+    let mut shares = exit_on_error(
+        split::split_raw(args.spec(), group_threshold, key.as_slice()),
+        "Error splitting file",
+    );
+    if let Some(identifier) = chosen_identifier(args) {
+        split::set_identifier(&mut shares, identifier);
+    }
+    if cli.paranoid {
+        paranoid_check(&shares, &encode_shares(&shares, args), key.as_slice());
+    }
+    let sealed_path = args
+        .sealed_file
+        .as_ref()
+        .map_or_else(|| sealed::default_path(path), PathBuf::from);
+    exit_on_error(
+        sealed::write_new(&sealed_path, &sealed_contents),
+        "Error writing sealed file",
+    );
+    eprintln!(
+        "Sealed {} bytes into \"{}\"; keep it with the shares, they only hold its key",
+        contents.len(),
+        sealed_path.display()
+    );
+
+    let (output, locale) = (&cli.output, cli.locale);
+    match output {
+        OutputFormat::Text => {
+            split_success(
+                group_threshold,
+                None,
+                encode_shares(&shares, args),
+                &exit_on_error(split::spec_labels(args.spec()), "Invalid spec"),
+                ShareDetails::of(args),
+                locale,
+            );
+        }
+        OutputFormat::Json => exit_on_error(
+            SplitOutput::raw(args.spec(), group_threshold, key.as_slice(), &shares)
+                .map(|document| SplitOutput {
+                    secret: None,
+                    ..document
+                })
+                .and_then(|document| with_segments(document, &shares, args))
+                .and_then(|document| output::print_json(&document)),
+            "Error formatting output",
+        ),
+    }
+    write_nfc_tags(&shares, args);
+    read_back(&shares, &encode_shares(&shares, args), args);
+}
+
+fn encode_shares(shares: &[Vec<Vec<u8>>], args: &SplitArgs) -> Vec<Vec<String>> {
+    if !args.envelope {
+        let style = args
+            .style
+            .unwrap_or(BytewordStyle::from_minimal(&args.minimal));
+        let groups = split::to_format(shares, args.share_format.share_format(style));
+        return match args.separator {
+            Some(separator) if style == BytewordStyle::Standard => groups
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .map(|share| separator.separate(share))
+                        .collect()
+                })
+                .collect(),
+            _ => groups,
+        };
+    }
+    let date = args.envelope_date.then(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() as i64)
+    });
+    split::to_envelopes(shares, args.envelope_label.as_deref(), date)
+}
+
+fn with_segments(
+    document: SplitOutput,
+    shares: &[Vec<Vec<u8>>],
+    args: &SplitArgs,
+) -> Result<SplitOutput, anyhow::Error> {
+    match args.segments {
+        Some(count) => document.with_segments(shares, count as usize),
+        None => Ok(document),
+    }
+}
+
+// Applies the profile a split names, if any
+fn with_profile(
+    cli: &mut Cli,
+    matches: &ArgMatches,
+    dir: impl FnOnce() -> Result<PathBuf, anyhow::Error>,
+) -> Result<(), anyhow::Error> {
+    let Commands::Split(args) = &mut cli.command else {
+        return Ok(());
+    };
+    let Some(name) = &args.profile else {
+        return Ok(());
+    };
+    let profile = profile::load(&dir()?, name)?;
+    let split = matches.subcommand_matches("split");
+    let given =
+        |id: &str| split.and_then(|split| split.value_source(id)) == Some(ValueSource::CommandLine);
+    cli.paranoid |= profile.paranoid;
+    apply_profile(args, profile, given);
+    Ok(())
+}
+
+// Fills in the split options the profile gives and the command line doesn't
+fn apply_profile(args: &mut SplitArgs, profile: profile::Profile, given: impl Fn(&str) -> bool) {
+    let spec_given = args.spec.is_some();
+    args.spec.get_or_insert(profile.spec);
+    if !spec_given && args.group_threshold.is_none() {
+        args.group_threshold = profile
+            .group_threshold
+            .map(|threshold| threshold.to_string());
+    }
+    if !given("words") {
+        args.words = profile.words.unwrap_or(args.words);
+    }
+    if !given("share_format") {
+        args.share_format = profile.share_format.unwrap_or(args.share_format);
+    }
+    if !given("name_template") {
+        args.name_template = profile.name_template.unwrap_or(args.name_template.clone());
+    }
+    if !given("hash") {
+        args.hash = profile.hash.unwrap_or(args.hash);
+    }
+    args.language = args.language.or(profile.language);
+    args.style = args.style.or(profile.style);
+    args.separator = args.separator.or(profile.separator);
+    args.out_dir = args.out_dir.take().or(profile.out_dir);
+    args.manifest = args.manifest.take().or(profile.manifest);
+    args.paper = args.paper.take().or(profile.paper);
+    args.seed_name = args.seed_name.take().or(profile.seed_name);
+    args.purpose = args.purpose.take().or(profile.purpose);
+    if args.mirror.is_empty() {
+        args.mirror = profile.mirror;
+    }
+    args.checksums |= profile.checksums;
+    args.confirm |= profile.confirm;
+    args.dual_control |= profile.dual_control;
+    args.encrypt |= profile.encrypt;
+    args.ascii_names |= profile.ascii_names;
+    args.hint_cards |= profile.hint_cards;
+
+    // The custodians are named for the profile's own spec
+    if !spec_given {
+        args.custodians = profile.custodians;
+    }
+}
+
+impl SplitArgs {
+    // Only --batch and --example splits go without a spec, and they never ask
+    // for this one
+    fn spec(&self) -> &str {
+        self.spec.as_deref().unwrap_or_default()
+    }
+
+    fn name_template(&self) -> naming::NameTemplate {
+        naming::NameTemplate::new(&self.name_template).ascii(self.ascii_names)
+    }
+}
+
+// Whether the secret to split came from the user rather than being generated
+fn secret_given(args: &SplitArgs) -> bool {
+    args.no_echo
+        || args.entropy_hex.is_some()
+        || args.raw_secret.is_some()
+        || split_parameters(args).is_ok_and(|(_, phrase)| phrase.is_some())
+}
+
+fn chosen_identifier(args: &SplitArgs) -> Option<u16> {
+    args.identifier.or_else(|| {
+        args.identifier_seed
+            .as_deref()
+            .map(split::seeded_identifier)
+    })
+}
+
+// The most combinations of groups listed in text; JSON lists them all
+const MAX_LISTED_QUORUMS: usize = 20;
+
+#[derive(Serialize)]
+struct SpecCheckOutput {
+    group_threshold: usize,
+    groups: Vec<SpecGroupOutput>,
+    shares: usize,
+    fewest_shares: usize,
+    // Group numbers, from 1
+    quorums: Vec<Vec<usize>>,
+    mnemonic_words: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct SpecGroupOutput {
+    label: Option<String>,
+    threshold: usize,
+    count: usize,
+}
+
+fn split_check(
+    group_threshold: usize,
+    phrase: Option<&str>,
+    args: &SplitArgs,
+    output: &OutputFormat,
+) {
+    let layout = exit_on_error(
+        split::spec_layout(args.spec(), group_threshold),
+        "Invalid spec",
+    );
+    let mnemonic = phrase.map(|phrase| {
+        let language = args
+            .language
+            .map(MnemonicLanguage::bip39)
+            .map_or_else(|| language::detect_language(phrase), Ok);
+        exit_on_error(
+            language.and_then(|language| Mnemonic::from_phrase(phrase, language)),
+            "Invalid mnemonic",
+        )
+    });
+    let result = SpecCheckOutput {
+        group_threshold: layout.group_threshold,
+        groups: layout
+            .groups
+            .iter()
+            .map(|group| SpecGroupOutput {
+                label: group.label.clone(),
+                threshold: group.threshold,
+                count: group.count,
+            })
+            .collect(),
+        shares: layout.share_count(),
+        fewest_shares: layout.fewest_shares(),
+        quorums: layout
+            .quorums()
+            .map(|quorum| quorum.iter().map(|group| group + 1).collect())
+            .collect(),
+        mnemonic_words: mnemonic
+            .as_ref()
+            .map(|mnemonic| mnemonic.phrase().split(' ').count()),
+    };
+
+    match output {
+        OutputFormat::Text => {
+            eprintln!("Spec checked, no shares were generated");
+            println!(
+                "{} of {} groups recover, {} shares in all:",
+                result.group_threshold,
+                result.groups.len(),
+                result.shares
+            );
+            for (number, group) in result.groups.iter().enumerate() {
+                let label = group
+                    .label
+                    .as_ref()
+                    .map_or(String::new(), |label| format!(" ({})", label));
+                println!(
+                    "  Group {}{}: {} of {} shares",
+                    number + 1,
+                    label,
+                    group.threshold,
+                    group.count
+                );
+            }
+            println!(
+                "Combinations of groups that recover ({}):",
+                result.quorums.len()
+            );
+            for quorum in result.quorums.iter().take(MAX_LISTED_QUORUMS) {
+                let groups: Vec<String> = quorum.iter().map(usize::to_string).collect();
+                let shares: usize = quorum
+                    .iter()
+                    .map(|group| result.groups[group - 1].threshold)
+                    .sum();
+                println!("  Groups {}: {} shares", groups.join(", "), shares);
+            }
+            if result.quorums.len() > MAX_LISTED_QUORUMS {
+                println!(
+                    "  ... and {} more",
+                    result.quorums.len() - MAX_LISTED_QUORUMS
+                );
+            }
+            println!("The fewest shares that recover: {}", result.fewest_shares);
+            if let Some(words) = result.mnemonic_words {
+                println!("Mnemonic: valid, {} words", words);
+            }
+        }
+        OutputFormat::Json => exit_on_error(output::print_json(&result), "Error formatting output"),
+    }
+}
+
+// The group threshold can be left out for single-group specs, in which case
+// the second argument, if any, is the mnemonic
+fn split_parameters(args: &SplitArgs) -> Result<(usize, Option<&str>), anyhow::Error> {
+    let (group_threshold, phrase) = match (&args.group_threshold, &args.mnemonic) {
+        (Some(threshold), phrase) if threshold.trim().parse::<usize>().is_ok() => {
+            (threshold.trim().parse()?, phrase.as_deref())
+        }
+        (Some(phrase), None) => (
+            split::default_group_threshold(args.spec())?,
+            Some(phrase.as_str()),
+        ),
+        (Some(threshold), Some(_)) => bail!("Invalid group threshold \"{}\"", threshold),
+        (None, _) => (split::default_group_threshold(args.spec())?, None),
+    };
+
+    if phrase.is_some() && args.entropy.is_some() {
+        bail!("--entropy generates a new mnemonic, it can't be used with a given mnemonic");
+    }
+    if phrase.is_some() && args.raw_secret.is_some() {
+        bail!("Give either a mnemonic or --raw-secret, not both");
+    }
+    if phrase.is_some() && args.entropy_hex.is_some() {
+        bail!("Give either a mnemonic or --entropy-hex, not both");
+    }
+    if args.share_format != SplitFormat::Bytewords
+        && (args.minimal || args.style.is_some() || args.envelope || args.paper.is_some())
+    {
+        bail!(
+            "--share-format {} writes shares as hex, it can't be combined with --minimal, \
+             --style, --envelope or --paper",
+            args.share_format.share_format(BytewordStyle::Standard)
+        );
+    }
+    if args.separator.is_some()
+        && (args.share_format != SplitFormat::Bytewords
+            || args
+                .style
+                .is_some_and(|style| style != BytewordStyle::Standard))
+    {
+        bail!("--separator only separates the words of standard bytewords");
+    }
+    if phrase.is_some() && args.no_echo {
+        bail!("--no-echo prompts for the mnemonic, it can't be given on the command line too");
+    }
+    Ok((group_threshold, phrase))
+}
+
+// Replacing shares deletes files, so it only goes ahead once confirmed, and
+// only needs confirming when there is something to delete
+fn existing_shares(dir: &str, args: &SplitArgs, confirmation_word: &str) -> ExistingShares {
+    if args.verify_existing {
+        return ExistingShares::Verify;
+    }
+    if !args.replace_existing {
+        return ExistingShares::Refuse;
+    }
+
+    let found = exit_on_error(
+        artifacts::existing_shares(Path::new(dir)),
+        "Error reading output directory",
+    );
+    if !found.is_empty() {
+        let action = format!(
+            "This deletes {} file(s) in \"{}\" holding shares of identifier(s) {}.",
+            found.len(),
+            dir,
+            found
+                .values()
+                .flatten()
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        exit_on_error(
+            confirm::confirm(
+                &action,
+                confirmation_word,
+                &mut std::io::stdin().lock(),
+                &mut std::io::stderr(),
+            ),
+            "Error replacing existing shares",
+        );
+    }
+    ExistingShares::Replace
+}
+
+fn add_to_manifest(path: &Path, seed: &SeedManifest) -> Result<(), anyhow::Error> {
+    let manifest = if path.exists() {
+        let mut manifest = Manifest::load(path)?;
+        manifest.add_seed(seed.clone())?;
+        manifest
+    } else {
+        Manifest::new(seed.clone())
+    };
+    manifest.save(path)
+}
+
+// What is printed with each share besides the share itself
+#[derive(Default)]
+struct ShareDetails {
+    checksums: bool,
+    segments: Option<usize>,
+    // The custodians of each group's shares, when a profile names them
+    custodians: Vec<Vec<String>>,
+    fingerprint: Option<String>,
+    // Printed after the shares, by group
+    hint_cards: Vec<Vec<hint::HintCard>>,
+}
+
+impl ShareDetails {
+    fn of(args: &SplitArgs) -> ShareDetails {
+        ShareDetails {
+            checksums: args.checksums,
+            segments: args.segments.map(usize::from),
+            custodians: args.custodians.clone(),
+            fingerprint: args.fingerprint.clone(),
+            hint_cards: vec![],
+        }
+    }
+
+    fn with_hint_cards(mut self, seed: Option<&SeedManifest>) -> ShareDetails {
+        self.hint_cards = seed.map(hint::hint_cards).unwrap_or_default();
+        self
+    }
+}
+
+// Column widths shared by every group, so that the largest split SSKR allows,
+// 16 groups of 16 shares, lines up the same as a small one
+struct SplitLayout {
+    label: usize,
+    group: usize,
+    member: usize,
+    custodian: usize,
+}
+
+impl SplitLayout {
+    fn of(groups: &[Vec<String>], labels: &[Option<String>], custodians: &[Vec<String>]) -> Self {
+        let widest = |names: Vec<&String>| {
+            names
+                .iter()
+                .map(|name| name.chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        let digits = |count: usize| count.to_string().len();
+        SplitLayout {
+            label: widest(labels.iter().flatten().collect()),
+            group: digits(groups.len()),
+            member: digits(groups.iter().map(Vec::len).max().unwrap_or(0)),
+            custodian: widest(custodians.iter().flatten().collect()),
+        }
+    }
+
+    // Where what is printed under a share starts, in line with the share
+    fn indent(&self) -> String {
+        let custodian = match self.custodian {
+            0 => 0,
+            width => width + 2,
+        };
+        " ".repeat(2 + self.member + 2 + custodian)
+    }
+}
+
+// Pads text and what follows it to the width in characters of the text,
+// outside the isolate marks around it
+fn padded(text: &str, suffix: &str, width: usize, locale: Locale) -> String {
+    let padding = " ".repeat(width.saturating_sub(text.chars().count()));
+    format!("{}{}{}", locale.ltr(text), suffix, padding)
+}
+
+fn split_success(
+    group_threshold: usize,
+    secret: Option<&Mnemonic>,
+    groups: Vec<Vec<String>>,
+    labels: &[Option<String>],
+    details: ShareDetails,
+    locale: Locale,
+) {
+    exit_on_error(
+        write_split(
+            &mut std::io::stdout().lock(),
+            group_threshold,
+            secret,
+            &groups,
+            labels,
+            details,
+            locale,
+        ),
+        "Error writing shares",
+    )
+}
+
+fn write_split(
+    out: &mut impl Write,
+    group_threshold: usize,
+    secret: Option<&Mnemonic>,
+    groups: &[Vec<String>],
+    labels: &[Option<String>],
+    details: ShareDetails,
+    locale: Locale,
+) -> Result<(), anyhow::Error> {
+    if let Some(mnemonic) = secret {
+        write_secret(out, mnemonic)?;
+        writeln!(out)?;
+    }
+    let first = groups.first().and_then(|group| group.first());
+    if let Some(Ok(metadata)) = first.map(|share| {
+        recover::decode_share(share).and_then(|share| ShareMetadataOutput::new(&share))
+    }) {
+        writeln!(out, "{}", locale.share_set(&metadata.identifier))?;
+    }
+    if let Some(fingerprint) = &details.fingerprint {
+        writeln!(
+            out,
+            "{}",
+            locale.fingerprint(&policy::shown(Field::Fingerprints, fingerprint))
+        )?;
+    }
+    writeln!(out, "{}\n", locale.split_header(group_threshold))?;
+
+    let layout = SplitLayout::of(groups, labels, &details.custodians);
+    let indent = layout.indent();
+    for (group_num, group) in groups.iter().enumerate() {
+        let member_threshold =
+            ShareMetadataOutput::new(&recover::decode_share(&group[0])?)?.member_threshold;
+        let needs = locale.group_needs(
+            format!("{:>width$}", group_num + 1, width = layout.group),
+            member_threshold,
+            group.len(),
+        );
+        // Labels are padded so the groups' descriptions line up, and groups
+        // without one leave the space blank
+        match labels.get(group_num).and_then(Option::as_ref) {
+            Some(label) => writeln!(
+                out,
+                "{} {}",
+                padded(label, ":", layout.label, locale),
+                needs
+            )?,
+            None if layout.label > 0 => {
+                writeln!(out, "{} {}", " ".repeat(layout.label + 1), needs)?
+            }
+            None => writeln!(out, "{}", needs)?,
+        }
+        let custodians = details.custodians.get(group_num);
+        for (share_num, share) in group.iter().enumerate() {
+            let custodian = match custodians.and_then(|names| names.get(share_num)) {
+                Some(name) => padded(name, "  ", layout.custodian, locale),
+                None if layout.custodian > 0 => " ".repeat(layout.custodian + 2),
+                None => String::new(),
+            };
+            writeln!(
+                out,
+                "  {}: {}{}",
+                locale.ltr(format!("{:>width$}", share_num + 1, width = layout.member)),
+                custodian,
+                // Words on lines of their own (--separator newline) are
+                // kept in the share's column
+                policy::shown(Field::Shares, share)
+                    .lines()
+                    .map(|line| locale.ltr(line))
+                    .collect::<Vec<_>>()
+                    .join(&format!("\n{}", indent))
+            )?;
+            if details.checksums {
+                let share = recover::decode_share(share).context("Error describing split")?;
+                write_checksum(out, &share_format::share_checksum(&share), &indent)?;
+            }
+            if let Some(count) = details.segments {
+                let segments = recover::decode_share(share)
+                    .and_then(|share| share_format::segments(&share, count))
+                    .context("Error segmenting share")?;
+                writeln!(out, "{}Segments:", indent)?;
+                for segment in segments {
+                    writeln!(
+                        out,
+                        "{}  {}",
+                        indent,
+                        locale.ltr(policy::shown(Field::Shares, segment))
+                    )?;
+                }
+            }
+        }
+        writeln!(out)?;
+    }
+    if !details.hint_cards.is_empty() {
+        writeln!(
+            out,
+            "Hint cards (no shares or secret, one for each custodian):\n"
+        )?;
+        for card in details.hint_cards.iter().flatten() {
+            writeln!(out, "{}", card.render())?;
+        }
+    }
+    Ok(())
+}
+
+// As the policy allows them shown
+fn write_secret(out: &mut impl Write, mnemonic: &Mnemonic) -> std::io::Result<()> {
+    let secret = SecretOutput::new(mnemonic);
+    writeln!(out, "Entropy:  {}", secret.entropy)?;
+    writeln!(out, "Mnemonic: {}", secret.mnemonic.unwrap_or_default())
+}
+
+fn print_secret(mnemonic: &Mnemonic) {
+    exit_on_error(
+        write_secret(&mut std::io::stdout().lock(), mnemonic).map_err(anyhow::Error::from),
+        "Error writing secret",
+    )
+}
+
+#[derive(Serialize)]
+struct EstateOutput {
+    #[serde(flatten)]
+    secret: SecretOutput,
+    executor: Vec<PathBuf>,
+    custodians: Vec<PathBuf>,
+    verified_existing: bool,
+}
+
+fn estate_success(
+    dir: &str,
+    mnemonic: Mnemonic,
+    groups: Vec<Vec<String>>,
+    seed: &SeedManifest,
+    args: &SplitArgs,
+    existing: ExistingShares,
+    output: &OutputFormat,
+) {
+    let packets = estate::write_estate(
+        Path::new(dir),
+        seed,
+        &groups,
+        &args.name_template(),
+        existing,
+    );
+
+    let packets = match packets {
+        Ok(packets) => packets,
+        Err(error) => {
+            eprintln!("Error writing estate packets: {:?}", error);
+            process::exit(error::exit_code(&error));
+        }
+    };
+
+    if *output == OutputFormat::Json {
+        let document = EstateOutput {
+            secret: SecretOutput::new(&mnemonic),
+            executor: packets.executor,
+            custodians: packets.custodians,
+            verified_existing: packets.outcome == WriteOutcome::Verified,
+        };
+        return exit_on_error(output::print_json(&document), "Error formatting output");
+    }
+
+    print_secret(&mnemonic);
+    println!();
+    if packets.outcome == WriteOutcome::Verified {
+        println!(
+            "Existing packets in \"{}\" match this split, nothing was written",
+            dir
+        );
+        println!();
+    }
+    println!("Executor packet (instructions only, give to the executor):");
+    for path in packets.executor {
+        println!("  {}", path.display());
+    }
+    println!();
+    println!("Custodian packets (one share each, give one to each custodian):");
+    for path in packets.custodians {
+        println!("  {}", path.display());
+    }
+}
+
+#[derive(Serialize)]
+struct ShareFilesOutput {
+    #[serde(flatten)]
+    secret: SecretOutput,
+    manifest: PathBuf,
+    shares: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hint_cards: Vec<PathBuf>,
+    verified_existing: bool,
+}
+
+#[derive(Serialize)]
+struct BatchSeedOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    identifier: String,
+    #[serde(flatten)]
+    secret: SecretOutput,
+    manifest: PathBuf,
+    shares: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hint_cards: Vec<PathBuf>,
+    verified_existing: bool,
+}
+
+fn split_batch(path: &Path, args: &SplitArgs, cli: &Cli) {
+    let batch = exit_on_error(batch::Batch::load(path), "Error reading batch");
+    let base = path.parent().unwrap_or(Path::new("."));
+    let jobs = exit_on_error(batch.jobs(base), "Error reading batch");
+
+    // Every seed is split before anything is written, so a bad mnemonic
+    // further down doesn't leave the batch half done
+    let splits = exit_on_error(
+        jobs.iter()
+            .map(|job| split_batch_job(job, args))
+            .collect::<Result<Vec<_>, _>>(),
+        "Error splitting batch",
+    );
+
+    let mut outputs = vec![];
+    for (job, (mnemonic, shares, seed)) in jobs.iter().zip(splits) {
+        let dir = job.out_dir.display().to_string();
+        let existing = existing_shares(&dir, args, &cli.confirmation_word);
+        let files = exit_on_error(
+            artifacts::write_share_files(
+                &job.out_dir,
+                &seed,
+                &encode_shares(&shares, args),
+                &args.name_template(),
+                existing,
+                args.hash,
+                args.hint_cards,
+            ),
+            &format!("Error writing share files to \"{}\"", dir),
+        );
+        outputs.push(BatchSeedOutput {
+            name: seed.name.clone(),
+            identifier: seed.identifier.clone(),
+            secret: match (&job.mnemonic, args.echo_secret) {
+                (Some(_), false) => SecretOutput::new(&mnemonic).withheld(),
+                _ => SecretOutput::new(&mnemonic),
+            },
+            manifest: files.manifest,
+            shares: files.shares,
+            hint_cards: files.hint_cards,
+            verified_existing: files.outcome == WriteOutcome::Verified,
+        });
+    }
+
+    if cli.output == OutputFormat::Json {
+        return exit_on_error(output::print_json(&outputs), "Error formatting output");
+    }
+    for seed in outputs {
+        match &seed.name {
+            Some(name) => println!("Seed:     {} (share set {})", name, seed.identifier),
+            None => println!("Seed:     share set {}", seed.identifier),
+        }
+        println!("Mnemonic: {}", seed.secret.mnemonic.unwrap_or_default());
+        println!("Manifest: {}", seed.manifest.display());
+        if seed.verified_existing {
+            println!("Existing files match this split, nothing was written");
+        } else {
+            println!("Shares:   {} files", seed.shares.len());
+            if !seed.hint_cards.is_empty() {
+                println!("Hints:    {} files", seed.hint_cards.len());
+            }
+        }
+        println!();
+    }
+}
+
+fn split_batch_job(
+    job: &batch::BatchJob,
+    args: &SplitArgs,
+) -> Result<(Mnemonic, split::ShareGroups, SeedManifest), anyhow::Error> {
+    let (mnemonic, shares) = match &job.mnemonic {
+        Some(phrase) => split::split_shares(&job.spec, job.group_threshold, phrase, None)?,
+        None => split::split_shares(
+            &job.spec,
+            job.group_threshold,
+            split::random_mnemonic(job.words, Language::English).phrase(),
+            Some(Language::English),
+        )?,
+    };
+    let seed = SeedManifest::from_shares(
+        &split::to_bytewords(&shares, &args.minimal),
+        &args.minimal,
+        mnemonic.language(),
+    )?
+    .with_details(&job.name, &job.purpose, &None)?
+    .with_group_labels(&split::spec_labels(&job.spec)?)
+    .with_share_checks(&shares, args.hash)
+    .with_creation_time();
+    Ok((mnemonic, shares, seed))
+}
+
+fn out_dir_success(
+    dir: &str,
+    mnemonic: Mnemonic,
+    groups: Vec<Vec<String>>,
+    seed: &SeedManifest,
+    args: &SplitArgs,
+    existing: ExistingShares,
+    output: &OutputFormat,
+) {
+    let files = exit_on_error(
+        artifacts::write_share_files(
+            Path::new(dir),
+            seed,
+            &groups,
+            &args.name_template(),
+            existing,
+            args.hash,
+            args.hint_cards,
+        ),
+        "Error writing share files",
+    );
+
+    if *output == OutputFormat::Json {
+        let document = ShareFilesOutput {
+            secret: SecretOutput::new(&mnemonic),
+            manifest: files.manifest,
+            shares: files.shares,
+            hint_cards: files.hint_cards,
+            verified_existing: files.outcome == WriteOutcome::Verified,
+        };
+        return exit_on_error(output::print_json(&document), "Error formatting output");
+    }
+
+    print_secret(&mnemonic);
+    println!();
+    if files.outcome == WriteOutcome::Verified {
+        println!(
+            "Existing files in \"{}\" match this split, nothing was written",
+            dir
+        );
+        println!();
+    }
+    println!("Manifest: {}", files.manifest.display());
+    println!();
+    println!("Shares (one file per share):");
+    for path in files.shares {
+        println!("  {}", path.display());
+    }
+    if !files.hint_cards.is_empty() {
+        println!();
+        println!("Hint cards (no shares or secret, one per share):");
+        for path in files.hint_cards {
+            println!("  {}", path.display());
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PaperOutput {
+    #[serde(flatten)]
+    secret: SecretOutput,
+    paper: PathBuf,
+    pages: usize,
+}
+
+fn paper_success(
+    path: &str,
+    mnemonic: Mnemonic,
+    shares: &[Vec<Vec<u8>>],
+    args: &SplitArgs,
+    output: &OutputFormat,
+) {
+    let pages = exit_on_error(
+        paper::write_paper(Path::new(path), shares, &args.paper_qr),
+        "Error writing paper backup",
+    );
+
+    if *output == OutputFormat::Json {
+        let document = PaperOutput {
+            secret: SecretOutput::new(&mnemonic),
+            paper: PathBuf::from(path),
+            pages,
+        };
+        return exit_on_error(output::print_json(&document), "Error formatting output");
+    }
+
+    print_secret(&mnemonic);
+    println!();
+    println!("Paper backup: {} ({} pages, one per share)", path, pages);
+}
+
+fn recover(args: &RecoverArgs, cli: &Cli) {
+    let located = match &args.serial {
+        Some(device) => {
+            let reader =
+                exit_on_error(scan::open_device(device, args.baud), "Error opening device");
+            let lines = exit_on_error(
+                scan::collect_shares(reader, &args.minimal, |message| eprintln!("{}", message)),
+                "Error reading shares",
+            );
+            lines
+                .into_iter()
+                .enumerate()
+                .map(|(line_num, text)| input::ShareLine {
+                    source: device.clone(),
+                    line: line_num + 1,
+                    text,
+                })
+                .collect()
+        }
+        None if args.example => example_share_lines(),
+        #[cfg(feature = "smartcard")]
+        None if args.nfc => read_nfc_tags(args),
+        None => exit_on_error(
+            input::read_located_share_lines(&args.files),
+            "Error reading shares",
+        ),
+    };
+    let lines: Vec<String> = located.iter().map(|line| line.text.clone()).collect();
+    if lines.iter().any(|line| codex32::is_codex32(line)) {
+        let (secret, identifier) =
+            exit_on_error(codex32::recover(&lines), "Error recovering from codex32");
+        return recovered(secret, None, vec![], Some(identifier), args, cli);
+    }
+    let lines = if args.auto_correct {
+        auto_correct(lines)
+    } else {
+        lines
+    };
+    let lines = if args.lenient { lenient(lines) } else { lines };
+
+    // Every line with a problem is reported, and the others still recover if
+    // they are enough
+    let problems = recover::diagnose_lines(&lines);
+    let reports = line_reports(&located, &problems);
+    for report in &reports {
+        eprintln!("{}: {}", report.location, report.message);
+        for suggestion in &report.suggestions {
+            eprintln!("  - {}", suggestion);
+        }
+    }
+    if !problems.is_empty() {
+        if args.strict {
+            eprintln!(
+                "Error recovering mnemonic: {} of {} lines can't be used",
+                problems.len(),
+                lines.len()
+            );
+            process::exit(1);
+        }
+        eprintln!(
+            "Left out {} of {} lines, recovering from the other {}",
+            problems.len(),
+            lines.len(),
+            lines.len() - problems.len()
+        );
+    }
+    let lines: Vec<String> = lines
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| problems.iter().all(|problem| problem.index != *index))
+        .map(|(_, line)| line)
+        .collect();
+
+    let seed = args.manifest.as_ref().and_then(|path| {
+        exit_on_error(
+            manifest_seed(Path::new(path), &lines),
+            "Error recovering mnemonic",
+        )
+    });
+
+    let secret = match recover::recover_secret(lines.clone(), &args.minimal) {
+        Ok(secret) => secret,
+        Err(error) => {
+            eprintln!("Error recovering mnemonic: {:?}", error);
+            let sets = status::status(lines, &args.minimal, None).unwrap_or_default();
+            for suggestion in sets.iter().flat_map(|set| &set.suggestions) {
+                eprintln!("  - {}", suggestion);
+            }
+            process::exit(error::exit_code(&error));
+        }
+    };
+    if cli.paranoid {
+        let shares = exit_on_error(
+            lines
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| recover::decode_share(line).map(Zeroizing::new))
+                .collect::<Result<Vec<_>, _>>(),
+            "Paranoid check failed",
+        );
+        let shares: Vec<&[u8]> = shares.iter().map(|share| share.as_slice()).collect();
+        exit_on_error(
+            paranoid::check_recovery(&shares, &secret),
+            "Paranoid check failed",
+        );
+        eprintln!("Paranoid checks passed: the shares recover the same secret on two code paths");
+    }
+    recovered(secret, seed, reports, None, args, cli)
+}
+
+// Decrypts the combined secret if it was encrypted, and shows it as the
+// arguments ask
+fn recovered(
+    secret: Zeroizing<Vec<u8>>,
+    seed: Option<SeedManifest>,
+    reports: Vec<LineReport>,
+    codex32_identifier: Option<String>,
+    args: &RecoverArgs,
+    cli: &Cli,
+) {
+    let output = &cli.output;
+    let dual_control = seed
+        .as_ref()
+        .and_then(|seed| seed.dual_control.clone())
+        .or_else(|| args.dual_control.then(DualControl::default));
+    if let (Some(seed), Some(_)) = (&seed, &dual_control) {
+        eprintln!(
+            "{} is under dual control, both operators have to enter their passphrase.",
+            seed.title()
+        );
+    }
+    let (recorded_check, recorded_salt) = seed
+        .map(|seed| (seed.passphrase_check, seed.passphrase_salt))
+        .unwrap_or_default();
+    let salt = args.passphrase_salt.clone().or(recorded_salt);
+    let passphrase = if let Some(dual_control) = dual_control {
+        Some(exit_on_error(
+            dual_control.passphrase(&mut read_operator_passphrase),
+            "Error reading passphrase",
+        ))
+    } else if args.decrypt || recorded_check.is_some() {
+        Some(exit_on_error(
+            input::read_hidden("Encryption passphrase (not echoed): "),
+            "Error reading passphrase",
+        ))
+    } else {
+        None
+    };
+    let secret = match passphrase {
+        Some(passphrase) => exit_on_error(
+            decrypt_secret(
+                &secret,
+                &passphrase,
+                recorded_check.as_deref(),
+                salt.as_deref(),
+            ),
+            "Error decrypting secret",
+        ),
+        None => secret,
+    };
+    // A secret that came in as codex32 keeps its identifier
+    let codex32_secret = args.codex32.then(|| {
+        let identifier = codex32_identifier.unwrap_or_else(codex32::random_identifier);
+        exit_on_error(
+            codex32::encode_secret(&secret, &identifier),
+            "Error encoding codex32",
+        )
+    });
+    let exports = SecretExports {
+        codex32: codex32_secret,
+        seed_ur: args.seed_ur.then(|| {
+            let metadata = SeedMetadata {
+                name: args.seed_name.clone(),
+                note: args.note.clone(),
+                creation_date: args.creation_date,
+            };
+            let ur_type = match args.crypto_seed {
+                true => SeedUrType::CryptoSeed,
+                false => SeedUrType::Seed,
+            };
+            seed_ur::encode(&secret, &metadata, ur_type).to_string()
+        }),
+    };
+    if let (Some(sealed_path), Some(output_path)) = (&args.sealed_file, &args.output_file) {
+        return sealed_file_success(
+            Path::new(sealed_path),
+            Path::new(output_path),
+            &secret,
+            reports,
+            output,
+        );
+    }
+    let Ok(mnemonic) = Mnemonic::from_entropy(&secret, args.language.bip39()) else {
+        if args.fingerprint || args.derive.is_some() {
+            eprintln!("Error recovering mnemonic: the secret is not a BIP-39 seed, it has no wallet to derive");
+            process::exit(1);
+        }
+        return raw_secret_success(&secret, exports, args.qr, output);
+    };
+    if cli.paranoid {
+        exit_on_error(paranoid::check_mnemonic(&mnemonic), "Paranoid check failed");
+    }
+    let wallet = if args.fingerprint || args.derive.is_some() {
+        Some(exit_on_error(
+            wallet::preview(
+                &mnemonic,
+                args.passphrase.as_deref().unwrap_or_default(),
+                args.derive.as_deref(),
+            ),
+            "Error deriving wallet",
+        ))
+    } else {
+        None
+    };
+    recover_success(mnemonic, wallet, reports, exports, args.qr, output)
+}
+
+#[derive(Serialize)]
+struct LineReport {
+    location: String,
+    message: String,
+    // What to do about it
+    suggestions: Vec<String>,
+}
+
+fn line_reports(
+    located: &[input::ShareLine],
+    problems: &[recover::LineProblem],
+) -> Vec<LineReport> {
+    let names: Vec<String> = located
+        .iter()
+        .map(|line| format!("the share on {}", line))
+        .collect();
+    problems
+        .iter()
+        .map(|problem| LineReport {
+            location: located[problem.index].to_string(),
+            message: match problem.conflicts_with {
+                Some(other) => format!("{} on {}", problem.message, located[other]),
+                None => problem.message.clone(),
+            },
+            suggestions: diagnostics::suggestions(&problem.problem, &names),
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct SealedFileOutput {
+    output_file: PathBuf,
+    bytes: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    problems: Vec<LineReport>,
+}
+
+fn sealed_file_success(
+    sealed_path: &Path,
+    output_path: &Path,
+    key: &[u8],
+    problems: Vec<LineReport>,
+    output: &OutputFormat,
+) {
+    let sealed = exit_on_error(
+        fs::read(sealed_path)
+            .with_context(|| format!("Unable to read \"{}\"", sealed_path.display())),
+        "Error opening sealed file",
+    );
+    let contents = exit_on_error(sealed::open(&sealed, key), "Error opening sealed file");
+    exit_on_error(
+        sealed::write_new(output_path, &contents),
+        "Error writing recovered file",
+    );
+
+    match output {
+        OutputFormat::Text => println!(
+            "Recovered {} bytes into \"{}\"",
+            contents.len(),
+            output_path.display()
+        ),
+        OutputFormat::Json => exit_on_error(
+            output::print_json(&SealedFileOutput {
+                output_file: output_path.to_path_buf(),
+                bytes: contents.len(),
+                problems,
+            }),
+            "Error formatting output",
+        ),
+    }
+}
+
+// The recovered secret in the other formats asked for
+struct SecretExports {
+    codex32: Option<String>,
+    seed_ur: Option<String>,
+}
+
+fn print_seed_qr(seed_ur: &str) {
+    if seed_ur == policy::REDACTED {
+        return;
+    }
+    let qr = exit_on_error(seed_ur::terminal_qr(seed_ur), "Error showing QR code");
+    println!("{}", qr.as_str());
+}
+
+// Secrets of a length no mnemonic has, split with --raw-secret
+fn raw_secret_success(secret: &[u8], exports: SecretExports, qr: bool, output: &OutputFormat) {
+    let document = SecretOutput::raw(secret).with_exports(exports.codex32, exports.seed_ur);
+    match output {
+        OutputFormat::Text => {
+            println!("Secret:    {}", document.entropy);
+            println!("Bytewords: {}", document.bytewords.unwrap_or_default());
+            if let Some(codex32) = document.codex32 {
+                println!("Codex32:   {}", codex32);
+            }
+            if let Some(seed_ur) = document.seed_ur {
+                println!("Seed UR:   {}", seed_ur);
+                if qr {
+                    print_seed_qr(&seed_ur);
+                }
+            }
+            eprintln!(
+                "The secret is {} bytes, which no BIP-39 mnemonic has, so it is shown as hex and bytewords",
+                secret.len()
+            );
+        }
+        OutputFormat::Json => {
+            exit_on_error(output::print_json(&document), "Error formatting output")
+        }
+    }
+}
+
+// The seed the manifest describes the shares of, for the passphrase check
+// and dual control it records
+fn manifest_seed(path: &Path, lines: &[String]) -> Result<Option<SeedManifest>, anyhow::Error> {
+    let manifest = Manifest::load(path)?;
+    let Some(first) = lines.first() else {
+        return Ok(None);
+    };
+    let identifier = ShareMetadataOutput::new(&recover::decode_share(first)?)?.identifier;
+    let Some(seed) = manifest
+        .seeds
+        .into_iter()
+        .find(|seed| seed.identifier == identifier)
+    else {
+        bail!(
+            "Manifest \"{}\" doesn't describe shares with identifier {}",
+            path.display(),
+            identifier
+        );
+    };
+    Ok(Some(seed))
+}
+
+// Shares split with --encrypt hold the entropy encrypted under a passphrase.
+// Any passphrase decrypts to some secret, so the check words tell the right
+// one, by the manifest when it recorded them and by eye otherwise.
+fn decrypt_secret(
+    secret: &[u8],
+    passphrase: &str,
+    recorded_check: Option<&str>,
+    salt: Option<&str>,
+) -> Result<Zeroizing<Vec<u8>>, anyhow::Error> {
+    let decrypted = encryption::decrypt(secret, passphrase, salt)?;
+    match recorded_check {
+        Some(check) if check != decrypted.check => bail!(
+            "Wrong passphrase, its check words are \"{}\" but the manifest records \"{}\"",
+            decrypted.check,
+            check
+        ),
+        Some(_) => eprintln!(
+            "Passphrase check: {} (matches the manifest)",
+            decrypted.check
+        ),
+        None => eprintln!(
+            "Passphrase check: {} (should match the words shown when the secret was split)",
+            decrypted.check
+        ),
+    }
+    Ok(decrypted.secret)
+}
+
+#[derive(Serialize)]
+struct RecoverOutput {
+    #[serde(flatten)]
+    secret: SecretOutput,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wallet: Option<WalletPreview>,
+    // Lines left out of the recovery
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    problems: Vec<LineReport>,
+}
+
+fn recover_success(
+    mnemonic: Mnemonic,
+    wallet: Option<WalletPreview>,
+    problems: Vec<LineReport>,
+    exports: SecretExports,
+    qr: bool,
+    output: &OutputFormat,
+) {
+    let secret = SecretOutput::new(&mnemonic).with_exports(exports.codex32, exports.seed_ur);
+    let wallet = wallet.map(WalletPreview::redacted);
+    match output {
+        OutputFormat::Text => {
+            println!("Entropy:  {}", secret.entropy);
+            println!("Mnemonic: {}", secret.mnemonic.unwrap_or_default());
+            if let Some(codex32) = &secret.codex32 {
+                println!("Codex32:  {}", codex32);
+            }
+            if let Some(seed_ur) = &secret.seed_ur {
+                println!("Seed UR:  {}", seed_ur);
+                if qr {
+                    print_seed_qr(seed_ur);
+                }
+            }
+            if let Some(wallet) = wallet {
+                println!("Fingerprint: {}", wallet.fingerprint);
+                if let (Some(path), Some(xpub)) = (wallet.path, wallet.xpub) {
+                    println!("Path:        {}", path);
+                    println!("Xpub:        {}", xpub);
+                }
+                for (index, address) in wallet.addresses.iter().enumerate() {
+                    println!("Address {}:   {}", index, address);
+                }
+            }
+        }
+        OutputFormat::Json => exit_on_error(
+            output::print_json(&RecoverOutput {
+                secret,
+                wallet,
+                problems,
+            }),
+            "Error formatting output",
+        ),
+    }
+}
+
+#[derive(Serialize)]
+struct ConvertOutput {
+    from: ShareFormat,
+    to: ShareFormat,
+    share: String,
+}
+
+fn convert(args: &ConvertArgs, output: &OutputFormat) {
+    let (from, share) = exit_on_error(
+        share_format::convert(&args.share, args.to),
+        "Error converting share",
+    );
+
+    match output {
+        OutputFormat::Text => println!("{}", policy::shown(Field::Shares, share)),
+        OutputFormat::Json => exit_on_error(
+            output::print_json(&ConvertOutput {
+                from,
+                to: args.to,
+                share: policy::shown(Field::Shares, share),
+            }),
+            "Error formatting output",
+        ),
+    }
+}
+
+fn inspect(args: &InspectArgs, output: &OutputFormat) {
+    let share = match EncodedShare::parse(&args.share) {
+        Ok(share) => share,
+        Err(error) => {
+            eprintln!("Invalid share: {:?}", error);
+            let problem = diagnostics::unreadable(0, &args.share);
+            for suggestion in diagnostics::suggestions(&problem, &["the share".to_string()]) {
+                eprintln!("  - {}", suggestion);
+            }
+            process::exit(error::exit_code(&error));
+        }
+    };
+    let mut inspection = Inspection::new(&share);
+    if let Some(path) = &args.manifest {
+        let manifest = exit_on_error(Manifest::load(Path::new(path)), "Error reading manifest");
+        let identifier = &inspection.metadata.identifier;
+        match manifest
+            .seeds
+            .iter()
+            .find(|seed| &seed.identifier == identifier)
+        {
+            Some(seed) => inspection = inspection.with_seed(seed),
+            None => eprintln!(
+                "The manifest doesn't describe shares with identifier {}",
+                identifier
+            ),
+        }
+    }
+    if args.public {
+        inspection = inspection.public();
+    }
+
+    match output {
+        OutputFormat::Text => print!("{}", inspection),
+        OutputFormat::Json => {
+            exit_on_error(output::print_json(&inspection), "Error formatting output")
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CheckOutput {
+    valid: bool,
+}
+
+fn check(args: &CheckArgs, output: &OutputFormat) {
+    let manifest = args
+        .manifest
+        .as_ref()
+        .map(|path| exit_on_error(Manifest::load(Path::new(path)), "Error reading manifest"));
+    let checks = manifest
+        .iter()
+        .flat_map(|manifest| &manifest.seeds)
+        .filter_map(|seed| seed.share_checks.as_ref())
+        .collect::<Vec<_>>();
+    if manifest.is_some() && checks.is_empty() {
+        eprintln!("The manifest has no share checks, any readable share is valid");
+    }
+
+    let mut blind = BlindCheck::new(&checks, args.attempts as usize);
+    let mut valid = false;
+    while blind.attempts_left() > 0 {
+        let share = exit_on_error(
+            input::read_hidden("Share (not echoed, empty to stop): "),
+            "Error reading share",
+        );
+        if share.is_empty() {
+            break;
+        }
+        valid = exit_on_error(blind.check(&share), "Error checking share");
+        match output {
+            OutputFormat::Text => println!("{}", if valid { "valid" } else { "invalid" }),
+            OutputFormat::Json => exit_on_error(
+                output::print_json(&CheckOutput { valid }),
+                "Error formatting output",
+            ),
+        }
+    }
+    if !valid {
+        process::exit(1);
+    }
+}
+
+fn audit(args: &AuditArgs, output: &OutputFormat) {
+    let manifest = exit_on_error(
+        Manifest::load(Path::new(&args.manifest)),
+        "Error reading manifest",
+    );
+    if manifest
+        .seeds
+        .iter()
+        .all(|seed| seed.share_checks.is_none())
+    {
+        eprintln!(
+            "The manifest has no share checks, shares can only be checked against its groups"
+        );
+    }
+    let lines = exit_on_error(
+        input::read_located_share_lines(&args.files),
+        "Error reading shares",
+    );
+    let audits = exit_on_error(audit::audit(&manifest, &lines), "Error auditing shares");
+    match output {
+        OutputFormat::Text => {
+            for audit in &audits {
+                println!("{}", audit);
+            }
+        }
+        OutputFormat::Json => exit_on_error(output::print_json(&audits), "Error formatting output"),
+    }
+    if !audits.iter().all(audit::ShareAudit::is_genuine) {
+        process::exit(1);
+    }
+}
+
+fn status(args: &StatusArgs, output: &OutputFormat, locale: Locale) {
+    let manifest = args
+        .manifest
+        .as_ref()
+        .map(|path| exit_on_error(Manifest::load(Path::new(path)), "Error reading manifest"));
+    let lines = exit_on_error(input::read_share_lines(&args.files), "Error reading shares");
+    let sets = exit_on_error(
+        status::status(lines, &args.minimal, manifest.as_ref()),
+        "Error checking shares",
+    );
+
+    if *output == OutputFormat::Json {
+        return exit_on_error(output::print_json(&sets), "Error formatting output");
+    }
+
+    for set in sets {
+        match &set.name {
+            Some(name) => println!("{} (\"{}\")", locale.share_set(&set.identifier), name),
+            None => println!("{}", locale.share_set(&set.identifier)),
+        }
+        for group in &set.groups {
+            println!(
+                "  {}",
+                locale.group_progress(
+                    group.group,
+                    group.present,
+                    group.member_threshold,
+                    group.missing
+                )
+            );
+        }
+        println!(
+            "  {}",
+            locale.groups_progress(
+                set.groups_satisfied,
+                set.group_threshold,
+                set.groups_missing
+            )
+        );
+        for suggestion in &set.suggestions {
+            println!("  - {}", suggestion);
+        }
+        println!();
+    }
+}
+
+fn reshare(args: &ReshareArgs, cli: &Cli) {
+    let group_threshold = match args.group_threshold {
+        Some(group_threshold) => group_threshold,
+        None => exit_on_error(
+            split::default_group_threshold(&args.spec),
+            "Error resharing",
+        ),
+    };
+    let lines = exit_on_error(
+        input::read_share_lines(&args.shares),
+        "Error reading shares",
+    );
+    let (mnemonic, shares) = exit_on_error(
+        split::reshare(
+            lines,
+            &args.minimal,
+            &args.spec,
+            group_threshold,
+            args.language.bip39(),
+        ),
+        "Error resharing",
+    );
+    if cli.paranoid {
+        let encoded = split::to_bytewords(&shares, &args.minimal);
+        paranoid_check(&shares, &encoded, mnemonic.entropy());
+    }
+    let secret = args.show_secret.then_some(&mnemonic);
+
+    let (output, locale) = (&cli.output, cli.locale);
+    match output {
+        OutputFormat::Text => {
+            let groups = split::to_bytewords(&shares, &args.minimal);
+            split_success(
+                group_threshold,
+                secret,
+                groups,
+                &exit_on_error(split::spec_labels(&args.spec), "Invalid spec"),
+                ShareDetails::default(),
+                locale,
+            );
+            eprintln!("The old shares still recover the same secret, destroy them.");
+        }
+        OutputFormat::Json => {
+            let document =
+                SplitOutput::new(&args.spec, group_threshold, &mnemonic, &shares).map(|document| {
+                    SplitOutput {
+                        secret: document.secret.filter(|_| args.show_secret),
+                        ..document
+                    }
+                });
+            exit_on_error(
+                document.and_then(|document| output::print_json(&document)),
+                "Error formatting output",
+            )
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SessionOutput {
+    entries: usize,
+    added: usize,
+    head: Option<String>,
+}
+
+fn session(args: &SessionArgs, output: &OutputFormat) {
+    let (entries, added) = match &args.command {
+        SessionCommands::Add(args) => {
+            let lines = exit_on_error(input::read_share_lines(&args.files), "Error reading shares");
+            let journal = Path::new(&args.journal);
+            let added = exit_on_error(
+                journal::append_shares(journal, &lines, &args.minimal),
+                "Error adding to journal",
+            );
+            let entries = exit_on_error(journal::read_journal(journal), "Error reading journal");
+            (entries, Some(added.len()))
+        }
+        SessionCommands::Verify(args) => (
+            exit_on_error(
+                journal::verify_journal(Path::new(&args.journal), args.head.as_deref()),
+                "Journal verification failed",
+            ),
+            None,
+        ),
+    };
+    let head = entries
+        .last()
+        .map(|entry| format!("{}:{}", entry.number, entry.hash));
+
+    if *output == OutputFormat::Json {
+        let document = SessionOutput {
+            entries: entries.len(),
+            added: added.unwrap_or(0),
+            head,
+        };
+        return exit_on_error(output::print_json(&document), "Error formatting output");
+    }
+
+    match added {
+        Some(added) => println!(
+            "Added {} share(s), the journal now holds {}",
+            added,
+            entries.len()
+        ),
+        None => println!("Journal intact, {} entries", entries.len()),
+    }
+    if let Some(head) = head {
+        println!("Head: {}", head);
+        if added.is_some() {
+            println!("Write the head down outside the journal, `session verify --head` checks against it");
+        }
+    }
+}
+
+fn verify(args: &VerifyArgs, output: &OutputFormat, locale: Locale) {
+    let expected = exit_on_error(expected_secret(args), "Error reading secret");
+    let lines = exit_on_error(input::read_share_lines(&args.files), "Error reading shares");
+    let lines = if args.auto_correct {
+        auto_correct(lines)
+    } else {
+        lines
+    };
+    let lines = if args.lenient { lenient(lines) } else { lines };
+    let verification = exit_on_error(
+        verify::verify(lines, &args.minimal, &expected),
+        "Error verifying shares",
+    );
+
+    if *output == OutputFormat::Json {
+        exit_on_error(output::print_json(&verification), "Error formatting output");
+    } else {
+        println!("{}", locale.share_set(&verification.identifier));
+        for group in &verification.groups {
+            let outcome = match group.outcome {
+                verify::GroupOutcome::Verified => "verified",
+                verify::GroupOutcome::Mismatch => "does NOT recover the secret",
+                verify::GroupOutcome::NotEnoughShares => "not enough shares to check",
+            };
+            println!(
+                "  {} ({}): {}",
+                locale.group(group.group),
+                locale.threshold(group.present, group.member_threshold),
+                outcome
+            );
+        }
+        if verification.matches {
+            println!("  The shares recover the given secret");
+        } else {
+            println!("  The shares do NOT recover the given secret");
+        }
+    }
+
+    if !verification.matches {
+        process::exit(1);
+    }
+}
+
+#[derive(Serialize)]
+struct VerifyMediaOutput {
+    files: Vec<media::MediaCheck>,
+    intact: bool,
+}
+
+fn verify_media(args: &VerifyMediaArgs, output: &OutputFormat) {
+    let files = exit_on_error(
+        media::verify_media(
+            Path::new(&args.path),
+            args.manifest.as_deref().map(Path::new),
+        ),
+        "Error verifying media",
+    );
+    let intact = files
+        .iter()
+        .all(|file| file.outcome == media::MediaOutcome::Intact);
+
+    if *output == OutputFormat::Json {
+        exit_on_error(
+            output::print_json(&VerifyMediaOutput { files, intact }),
+            "Error formatting output",
+        );
+    } else {
+        for file in &files {
+            let outcome = match file.outcome {
+                media::MediaOutcome::Intact => "intact",
+                media::MediaOutcome::Corrupted => "CORRUPTED, it no longer matches its hash",
+                media::MediaOutcome::Missing => "MISSING",
+                media::MediaOutcome::Unreadable => "UNREADABLE",
+            };
+            println!("  {} ({}): {}", file.path, file.identifier, outcome);
+        }
+        if intact {
+            println!("All {} files are intact", files.len());
+        } else {
+            println!("The medium has rotted, write the affected shares again from other copies");
+        }
+    }
+
+    if !intact {
+        process::exit(1);
+    }
+}
+
+#[derive(Serialize)]
+struct MigrateOutput {
+    path: String,
+    kind: migrate::ArtifactKind,
+    changes: Vec<String>,
+    // Where the original was kept, when the file was upgraded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original: Option<String>,
+}
+
+fn completions(args: &CompletionsArgs) {
+    clap_complete::generate(
+        args.shell,
+        &mut Cli::command(),
+        env!("CARGO_PKG_NAME"),
+        &mut std::io::stdout(),
+    );
+}
+
+fn migrate(args: &MigrateArgs, output: &OutputFormat) {
+    let mut outdated = 0;
+    let mut results = vec![];
+    for path in &args.files {
+        let contents = exit_on_error(
+            fs::read_to_string(path).with_context(|| format!("Unable to read \"{}\"", path)),
+            "Error migrating",
+        );
+        let migration = exit_on_error(
+            migrate::migrate(&contents).with_context(|| format!("\"{}\"", path)),
+            "Error migrating",
+        );
+        let original = match (&migration.contents, args.check) {
+            (Some(_), true) => {
+                outdated += 1;
+                None
+            }
+            (Some(upgraded), false) => Some(exit_on_error(
+                replace_keeping_original(Path::new(path), &contents, upgraded),
+                "Error migrating",
+            )),
+            (None, _) => None,
+        };
+        results.push(MigrateOutput {
+            path: path.clone(),
+            kind: migration.kind,
+            changes: migration.changes,
+            original,
+        });
+    }
+
+    match output {
+        OutputFormat::Text => {
+            for result in &results {
+                let status = match (&result.original, result.changes.is_empty()) {
+                    (_, true) => "already current".to_string(),
+                    (Some(original), false) => format!(
+                        "{}; the original is kept as {}",
+                        result.changes.join(", "),
+                        original
+                    ),
+                    (None, false) => format!("{} (not written)", result.changes.join(", ")),
+                };
+                println!("{}: {}, {}", result.path, result.kind.name(), status);
+            }
+        }
+        OutputFormat::Json => {
+            exit_on_error(output::print_json(&results), "Error formatting output")
+        }
+    }
+    if outdated > 0 {
+        eprintln!("{} of {} files need migrating", outdated, results.len());
+        process::exit(1);
+    }
+}
+
+// The original is copied aside before the upgrade is written, and an earlier
+// original is never overwritten
+fn replace_keeping_original(
+    path: &Path,
+    original: &str,
+    upgraded: &str,
+) -> Result<String, anyhow::Error> {
+    let kept = PathBuf::from(format!("{}.orig", path.display()));
+    if kept.exists() {
+        bail!(
+            "\"{}\" already exists, move it away to migrate \"{}\" again",
+            kept.display(),
+            path.display()
+        );
+    }
+    fs::write(&kept, original)
+        .with_context(|| format!("Unable to write \"{}\"", kept.display()))?;
+    fs::write(path, upgraded).with_context(|| format!("Unable to write \"{}\"", path.display()))?;
+    Ok(kept.display().to_string())
+}
+
+#[derive(Serialize)]
+struct SelftestOutput {
+    vectors: Vec<selftest::VectorResult>,
+    passed: bool,
+}
+
+fn selftest(output: &OutputFormat) {
+    let vectors = selftest::run();
+    let passed = vectors.iter().all(|vector| vector.passed);
+
+    if *output == OutputFormat::Json {
+        exit_on_error(
+            output::print_json(&SelftestOutput { vectors, passed }),
+            "Error formatting output",
+        );
+    } else {
+        for vector in &vectors {
+            match &vector.error {
+                None => println!("  PASS  {} [{}]", vector.name, vector.source),
+                Some(error) => println!("  FAIL  {} [{}]: {}", vector.name, vector.source, error),
+            }
+        }
+        if passed {
+            println!("All {} reference vectors pass", vectors.len());
+        } else {
+            println!("This build fails reference vectors, don't use it with a real seed");
+        }
+    }
+
+    if !passed {
+        process::exit(1);
+    }
+}
+
+#[derive(Serialize)]
+struct DiagnoseOutput {
+    cases: Vec<testdata::CaseOutcome>,
+    passed: bool,
+}
+
+fn diagnose(args: &DiagnoseArgs, output: &OutputFormat) {
+    let corpus = exit_on_error(
+        match &args.file {
+            Some(path) => testdata::Corpus::load(Path::new(path)),
+            None => testdata::corpus(),
+        },
+        "Error reading corpus",
+    );
+    if args.export {
+        return print!(
+            "{}",
+            exit_on_error(corpus.to_toml(), "Error formatting corpus")
+        );
+    }
+    let cases = corpus.run();
+    let failed = cases.iter().filter(|case| !case.passed).count();
+
+    if *output == OutputFormat::Json {
+        exit_on_error(
+            output::print_json(&DiagnoseOutput {
+                passed: failed == 0,
+                cases,
+            }),
+            "Error formatting output",
+        );
+    } else {
+        for case in &cases {
+            if case.passed {
+                println!("  PASS  {}", case.name);
+                continue;
+            }
+            println!("  FAIL  {}", case.name);
+            println!(
+                "          expected: {}",
+                serde_json::to_string(&case.expected).unwrap_or_default()
+            );
+            println!(
+                "          found:    {}",
+                serde_json::to_string(&case.found).unwrap_or_default()
+            );
+        }
+        if failed == 0 {
+            println!("All {} cases are diagnosed as expected", cases.len());
+        } else {
+            println!(
+                "{} of {} cases are not diagnosed as expected",
+                failed,
+                cases.len()
+            );
+        }
+    }
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn review(args: &ReviewArgs, duress: &DuressKeys) {
+    exit_on_error(
+        policy::check(Field::Shares, Channel::Display),
+        "Error reviewing shares",
+    );
+    // Keys are read from the terminal, which stdin would have to be
+    if args.files.iter().any(|file| file == input::STDIN) {
+        eprintln!("Error reading shares: shares can't be read from stdin while reviewing");
+        process::exit(1);
+    }
+    if !std::io::stdout().is_terminal() {
+        eprintln!("Error reviewing shares: the review needs a terminal to show the shares on");
+        process::exit(1);
+    }
+    let mut lines = exit_on_error(
+        input::read_located_share_lines(&args.files),
+        "Error reading shares",
+    );
+    let cards = exit_on_error(review::cards(&lines, args.to), "Error reading shares");
+    let ending = exit_on_error(review::run(&cards, duress), "Error reviewing shares");
+    if ending == Ending::Wiped {
+        drop(cards);
+        for line in &mut lines {
+            line.text.zeroize();
+        }
+        process::exit(duress::EXIT_CODE);
+    }
+}
+
+fn run_tui(args: &TuiArgs, duress: &DuressKeys) {
+    let mut wizard = match &args.checklist {
+        Some(path) => tui::Wizard::with_checklist(exit_on_error(
+            Checklist::load(path),
+            "Error reading checklist",
+        )),
+        None => tui::Wizard::new(),
+    };
+    if let Some(language) = args.language {
+        wizard = wizard.with_language(language.bip39());
+    }
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        eprintln!("Error starting the ceremony: it needs a terminal to run in");
+        process::exit(1);
+    }
+    let started = checklist::unix_time();
+    let ending = exit_on_error(tui::run(&mut wizard, duress), "Error running the ceremony");
+    if ending == Ending::Wiped {
+        process::exit(duress::EXIT_CODE);
+    }
+    if let Some(path) = &args.audit_log {
+        let record = AuditRecord {
+            started,
+            finished: checklist::unix_time(),
+            outcome: wizard.outcome(),
+            checklist: wizard
+                .checklist()
+                .map(Checklist::checked)
+                .unwrap_or_default(),
+        };
+        exit_on_error(
+            checklist::append_audit(path, &record),
+            "Error writing the audit log",
+        );
+        eprintln!("Ceremony recorded in {}", path.display());
+    }
+}
+
+fn tones(args: &TonesArgs) {
+    match &args.command {
+        TonesCommands::Encode(args) => {
+            let share = exit_on_error(recover::decode_share(&args.share), "Invalid share");
+            exit_on_error(
+                policy::check(Field::Shares, Channel::Disk)
+                    .and_then(|()| tones::write_wav(&args.output_file, &share)),
+                "Error writing tones",
+            );
+            eprintln!(
+                "Tones written to {}, play them here and record them on the other machine for `tones decode`",
+                args.output_file.display()
+            );
+        }
+        TonesCommands::Decode(args) => {
+            let share = exit_on_error(tones::read_wav(&args.file), "Error reading tones");
+            println!(
+                "{}",
+                policy::shown(Field::Shares, share_format::encode(&share, args.to))
+            );
+        }
+    }
+}
+
+fn profiles(args: &ProfileArgs) {
+    let dir = exit_on_error(profiles_dir(), "Error locating profiles");
+    match &args.command {
+        ProfileCommands::Save(args) => {
+            let profile = exit_on_error(
+                profile::save(&dir, &args.name, &args.file),
+                "Error saving profile",
+            );
+            eprintln!(
+                "Profile \"{}\" saved, `split --profile {}` splits {}",
+                args.name, args.name, profile.spec
+            );
+        }
+        ProfileCommands::Load(args) => {
+            print!(
+                "{}",
+                exit_on_error(profile::export(&dir, &args.name), "Error reading profile")
+            );
+        }
+        ProfileCommands::List => {
+            for name in exit_on_error(profile::list(&dir), "Error listing profiles") {
+                println!("{}", name);
+            }
+        }
+    }
+}
+
+fn drill(args: &DrillArgs, output: &OutputFormat) {
+    let manifest = args
+        .manifest
+        .as_ref()
+        .map(|path| exit_on_error(Manifest::load(Path::new(path)), "Error reading manifest"));
+    let checks = manifest
+        .iter()
+        .flat_map(|manifest| &manifest.seeds)
+        .filter_map(|seed| seed.share_checks.as_ref())
+        .collect::<Vec<_>>();
+    if manifest.is_some() && checks.is_empty() {
+        eprintln!("The manifest has no share checks, only the share's checksum can be checked");
+    }
+
+    let result = exit_on_error(
+        drill::run(
+            &checks,
+            |order| order.shuffle(&mut rand::thread_rng()),
+            &mut std::io::stdin().lock(),
+            &mut std::io::stderr(),
+        ),
+        "Drill failed",
+    );
+
+    match output {
+        OutputFormat::Json => exit_on_error(output::print_json(&result), "Error formatting output"),
+        OutputFormat::Text => {
+            println!(
+                "Your copy reads as share {} of group {} in share set {}.",
+                result.metadata.member_index + 1,
+                result.metadata.group_index + 1,
+                result.metadata.identifier
+            );
+            match result.matches_manifest {
+                Some(true) => println!("It matches a share recorded in the manifest."),
+                Some(false) if !result.mismatched_words.is_empty() => println!(
+                    "Word(s) {} do NOT match the share recorded in the manifest.",
+                    result
+                        .mismatched_words
+                        .iter()
+                        .map(usize::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                Some(false) => println!("It does NOT match any share recorded in the manifest."),
+                None => println!("Its checksum is right."),
+            }
+        }
+    }
+    if result.matches_manifest == Some(false) {
+        process::exit(1);
+    }
+}
+
+fn escrow(args: &EscrowArgs, output: &OutputFormat) {
+    let (dir, signer) = match &args.command {
+        EscrowCommands::Export(args) => {
+            let lines = exit_on_error(input::read_share_lines(&args.files), "Error reading shares");
+            let groups: Vec<usize> = args.group.iter().map(|group| *group as usize).collect();
+            let signing_key = exit_on_error(
+                escrow::load_signing_key(&args.signing_key),
+                "Error reading signing key",
+            );
+            exit_on_error(
+                escrow::export(
+                    &lines,
+                    &groups,
+                    &args.recipient,
+                    &signing_key,
+                    args.hash,
+                    &args.out_dir,
+                ),
+                "Error lodging shares",
+            );
+            (args.out_dir.as_path(), None)
+        }
+        EscrowCommands::Verify(args) => (args.dir.as_path(), args.signer.as_deref()),
+        EscrowCommands::Keygen(args) => {
+            if args.output_file.exists() {
+                eprintln!(
+                    "Error writing signing key: \"{}\" already exists",
+                    args.output_file.display()
+                );
+                process::exit(1);
+            }
+            let key = escrow::generate_signing_key();
+            exit_on_error(
+                fs::write(&args.output_file, format!("{}\n", *key)).map_err(anyhow::Error::from),
+                "Error writing signing key",
+            );
+            let signing_key = exit_on_error(
+                escrow::load_signing_key(&args.output_file),
+                "Error reading signing key",
+            );
+            println!("{}", hex::encode(signing_key.verifying_key().as_bytes()));
+            eprintln!(
+                "Signing key written to {}, give the public key above to the escrow agent",
+                args.output_file.display()
+            );
+            return;
+        }
+    };
+
+    let manifest = exit_on_error(escrow::verify(dir, signer), "Escrow verification failed");
+    if *output == OutputFormat::Json {
+        exit_on_error(output::print_json(&manifest), "Error formatting output");
+    } else {
+        println!("Lodgement intact: {}", dir.display());
+        println!("  Identifier: {}", manifest.identifier);
+        for group in &manifest.groups {
+            println!(
+                "  Group {}:    members {} ({} needed)",
+                group.group,
+                group
+                    .members
+                    .iter()
+                    .map(|member| member.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                group.member_threshold
+            );
+        }
+        println!("  Recipient:  {}", manifest.recipient);
+        println!("  Signer:     {}", manifest.signer);
+    }
+}
+
+fn bundle(args: &BundleArgs, output: &OutputFormat) {
+    let (path, release) = match &args.command {
+        BundleCommands::Create(args) => {
+            let shares = exit_on_error(
+                input::read_share_lines(&args.shares),
+                "Error reading shares",
+            );
+            let manifest = args.manifest.as_ref().map(|path| {
+                exit_on_error(Manifest::load(Path::new(path)), "Error reading manifest")
+            });
+            let release = match &args.release {
+                Some(release) => PathBuf::from(release),
+                None => exit_on_error(
+                    std::env::current_exe().map_err(anyhow::Error::from),
+                    "Error locating the running executable",
+                ),
+            };
+            let path = Path::new(&args.bundle);
+            exit_on_error(
+                bundle::Bundle::new(manifest, shares, &release, args.hash)
+                    .and_then(|bundle| bundle.save(path)),
+                "Error creating bundle",
+            );
+            (path, None)
+        }
+        BundleCommands::Verify(args) => (Path::new(&args.bundle), args.release.as_deref()),
+    };
+
+    let verification = exit_on_error(
+        bundle::Bundle::load(path).and_then(|bundle| bundle.verify(release.map(Path::new))),
+        "Bundle verification failed",
+    );
+
+    if *output == OutputFormat::Json {
+        exit_on_error(output::print_json(&verification), "Error formatting output");
+    } else {
+        let software = &verification.software;
+        println!("Bundle intact: {}", path.display());
+        println!("  Identifiers: {}", verification.identifiers.join(", "));
+        println!("  Shares:      {}", verification.shares);
+        println!(
+            "  Software:    {} {} ({})",
+            software.name, software.version, software.file
+        );
+        println!(
+            "  {:<13}{}",
+            format!("{}:", software.hash.algorithm().name()),
+            software.hash.hex()
+        );
+        match verification.release_matches {
+            Some(true) => println!("  The given release is the recorded one"),
+            Some(false) => println!("  The given release is NOT the recorded one"),
+            None => (),
+        }
+    }
+
+    if verification.release_matches == Some(false) {
+        process::exit(1);
+    }
+}
+
+fn expected_secret(args: &VerifyArgs) -> Result<Zeroizing<Vec<u8>>, anyhow::Error> {
+    let hidden = match args.no_echo {
+        true => Some(input::read_hidden("Mnemonic (not echoed): ")?),
+        false => None,
+    };
+    let phrase = hidden.as_deref().or(args.mnemonic.as_ref());
+    match (phrase, &args.entropy_hex) {
+        (Some(phrase), _) => {
+            let language = language::detect_language(phrase)?;
+            Ok(Zeroizing::new(
+                Mnemonic::from_phrase(phrase, language)?.entropy().to_vec(),
+            ))
+        }
+        (None, Some(entropy)) => {
+            let entropy = entropy.trim();
+            Ok(Zeroizing::new(hex::decode(
+                entropy.strip_prefix("0x").unwrap_or(entropy),
+            )?))
+        }
+        (None, None) => bail!("Give the --mnemonic or --entropy-hex to check against"),
+    }
+}
+
+// Every correction is reported, so the paper backup can be fixed too
+fn auto_correct(lines: Vec<String>) -> Vec<String> {
+    let mut corrected_lines = vec![];
+    for (line_num, line) in lines.iter().enumerate() {
+        // Lines that can't be corrected are left for the error they give later
+        let Ok((corrected, corrections)) = share_format::auto_correct(line) else {
+            corrected_lines.push(line.clone());
+            continue;
+        };
+        for (word, replacement) in corrections {
+            eprintln!(
+                "Share {}: corrected \"{}\" to \"{}\"",
+                line_num + 1,
+                word,
+                replacement
+            );
+        }
+        corrected_lines.push(corrected);
+    }
+    corrected_lines
+}
+
+fn lenient(lines: Vec<String>) -> Vec<String> {
+    let mut relaxed_lines = vec![];
+    for (line_num, line) in lines.iter().enumerate() {
+        let Ok((relaxed, warnings)) = recover::lenient_share(line) else {
+            relaxed_lines.push(line.clone());
+            continue;
+        };
+        for warning in warnings {
+            eprintln!("Warning: share {}: {}", line_num + 1, warning);
+        }
+        relaxed_lines.push(relaxed);
+    }
+    relaxed_lines
+}
+
+fn exit_on_error<T>(result: Result<T, anyhow::Error>, context: &str) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("{}: {:?}", context, error);
+            process::exit(error::exit_code(&error));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Error;
+    use clap::ValueEnum;
+    use rand::prelude::SliceRandom;
+    use rand::seq::IteratorRandom;
+    use rand::Rng;
+
+    static TEST_ITERATIONS: usize = 50000;
+
+    #[test]
+    fn test_roundtrip_all_full_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, _sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            ensure_recoverable(&mnemonic, groups.into_iter().flatten().collect())?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_all_sufficient_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            ensure_recoverable(
+                &mnemonic,
+                groups
+                    .into_iter()
+                    .zip(sizes)
+                    .flat_map(|(group, (m, _n))| {
+                        group
+                            .into_iter()
+                            .choose_multiple(&mut rand::thread_rng(), m)
+                    })
+                    .collect(),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_all_insufficient_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (_mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            let mut shares: Vec<String> = groups
+                .into_iter()
+                .zip(sizes)
+                .flat_map(|(group, (m, _n))| {
+                    group
+                        .into_iter()
+                        .choose_multiple(&mut rand::thread_rng(), m - 1)
+                })
+                .collect();
+            if shares.is_empty() {
+                continue;
+            }
+            shares.shuffle(&mut rand::thread_rng());
+            ensure_unrecoverable(shares);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_enough_full_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, _sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            ensure_recoverable(
+                &mnemonic,
+                groups
+                    .into_iter()
+                    .choose_multiple(&mut rand::thread_rng(), group_threshold)
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_enough_sufficient_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            let mut shares: Vec<String> = groups
+                .into_iter()
+                .zip(sizes)
+                .map(|(group, (m, _n))| {
+                    group
+                        .into_iter()
+                        .choose_multiple(&mut rand::thread_rng(), m)
+                })
+                .choose_multiple(&mut rand::thread_rng(), group_threshold)
+                .into_iter()
+                .flatten()
+                .collect();
+            shares.shuffle(&mut rand::thread_rng());
+            ensure_recoverable(&mnemonic, shares)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_enough_sufficient_groups_minus_one() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (_mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            let mut shares = groups
+                .into_iter()
+                .zip(sizes)
+                .map(|(group, (m, _n))| {
+                    group
+                        .into_iter()
+                        .choose_multiple(&mut rand::thread_rng(), m)
+                })
+                .choose_multiple(&mut rand::thread_rng(), group_threshold)
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>();
+            if shares.len() == 1 {
+                continue;
+            }
+            shares.shuffle(&mut rand::thread_rng());
+            ensure_unrecoverable(shares.split_last().unwrap().1.to_vec());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_enough_insufficient_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (_mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            let mut shares: Vec<String> = groups
+                .into_iter()
+                .zip(sizes)
+                .map(|(group, (m, _n))| {
+                    group
+                        .into_iter()
+                        .choose_multiple(&mut rand::thread_rng(), m - 1)
+                })
+                .choose_multiple(&mut rand::thread_rng(), group_threshold)
+                .into_iter()
+                .flatten()
+                .collect();
+            if shares.is_empty() {
+                continue;
+            }
+            shares.shuffle(&mut rand::thread_rng());
+            ensure_unrecoverable(shares);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_not_enough_full_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, _sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (_mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            let mut shares: Vec<String> = groups
+                .into_iter()
+                .choose_multiple(&mut rand::thread_rng(), group_threshold - 1)
+                .into_iter()
+                .flatten()
+                .collect();
+            if shares.is_empty() {
+                continue;
+            }
+            shares.shuffle(&mut rand::thread_rng());
+            ensure_unrecoverable(shares);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_not_enough_sufficient_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (_mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            let mut shares: Vec<String> = groups
+                .into_iter()
+                .zip(sizes)
+                .map(|(group, (m, _n))| {
+                    group
+                        .into_iter()
+                        .choose_multiple(&mut rand::thread_rng(), m)
+                })
+                .choose_multiple(&mut rand::thread_rng(), group_threshold - 1)
+                .into_iter()
+                .flatten()
+                .collect();
+            if shares.is_empty() {
+                continue;
+            }
+            shares.shuffle(&mut rand::thread_rng());
+            ensure_unrecoverable(shares);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_not_enough_insufficient_groups() -> Result<(), Error> {
+        for _ in 0..TEST_ITERATIONS {
+            let (spec, sizes, group_threshold) = gen_random_params();
+            let words = gen_random_words();
+            let (_mnemonic, groups) = split::split_random_phrase(
+                &spec,
+                group_threshold,
+                words,
+                &false,
+                Language::English,
+            )?;
+            let mut shares: Vec<String> = groups
+                .into_iter()
+                .zip(sizes)
+                .map(|(group, (m, _n))| {
+                    group
+                        .into_iter()
+                        .choose_multiple(&mut rand::thread_rng(), m - 1)
+                })
+                .choose_multiple(&mut rand::thread_rng(), group_threshold - 1)
+                .into_iter()
+                .flatten()
+                .collect();
+            if shares.is_empty() {
+                continue;
+            }
+            shares.shuffle(&mut rand::thread_rng());
+            ensure_unrecoverable(shares);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_estate_packets_are_separated() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!("sskr-tool-estate-{}", process::id()));
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3,3of5",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?;
+        let packets = estate::write_estate(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse,
+        )?;
+        let shares: Vec<String> = groups.into_iter().flatten().collect();
+
+        for path in &packets.executor {
+            let contents = std::fs::read_to_string(path)?;
+            assert!(shares
+                .iter()
+                .all(|share| !contents.contains(share.as_str())));
+        }
+
+        let mut recovered_shares = vec![];
+        for path in &packets.custodians {
+            let contents = std::fs::read_to_string(path)?;
+            let held: Vec<&String> = shares
+                .iter()
+                .filter(|share| contents.contains(share.as_str()))
+                .collect();
+            assert_eq!(held.len(), 1);
+            assert!(!contents.contains("recover"));
+            recovered_shares.push(held[0].clone());
+        }
+
+        std::fs::remove_dir_all(&dir)?;
+        ensure_recoverable(&mnemonic, recovered_shares)
+    }
+
+    #[test]
+    fn test_roundtrip_all_languages() -> Result<(), Error> {
+        for language in MnemonicLanguage::value_variants() {
+            let original = Mnemonic::new(bip39::MnemonicType::Words24, language.bip39());
+            let (mnemonic, groups) = split::split("2of3", 1, original.phrase(), &false, None)?;
+            assert_eq!(mnemonic.language(), language.bip39());
+            let recovered = recover::recover(
+                groups.into_iter().flatten().collect(),
+                &false,
+                language.bip39(),
+            )?;
+            assert_eq!(recovered.phrase(), original.phrase());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_json_output() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (mnemonic, shares) =
+            split::split_shares("2of3,3of5", 2, mnemonic.phrase(), Some(Language::English))?;
+        let document = SplitOutput::new("2of3,3of5", 2, &mnemonic, &shares)?;
+        let json: serde_json::Value = serde_json::to_value(&document)?;
+
+        assert_eq!(json["mnemonic"], mnemonic.phrase());
+        assert_eq!(json["group_threshold"], 2);
+        assert_eq!(json["groups"].as_array().unwrap().len(), 2);
+        assert_eq!(json["groups"][1]["member_threshold"], 3);
+        assert_eq!(json["groups"][1]["shares"].as_array().unwrap().len(), 5);
+        assert!(json["groups"][0]["shares"][0]["ur"]
+            .as_str()
+            .unwrap()
+            .starts_with("ur:sskr/"));
+
+        let minimal_shares = document
+            .groups
+            .iter()
+            .flat_map(|group| group.shares.iter().map(|share| share.minimal.clone()))
+            .collect();
+        let recovered = recover::recover(minimal_shares, &true, Language::English)?;
+        assert_eq!(recovered.phrase(), mnemonic.phrase());
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_describes_several_seeds() -> Result<(), Error> {
+        let path = std::env::temp_dir().join(format!("sskr-tool-manifest-{}.json", process::id()));
+        let mut identifiers = vec![];
+        for (name, fingerprint) in [("Savings", "73C5DA0A"), ("Spending", "0d5f2a11")] {
+            let (_mnemonic, groups) = split::split_random_phrase(
+                "2of3,2of2",
+                1,
+                MnemonicType::Words12,
+                &false,
+                Language::English,
+            )?;
+            let seed = SeedManifest::from_shares(&groups, &false, Language::English)?
+                .with_details(
+                    &Some(name.to_string()),
+                    &None,
+                    &Some(fingerprint.to_string()),
+                )?;
+            identifiers.push(seed.identifier.clone());
+            add_to_manifest(&path, &seed)?;
+        }
+
+        let manifest = Manifest::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(manifest.seeds.len(), 2);
+        assert_eq!(manifest.seeds[0].name.as_deref(), Some("Savings"));
+        assert_eq!(manifest.seeds[0].fingerprint.as_deref(), Some("73c5da0a"));
+        assert_eq!(
+            manifest
+                .seeds
+                .iter()
+                .map(|seed| seed.identifier.clone())
+                .collect::<Vec<_>>(),
+            identifiers
+        );
+
+        let legacy = Manifest::from_json(&serde_json::to_string(&manifest.seeds[1])?)?;
+        assert_eq!(legacy.seeds, vec![manifest.seeds[1].clone()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_roundtrips_between_all_formats() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("3of5", 1, mnemonic.phrase(), Some(Language::English))?;
+        let share = &shares[0][0];
+
+        for from in ShareFormat::value_variants() {
+            for to in ShareFormat::value_variants() {
+                let input = share_format::encode(share, *from);
+                let (detected, converted) = share_format::convert(&input, *to)?;
+                assert_eq!(detected, *from);
+                assert_eq!(converted, share_format::encode(share, *to));
+                assert_eq!(share_format::decode_as(&converted, *to)?, *share);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encoded_shares_are_checked() -> Result<(), Error> {
+        let secret: Vec<u8> = (0..16).collect();
+        let shares = split::split_raw("2of3", 1, &secret)?;
+        let parsed = shares[0]
+            .iter()
+            .map(|share| EncodedShare::parse(&share_format::encode(share, ShareFormat::Ur)))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(parsed[1].format(), ShareFormat::Ur);
+        assert_eq!(parsed[1].share(), shares[0][1]);
+        assert_eq!(parsed[1].metadata(), [0, 1, 1, 1, 2]);
+        assert_eq!(*recover::recover_shares(&parsed[1..])?, secret);
+        assert!(!format!("{:?}", parsed[0]).contains(&hex::encode(&shares[0][0])));
+
+        // Text that decodes still isn't a share unless its metadata is usable
+        let mut reserved = shares[0][0].clone();
+        reserved[4] |= 0x10;
+        let bytewords = share_format::encode(&shares[0][0], ShareFormat::Bytewords);
+        let wrong_checksum = format!("{} able", &bytewords[..bytewords.rfind(' ').unwrap()]);
+        for input in [
+            share_format::encode(&reserved, ShareFormat::Bytewords),
+            share_format::encode(&shares[0][0][..4], ShareFormat::Hex),
+            wrong_checksum,
+        ] {
+            assert!(EncodedShare::parse(&input).is_err());
+            assert!(share_format::convert(&input, ShareFormat::Hex).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_inspect_share_metadata() -> Result<(), Error> {
+        let mnemonic = Mnemonic::new(bip39::MnemonicType::Words24, Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3,3of5", 2, mnemonic.phrase(), Some(Language::English))?;
+        let identifier = ShareMetadataOutput::new(&shares[0][0])?.identifier;
+
+        for (group_num, group) in shares.iter().enumerate() {
+            for (share_num, share) in group.iter().enumerate() {
+                let input = share_format::encode(share, ShareFormat::Ur);
+                let (_format, decoded) = share_format::decode(&input)?;
+                let metadata = ShareMetadataOutput::new(&decoded)?;
+                assert_eq!(metadata.identifier, identifier);
+                assert_eq!(metadata.group_index, group_num);
+                assert_eq!(metadata.group_threshold, 2);
+                assert_eq!(metadata.group_count, 2);
+                assert_eq!(metadata.member_index, share_num);
+                assert_eq!(metadata.member_threshold, [2, 3][group_num]);
+                assert_eq!(metadata.secret_length, 32);
+            }
+        }
+
+        // A public inspection leaves out the checksum, which holds the share
+        let share = share_format::encode(&shares[1][2], ShareFormat::Bytewords);
+        let inspection = Inspection::new(&EncodedShare::parse(&share)?);
+        assert_eq!(inspection.format, ShareFormat::Bytewords);
+        assert_eq!(
+            inspection.metadata,
+            ShareMetadataOutput::new(&shares[1][2])?
+        );
+        assert!(inspection.to_string().contains("Payload:"));
+        let public = inspection.public();
+        assert!(!public.to_string().contains("Payload:"));
+        assert!(!serde_json::to_string(&public)?.contains("checksum"));
+        assert!(Cli::try_parse_from(["sskr-tool", "inspect", "--public", "ur:sskr/x"]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_estate_refuses_to_mix_splits() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!("sskr-tool-rerun-{}", process::id()));
+        let describe = |groups: &Vec<Vec<String>>| {
+            SeedManifest::from_shares(groups, &false, Language::English)
+        };
+
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = describe(&groups)?;
+        let packets = estate::write_estate(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse,
+        )?;
+        assert_eq!(packets.outcome, WriteOutcome::Written);
+
+        let (_mnemonic, other) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        assert!(estate::write_estate(
+            &dir,
+            &describe(&other)?,
+            &other,
+            &naming::NameTemplate::default(),
+            ExistingShares::Verify
+        )
+        .is_err());
+        assert!(estate::write_estate(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse
+        )
+        .is_err());
+
+        let packets = estate::write_estate(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Verify,
+        )?;
+        assert_eq!(packets.outcome, WriteOutcome::Verified);
+
+        std::fs::write(&packets.custodians[1], "tampered")?;
+        assert!(estate::write_estate(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Verify
+        )
+        .is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_reports_missing_shares() -> Result<(), Error> {
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3,3of5,2of2",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let (_mnemonic, other) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let mut manifest = Manifest::new(SeedManifest::from_shares(
+            &groups,
+            &false,
+            Language::English,
+        )?);
+        manifest.add_seed(SeedManifest::from_shares(
+            &other,
+            &false,
+            Language::English,
+        )?)?;
+
+        // Duplicates and blank lines don't count towards a group
+        let lines = vec![
+            groups[0][0].clone(),
+            groups[0][0].clone(),
+            String::new(),
+            groups[1][0].clone(),
+            groups[1][1].clone(),
+            groups[1][2].clone(),
+        ];
+        let sets = status::status(lines, &false, Some(&manifest))?;
+        let set = sets
+            .iter()
+            .find(|set| set.identifier == manifest.seeds[0].identifier)
+            .unwrap();
+
+        assert_eq!(sets.len(), 2);
+        assert_eq!(set.groups.len(), 3);
+        assert_eq!(set.groups[0].present, 1);
+        assert_eq!(set.groups[0].missing, Some(1));
+        assert!(set.groups[1].satisfied);
+        assert_eq!(set.groups[2].present, 0);
+        assert_eq!(set.groups[2].missing, Some(2));
+        assert_eq!(set.groups_satisfied, 1);
+        assert_eq!(set.groups_missing, Some(1));
+        assert!(!set.recoverable);
+
+        let unseen = sets
+            .iter()
+            .find(|set| set.identifier == manifest.seeds[1].identifier)
+            .unwrap();
+        assert_eq!(unseen.groups[0].missing, Some(2));
+        assert_eq!(unseen.groups_missing, Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_name_template() -> Result<(), Error> {
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3,3of5",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?.with_details(
+            &Some("Savings".to_string()),
+            &None,
+            &Some("73c5da0a".to_string()),
+        )?;
+        let share = naming::ShareName {
+            seed: &seed,
+            group: 2,
+            member: 4,
+            members: 5,
+        };
+
+        assert_eq!(
+            naming::render_share_name(naming::DEFAULT_SHARE_TEMPLATE, &share)?,
+            "share-g2-m4-of-5"
+        );
+        assert_eq!(
+            naming::render_share_name("{custodian}-{group}-{member}-{fingerprint}", &share)?,
+            "group2-member4-2-4-73c5da0a"
+        );
+        assert_eq!(
+            naming::render_share_name("{name}_{identifier}_{threshold}of{members}", &share)?,
+            format!("Savings_{}_3of5", seed.identifier)
+        );
+        assert!(naming::render_share_name("{group}-{unknown}", &share).is_err());
+        assert!(naming::render_share_name("{group}/{member}", &share).is_err());
+
+        // Names come out the same on every filesystem
+        let named = seed.clone().with_custodians(&[
+            vec!["Alice".into(), "Bob".into(), "Carol".into()],
+            vec![
+                "Zoë: Müller/Ørsted".into(),
+                "con".into(),
+                "Ana\u{0301}.".into(),
+                "ALICE".into(),
+                "Dewey & Co".into(),
+            ],
+        ]);
+        let name = |member: usize, ascii: bool| {
+            naming::NameTemplate::new("{custodian}")
+                .ascii(ascii)
+                .render(&naming::ShareName {
+                    seed: &named,
+                    group: 2,
+                    member,
+                    members: 5,
+                })
+        };
+        assert_eq!(name(1, false)?, "Zoë_ Müller_Ørsted");
+        assert_eq!(name(1, true)?, "Zoe_ Muller_Orsted");
+        assert_eq!(name(2, false)?, "con_");
+        assert_eq!(name(3, false)?, "An\u{e1}");
+        assert_eq!(name(3, true)?, "Ana");
+        assert_eq!(name(5, true)?, "Dewey & Co");
+        assert!(naming::render_share_name("{group}:{member}", &share).is_err());
+        assert!(naming::ensure_unique_names(&["Alice".into(), "ALICE".into()]).is_err());
+
+        let dir = std::env::temp_dir().join(format!("sskr-tool-naming-{}", process::id()));
+        assert!(estate::write_estate(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::new("{name}-{group}"),
+            ExistingShares::Refuse
+        )
+        .is_err());
+        assert!(!dir.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_out_dir_writes_one_file_per_share() -> Result<(), Error> {
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3,2of2",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?;
+        let dir = std::env::temp_dir().join(format!("sskr-tool-out-dir-{}", process::id()));
+
+        let files = artifacts::write_share_files(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse,
+            HashAlgorithm::Sha256,
+            false,
+        )?;
+        assert_eq!(files.outcome, WriteOutcome::Written);
+        assert_eq!(files.shares.len(), 5);
+        assert!(files.shares.contains(&dir.join("share-g2-m1-of-2.txt")));
+        let mut written = Manifest::load(&files.manifest)?;
+        assert_eq!(written.seeds[0].artifacts.len(), 5);
+        written.seeds[0].artifacts.clear();
+        assert_eq!(written, Manifest::new(seed.clone()));
+
+        let mut shares = vec![];
+        for path in &files.shares {
+            let contents = std::fs::read_to_string(path)?;
+            assert_eq!(contents.lines().count(), 1);
+            shares.push(contents.trim().to_string());
+        }
+        ensure_recoverable(&mnemonic, shares)?;
+
+        let rerun = artifacts::write_share_files(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Verify,
+            HashAlgorithm::Sha256,
+            false,
+        )?;
+        assert_eq!(rerun.outcome, WriteOutcome::Verified);
+
+        // Splitting the mnemonic again gives other shares, which the existing
+        // ones are checked against by layout and secret
+        let verify = |spec: &str, group_threshold: usize, phrase: &str| {
+            let (_, groups) = split::split(spec, group_threshold, phrase, &false, None)?;
+            artifacts::write_share_files(
+                &dir,
+                &SeedManifest::from_shares(&groups, &false, Language::English)?,
+                &groups,
+                &naming::NameTemplate::default(),
+                ExistingShares::Verify,
+                HashAlgorithm::Sha256,
+                false,
+            )
+        };
+        assert_eq!(
+            verify("2of3,2of2", 2, mnemonic.phrase())?.outcome,
+            WriteOutcome::Verified
+        );
+        assert!(verify("2of3,2of3", 2, mnemonic.phrase()).is_err());
+        assert!(verify("2of3,2of2", 1, mnemonic.phrase()).is_err());
+        let (other, _) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        assert!(verify("2of3,2of2", 2, other.phrase()).is_err());
+        std::fs::remove_file(&files.shares[4])?;
+        assert!(verify("2of3,2of2", 2, mnemonic.phrase()).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_shares_from_several_sources() -> Result<(), Error> {
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3,2of3",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let dir = std::env::temp_dir().join(format!("sskr-tool-input-{}", process::id()));
+        let custodians = dir.join("custodians");
+        std::fs::create_dir_all(&custodians)?;
+
+        let first = dir.join("first.txt");
+        std::fs::write(
+            &first,
+            format!("# from the first custodian\n\n  {}  \n\n", groups[0][0]),
+        )?;
+        std::fs::write(custodians.join("a.txt"), format!("{}\n", groups[0][2]))?;
+        std::fs::write(custodians.join("b.txt"), format!("{}\n", groups[1][1]))?;
+        std::fs::write(custodians.join("c.txt"), format!("{}\n", groups[1][2]))?;
+        std::fs::write(custodians.join("notes.md"), "not a share\n")?;
+
+        let lines = input::read_share_lines(&[
+            first.display().to_string(),
+            custodians.display().to_string(),
+        ])?;
+        assert_eq!(
+            lines,
+            vec![
+                groups[0][0].clone(),
+                groups[0][2].clone(),
+                groups[1][1].clone(),
+                groups[1][2].clone(),
+            ]
+        );
+        ensure_recoverable(&mnemonic, lines)?;
+
+        assert!(input::read_share_lines(&[dir.join("missing.txt").display().to_string()]).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_scanned_shares_are_checked_as_they_arrive() -> Result<(), Error> {
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3,2of2",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let (_other, other_groups) = split::split_random_phrase(
+            "2of3,2of2",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+
+        // Scanners end lines with either \r or \n, and the last one may have neither
+        let scanned = format!(
+            "{}\r\nmisread\r{}\n{}\n{}\r{}\n{}",
+            groups[0][0],
+            groups[0][0],
+            other_groups[1][0],
+            groups[1][1],
+            groups[1][0],
+            groups[0][2],
+        );
+        let mut reports = vec![];
+        let shares = scan::collect_shares(scanned.as_bytes(), &false, |message| {
+            reports.push(message.to_string())
+        })?;
+
+        assert_eq!(shares.len(), 4);
+        assert_eq!(reports.len(), 7);
+        assert!(reports[1].starts_with("Rejected unreadable"));
+        assert!(reports[2].starts_with("Rejected duplicate"));
+        assert!(reports[3].starts_with("Rejected share from a different set"));
+        ensure_recoverable(&mnemonic, shares)?;
+
+        // Scanning stops as soon as enough shares are present
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let scanned = format!("{}\n{}\n{}\n", groups[0][0], groups[0][1], groups[0][2]);
+        let shares = scan::collect_shares(scanned.as_bytes(), &false, |_| {})?;
+        assert_eq!(shares.len(), 2);
+        ensure_recoverable(&mnemonic, shares)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_mnemonic_from_physical_entropy() -> Result<(), Error> {
+        let rolls: EntropySource = format!("dice:{}", "16 25 34 ".repeat(9)).parse()?;
+        assert_eq!(rolls.count(), 54);
+        assert_eq!(rolls.required(128), 50);
+
+        let first =
+            split::entropy_mnemonic(&rolls, &false, MnemonicType::Words12, Language::English)?;
+        let second =
+            split::entropy_mnemonic(&rolls, &false, MnemonicType::Words12, Language::English)?;
+        assert_eq!(first.entropy(), second.entropy());
+        assert_eq!(first.entropy().len(), 16);
+
+        let mixed =
+            split::entropy_mnemonic(&rolls, &true, MnemonicType::Words12, Language::English)?;
+        assert_ne!(mixed.entropy(), first.entropy());
+
+        // Heads/tails and 1/0 are the same flips
+        let heads_tails: EntropySource = format!("coin:{}", "HT".repeat(64)).parse()?;
+        let ones_zeros: EntropySource = format!("coin:{}", "10".repeat(64)).parse()?;
+        assert_eq!(heads_tails.entropy(16)?, ones_zeros.entropy(16)?);
+
+        let too_few: EntropySource = "dice:123456".parse()?;
+        assert!(split::entropy_mnemonic(
+            &too_few,
+            &false,
+            MnemonicType::Words12,
+            Language::English
+        )
+        .is_err());
+        assert!("dice:1237".parse::<EntropySource>().is_err());
+        assert!("coin:hxt".parse::<EntropySource>().is_err());
+        assert!("cards:123".parse::<EntropySource>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyboard_wedge_artifacts_are_cleaned_up() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (_mnemonic, shares) = split::split_shares("2of3", 1, mnemonic.phrase(), None)?;
+        let share = &shares[0][0];
+        let bytewords = share_format::encode(share, ShareFormat::Bytewords);
+        let minimal = share_format::encode(share, ShareFormat::Minimal);
+        let ur = share_format::encode(share, ShareFormat::Ur);
+
+        // Pasted from a document: smart quotes, non-breaking spaces, zero-width
+        // characters and full-width letters
+        let pasted: String = bytewords
+            .replacen(' ', "\u{a0}\u{200b}", 3)
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match (i, c) {
+                (0, c) => char::from_u32(c as u32 + 0xfee0).unwrap(),
+                (_, c) => c,
+            })
+            .collect();
+        assert_eq!(
+            share_format::decode(&format!("“{}”", pasted))?,
+            (ShareFormat::Bytewords, share.clone())
+        );
+
+        // A German (QWERTZ) host swaps y and z
+        let qwertz: String = minimal
+            .chars()
+            .map(|c| match c {
+                'y' => 'z',
+                'z' => 'y',
+                c => c,
+            })
+            .collect();
+        assert_eq!(recover::decode_share(&qwertz)?, share.clone());
+
+        // A French (AZERTY) host swaps a/q and w/z and mangles the UR prefix
+        let azerty: String = ur
+            .to_uppercase()
+            .chars()
+            .map(|c| match c {
+                'A' => 'Q',
+                'Q' => 'A',
+                'W' => 'Z',
+                'Z' => 'W',
+                'M' => '?',
+                ':' => 'M',
+                '/' => '!',
+                c => c,
+            })
+            .collect();
+        assert_eq!(
+            share_format::decode(&azerty)?,
+            (ShareFormat::Ur, share.clone())
+        );
+
+        assert!(share_format::decode("“tuna acid”").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_share_diagnostics() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (_mnemonic, shares) = split::split_shares("2of3", 1, mnemonic.phrase(), None)?;
+        let minimal = share_format::encode(&shares[0][1], ShareFormat::Minimal);
+
+        let diagnostics = sskr_tool::validate_share(&minimal);
+        assert!(diagnostics.is_valid());
+        assert_eq!(diagnostics.format, ShareFormat::Minimal);
+        assert_eq!(diagnostics.checksum_ok, Some(true));
+        assert_eq!(
+            diagnostics.metadata,
+            Some(ShareMetadataOutput::new(&shares[0][1])?)
+        );
+        assert!(diagnostics.warnings.is_empty());
+
+        let quoted = sskr_tool::validate_share(&format!("\"{}\"", minimal.to_uppercase()));
+        assert!(quoted.is_valid());
+        assert_eq!(quoted.warnings.len(), 1);
+
+        // Replacing the last byteword breaks only the checksum
+        let mut words: Vec<_> = share_format::encode(&shares[0][1], ShareFormat::Bytewords)
+            .split(' ')
+            .map(String::from)
+            .collect();
+        let last = words.pop().unwrap();
+        words.push(if last == "able" { "acid" } else { "able" }.to_string());
+        let corrupted = sskr_tool::validate_share(&words.join(" "));
+        assert!(!corrupted.is_valid());
+        assert_eq!(corrupted.format, ShareFormat::Bytewords);
+        assert_eq!(corrupted.checksum_ok, Some(false));
+        assert_eq!(corrupted.metadata, None);
+
+        let unreadable = sskr_tool::validate_share("not a share");
+        assert_eq!(unreadable.checksum_ok, None);
+        assert!(unreadable.error.is_some());
+
+        let (_mnemonic, single) = split::split_shares("1of1", 1, mnemonic.phrase(), None)?;
+        let alone =
+            sskr_tool::validate_share(&share_format::encode(&single[0][0], ShareFormat::Ur));
+        assert!(alone.is_valid());
+        assert_eq!(
+            alone.warnings,
+            vec!["This share alone is enough to recover the secret"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_mnemonic_lengths() -> Result<(), Error> {
+        for (count, entropy_length) in [(12, 16), (15, 20), (18, 24), (21, 28), (24, 32)] {
+            let words = split::parse_word_count(&count.to_string())?;
+            let (mnemonic, groups) =
+                split::split_random_phrase("2of3,3of5", 2, words, &true, Language::English)?;
+            assert_eq!(mnemonic.phrase().split(' ').count(), count);
+            assert_eq!(mnemonic.entropy().len(), entropy_length);
+
+            let shares = groups.into_iter().flatten().collect();
+            let recovered = recover::recover(shares, &true, Language::English)?;
+            assert_eq!(recovered.phrase(), mnemonic.phrase());
+        }
+
+        assert!(split::parse_word_count("13").is_err());
+        assert!(split::parse_word_count("twelve").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_locale_formatting() {
+        assert_eq!(Locale::En.threshold(2, 3), "2 of 3");
+        assert_eq!(
+            Locale::En.group_needs(1, 2, 3),
+            "Group 1 - need 2 of 3 shares to recover group"
+        );
+        assert_eq!(
+            Locale::En.group_progress(2, 1, None, None),
+            "Group 2: 1 of ? needed shares present - threshold unknown"
+        );
+        assert_eq!(Locale::Fr.threshold(2, 3), "2 sur 3");
+        assert_eq!(Locale::De.group(4), "Gruppe 4");
+
+        // Numbers keep their left-to-right order inside right-to-left text
+        assert!(!Locale::En.is_rtl());
+        assert_eq!(Locale::Ar.ltr(12), "\u{2066}12\u{2069}");
+        assert_eq!(
+            Locale::Ar.threshold(2, 3),
+            "\u{2066}2\u{2069} من \u{2066}3\u{2069}"
+        );
+    }
+
+    #[test]
+    fn test_spec_shortcuts() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (_mnemonic, groups) = split::split_shares(" 2/3, 3 OF 5 ", 2, mnemonic.phrase(), None)?;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].len(), 5);
+        assert!(split::split_shares("2/3/4", 1, mnemonic.phrase(), None).is_err());
+        assert_eq!(split::spec_layout("a = 2of3 ;3/5", 2)?.share_count(), 8);
+
+        // Errors point at where the spec goes wrong
+        use error::SskrToolError;
+        let error = |spec: &str| {
+            let error = split::spec_layout(spec, 1).unwrap_err();
+            match error.downcast_ref::<SskrToolError>() {
+                Some(SskrToolError::InvalidSpec { start, end, .. }) => {
+                    (*start..*end, error.to_string())
+                }
+                _ => panic!("{} isn't a syntax error", error),
+            }
+        };
+        assert_eq!(
+            error("2/3/4"),
+            (
+                3..4,
+                "Invalid group spec at column 4, expected \",\" or \";\" between groups:\n  2/3/4\n     ^"
+                    .to_string()
+            )
+        );
+        assert_eq!(error("2of3,").0, 5..5);
+        assert_eq!(error("Zoë=2x3").0, 6..7);
+        assert_eq!(error("2of3 [paper]").0, 5..12);
+        assert_eq!(error("2of3 [paper").0, 5..11);
+        assert_eq!(error("99999999999999999999999of3").0, 0..23);
+        assert!(split::spec_layout("4of3", 1).is_err());
+        assert_eq!(error("2of17").0, 0..5);
+        assert_eq!(error(&vec!["2of3"; 17].join(",")).0, 80..84);
+
+        assert_eq!(split::default_group_threshold("3of5")?, 1);
+        assert!(split::default_group_threshold("2/3,3/5").is_err());
+
+        let parameters = |arguments: &[&str]| -> Result<(usize, Option<String>), Error> {
+            let cli = Cli::try_parse_from([&["sskr-tool", "split"], arguments].concat())?;
+            let Commands::Split(args) = cli.command else {
+                unreachable!()
+            };
+            let (group_threshold, phrase) = split_parameters(&args)?;
+            Ok((group_threshold, phrase.map(String::from)))
+        };
+        assert_eq!(parameters(&["3of5"])?, (1, None));
+        assert_eq!(
+            parameters(&["3of5", mnemonic.phrase()])?,
+            (1, Some(mnemonic.phrase().to_string()))
+        );
+        assert_eq!(
+            parameters(&["2of3,3of5", "2", mnemonic.phrase()])?,
+            (2, Some(mnemonic.phrase().to_string()))
+        );
+        assert!(parameters(&["2of3,3of5"]).is_err());
+        assert!(parameters(&["2of3,3of5", mnemonic.phrase()]).is_err());
+        assert!(parameters(&["3of5", mnemonic.phrase(), "--entropy", "dice:1"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_replacing_shares_needs_confirmation() -> Result<(), Error> {
+        let mut prompt = vec![];
+        let mut confirmed = |answer: &str, word: &str| {
+            confirm::confirm("Delete?", word, &mut answer.as_bytes(), &mut prompt).is_ok()
+        };
+        assert!(confirmed("shred\n", "shred"));
+        assert!(!confirmed("y\n", "shred"));
+        assert!(!confirmed("\n", "shred"));
+        assert!(!confirmed("Shred\n", "shred"));
+        assert!(!confirmed("y\n", "y"));
+
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let (_mnemonic, other) = split::split_random_phrase(
+            "2of2",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let describe = |groups| SeedManifest::from_shares(groups, &false, Language::English);
+        let dir = std::env::temp_dir().join(format!("sskr-tool-replace-{}", process::id()));
+
+        artifacts::write_share_files(
+            &dir,
+            &describe(&groups)?,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse,
+            HashAlgorithm::Sha256,
+            false,
+        )?;
+        let replaced = artifacts::write_share_files(
+            &dir,
+            &describe(&other)?,
+            &other,
+            &naming::NameTemplate::default(),
+            ExistingShares::Replace,
+            HashAlgorithm::Sha256,
+            false,
+        )?;
+        assert_eq!(replaced.outcome, WriteOutcome::Written);
+
+        // Only the new split's shares are left
+        let found = artifacts::existing_shares(&dir)?;
+        let identifier = describe(&other)?.identifier;
+        assert_eq!(found.len(), 2);
+        assert!(found.values().all(|ids| ids.iter().eq([&identifier])));
+        assert!(!dir.join("share-g1-m3-of-3.txt").exists());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_paper_backup_has_a_page_per_share() -> Result<(), Error> {
+        let phrase = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (mnemonic, shares) =
+            split::split_shares("2of3,2of2", 2, phrase.phrase(), Some(Language::English))?;
+        let dir = std::env::temp_dir().join(format!("sskr-tool-paper-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+
+        let html = dir.join("shares.html");
+        assert_eq!(paper::write_paper(&html, &shares, &true)?, 5);
+        let contents = std::fs::read_to_string(&html)?;
+        assert_eq!(contents.matches("<section class=\"page\">").count(), 5);
+        assert_eq!(contents.matches("<svg class=\"qr\"").count(), 5);
+        assert!(contents.contains("Share 2 of 3 - 2 of 3 shares needed to recover the group"));
+
+        // Every share is on its page, with exactly its last 4 words highlighted
+        let mut printed = vec![];
+        for page in contents.split("<section class=\"page\">").skip(1) {
+            let words = page
+                .lines()
+                .filter(|line| line.contains("</small>") && !line.ends_with("</small></span>"))
+                .map(|line| {
+                    line.rsplit("</small>")
+                        .next()
+                        .unwrap()
+                        .trim_end_matches("</span>")
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(page.matches("class=\"checksum\"").count(), 4);
+            printed.push(words.join(" "));
+        }
+        ensure_recoverable(&mnemonic, printed)?;
+
+        let pdf = dir.join("shares.pdf");
+        assert_eq!(paper::write_paper(&pdf, &shares, &false)?, 5);
+        let contents = std::fs::read(&pdf)?;
+        assert!(contents.starts_with(b"%PDF"));
+        assert_eq!(
+            String::from_utf8_lossy(&contents)
+                .matches("/Type /Page\n")
+                .count(),
+            5
+        );
+
+        // Neither overwritten nor written in an unknown format
+        assert!(paper::write_paper(&pdf, &shares, &false).is_err());
+        assert!(paper::write_paper(&dir.join("shares.txt"), &shares, &false).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_dual_control_needs_both_passphrases() -> Result<(), Error> {
+        let answering = |answers: &[&str]| {
+            let mut answers = answers
+                .iter()
+                .map(|answer| Zeroizing::new(answer.to_string()))
+                .collect::<Vec<_>>()
+                .into_iter();
+            move |_: &str| {
+                answers
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("No passphrase entered"))
+            }
+        };
+        let (dual_control, passphrase) = DualControl::enroll(&mut answering(&[
+            "first keyholder",
+            "first keyholder",
+            "second keyholder",
+            "second keyholder",
+        ]))?;
+        assert_eq!(dual_control.operators, dual_control::OPERATORS);
+
+        // The entropy is encrypted under both passphrases, in order
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let encrypted = encryption::encrypt(mnemonic.entropy(), &passphrase)?;
+        let decrypt = |answers: &[&str]| -> Result<_, Error> {
+            let passphrase = dual_control.passphrase(&mut answering(answers))?;
+            encryption::decrypt(&encrypted.secret, &passphrase, None)
+        };
+        let decrypted = decrypt(&["first keyholder", "second keyholder"])?;
+        assert_eq!(decrypted.secret.as_slice(), mnemonic.entropy());
+        assert_eq!(decrypted.check, encrypted.check);
+        for wrong in [
+            ["first keyholder", "first keyholder"],
+            ["second keyholder", "first keyholder"],
+            ["first keyholdersecond", " keyholder"],
+        ] {
+            let decrypted = decrypt(&wrong)?;
+            assert_ne!(decrypted.secret.as_slice(), mnemonic.entropy());
+            assert_ne!(decrypted.check, encrypted.check);
+        }
+        assert!(decrypt(&["first keyholder"]).is_err());
+
+        let enroll = |answers: &[&str]| DualControl::enroll(&mut answering(answers));
+        assert!(enroll(&["same words", "same words", "same words", "same words"]).is_err());
+        assert!(enroll(&["mistyped", "mistyoed"]).is_err());
+        assert!(enroll(&["short", "short"]).is_err());
+
+        // Recorded in the manifest, which still reads without it
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let mut seed = SeedManifest::from_shares(&groups, &false, Language::English)?;
+        let plain = Manifest::new(seed.clone()).to_json()?;
+        assert!(!plain.contains("dual_control"));
+        seed.dual_control = Some(dual_control);
+        let manifest = Manifest::new(seed);
+        assert!(!manifest.to_json()?.contains("keyholder"));
+        assert_eq!(Manifest::from_json(&manifest.to_json()?)?, manifest);
+        assert_eq!(Manifest::from_json(&plain)?.seeds[0].dual_control, None);
+
+        // Only where the seed is recorded
+        let parse = |arguments: &[&str]| {
+            Cli::try_parse_from(
+                [&["sskr-tool", "split", "2of3", "--dual-control"], arguments].concat(),
+            )
+        };
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--manifest", "custody.json"]).is_ok());
+        assert!(parse(&["--manifest", "custody.json", "--encrypt"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reshare_keeps_the_secret() -> Result<(), Error> {
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3,3of5",
+            1,
+            gen_random_words(),
+            &false,
+            Language::English,
+        )?;
+        let old = SeedManifest::from_shares(&groups, &false, Language::English)?;
+
+        // One custodian of the first group is gone, the other two are enough
+        let lines = groups[0][1..].to_vec();
+        let (reshared, shares) = split::reshare(lines, &false, "2/2,2/3", 2, Language::English)?;
+        assert_eq!(reshared.entropy(), mnemonic.entropy());
+
+        let new_groups = split::to_bytewords(&shares, &false);
+        let new = SeedManifest::from_shares(&new_groups, &false, Language::English)?;
+        assert_ne!(new.identifier, old.identifier);
+        assert_eq!(new.group_threshold, 2);
+        ensure_recoverable(
+            &mnemonic,
+            [new_groups[0].clone(), new_groups[1][..2].to_vec()].concat(),
+        )?;
+        ensure_unrecoverable(new_groups[0].clone());
+
+        // Old and new shares don't go together
+        ensure_unrecoverable([&groups[0][..1], &new_groups[0][..1]].concat());
+        assert!(split::reshare(
+            groups[1][..2].to_vec(),
+            &false,
+            "2of3",
+            1,
+            Language::English
+        )
+        .is_err());
+        assert!(split::reshare(vec![], &false, "2of3", 1, Language::English).is_err());
+
+        let document = SplitOutput {
+            secret: None,
+            ..SplitOutput::new("2/2,2/3", 2, &reshared, &shares)?
+        };
+        let json = serde_json::to_string(&document)?;
+        assert!(!json.contains("mnemonic") && !json.contains("entropy"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_journal_is_tamper_evident() -> Result<(), Error> {
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3,3of5",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let dir = std::env::temp_dir().join(format!("sskr-tool-journal-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("session.txt");
+
+        // Collected over several days, repeats are skipped
+        let added = journal::append_shares(&path, &groups[0][..2], &false)?;
+        assert_eq!(added.len(), 2);
+        let head = format!("{}:{}", added[1].number, added[1].hash);
+        let added = journal::append_shares(&path, &groups[1][1..4], &false)?;
+        assert_eq!(added.len(), 3);
+        let latest = format!("{}:{}", added[2].number, added[2].hash);
+        assert!(journal::append_shares(&path, &groups[0][1..2], &false)?.is_empty());
+        assert!(journal::append_shares(&path, &["not a share".to_string()], &false).is_err());
+
+        let entries = journal::verify_journal(&path, Some(&head))?;
+        assert_eq!(entries.len(), 5);
+        let lines = input::read_share_lines(&[path.display().to_string()])?;
+        assert_eq!(lines.len(), 5);
+        ensure_recoverable(&mnemonic, lines)?;
+
+        let original = std::fs::read_to_string(&path)?;
+        let tampered = |contents: String| -> Result<bool, Error> {
+            std::fs::write(&path, contents)?;
+            Ok(journal::verify_journal(&path, Some(&head)).is_err())
+        };
+        let lines = original.lines().collect::<Vec<_>>();
+
+        // An edited share, a removed entry, swapped entries and a cut off end
+        assert!(tampered(original.replace(&groups[1][2], &groups[1][0]))?);
+        assert!(tampered([&lines[..3], &lines[5..]].concat().join("\n"))?);
+        assert!(tampered(
+            [&lines[..1], &lines[3..5], &lines[1..3], &lines[5..]]
+                .concat()
+                .join("\n")
+        )?);
+        assert!(tampered(lines[..3].join("\n"))?);
+        assert!(tampered(lines[..4].join("\n"))?);
+        // Cut off after the head the chain is still valid, only a later head
+        // shows what is missing
+        assert!(!tampered(lines[..7].join("\n"))?);
+        assert!(journal::verify_journal(&path, Some(&latest)).is_err());
+        assert!(journal::verify_journal(&path, Some(&format!("3:{}", &head[2..]))).is_err());
+        assert!(!tampered(original)?);
+        assert!(journal::verify_journal(&path, Some(&latest)).is_ok());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_shares_against_mnemonic() -> Result<(), Error> {
+        let phrase = split::random_mnemonic(gen_random_words(), Language::English);
+        let (mnemonic, mut shares) =
+            split::split_shares("2of3,2of3,3of5", 2, phrase.phrase(), None)?;
+        let encoded = |shares: &split::ShareGroups, groups: &[(usize, usize)]| {
+            groups
+                .iter()
+                .flat_map(|(group, count)| shares[*group][..*count].to_vec())
+                .map(|share| share_format::encode(&share, ShareFormat::Bytewords))
+                .collect::<Vec<_>>()
+        };
+        let outcomes = |verification: &verify::Verification| {
+            verification
+                .groups
+                .iter()
+                .map(|group| group.outcome)
+                .collect::<Vec<_>>()
+        };
+
+        let all = encoded(&shares, &[(0, 3), (1, 2), (2, 4)]);
+        let verification = verify::verify(all.clone(), &false, mnemonic.entropy())?;
+        assert!(verification.matches);
+        assert_eq!(
+            outcomes(&verification),
+            vec![verify::GroupOutcome::Verified; 3]
+        );
+        assert!(!serde_json::to_string(&verification)?.contains(&hex::encode(mnemonic.entropy())));
+
+        let other = split::random_mnemonic(gen_random_words(), Language::English);
+        let verification = verify::verify(all, &false, other.entropy())?;
+        assert!(!verification.matches);
+        assert_eq!(
+            outcomes(&verification),
+            vec![verify::GroupOutcome::Mismatch; 3]
+        );
+
+        // A mistranscribed share that still passes its checksum is pinned to
+        // its group, and the third group is too short to check
+        let last = shares[1][0].len() - 1;
+        shares[1][0][last] ^= 1;
+        let verification = verify::verify(
+            encoded(&shares, &[(0, 2), (1, 2), (2, 2)]),
+            &false,
+            mnemonic.entropy(),
+        )?;
+        assert!(!verification.matches);
+        assert_eq!(
+            outcomes(&verification),
+            vec![
+                verify::GroupOutcome::Mismatch,
+                verify::GroupOutcome::Mismatch,
+                verify::GroupOutcome::NotEnoughShares
+            ]
+        );
+        let verification = verify::verify(
+            encoded(&shares, &[(0, 2), (1, 2), (2, 3)]),
+            &false,
+            mnemonic.entropy(),
+        )?;
+        assert!(verification.matches);
+        assert_eq!(
+            outcomes(&verification),
+            vec![
+                verify::GroupOutcome::Verified,
+                verify::GroupOutcome::Mismatch,
+                verify::GroupOutcome::Verified
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_correct_transcription_errors() -> Result<(), Error> {
+        let (mnemonic, groups) =
+            split::split_random_phrase("2of3", 1, gen_random_words(), &false, Language::English)?;
+        let share = &groups[0][0];
+        let mut words = share.split(' ').map(String::from).collect::<Vec<_>>();
+
+        // Swapped letters and a wrong letter, in a data word and a checksum word
+        let mut rng = rand::thread_rng();
+        let (first, last) = (rng.gen_range(0..words.len() - 4), words.len() - 1);
+        let misread = |word: &str, misread: &dyn Fn(&mut Vec<char>)| {
+            let mut chars = word.chars().collect::<Vec<_>>();
+            misread(&mut chars);
+            chars.into_iter().collect::<String>()
+        };
+        words[first] = misread(&words[first], &|chars| chars.swap(1, 2));
+        words[last] = misread(&words[last], &|chars| chars[3] = 'q');
+        let typo = words.join(" ");
+        if typo == *share {
+            return Ok(());
+        }
+
+        let error = share_format::decode(&typo).unwrap_err().to_string();
+        assert!(error.contains("closest: "), "{}", error);
+        let (corrected, corrections) = share_format::auto_correct(&typo)?;
+        assert_eq!(corrected, *share);
+        assert!(corrections.len() <= 2 && !corrections.is_empty());
+        assert!(corrections
+            .iter()
+            .all(|(word, replacement)| words.contains(word)
+                && share.split(' ').any(|original| original == *replacement)));
+        ensure_recoverable(&mnemonic, vec![corrected, groups[0][1].clone()])?;
+
+        // The same typo in a hyphenated share is corrected in that style
+        let hyphenated = share_format::encode(
+            &share_format::decode(share)?.1,
+            share_format::ShareFormat::Hyphenated,
+        );
+        let (corrected, corrections) = share_format::auto_correct(&words.join("-"))?;
+        assert_eq!(corrected, hyphenated);
+        assert!(corrections.len() <= 2 && !corrections.is_empty());
+
+        // Shares that decode are left alone, and nothing is guessed for words
+        // nowhere near a byteword or a wrong checksum of valid words
+        assert_eq!(share_format::auto_correct(share)?, (share.clone(), vec![]));
+        assert!(share_format::auto_correct(&share.replacen(' ', " xxxxxxxx ", 1)).is_err());
+        let swapped = [&groups[0][1][..5], &share[5..]].concat();
+        if swapped != *share {
+            assert!(share_format::auto_correct(&swapped).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_byteword_round_trips() -> Result<(), Error> {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        for format in [
+            ShareFormat::Bytewords,
+            ShareFormat::Hyphenated,
+            ShareFormat::Minimal,
+        ] {
+            let encoded = share_format::encode(&bytes, format);
+            assert_eq!(share_format::decode_as(&encoded, format)?, bytes);
+            assert_eq!(
+                share_format::decode_as(&encoded.to_uppercase(), format)?,
+                bytes
+            );
+        }
+        let minimal = share_format::encode(&bytes, ShareFormat::Minimal);
+        let error = share_format::decode_as(&format!("qq{}", minimal), ShareFormat::Minimal);
+        assert!(error.unwrap_err().to_string().contains("\"qq\""));
+        let words = share_format::encode(&bytes, ShareFormat::Bytewords);
+        let error = share_format::decode_as(&format!("tunx {}", words), ShareFormat::Bytewords);
+        assert!(error.unwrap_err().to_string().contains("closest: tuna"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_standard_secret_lengths() -> Result<(), Error> {
+        let mut rng = rand::thread_rng();
+        for length in (16..=32).step_by(2) {
+            let secret = (0..length).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let shares = split::split_raw("2of3,2of2", 2, &secret)?;
+            let lines = [&shares[0][1..], &shares[1][..]]
+                .concat()
+                .iter()
+                .map(|share| share_format::encode(share, ShareFormat::Bytewords))
+                .collect::<Vec<_>>();
+
+            assert_eq!(*recover::recover_secret(lines.clone(), &false)?, secret);
+            let bip39 = split::BIP39_ENTROPY_LENGTHS.contains(&length);
+            assert_eq!(
+                recover::recover(lines, &false, Language::English).is_ok(),
+                bip39
+            );
+            assert_eq!(
+                sskr_tool::validate_share(&share_format::encode(
+                    &shares[0][0],
+                    ShareFormat::Minimal
+                ))
+                .warnings
+                .is_empty(),
+                bip39
+            );
+        }
+
+        let secret = [0x5a; 20];
+        let document = serde_json::to_value(SecretOutput::raw(&secret))?;
+        assert_eq!(document["entropy"], format!("0x{}", hex::encode(secret)));
+        assert!(document.get("mnemonic").is_none());
+        let bytewords = document["bytewords"].as_str().unwrap();
+        assert_eq!(bytewords.split(' ').count(), 24);
+
+        for length in [0, 14, 17, 31, 34] {
+            assert!(split::split_raw("2of3", 1, &vec![1; length]).is_err());
+            assert!(!limits::is_valid_secret_len(length));
+            assert!(!limits::is_valid_share_len(
+                length + limits::METADATA_SIZE_BYTES
+            ));
+        }
+        let share = &split::split_raw("2of3", 1, &[1; 20])?[0][0];
+        assert_eq!(limits::secret_len(share.len()), Some(20));
+        assert_eq!(limits::secret_len(3), None);
+        assert_eq!((limits::max_groups(), limits::max_members()), (16, 16));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirror_split_record() -> Result<(), Error> {
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?;
+        let dir = std::env::temp_dir().join(format!("sskr-tool-mirror-{}", process::id()));
+        let devices = ["usb1", "usb2"].map(|device| dir.join(device).display().to_string());
+        for device in &devices {
+            std::fs::create_dir_all(device)?;
+        }
+
+        let paths = mirror::mirror_record(&devices, &seed)?;
+        assert_eq!(paths.len(), 2);
+        for path in &paths {
+            assert_eq!(Manifest::load(path)?, Manifest::new(seed.clone()));
+            assert!(!std::fs::read_to_string(path)?.contains(&groups[0][0]));
+        }
+        // The same record again is fine, a different one is not
+        mirror::mirror_record(&devices, &seed)?;
+        std::fs::write(&paths[1], "{}")?;
+        assert!(mirror::mirror_record(&devices, &seed).is_err());
+        std::fs::remove_file(&paths[1])?;
+
+        // Nothing is written unless every device is there, and separate
+        let missing = [devices[0].clone(), dir.join("usb3").display().to_string()];
+        std::fs::remove_file(&paths[0])?;
+        assert!(mirror::mirror_record(&missing, &seed).is_err());
+        assert!(!paths[0].exists());
+        let twice = [devices[0].clone(), format!("{}/", devices[0])];
+        assert!(mirror::mirror_record(&twice, &seed).is_err());
+        assert!(!paths[0].exists());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_shares_in_mixed_formats() -> Result<(), Error> {
+        let (mnemonic, groups) =
+            split::split_random_phrase("5of5", 1, gen_random_words(), &false, Language::English)?;
+        let shares = groups[0]
+            .iter()
+            .map(|line| recover::decode_share(line))
+            .collect::<Result<Vec<_>, _>>()?;
+        let formats = [
+            ShareFormat::Bytewords,
+            ShareFormat::Minimal,
+            ShareFormat::Ur,
+            ShareFormat::Hex,
+            ShareFormat::Hyphenated,
+        ];
+        let lines = shares
+            .iter()
+            .zip(formats)
+            .map(|(share, format)| share_format::encode(share, format))
+            .collect::<Vec<_>>();
+        ensure_recoverable(&mnemonic, lines.clone())?;
+
+        // Hex of the tagged CBOR, as other tools write it, reads the same
+        // Byte strings over 23 bytes take an extra length byte
+        let header = match shares[3].len() {
+            len @ 0..=23 => format!("{:02x}", 0x40 + len),
+            len => format!("58{:02x}", len),
+        };
+        let tagged = format!("d90135{}{}", header, hex::encode(&shares[3]));
+        assert_eq!(recover::decode_share(&tagged)?, shares[3]);
+        assert_eq!(
+            share_format::encode(&shares[3], ShareFormat::CborHex),
+            tagged
+        );
+        assert_eq!(
+            ShareFormat::detect(&tagged.to_uppercase()),
+            ShareFormat::CborHex
+        );
+        ensure_recoverable(
+            &mnemonic,
+            [&lines[..3], std::slice::from_ref(&tagged), &lines[4..]].concat(),
+        )?;
+
+        // Split writes either hex, with no byteword style on top
+        let hex = SplitFormat::CborHex.share_format(BytewordStyle::Minimal);
+        assert_eq!(
+            split::to_format(std::slice::from_ref(&shares), hex)[0][3],
+            tagged
+        );
+        for (extra, valid) in [
+            (None, true),
+            (Some("--minimal"), false),
+            (Some("--envelope"), false),
+        ] {
+            let args = ["sskr-tool", "split", "2of3", "--share-format", "cbor-hex"];
+            let Commands::Split(args) = Cli::try_parse_from(args.into_iter().chain(extra))?.command
+            else {
+                unreachable!()
+            };
+            assert_eq!(split_parameters(&args).is_ok(), valid);
+        }
+
+        // The URI style that other tools write, as split --style prints it
+        assert!(lines[4].starts_with("tuna-acid-"));
+        assert_eq!(ShareFormat::detect(&lines[4]), ShareFormat::Hyphenated);
+        assert_eq!(recover::decode_share(&lines[4].to_uppercase())?, shares[4]);
+        let styled = split::to_styled_bytewords(std::slice::from_ref(&shares), BytewordStyle::Uri);
+        assert_eq!(styled[0][4], lines[4]);
+        assert!(Cli::try_parse_from(["sskr-tool", "split", "2of3", "--style", "uri"]).is_ok());
+        assert!(
+            Cli::try_parse_from(["sskr-tool", "split", "2of3", "--style", "uri", "-m"]).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_word_separators() -> Result<(), Error> {
+        let secret: Vec<u8> = (0..16).collect();
+        let shares = split::split_raw("2of3", 1, &secret)?;
+        let split_args = |extra: &[&str]| -> Result<SplitArgs, Error> {
+            let arguments = [&["sskr-tool", "split", "2of3"], extra].concat();
+            let Commands::Split(args) = Cli::try_parse_from(arguments)?.command else {
+                unreachable!()
+            };
+            split_parameters(&args)?;
+            Ok(*args)
+        };
+        let words = encode_shares(&shares, &split_args(&[])?)[0][0].clone();
+        let hyphens = encode_shares(&shares, &split_args(&["--separator", "hyphen"])?);
+        assert_eq!(hyphens[0][0], words.replace(' ', "-"));
+        let newlines = encode_shares(&shares, &split_args(&["--separator", "newline"])?);
+        assert_eq!(newlines[0][0], words.replace(' ', "\n"));
+        for extra in [
+            &["--minimal"][..],
+            &["--style", "uri"],
+            &["--share-format", "hex"],
+        ] {
+            assert!(split_args(&[extra, &["--separator", "newline"]].concat()).is_err());
+        }
+
+        // Words on lines of their own stay in the share's column
+        let mut out = vec![];
+        let details = ShareDetails::default();
+        write_split(&mut out, 1, None, &newlines, &[None], details, Locale::En)?;
+        let text = String::from_utf8(out)?;
+        assert!(text.contains("  1: tuna\n     acid\n"));
+
+        // Any separator reads back, mixed in one share, or one word per line
+        let mixed = words.replacen(' ', "-", 3).replacen(' ', "\t", 2);
+        assert_eq!(recover::decode_share(&mixed)?, shares[0][0]);
+        let lines = newlines[0][1].lines().collect::<Vec<_>>();
+        let joined = share_format::join_wrapped(&lines);
+        assert_eq!(joined.len(), 1);
+        assert_eq!(recover::decode_share(&joined[0].1)?, shares[0][1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wallet_preview() -> Result<(), Error> {
+        // The test vectors of BIP-84, 44 and 86
+        let mnemonic = Mnemonic::from_entropy(&[0; 16], Language::English)?;
+        let preview = wallet::preview(&mnemonic, "", Some("m/84'/0'/0'"))?;
+        assert_eq!(preview.fingerprint, "73c5da0a");
+        assert_eq!(preview.xpub.as_deref(), Some("xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"));
+        assert_eq!(preview.addresses.len(), wallet::PREVIEW_ADDRESSES as usize);
+        assert_eq!(
+            preview.addresses[0],
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        let legacy = wallet::preview(&mnemonic, "", Some("m/44'/0'/0'"))?;
+        assert_eq!(legacy.addresses[0], "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+        let taproot = wallet::preview(&mnemonic, "", Some("m/86'/0'/0'"))?;
+        assert_eq!(
+            taproot.addresses[0],
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+
+        // The passphrase makes it another wallet, other paths have no addresses
+        let other = wallet::preview(&mnemonic, "TREZOR", None)?;
+        assert_ne!(other.fingerprint, preview.fingerprint);
+        assert_eq!((other.xpub, other.addresses.len()), (None, 0));
+        let custom = wallet::preview(&mnemonic, "", Some("m/0'/1"))?;
+        assert!(custom.xpub.is_some() && custom.addresses.is_empty());
+        assert!(wallet::preview(&mnemonic, "", Some("m/x")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_media_reports_rotted_files() -> Result<(), Error> {
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?;
+        let dir = std::env::temp_dir().join(format!("sskr-tool-media-{}", process::id()));
+        let files = artifacts::write_share_files(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse,
+            HashAlgorithm::Sha256,
+            false,
+        )?;
+
+        let checks = media::verify_media(&dir, None)?;
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0].path, "share-g1-m1-of-3.txt");
+        assert!(checks
+            .iter()
+            .all(|check| check.outcome == media::MediaOutcome::Intact));
+
+        // A flipped letter still reads, but no longer matches
+        let rotted = std::fs::read_to_string(&files.shares[0])?.replacen('a', "e", 1);
+        std::fs::write(&files.shares[0], rotted)?;
+        std::fs::remove_file(&files.shares[1])?;
+        let outcomes = media::verify_media(&dir, None)?
+            .iter()
+            .map(|check| check.outcome)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            [
+                media::MediaOutcome::Corrupted,
+                media::MediaOutcome::Missing,
+                media::MediaOutcome::Intact
+            ]
+        );
+
+        // Manifests written without the shares have nothing to check
+        let record = dir.join("record.json");
+        Manifest::new(seed).save(&record)?;
+        assert!(media::verify_media(&dir, Some(&record)).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint_cards() -> Result<(), Error> {
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "family=2of3,lawyers=1of1",
+            2,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = SeedManifest::from_shares(&groups, &false, Language::Japanese)?
+            .with_details(&Some("Cold storage".to_string()), &None, &None)?
+            .with_group_labels(&split::spec_labels("family=2of3,lawyers=1of1")?)
+            .with_custodians(&[vec!["Alice".to_string(), "Bob".to_string()]]);
+        let cards = hint::hint_cards(&seed);
+        assert_eq!(cards.iter().map(Vec::len).collect::<Vec<_>>(), [3, 1]);
+        assert_eq!(
+            (
+                cards[0][1].custodian.as_deref(),
+                cards[0][2].custodian.as_deref()
+            ),
+            (Some("Bob"), None)
+        );
+        let card = cards[0][1].render();
+        assert!(card.contains("\"Cold storage\""));
+        assert!(card.contains("Share:      2 of 3 in family (group 1)"));
+        assert!(card.contains("this share and 1 other share of the same group"));
+        assert!(card.contains("Shares of 2 of the 2 groups are needed in all"));
+        assert!(card.contains("sskr-tool recover shares.txt --language japanese"));
+        assert!(cards[1][0]
+            .render()
+            .contains("recovers its group on its own"));
+        // Nothing of a share is on a card
+        for (card, share) in cards.iter().flatten().zip(groups.iter().flatten()) {
+            assert!(!card.render().contains(&share[..20]));
+        }
+
+        let details = ShareDetails::default().with_hint_cards(Some(&seed));
+        let mut out = vec![];
+        write_split(&mut out, 2, None, &groups, &[], details, Locale::En)?;
+        let text = String::from_utf8(out)?;
+        assert_eq!(text.matches("SSKR RECOVERY HINT CARD").count(), 4);
+
+        // Written under hints/, named as the shares are, and checked with them
+        let dir = std::env::temp_dir().join(format!("sskr-tool-hints-{}", process::id()));
+        let files = artifacts::write_share_files(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse,
+            HashAlgorithm::Sha256,
+            true,
+        )?;
+        assert_eq!(files.hint_cards.len(), 4);
+        assert_eq!(
+            files.hint_cards[0],
+            dir.join("hints").join("share-g1-m1-of-3.txt")
+        );
+        assert_eq!(
+            std::fs::read_to_string(&files.hint_cards[3])?,
+            cards[1][0].render()
+        );
+        assert_eq!(media::verify_media(&dir, None)?.len(), 8);
+        std::fs::remove_dir_all(&dir)?;
+
+        assert!(Cli::try_parse_from([
+            "sskr-tool",
+            "split",
+            "2of3",
+            "--hint-cards",
+            "--paper",
+            "a.pdf"
+        ])
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_duplicate_and_conflicting_shares() -> Result<(), Error> {
+        let (mnemonic, groups) = split::split_random_phrase(
+            "2of3,2of3",
+            2,
+            gen_random_words(),
+            &false,
+            Language::English,
+        )?;
+
+        // Duplicates and blank lines are skipped
+        let lines = vec![
+            groups[0][0].clone(),
+            String::new(),
+            groups[0][0].clone(),
+            "  ".to_string(),
+            groups[0][1].clone(),
+            groups[1][2].clone(),
+            groups[1][0].clone(),
+            groups[1][2].clone(),
+        ];
+        ensure_recoverable(&mnemonic, lines)?;
+        assert!(recover::recover(vec![String::new()], &false, Language::English).is_err());
+
+        // A different share in the same place is named
+        let mut forged = recover::decode_share(&groups[1][2])?;
+        forged[6] ^= 0x01;
+        let lines = vec![
+            groups[0][0].clone(),
+            groups[0][1].clone(),
+            groups[1][2].clone(),
+            share_format::encode(&forged, ShareFormat::Bytewords),
+            groups[1][0].clone(),
+        ];
+        let error = recover::recover(lines, &false, Language::English).unwrap_err();
+        assert!(error.to_string().contains("group 2, member 3"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_archival_bundle() -> Result<(), Error> {
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?;
+        let dir = std::env::temp_dir().join(format!("sskr-tool-bundle-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let release = dir.join("sskr-tool-0.1.0.tar.gz");
+        std::fs::write(&release, "release")?;
+
+        let path = dir.join("bundle.json");
+        let bundle = bundle::Bundle::new(
+            Some(Manifest::new(seed.clone())),
+            groups[0].clone(),
+            &release,
+            HashAlgorithm::Sha256,
+        )?;
+        bundle.save(&path)?;
+        assert!(bundle.save(&path).is_err());
+        let loaded = bundle::Bundle::load(&path)?;
+        assert_eq!(loaded, bundle);
+        assert_eq!(loaded.spec, bundle::SSKR_SPEC);
+        assert_eq!(loaded.software.file, "sskr-tool-0.1.0.tar.gz");
+
+        let verification = loaded.verify(Some(&release))?;
+        assert_eq!(verification.identifiers, vec![seed.identifier.clone()]);
+        assert_eq!(verification.shares, 3);
+        assert_eq!(verification.release_matches, Some(true));
+        let other = dir.join("other.tar.gz");
+        std::fs::write(&other, "other release")?;
+        assert_eq!(loaded.verify(Some(&other))?.release_matches, Some(false));
+
+        // Any edit breaks the seal
+        let mut edited = loaded.clone();
+        edited.shares.pop();
+        assert!(edited.verify(None).is_err());
+        edited.software.version = "9.9.9".to_string();
+        assert!(edited.verify(None).is_err());
+
+        // Shares from a split the manifest doesn't describe are refused
+        let (_mnemonic, others) = split::split_random_phrase(
+            "1of1",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let mixed = [groups[0].clone(), others[0].clone()].concat();
+        assert!(bundle::Bundle::new(
+            Some(Manifest::new(seed)),
+            mixed,
+            &release,
+            HashAlgorithm::Sha256
+        )
+        .is_err());
+        assert!(bundle::Bundle::new(None, vec![], &release, HashAlgorithm::Sha256).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_chosen_identifier() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, mut shares) =
+            split::split_shares("2of3,3of5", 2, mnemonic.phrase(), Some(Language::English))?;
+        split::set_identifier(&mut shares, split::parse_identifier("0x3F1a")?);
+        for share in shares.iter().flatten() {
+            assert_eq!(ShareMetadataOutput::new(share)?.identifier, "3f1a");
+        }
+        let groups = split::to_bytewords(&shares, &false);
+        ensure_recoverable(&mnemonic, [&groups[0][..2], &groups[1][1..4]].concat())?;
+
+        assert_eq!(
+            split::seeded_identifier("replacement 2024"),
+            split::seeded_identifier("replacement 2024")
+        );
+        assert_ne!(
+            split::seeded_identifier("replacement 2024"),
+            split::seeded_identifier("replacement 2025")
+        );
+        // "abc" hashes to ba7816bf...
+        assert_eq!(split::seeded_identifier("abc"), 0xba78);
+        for invalid in ["3f1", "3f1a5", "3g1a", ""] {
+            assert!(split::parse_identifier(invalid).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_lenient_reserved_bits() -> Result<(), Error> {
+        let (mnemonic, groups) =
+            split::split_random_phrase("2of3", 1, gen_random_words(), &false, Language::English)?;
+        let future = groups[0]
+            .iter()
+            .take(2)
+            .map(|line| {
+                let mut share = recover::decode_share(line)?;
+                share[4] |= 0x20;
+                Ok(share_format::encode(&share, ShareFormat::Ur))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        ensure_unrecoverable(future.clone());
+
+        let (relaxed, warnings) = recover::lenient_share(&future[0])?;
+        assert!(relaxed.starts_with("ur:sskr/"));
+        assert!(warnings[0].contains("reserved bits"));
+        let relaxed = future
+            .iter()
+            .map(|line| Ok(recover::lenient_share(line)?.0))
+            .collect::<Result<Vec<_>, Error>>()?;
+        ensure_recoverable(&mnemonic, relaxed)?;
+
+        // Intact shares pass through untouched, oversized ones are still refused
+        assert_eq!(
+            recover::lenient_share(&groups[0][0])?,
+            (groups[0][0].clone(), vec![])
+        );
+        let mut oversized = recover::decode_share(&groups[0][0])?;
+        oversized.resize(5 + 34, 0);
+        let oversized = share_format::encode(&oversized, ShareFormat::Bytewords);
+        assert!(recover::lenient_share(&oversized).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_checksum() -> Result<(), Error> {
+        let (_mnemonic, shares) = split::split_shares(
+            "2of3",
+            1,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        for share in &shares[0] {
+            let checksum = share_format::share_checksum(share);
+            let words = share_format::encode(share, ShareFormat::Bytewords);
+            assert!(words.ends_with(&checksum.bytewords));
+            assert_eq!(checksum.bytewords.split(' ').count(), 4);
+
+            // Anyone can recompute it from the payload with a plain CRC-32
+            let payload = hex::decode(&checksum.payload)?;
+            assert!(payload.starts_with(&[0xd9, 0x01, 0x35]));
+            let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(&payload);
+            assert_eq!(checksum.crc32, format!("{:08x}", crc));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_envelope_shares() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let envelopes = split::to_envelopes(&shares, Some("Cold wallet"), Some(1700000000));
+        for (share, line) in shares[0].iter().zip(&envelopes[0]) {
+            assert_eq!(ShareFormat::detect(line), ShareFormat::Envelope);
+            let envelope = sskr_tool::envelope::ShareEnvelope::decode(line)?;
+            assert_eq!(&envelope.share, share);
+            assert_eq!(envelope.label.as_deref(), Some("Cold wallet"));
+            assert_eq!(envelope.date, Some(1700000000));
+        }
+
+        // The metadata doesn't get in the way of recovering, on its own or
+        // among shares in other formats
+        let lines = vec![
+            envelopes[0][0].clone(),
+            share_format::encode(&shares[0][2], ShareFormat::Bytewords),
+        ];
+        let secret = recover::recover_secret(lines, &false)?;
+        assert_eq!(secret.as_slice(), mnemonic.entropy());
+        let bare = share_format::encode(&shares[0][1], ShareFormat::Envelope);
+        assert_eq!(recover::decode_share(&bare)?, shares[0][1]);
+
+        // Envelopes whose subject is an encrypted seed are refused
+        let encrypted = "ur:envelope/lftansfwfpaeoyamtpsotantkpgotobtaeadaebwcthplbsnjelsjlluosktuyfxsrfgeswdroiowp";
+        let error = recover::decode_share(encrypted).unwrap_err();
+        assert!(error.to_string().contains("encrypted"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_order() -> Result<(), Error> {
+        let (_mnemonic, mut shares) = split::split_shares(
+            "2of3,3of5",
+            2,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        split::set_identifier(&mut shares, 0x1234);
+
+        let positions = |order| -> Result<Vec<(usize, usize)>, Error> {
+            split::ordered(&shares, order)
+                .into_iter()
+                .map(|share| {
+                    let (identifier, group, member) = split::share_position(share)?;
+                    assert_eq!(identifier, 0x1234);
+                    Ok((group, member))
+                })
+                .collect()
+        };
+        assert_eq!(
+            positions(split::ShareOrder::Group)?,
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (1, 4)
+            ]
+        );
+        assert_eq!(
+            positions(split::ShareOrder::Member)?,
+            vec![
+                (0, 0),
+                (1, 0),
+                (0, 1),
+                (1, 1),
+                (0, 2),
+                (1, 2),
+                (1, 3),
+                (1, 4)
+            ]
+        );
+
+        // Shares read back in any order are grouped as the split emitted them
+        let mut flat = split::ordered(&shares, split::ShareOrder::Member)
+            .into_iter()
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        flat.reverse();
+        assert_eq!(split::regroup(&flat)?, shares);
+
+        let (_mnemonic, other) = split::split_shares(
+            "2of3",
+            1,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        flat.push(other[0][0].clone());
+        assert!(split::regroup(&flat).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_selftest_vectors() {
+        let vectors = selftest::run();
+        assert!(vectors.len() >= 4);
+        for vector in vectors {
+            assert!(vector.passed, "{}: {:?}", vector.name, vector.error);
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_corpus() -> Result<(), Error> {
+        let corpus = testdata::corpus()?;
+        for case in corpus.run() {
+            assert!(case.passed, "{}: found {:?}", case.name, case.found);
+        }
+        // Exported, it reads back as the same corpus
+        assert_eq!(testdata::Corpus::parse(&corpus.to_toml()?)?, corpus);
+
+        // Fields left out match anything, and a wrong expectation fails
+        let mut case = corpus.cases[4].clone();
+        case.expect[0] = serde_json::json!({ "problem": "checksum_mismatch" });
+        let mut wrong = case.clone();
+        wrong.expect[0] = serde_json::json!({ "problem": "not_bytewords" });
+        let mut short = case.clone();
+        short.expect.pop();
+        let outcomes = testdata::Corpus {
+            cases: vec![case, wrong, short],
+        }
+        .run();
+        assert_eq!(
+            outcomes.iter().map(|case| case.passed).collect::<Vec<_>>(),
+            [true, false, false]
+        );
+        assert!(
+            testdata::Corpus::parse("[[case]]\nname = \"x\"\nshares = []\nexpected = []").is_err()
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "smartcard")]
+    #[test]
+    fn test_smartcard_shares() -> Result<(), Error> {
+        use sskr_tool::smartcard::{CardTransport, OpenPgpCard, PrivateSlot};
+        use std::collections::HashMap;
+
+        // An OpenPGP card as far as private data slots go, with the default PINs
+        #[derive(Default)]
+        struct SimulatedCard {
+            user: bool,
+            admin: bool,
+            slots: HashMap<u8, Vec<u8>>,
+        }
+
+        impl CardTransport for SimulatedCard {
+            fn transmit(&mut self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+                let ok = |mut data: Vec<u8>| {
+                    data.extend([0x90, 0x00]);
+                    Ok(data)
+                };
+                let allowed = |slot: u8, user: bool, admin: bool| match slot {
+                    1 | 3 => user,
+                    _ => admin,
+                };
+                match apdu[1] {
+                    0xa4 => ok(vec![]),
+                    0x20 => match (apdu[3], &apdu[5..]) {
+                        (0x82, b"123456") => {
+                            self.user = true;
+                            ok(vec![])
+                        }
+                        (0x83, b"12345678") => {
+                            self.admin = true;
+                            ok(vec![])
+                        }
+                        _ => Ok(vec![0x63, 0xc2]),
+                    },
+                    0xda if allowed(apdu[3], self.user, self.admin) => {
+                        self.slots.insert(apdu[3], apdu[5..].to_vec());
+                        ok(vec![])
+                    }
+                    0xca if apdu[3] < 3 || allowed(apdu[3], self.user, self.admin) => {
+                        ok(self.slots.get(&apdu[3]).cloned().unwrap_or_default())
+                    }
+                    _ => Ok(vec![0x69, 0x82]),
+                }
+            }
+        }
+
+        let (mnemonic, shares) = split::split_shares(
+            "2of3",
+            1,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        let mut simulated = SimulatedCard::default();
+        let mut card = OpenPgpCard::open(&mut simulated)?;
+        let error = card
+            .write_share(PrivateSlot::Three, &shares[0][0], "000000")
+            .unwrap_err();
+        assert!(error.to_string().contains("2 tries left"));
+        card.write_share(PrivateSlot::Three, &shares[0][0], "123456")?;
+        card.write_share(PrivateSlot::Two, &shares[0][1], "12345678")?;
+
+        // As when the card is next inserted, with no PIN entered yet
+        simulated.user = false;
+        simulated.admin = false;
+        let mut card = OpenPgpCard::open(&mut simulated)?;
+        assert!(card.read_share(PrivateSlot::Three, None).is_err());
+        let lines = vec![
+            share_format::encode(&card.read_share(PrivateSlot::Two, None)?, ShareFormat::Ur),
+            share_format::encode(
+                &card.read_share(PrivateSlot::Three, Some("123456"))?,
+                ShareFormat::Ur,
+            ),
+        ];
+        ensure_recoverable(&mnemonic, lines)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_labels() -> Result<(), Error> {
+        let spec = "Family=2of3, lawyers = 3/5,1of1";
+        assert_eq!(
+            split::spec_labels(spec)?,
+            vec![
+                Some("Family".to_string()),
+                Some("lawyers".to_string()),
+                None
+            ]
+        );
+        assert_eq!(split::default_group_threshold("Family=3of5")?, 1);
+        assert!(split::spec_labels("a=2of3,a=3of5").is_err());
+        assert!(split::spec_labels("=2of3").is_err());
+
+        let (_mnemonic, shares) = split::split_shares(
+            spec,
+            2,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        let seed = SeedManifest::from_shares(
+            &split::to_bytewords(&shares, &false),
+            &false,
+            Language::English,
+        )?
+        .with_group_labels(&split::spec_labels(spec)?);
+        assert_eq!(seed.group_title(2), "lawyers (group 2)");
+        assert_eq!(seed.group_title(3), "Group 3");
+
+        let name = |group| {
+            naming::render_share_name(
+                "{label}-share-{member}",
+                &naming::ShareName {
+                    seed: &seed,
+                    group,
+                    member: 4,
+                    members: 5,
+                },
+            )
+        };
+        assert_eq!(name(2)?, "lawyers-share-4");
+        assert_eq!(name(3)?, "group3-share-4");
+
+        let document = SplitOutput::raw(spec, 2, &[0; 16], &shares)?;
+        assert_eq!(document.groups[0].label.as_deref(), Some("Family"));
+        assert_eq!(document.groups[2].label, None);
+        Ok(())
+    }
+
+    #[cfg(feature = "smartcard")]
+    #[test]
+    fn test_nfc_shares() -> Result<(), Error> {
+        use sskr_tool::nfc::NfcTag;
+        use sskr_tool::smartcard::CardTransport;
+
+        // An NTAG213 behind a PC/SC reader, as far as reading and writing
+        // pages goes
+        struct SimulatedTag {
+            pages: Vec<u8>,
+        }
+
+        impl CardTransport for SimulatedTag {
+            fn transmit(&mut self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+                let offset = apdu[3] as usize * 4;
+                let mut response = match apdu[1] {
+                    0xd6 => {
+                        self.pages[offset..offset + 4].copy_from_slice(&apdu[5..9]);
+                        vec![]
+                    }
+                    _ => self.pages[offset..offset + 16].to_vec(),
+                };
+                response.extend([0x90, 0x00]);
+                Ok(response)
+            }
+        }
+
+        let mnemonic = split::random_mnemonic(MnemonicType::Words24, Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let mut lines = vec![];
+        for share in &shares[0][..2] {
+            let mut tag = SimulatedTag {
+                pages: vec![0; 45 * 4],
+            };
+            NfcTag::new(&mut tag).write_share(share)?;
+
+            // The record holds the share as a ur:sskr, as phones show it
+            let ur = share_format::encode(share, ShareFormat::Ur);
+            assert!(tag
+                .pages
+                .windows(ur.len())
+                .any(|bytes| bytes == ur.as_bytes()));
+            lines.push(share_format::encode(
+                &NfcTag::new(&mut tag).read_share()?,
+                ShareFormat::Bytewords,
+            ));
+        }
+        ensure_recoverable(&mnemonic, lines)?;
+
+        let mut blank = SimulatedTag {
+            pages: vec![0; 45 * 4],
+        };
+        assert!(NfcTag::new(&mut blank).read_share().is_err());
+
+        // Reader names as PC/SC lists them, and the options that use them
+        assert_eq!(
+            sskr_tool::pcsc::reader_names(b"ACS ACR122U 00 00\0Yubico YubiKey 01 00\0\0"),
+            ["ACS ACR122U 00 00", "Yubico YubiKey 01 00"]
+        );
+        assert!(sskr_tool::pcsc::reader_names(b"\0").is_empty());
+        let parse = |arguments: &[&str]| Cli::try_parse_from([&["sskr-tool"], arguments].concat());
+        assert!(parse(&["recover", "--nfc", "--reader", "ACR122U"]).is_ok());
+        assert!(parse(&["recover", "--nfc", "shares.txt"]).is_err());
+        assert!(parse(&["split", "2of3", "--nfc"]).is_ok());
+        assert!(parse(&["split", "2of3", "--reader", "ACR122U"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagnose_every_line() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3,2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let (_mnemonic, other) = split::split_shares(
+            "2of3",
+            1,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        let lines = split::to_bytewords(&shares, &false);
+
+        // A share with the place of the first one but another value
+        let mut conflicting = shares[0][0].clone();
+        *conflicting.last_mut().unwrap() ^= 1;
+
+        let mut typo = lines[0][1].clone();
+        typo.replace_range(0..4, "tune");
+        let given = vec![
+            lines[0][0].clone(),
+            typo,
+            lines[1][0].clone(),
+            share_format::encode(&other[0][0], ShareFormat::Bytewords),
+            lines[0][0].clone(),
+            share_format::encode(&conflicting, ShareFormat::Minimal),
+            lines[1][1].clone(),
+        ];
+        let problems = recover::diagnose_lines(&given);
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (problem.index, problem.conflicts_with))
+                .collect::<Vec<_>>(),
+            vec![(1, None), (3, None), (5, Some(0))]
+        );
+        assert!(problems[0].message.contains("tune"));
+        assert!(problems[1].message.contains("Identifier"));
+
+        // What is left still recovers, the repeated share counting once
+        let usable = given
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| problems.iter().all(|problem| problem.index != *index))
+            .map(|(_, line)| line)
+            .collect();
+        ensure_recoverable(&mnemonic, usable)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_segments() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let first = share_format::segments(&shares[0][0], 3)?;
+        let second = share_format::segments(&shares[0][1], 4)?;
+        assert!(first[1].starts_with("ur:sskr/2-3/"));
+        assert!(share_format::segments(&shares[0][0], 1).is_err());
+
+        // Segments are joined in any order and among other lines, each share
+        // taking the place of its first segment
+        let lines = [
+            second[3].as_str(),
+            first[2].as_str(),
+            "tuna acid",
+            second[0].as_str(),
+            first[0].as_str(),
+            second[2].as_str(),
+            first[1].as_str(),
+            second[1].as_str(),
+        ];
+        let joined = share_format::join_segments(&lines);
+        assert_eq!(
+            joined
+                .iter()
+                .map(|(index, line)| (*index, recover::decode_share(line).ok()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, Some(shares[0][1].clone())),
+                (1, Some(shares[0][0].clone())),
+                (2, None)
+            ]
+        );
+
+        // A share with a segment missing is left for recover to report
+        let joined = share_format::join_segments(&[first[0].as_str(), first[2].as_str()]);
+        assert_eq!(joined.len(), 2);
+        let error = recover::decode_share(&joined[0].1).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Segment 1 of a share split into 3"));
+
+        let dir = std::env::temp_dir().join(format!("sskr-tool-segments-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let file = dir.join("shares.txt");
+        std::fs::write(&file, [first.join("\n"), second.join("\n")].join("\n"))?;
+        let lines = input::read_share_lines(&[file.display().to_string()])?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(lines.len(), 2);
+        ensure_recoverable(&mnemonic, lines)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_review_cards() -> Result<(), Error> {
+        let (_mnemonic, shares) = split::split_shares(
+            "2of3,2of3",
+            2,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        let lines = split::to_bytewords(&shares, &false);
+        let located = [&lines[0][2], &lines[1][0]]
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| input::ShareLine {
+                source: "shares.txt".to_string(),
+                line: index + 1,
+                text: text.clone(),
+            })
+            .collect::<Vec<_>>();
+        let cards = review::cards(&located, Some(ShareFormat::Minimal))?;
+        assert!(cards[0].title.ends_with("group 1 of 2, share 3 (need 2)"));
+        assert!(cards[1].title.contains("group 2 of 2, share 1"));
+        assert_eq!(recover::decode_share(&cards[1].text)?, shares[1][0]);
+        assert!(!cards[1].text.contains(' '));
+
+        // Whole words stay on one row, and every row fits the screen
+        let big = review::big_text(&lines[0][0], 80);
+        assert_eq!(big.len() % 6, 0);
+        assert!(big.iter().all(|line| line.chars().count() <= 80));
+        let words_per_row = 80 / 6 / 5;
+        assert_eq!(
+            big.len() / 6,
+            lines[0][0].split(' ').count().div_ceil(words_per_row)
+        );
+        assert!(big[0].starts_with("█████"));
+
+        let mut bad = located.clone();
+        bad[1].text.push_str(" able");
+        let error = review::cards(&bad, None).err().unwrap();
+        assert!(error.to_string().starts_with("shares.txt:2: "));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drill() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let line = share_format::encode(&shares[0][1], ShareFormat::Bytewords);
+        let words = line.split(' ').collect::<Vec<_>>();
+        let checks = drill::ShareChecks::new(&shares, HashAlgorithm::Sha256);
+        let other = drill::ShareChecks::new(&[vec![shares[0][0].clone()]], HashAlgorithm::Sha256);
+        // The CBOR header and the metadata name the share
+        let naming = words.len() - 4 - shares[0][1].len() + 5;
+
+        // No place is checked on both shares it takes to recover, and a few
+        // words of each share are
+        let mut places = checks
+            .words
+            .iter()
+            .flat_map(|words| words.checks.iter().map(|check| check.word))
+            .collect::<Vec<_>>();
+        assert_eq!(places.len(), 12);
+        places.sort_unstable();
+        places.dedup();
+        assert_eq!(places.len(), 12);
+        assert!(places
+            .iter()
+            .all(|word| (naming + 1..=words.len() - 4).contains(word)));
+        let (_mnemonic, single) =
+            split::split_shares("1of1", 1, mnemonic.phrase(), Some(Language::English))?;
+        assert!(drill::ShareChecks::new(&single, HashAlgorithm::Sha256)
+            .words
+            .is_empty());
+
+        // Only the words naming the share and the words checked are asked,
+        // the checked ones last to first here, with a typo on the way
+        let checked = checks
+            .words
+            .iter()
+            .find(|words| words.group == 1 && words.member == 2)
+            .unwrap()
+            .checks
+            .iter()
+            .map(|check| check.word)
+            .collect::<Vec<_>>();
+        let quiz = |answer: &dyn Fn(usize) -> String| {
+            let mut answers = vec![words.len().to_string()];
+            answers.extend(words[..naming].iter().map(|word| word.to_string()));
+            answers.extend(checked.iter().rev().map(|word| answer(*word)));
+            answers
+        };
+        let mut answers = quiz(&|word| words[word - 1].to_uppercase());
+        answers.insert(naming + 2, "tuan".to_string());
+        let answers = answers.join("\n") + "\n";
+        let mut prompt = vec![];
+        let result = drill::run(
+            &[&other, &checks],
+            |order| order.reverse(),
+            &mut answers.as_bytes(),
+            &mut prompt,
+        )?;
+        assert_eq!(result.metadata.member_index, 1);
+        assert_eq!(result.metadata.secret_length, shares[0][1].len() - 5);
+        assert_eq!(result.matches_manifest, Some(true));
+        let prompt = String::from_utf8(prompt)?;
+        assert_eq!(prompt.matches("Word ").count(), naming + checked.len() + 1);
+        assert!(prompt.contains(&format!("Word {}: ", checked[3])));
+        assert!(prompt.contains("\"tuan\" is not a byteword (closest: "));
+
+        let run = |checks: &[&drill::ShareChecks], answers: &[String]| {
+            drill::run(
+                checks,
+                |_| {},
+                &mut (answers.join("\n") + "\n").as_bytes(),
+                &mut vec![],
+            )
+        };
+        // A word copied wrong is the one reported
+        let wrong = |word: &str| if word == "able" { "acid" } else { "able" }.to_string();
+        let answers = quiz(&|word| {
+            if word == checked[1] {
+                wrong(words[word - 1])
+            } else {
+                words[word - 1].to_string()
+            }
+        });
+        let result = drill::run(
+            &[&checks],
+            |order| order.reverse(),
+            &mut (answers.join("\n") + "\n").as_bytes(),
+            &mut vec![],
+        )?;
+        assert_eq!(result.matches_manifest, Some(false));
+        assert_eq!(result.mismatched_words, vec![checked[1]]);
+
+        // Without checks of its words, the whole share is read
+        let mut answers = vec![words.len().to_string()];
+        answers.extend(words.iter().map(|word| word.to_string()));
+        assert_eq!(run(&[&other], &answers)?.matches_manifest, Some(false));
+        assert_eq!(run(&[], &answers)?.matches_manifest, None);
+        let old = drill::ShareChecks {
+            words: vec![],
+            ..checks.clone()
+        };
+        assert_eq!(run(&[&old], &answers)?.matches_manifest, Some(true));
+
+        // A word copied wrong fails the checksum
+        answers[naming + 2] = wrong(&answers[naming + 2]);
+        assert!(run(&[&old], &answers).is_err());
+        assert!(run(&[&checks], &["12".to_string()]).is_err());
+
+        // The manifest holds digests, never the shares
+        let json = serde_json::to_string(&checks)?;
+        assert!(!json.contains(&hex::encode(&shares[0][1])));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sealed_file() -> Result<(), Error> {
+        let contents = (0..3000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        let (key, sealed_contents) = sealed::seal(&contents);
+        let shares = split::split_raw("2of3", 1, key.as_slice())?;
+        let lines = split::to_bytewords(&shares, &false)[0][1..].to_vec();
+        let recovered = recover::recover_secret(lines, &false)?;
+        assert_eq!(*sealed::open(&sealed_contents, &recovered)?, contents);
+
+        // The wrong key, a changed file or another secret don't open it
+        let mut changed = sealed_contents.clone();
+        *changed.last_mut().unwrap() ^= 1;
+        assert!(sealed::open(&changed, &recovered).is_err());
+        assert!(sealed::open(&sealed_contents, &[0; 32]).is_err());
+        assert!(sealed::open(&sealed_contents, &recovered[..16]).is_err());
+        assert!(sealed::open(&contents, &recovered).is_err());
+
+        let dir = std::env::temp_dir().join(format!("sskr-tool-sealed-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = sealed::default_path(&dir.join("keystore.json"));
+        assert_eq!(path, dir.join("keystore.json.sealed"));
+        sealed::write_new(&path, &sealed_contents)?;
+        assert!(sealed::write_new(&path, b"").is_err());
+        assert_eq!(std::fs::read(&path)?, sealed_contents);
+        std::fs::remove_dir_all(&dir)?;
+
+        let parse = |arguments: &[&str]| Cli::try_parse_from([&["sskr-tool"], arguments].concat());
+        assert!(parse(&["split", "2of3", "--input-file", "keystore.json"]).is_ok());
+        assert!(parse(&["split", "2of3", "--sealed-file", "out.sealed"]).is_err());
+        assert!(parse(&["recover", "shares.txt", "--output-file", "keystore.json"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!("sskr-tool-batch-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("family.toml");
+        let load = |contents: &str| -> Result<Vec<batch::BatchJob>, Error> {
+            std::fs::write(&path, contents)?;
+            batch::Batch::load(&path)?.jobs(&dir)
+        };
+
+        let jobs = load(
+            r#"
+            [[seed]]
+            name = "Alice"
+            spec = "family=2of3,lawyers=2of2"
+            group_threshold = 1
+            mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+            out_dir = "alice"
+
+            [[seed]]
+            name = "Kids"
+            spec = "2of3"
+            words = 24
+            count = 2
+            out_dir = "kids"
+            "#,
+        )?;
+        assert_eq!(
+            jobs.iter()
+                .map(|job| (job.name.clone().unwrap(), job.out_dir.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Alice".to_string(), dir.join("alice")),
+                ("Kids 1".to_string(), dir.join("kids-1")),
+                ("Kids 2".to_string(), dir.join("kids-2")),
+            ]
+        );
+        assert_eq!(jobs[1].group_threshold, 1);
+
+        let args =
+            match Cli::try_parse_from(["sskr-tool", "split", "--batch", "family.toml"])?.command {
+                Commands::Split(args) => args,
+                _ => unreachable!(),
+            };
+        let (mnemonic, shares, seed) = split_batch_job(&jobs[0], &args)?;
+        assert_eq!(mnemonic.entropy(), [0; 16]);
+        assert_eq!(seed.group_title(2), "lawyers (group 2)");
+        assert_eq!(seed.name.as_deref(), Some("Alice"));
+        assert!(seed.share_checks.unwrap().matches(&shares[1][0])?);
+        let (kid, _, _) = split_batch_job(&jobs[1], &args)?;
+        assert_eq!(kid.phrase().split(' ').count(), 24);
+        assert_ne!(kid.phrase(), split_batch_job(&jobs[2], &args)?.0.phrase());
+
+        // Mistakes anywhere in the batch stop it before anything is split
+        for (contents, error) in [
+            ("", "missing field `seed`"),
+            ("[[seed]]\nspec = \"2of3\"\nout_dir = \"a\"\nlabel = \"x\"", "unknown field"),
+            ("[[seed]]\nspec = \"2of3\"\nout_dir = \"a\"\ncount = 2\nmnemonic = \"x\"", "count"),
+            ("[[seed]]\nspec = \"2of3\"\nout_dir = \"a\"\nwords = 13", "12, 15, 18, 21 or 24"),
+            ("[[seed]]\nspec = \"2of3,2of3\"\nout_dir = \"a\"", "seed 1"),
+            ("[[seed]]\nspec = \"2of3\"\nout_dir = \"a\"\n[[seed]]\nspec = \"2of3\"\nout_dir = \"a\"", "Two seeds"),
+        ] {
+            let message = format!("{:#}", load(contents).err().unwrap());
+            assert!(message.contains(error), "{}", message);
+        }
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_blind_check() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let (_mnemonic, strangers) =
+            split::split_shares("2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let share = share_format::encode(&shares[0][2], ShareFormat::Bytewords);
+        let mut words = share.split(' ').collect::<Vec<_>>();
+        words[8] = if words[8] == "able" { "acid" } else { "able" };
+        let response_time = std::time::Duration::from_millis(100);
+
+        // Every answer takes the response time, whatever went wrong
+        let mut blind = BlindCheck::new(&[], 4).with_response_time(response_time);
+        for (input, valid) in [
+            (share.to_uppercase(), true),
+            (words.join(" "), false),
+            ("not a share".to_string(), false),
+        ] {
+            let start = std::time::Instant::now();
+            assert_eq!(blind.check(&input)?, valid, "{}", input);
+            assert!(start.elapsed() >= response_time);
+        }
+        assert_eq!(blind.attempts_left(), 1);
+        blind.check("")?;
+        assert!(blind.check(&share).is_err());
+
+        // With a manifest's checks only the split's own shares are valid
+        let checks = drill::ShareChecks::new(&shares, HashAlgorithm::Sha256);
+        let checks = [&checks];
+        let mut blind = BlindCheck::new(&checks, 2).with_response_time(response_time);
+        assert!(blind.check(&share)?);
+        assert!(!blind.check(&share_format::encode(&strangers[0][2], ShareFormat::Ur))?);
+
+        assert!(Cli::try_parse_from(["sskr-tool", "check", "--attempts", "0"]).is_err());
+        assert!(Cli::try_parse_from(["sskr-tool", "check", "--manifest", "m.json"]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_back() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of2,2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let printed = shares
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|share| share_format::encode(share, ShareFormat::Bytewords))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut mistyped = printed[0][1].split(' ').collect::<Vec<_>>();
+        mistyped[8] = if mistyped[8] == "able" {
+            "acid"
+        } else {
+            "able"
+        };
+
+        // Shares can be typed back in any format, with a second try at a typo
+        let answers = [
+            printed[0][0].to_uppercase(),
+            mistyped.join(" "),
+            printed[0][1].clone(),
+            share_format::encode(&shares[1][0], ShareFormat::Minimal),
+            share_format::encode(&shares[1][1], ShareFormat::Ur),
+            printed[1][2].clone(),
+        ]
+        .join("\n");
+        let mut prompt = vec![];
+        let confirmed = readback::run(
+            &shares,
+            &printed,
+            readback::ReadBack::Full,
+            |_, _| unreachable!(),
+            &mut answers.as_bytes(),
+            &mut prompt,
+        )?;
+        assert_eq!(confirmed, 5);
+        let prompt = String::from_utf8(prompt)?;
+        assert!(prompt.contains("Share 2 of group 1: That doesn't match the share at word 9"));
+        assert!(prompt.contains("Share 3 of group 2 matches"));
+
+        // Sampled words can be given in full or as minimal bytewords
+        let words = |group: usize, member: usize, positions: &[usize]| {
+            let words = printed[group][member].split(' ').collect::<Vec<_>>();
+            positions
+                .iter()
+                .map(|position| words[*position].to_string())
+                .collect::<Vec<_>>()
+        };
+        let mut answers = vec![];
+        for (group, member) in [(0, 0), (0, 1), (1, 0), (1, 1), (1, 2)] {
+            let sampled = words(group, member, &[2, 28]);
+            answers.push(sampled[0].clone());
+            answers.push(format!(
+                "{}{}",
+                &sampled[1][..1],
+                &sampled[1][sampled[1].len() - 1..]
+            ));
+        }
+        let mut prompt = vec![];
+        let confirmed = readback::run(
+            &shares,
+            &printed,
+            readback::ReadBack::Sample(2),
+            |words, count| {
+                assert_eq!((words, count), (printed[0][0].split(' ').count(), 2));
+                vec![28, 2]
+            },
+            &mut answers.join("\n").as_bytes(),
+            &mut prompt,
+        )?;
+        assert_eq!(confirmed, 5);
+        assert!(String::from_utf8(prompt)?.contains("Share 1 of group 2, word 29: "));
+
+        // A share that doesn't match after three tries fails the split
+        let wrong = vec![mistyped.join(" "); 3].join("\n");
+        let error = readback::run(
+            &shares[..1],
+            &printed[..1],
+            readback::ReadBack::Full,
+            |_, _| unreachable!(),
+            &mut format!("{}\n{}", printed[0][0], wrong).as_bytes(),
+            &mut vec![],
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Share 2 of group 1 doesn't match its copy, make the copy again"
+        );
+
+        assert!(
+            Cli::try_parse_from(["sskr-tool", "split", "2of3", "--confirm-words", "4"]).is_err()
+        );
+        assert!(Cli::try_parse_from([
+            "sskr-tool",
+            "split",
+            "2of3",
+            "--confirm",
+            "--segments",
+            "2"
+        ])
+        .is_err());
+        Ok(())
+    }
+
+    // Every random byte asked for is the next of a counter, so a split comes
+    // out the same on every run
+    struct CountingRandomNumberGenerator(u8);
+
+    impl bc_crypto::RandomNumberGenerator for CountingRandomNumberGenerator {
+        fn next_u64(&mut self) -> u64 {
+            let mut bytes = [0u8; 8];
+            self.fill_random_data(&mut bytes);
+            u64::from_le_bytes(bytes)
+        }
+
+        fn random_data(&mut self, size: usize) -> Vec<u8> {
+            let mut data = vec![0u8; size];
+            self.fill_random_data(&mut data);
+            data
+        }
+
+        fn fill_random_data(&mut self, data: &mut [u8]) {
+            for x in data.iter_mut() {
+                *x = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_text_lines_up_at_the_largest_layout() -> Result<(), Error> {
+        let spec = vec!["2of16"; 16].join(",");
+        let secret: Vec<u8> = (0..16).collect();
+        let groups = split::to_bytewords(&split::split_raw(&spec, 16, &secret)?, &false);
+        let mut labels = vec![None; 16];
+        labels[0] = Some("family".to_string());
+        labels[9] = Some("executors".to_string());
+        let mut custodians = (1..=16)
+            .map(|group| {
+                (1..=16)
+                    .map(|member| format!("Custodian {}.{}", group, member))
+                    .collect()
+            })
+            .collect::<Vec<Vec<String>>>();
+        custodians[3][4] = "Zoë".to_string();
+        let details = ShareDetails {
+            checksums: true,
+            custodians,
+            fingerprint: Some("73c5da0a".to_string()),
+            ..ShareDetails::default()
+        };
+        let mut out = vec![];
+        write_split(&mut out, 16, None, &groups, &labels, details, Locale::En)?;
+        let text = String::from_utf8(out)?;
+        assert!(text.contains("Fingerprint 73c5da0a"));
+
+        // Every group's description starts in the same column, as does every
+        // share and every checksum
+        let column = |line: &str, text: &str| line.find(text).map(|at| line[..at].chars().count());
+        let headers = text
+            .lines()
+            .filter(|line| line.contains("Group"))
+            .collect::<Vec<_>>();
+        assert_eq!(headers.len(), 16);
+        assert!(headers
+            .iter()
+            .all(|line| column(line, "Group") == Some("executors: ".len())));
+        assert!(headers.contains(&"family:    Group  1 - need 2 of 16 shares to recover group"));
+        assert!(headers.contains(&"           Group 16 - need 2 of 16 shares to recover group"));
+        let shares = text
+            .lines()
+            .filter(|line| {
+                groups
+                    .iter()
+                    .flatten()
+                    .any(|share| line.ends_with(share.as_str()))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(shares.len(), 256);
+        let share_column = "   1: Custodian 16.16  ".len();
+        assert!(groups.iter().flatten().all(|share| shares
+            .iter()
+            .any(|line| column(line, share) == Some(share_column))));
+        assert!(shares.contains(&&*format!("   5: Zoë{}{}", " ".repeat(14), &groups[3][4])));
+        assert!(shares.contains(&&*format!("  16: Custodian 16.16  {}", &groups[15][15])));
+        assert!(text
+            .lines()
+            .filter(|line| line.contains("Payload:"))
+            .all(|line| column(line, "Payload:") == Some(share_column)));
+        Ok(())
+    }
+
+    // The layouts scripts parse and printed artifacts are made from. A change
+    // here changes what custodians hold, so snapshots are only updated on
+    // purpose (`cargo insta review`).
+    #[test]
+    fn test_renderer_snapshots() -> Result<(), Error> {
+        let spec = "family=2of3,lawyers=2of2";
+        let mnemonic = Mnemonic::from_entropy(&[0; 16], Language::English)?;
+        let sskr_spec = sskr::Spec::new(
+            1,
+            vec![sskr::GroupSpec::new(2, 3)?, sskr::GroupSpec::new(2, 2)?],
+        )?;
+        let shares = sskr::sskr_generate_using(
+            &sskr_spec,
+            &sskr::Secret::new(mnemonic.entropy())?,
+            &mut CountingRandomNumberGenerator(0),
+        )?;
+        let groups = split::to_bytewords(&shares, &false);
+        let labels = split::spec_labels(spec)?;
+
+        let text = |groups: &[Vec<String>], details, locale| -> Result<String, Error> {
+            let mut out = vec![];
+            write_split(
+                &mut out,
+                1,
+                Some(&mnemonic),
+                groups,
+                &labels,
+                details,
+                locale,
+            )?;
+            Ok(String::from_utf8(out)?)
+        };
+        let details = ShareDetails {
+            checksums: true,
+            ..ShareDetails::default()
+        };
+        insta::assert_snapshot!("split_text", text(&groups, details, Locale::En)?);
+        let minimal = split::to_bytewords(&shares, &true);
+        let details = ShareDetails::default();
+        insta::assert_snapshot!("split_text_ar", text(&minimal, details, Locale::Ar)?);
+
+        let json = SplitOutput::new(spec, 1, &mnemonic, &shares)?
+            .with_envelopes(&split::to_envelopes(&shares, Some("Family"), Some(0)));
+        insta::assert_snapshot!("split_json", serde_json::to_string_pretty(&json)?);
+
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?
+            .with_details(
+                &Some("Family savings".to_string()),
+                &Some("Cold storage".to_string()),
+                &Some("73c5da0a".to_string()),
+            )?
+            .with_group_labels(&labels);
+        let manifest = Manifest::new(seed);
+        insta::assert_snapshot!("manifest", manifest.to_json()?);
+        insta::assert_snapshot!("runbook", estate::runbook(&manifest));
+
+        insta::assert_snapshot!("big_text", review::big_text("tuna acid", 60).join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tui_ceremonies() -> Result<(), Error> {
+        use crossterm::event::KeyCode;
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let type_in = |wizard: &mut tui::Wizard, text: &str| {
+            text.chars().for_each(|c| wizard.key(KeyCode::Char(c)));
+            wizard.key(KeyCode::Enter);
+        };
+        let screen = |wizard: &tui::Wizard| wizard.screen().join("\n");
+
+        // Split a typed in mnemonic into family=2of3 and lawyers=2of2, both
+        // groups needed
+        let mut wizard = tui::Wizard::new();
+        for key in [
+            KeyCode::Enter,
+            KeyCode::Enter,
+            KeyCode::Down,
+            KeyCode::Enter,
+        ] {
+            wizard.key(key);
+        }
+        type_in(&mut wizard, "not a mnemonic");
+        assert!(screen(&wizard).contains("That is not a valid mnemonic"));
+        type_in(&mut wizard, phrase);
+        assert!(!screen(&wizard).contains("abandon"));
+        for key in [KeyCode::Left, KeyCode::Enter] {
+            wizard.key(key);
+        }
+        assert!(screen(&wizard).contains("Group 1: any 1 of 3 shares"));
+        assert!(screen(&wizard).contains("1 of N groups (where N > 1) not supported"));
+        for c in ['a', '-', '>'] {
+            wizard.key(KeyCode::Right);
+            wizard.key(KeyCode::Char(c));
+        }
+        assert_eq!(wizard.spec(), "2of3,2of2");
+        assert!(screen(&wizard).contains("Recovery needs 2 of the 2 group(s)"));
+        wizard.key(KeyCode::Enter);
+
+        // Each share is only shown once its custodian asks for it
+        let mut shares = vec![];
+        for number in 1..=5 {
+            let handoff = screen(&wizard);
+            assert!(handoff.contains(" of 5"), "{}", handoff);
+            assert!(!handoff.contains("tuna"));
+            wizard.key(KeyCode::Enter);
+            let shown = wizard.screen();
+            assert!(shown[0].starts_with(&format!("[{}/5] ", number)));
+            let words = shown[2..shown.len() - 1]
+                .iter()
+                .flat_map(|row| row.split_whitespace())
+                .filter(|word| !word.ends_with('.'))
+                .collect::<Vec<_>>();
+            shares.push(words.join(" "));
+            wizard.key(KeyCode::Enter);
+        }
+        assert!(screen(&wizard).contains("All 5 shares have been handed out."));
+        wizard.key(KeyCode::Enter);
+        assert!(wizard.is_done());
+        assert_eq!(wizard.outcome(), checklist::Outcome::Split);
+
+        // Recovery shows progress but never the shares typed in
+        let mut wizard = tui::Wizard::new();
+        wizard.key(KeyCode::Down);
+        wizard.key(KeyCode::Enter);
+        type_in(&mut wizard, "tuna acid");
+        assert!(screen(&wizard).contains("That is not a valid share"));
+        type_in(&mut wizard, &shares[0]);
+        let progress = screen(&wizard);
+        assert!(progress.contains("Share accepted"));
+        assert!(progress.contains("Group 1: 1 of 2 shares"));
+        assert!(progress.contains("2 more group(s) needed"));
+        assert!(!progress.contains(&shares[0][..20]));
+        type_in(&mut wizard, &shares[0].to_uppercase());
+        assert!(screen(&wizard).contains("That share was already entered"));
+        for share in [&shares[2], &shares[4], &shares[3]] {
+            type_in(&mut wizard, share);
+        }
+        let recovered = screen(&wizard);
+        assert!(recovered.contains("Recovered mnemonic:"));
+        assert!(recovered.contains("12. about"));
+        wizard.key(KeyCode::Enter);
+        assert!(wizard.is_done());
+        assert_eq!(wizard.outcome(), checklist::Outcome::Recovered);
+
+        let mut wizard = tui::Wizard::new();
+        wizard.key(KeyCode::Esc);
+        assert!(wizard.is_done());
+        assert_eq!(wizard.outcome(), checklist::Outcome::Abandoned);
+
+        // A mnemonic typed in is in the language given, or the one detected,
+        // and a recovered one is in the language given
+        let french = Mnemonic::from_entropy(&[0; 16], Language::French)?;
+        for (wizard, accepted) in [
+            (tui::Wizard::new(), true),
+            (tui::Wizard::new().with_language(Language::French), true),
+            (tui::Wizard::new().with_language(Language::English), false),
+        ] {
+            let mut wizard = wizard;
+            for key in [
+                KeyCode::Enter,
+                KeyCode::Enter,
+                KeyCode::Down,
+                KeyCode::Enter,
+            ] {
+                wizard.key(key);
+            }
+            type_in(&mut wizard, french.phrase());
+            assert_eq!(
+                !screen(&wizard).contains("That is not a valid mnemonic"),
+                accepted
+            );
+        }
+        let mut wizard = tui::Wizard::new().with_language(Language::French);
+        wizard.key(KeyCode::Down);
+        wizard.key(KeyCode::Enter);
+        for share in [&shares[0], &shares[2], &shares[4], &shares[3]] {
+            type_in(&mut wizard, share);
+        }
+        assert!(screen(&wizard).contains("12. abeille"));
+
+        // Nothing the policy keeps off the display is shown
+        let denied = Policy {
+            display: policy::Permissions {
+                mnemonic: false,
+                shares: false,
+                ..policy::Permissions::default()
+            },
+            ..Policy::default()
+        };
+        let mut wizard = tui::Wizard::new().with_policy(denied.clone());
+        for key in [KeyCode::Enter, KeyCode::Enter, KeyCode::Enter] {
+            wizard.key(key);
+        }
+        assert!(screen(&wizard).contains(policy::REDACTED));
+        assert!(!screen(&wizard).contains(" 1. "));
+        for key in [KeyCode::Enter, KeyCode::Enter, KeyCode::Enter] {
+            wizard.key(key);
+        }
+        assert!(screen(&wizard).starts_with("[1/3] "));
+        assert!(screen(&wizard).contains(policy::REDACTED));
+        assert!(!screen(&wizard).contains(" 1. "));
+        let mut wizard = tui::Wizard::new().with_policy(denied);
+        wizard.key(KeyCode::Down);
+        wizard.key(KeyCode::Enter);
+        for share in [&shares[0], &shares[2], &shares[4], &shares[3]] {
+            type_in(&mut wizard, share);
+        }
+        assert!(screen(&wizard).contains("Recovered mnemonic:"));
+        assert!(screen(&wizard).contains(policy::REDACTED));
+        assert!(!screen(&wizard).contains("about"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ceremony_checklist() -> Result<(), Error> {
+        use crossterm::event::KeyCode;
+        use std::time::Duration;
+        assert!(Checklist::parse("# nothing to check\n\n").is_err());
+        assert_eq!(checklist::elapsed(Duration::from_secs(245)), "4:05");
+        assert_eq!(checklist::elapsed(Duration::from_secs(3845)), "1:04:05");
+
+        // The steps are confirmed in order before the ceremony starts
+        let steps = "# Before anyone sees a share\nCheck the room\n\n  Confirm identities  \n";
+        let mut wizard = tui::Wizard::with_checklist(Checklist::parse(steps)?);
+        let screen = wizard.screen().join("\n");
+        assert!(screen.contains("> [ ] Check the room  0:00"));
+        assert!(screen.contains("  [ ] Confirm identities\n"));
+        wizard.key(KeyCode::Down);
+        wizard.key(KeyCode::Enter);
+        let screen = wizard.screen().join("\n");
+        assert!(screen.contains("  [x] Check the room  0:00"));
+        assert!(screen.contains("> [ ] Confirm identities  0:00"));
+        wizard.key(KeyCode::Enter);
+        assert!(wizard.screen()[0].contains("SSKR ceremony"));
+        wizard.key(KeyCode::Esc);
+        assert_eq!(wizard.outcome(), checklist::Outcome::Abandoned);
+
+        // Each ceremony is a line of JSON in the audit log
+        let checked = wizard
+            .checklist()
+            .map(Checklist::checked)
+            .unwrap_or_default();
+        assert_eq!(
+            checked
+                .iter()
+                .map(|step| step.step.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Check the room", "Confirm identities"]
+        );
+        let dir = std::env::temp_dir().join(format!("sskr-tool-audit-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("audit.jsonl");
+        let record = AuditRecord {
+            started: 1700000000,
+            finished: 1700000300,
+            outcome: wizard.outcome(),
+            checklist: checked,
+        };
+        checklist::append_audit(&path, &record)?;
+        checklist::append_audit(&path, &record)?;
+        let log = std::fs::read_to_string(&path)?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(log.lines().count(), 2);
+        let entry: serde_json::Value = serde_json::from_str(log.lines().next().unwrap_or(""))?;
+        assert_eq!(entry["outcome"], "abandoned");
+        assert_eq!(entry["checklist"][1]["step"], "Confirm identities");
+        assert_eq!(entry["checklist"][1]["seconds"], 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagnostics_suggestions() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (_mnemonic, shares) = split::split_shares("2of3,2of3", 1, mnemonic.phrase(), None)?;
+        let lines = split::to_bytewords(&shares, &false);
+        let names = ["the first".to_string(), "the second".to_string()];
+
+        // A single byteword swapped for another is pinned down by the checksum
+        let mut words: Vec<&str> = lines[0][0].split(' ').collect();
+        words[13] = if words[13] == "able" { "acid" } else { "able" };
+        let problem = diagnostics::unreadable(1, &words.join(" "));
+        assert_eq!(
+            problem,
+            diagnostics::Problem::ChecksumMismatch {
+                share: 1,
+                suspects: vec![14]
+            }
+        );
+        assert_eq!(
+            diagnostics::suggestions(&problem, &names),
+            vec!["Re-check word 14 of the second, the checksum matches with another word there"]
+        );
+        words[20] = if words[20] == "able" { "acid" } else { "able" };
+        assert!(
+            diagnostics::suggestions(&diagnostics::unreadable(0, &words.join(" ")), &names)[0]
+                .contains("more than one word is off")
+        );
+
+        words[3] = "tunx";
+        words[9] = "zzzz";
+        assert_eq!(
+            diagnostics::suggestions(&diagnostics::unreadable(0, &words.join(" ")), &names),
+            vec!["Re-check words 4 and 10 of the first, they aren't a byteword"]
+        );
+        assert_eq!(
+            diagnostics::unreadable(2, "not a share at all"),
+            diagnostics::Problem::Unreadable { share: 2 }
+        );
+
+        // Lines that can't be used carry what to do about them
+        let given = vec![lines[0][0].clone(), lines[0][0].replacen(' ', "", 1)];
+        let problems = recover::diagnose_lines(&given);
+        assert_eq!(
+            problems[0].problem,
+            diagnostics::Problem::NotBytewords {
+                share: 1,
+                words: vec![1]
+            }
+        );
+
+        // Shortfalls name the closest groups first
+        let sets = status::status(vec![lines[1][2].clone()], &false, None)?;
+        assert_eq!(
+            sets[0].suggestions,
+            vec![
+                "Collect 1 more share from group 2",
+                "Or instead, collect shares from group 1, the first one tells how many it needs",
+            ]
+        );
+        let sets = status::status(lines[0].clone(), &false, None)?;
+        assert!(sets[0].suggestions.is_empty());
+
+        let diagnostics = sskr_tool::validate_share(&words.join(" "));
+        assert_eq!(diagnostics.suggestions.len(), 1);
+        assert!(sskr_tool::validate_share(&lines[0][0])
+            .suggestions
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_split() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words24, Language::English);
+        let encrypted = encryption::encrypt(mnemonic.entropy(), "correct horse battery")?;
+        assert_eq!(encrypted.secret.len(), 32);
+        assert_ne!(encrypted.secret.as_slice(), mnemonic.entropy());
+        assert_eq!(encrypted.check.split(' ').count(), 2);
+        let salt = encrypted.salt.clone().expect("no room for the salt");
+
+        // Enough shares give back only the encrypted entropy
+        let shares = split::split_raw("2of3", 1, &encrypted.secret)?;
+        let lines = split::to_bytewords(&shares, &false)[0][..2].to_vec();
+        let combined = recover::recover_secret(lines.clone(), &false)?;
+        assert_eq!(combined, encrypted.secret);
+
+        let decrypted = encryption::decrypt(&combined, "correct horse battery", Some(&salt))?;
+        assert_eq!(decrypted.secret.as_slice(), mnemonic.entropy());
+        assert_eq!(decrypted.check, encrypted.check);
+        let wrong = encryption::decrypt(&combined, "correct horse battery!", Some(&salt))?;
+        assert_ne!(wrong.secret.as_slice(), mnemonic.entropy());
+        assert_ne!(wrong.check, encrypted.check);
+        // Without its salt the entropy doesn't decrypt, and each split gets
+        // a salt of its own
+        let unsalted = encryption::decrypt(&combined, "correct horse battery", None)?;
+        assert_ne!(unsalted.secret.as_slice(), mnemonic.entropy());
+        let again = encryption::encrypt(mnemonic.entropy(), "correct horse battery")?;
+        assert_ne!(again.salt, encrypted.salt);
+        assert_ne!(again.secret, encrypted.secret);
+
+        // Resharing gives a new identifier, which the passphrase doesn't depend on
+        let (_, reshared) = split::reshare(lines, &false, "3of5", 1, Language::English)?;
+        let lines = split::to_bytewords(&reshared, &false)[0][..3].to_vec();
+        let combined = recover::recover_secret(lines, &false)?;
+        assert_eq!(
+            encryption::decrypt(&combined, "correct horse battery", Some(&salt))?
+                .secret
+                .as_slice(),
+            mnemonic.entropy()
+        );
+
+        // The salt of 16 byte entropy goes in the shares
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let encrypted = encryption::encrypt(mnemonic.entropy(), "correct horse battery")?;
+        assert!(encrypted.salt.is_none());
+        assert_eq!(encrypted.secret.len(), 32);
+        let decrypted = encryption::decrypt(&encrypted.secret, "correct horse battery", None)?;
+        assert_eq!(decrypted.secret.as_slice(), mnemonic.entropy());
+        assert_eq!(decrypted.check, encrypted.check);
+        assert!(
+            encryption::decrypt(&encrypted.secret[16..], "correct horse battery", None).is_err()
+        );
+
+        assert!(encryption::check_passphrase("short", "short").is_err());
+        assert!(encryption::check_passphrase("long enough", "long enougH").is_err());
+        assert!(Cli::try_parse_from([
+            "sskr-tool",
+            "split",
+            "2of3",
+            "--encrypt",
+            "--raw-secret",
+            "00"
+        ])
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_paranoid_checks() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words24, Language::English);
+        let shares = split::split_raw("2of3,3of5,1of1", 2, mnemonic.entropy())?;
+        let encoded = split::to_bytewords(&shares, &false);
+        let checked = paranoid::check_split(&shares, &encoded, mnemonic.entropy())?;
+        assert_eq!(checked.shares, 9);
+        assert_eq!(checked.combinations, 9);
+
+        // The independent combine agrees with the sskr crate on any quorum
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let mut quorum: Vec<&[u8]> = shares[0]
+                .choose_multiple(&mut rng, 2)
+                .chain(shares[1].choose_multiple(&mut rng, 3))
+                .map(Vec::as_slice)
+                .collect();
+            quorum.shuffle(&mut rng);
+            assert_eq!(
+                paranoid::combine(&quorum)?.as_slice(),
+                sskr::sskr_combine(&quorum)?.data()
+            );
+        }
+        let short: Vec<&[u8]> = shares[1][..2].iter().map(Vec::as_slice).collect();
+        assert!(paranoid::combine(&short).is_err());
+        let mut tampered = shares[0][1].clone();
+        tampered[10] ^= 1;
+        assert!(paranoid::combine(&[&shares[0][0], &tampered, &shares[2][0]]).is_err());
+
+        // A share shown wrong or the wrong secret fails the split
+        let mut corrupted = encoded.clone();
+        let mut words: Vec<&str> = corrupted[1][2].split(' ').collect();
+        words[5] = if words[5] == "able" { "acid" } else { "able" };
+        corrupted[1][2] = words.join(" ");
+        assert!(paranoid::check_split(&shares, &corrupted, mnemonic.entropy()).is_err());
+        let other = split::random_mnemonic(MnemonicType::Words24, Language::English);
+        assert!(paranoid::check_split(&shares, &encoded, other.entropy()).is_err());
+
+        paranoid::check_mnemonic(&mnemonic)?;
+        assert!(Cli::try_parse_from(["sskr-tool", "recover", "--paranoid", "shares.txt"]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_skips_a_bad_group() -> Result<(), Error> {
+        let secret: Vec<u8> = (0..16).collect();
+        let shares = split::split_raw("2of3,2of3,2of3", 2, &secret)?;
+        let line = |share: &Vec<u8>| share_format::encode(share, ShareFormat::Bytewords);
+        // A share that decodes fine but was changed, which only the group's
+        // digest tells
+        let mut tampered = shares[0][1].clone();
+        tampered[8] ^= 1;
+
+        let lines = vec![
+            line(&shares[0][0]),
+            line(&tampered),
+            line(&shares[1][0]),
+            line(&shares[1][2]),
+        ];
+        let error = recover::recover_secret(lines.clone(), &false).unwrap_err();
+        assert!(error.to_string().contains("SSKR combination"));
+
+        // With a third group at hand, the groups without the bad share recover
+        let mut lines = lines;
+        lines.extend([line(&shares[2][1]), line(&shares[2][2])]);
+        assert_eq!(*recover::recover_secret(lines, &false)?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn test_codex32() -> Result<(), Error> {
+        // The test vectors of BIP-93
+        let secret = codex32::parse("ms10testsxxxxxxxxxxxxxxxxxxxxxxxxxx4nzvca9cmczlw")?;
+        assert_eq!(secret.threshold, 0);
+        assert_eq!(secret.identifier, "test");
+        assert_eq!(
+            hex::encode(secret.payload()),
+            "318c6318c6318c6318c6318c6318c631"
+        );
+        let shares = [
+            "MS12NAMEA320ZYXWVUTSRQPNMLKJHGFEDCAXRPP870HKKQRM".to_string(),
+            "MS12NAMECACDEFGHJKLMNPQRSTUVWXYZ023FTR2GDZMPY6PN".to_string(),
+        ];
+        assert_eq!(
+            hex::encode(codex32::recover(&shares)?.0),
+            "d1808e096b35b209ca12132b264662a5"
+        );
+
+        // Any 3 of 5 shares recover, in either case, and the secret's own
+        // string keeps the identifier
+        let secret: Vec<u8> = (0..32).collect();
+        let shares = codex32::split(&secret, 3, 5, "cash")?;
+        assert!(shares.iter().all(|share| share.starts_with("ms13cash")));
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let mut chosen: Vec<String> = shares.choose_multiple(&mut rng, 3).cloned().collect();
+            chosen[0] = chosen[0].to_uppercase();
+            let (recovered, identifier) = codex32::recover(&chosen)?;
+            assert_eq!(*recovered, secret);
+            assert_eq!(identifier, "cash");
+        }
+        let encoded = codex32::encode_secret(&secret, "cash")?;
+        assert_eq!(*codex32::recover(&[encoded])?.0, secret);
+
+        assert!(codex32::recover(&shares[..2]).is_err());
+        let mut mistyped = shares[1].clone();
+        mistyped.replace_range(12..13, if &mistyped[12..13] == "q" { "p" } else { "q" });
+        assert!(codex32::parse(&mistyped).is_err());
+        assert!(codex32::parse(&shares[0].replacen("ms1", "MS1", 1)).is_err());
+        assert!(codex32::split(&secret[..15], 2, 3, "cash").is_err());
+        assert!(split::single_group("2of3,2of3").is_err());
+        assert_eq!(split::single_group("work=3/5")?, (3, 5));
+        assert!(Cli::try_parse_from([
+            "sskr-tool",
+            "split",
+            "2of3",
+            "--codex32",
+            "--paper",
+            "a.pdf"
+        ])
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let shares = split::split_raw("2of3,2of3", 2, mnemonic.entropy())?;
+        let current =
+            serde_json::to_string_pretty(&SplitOutput::new("2of3,2of3", 2, &mnemonic, &shares)?)?;
+        let current_value: serde_json::Value = serde_json::from_str(&current)?;
+        let unchanged = migrate::migrate(&current)?;
+        assert_eq!(unchanged.kind, migrate::ArtifactKind::SplitDocument);
+        assert_eq!(unchanged.contents, None);
+
+        // The first JSON output had no hex, cbor_hex or checksum, nor the
+        // secret length in the metadata
+        let mut old = current_value.clone();
+        for group in old["groups"].as_array_mut().unwrap() {
+            for share in group["shares"].as_array_mut().unwrap() {
+                let share = share.as_object_mut().unwrap();
+                for field in ["hex", "cbor_hex", "checksum"] {
+                    share.remove(field);
+                }
+                share["metadata"]
+                    .as_object_mut()
+                    .unwrap()
+                    .remove("secret_length");
+            }
+        }
+        let migrated = migrate::migrate(&serde_json::to_string(&old)?)?;
+        assert_eq!(
+            migrated.changes,
+            ["shares rebuilt with cbor_hex, checksum, hex, metadata.secret_length"]
+        );
+        let upgraded: serde_json::Value = serde_json::from_str(&migrated.contents.unwrap())?;
+        assert_eq!(upgraded, current_value);
+
+        // A version 1 manifest was a single seed without a version
+        let lines = split::to_bytewords(&shares, &false);
+        let seed = SeedManifest::from_shares(&lines, &false, Language::English)?;
+        let migrated = migrate::migrate(&serde_json::to_string(&seed)?)?;
+        assert_eq!(migrated.kind, migrate::ArtifactKind::Manifest);
+        assert_eq!(
+            Manifest::from_json(&migrated.contents.unwrap())?,
+            Manifest::new(seed.clone())
+        );
+        let manifest = Manifest::new(seed).to_json()?;
+        assert_eq!(migrate::migrate(&manifest)?.contents, None);
+        assert!(migrate::migrate(&manifest.replace("\"version\": 2", "\"version\": 3")).is_err());
+
+        let journal = format!("{}\n", journal::HEADER);
+        assert_eq!(
+            migrate::migrate(&journal)?.kind,
+            migrate::ArtifactKind::Journal
+        );
+        assert!(migrate::migrate(&journal.replace("v1", "v2")).is_err());
+        assert!(migrate::migrate("{\"format\": \"sskr-tool archival bundle v2\"}").is_err());
+        assert!(migrate::migrate("not an artifact").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "verify-only")]
+    #[test]
+    fn test_verify_only() -> Result<(), Error> {
+        use sskr_tool::verify_only::{check_transcription, command, validate_lines};
+
+        let secret: Vec<u8> = (0..16).collect();
+        let shares = split::split_raw("2of3,2of3", 2, &secret)?;
+        let strangers = split::split_raw("2of3,2of3", 2, &secret)?;
+        let line = |share: &Vec<u8>| share_format::encode(share, ShareFormat::Bytewords);
+        let seed =
+            SeedManifest::from_shares(&[vec![line(&shares[0][0])]], &false, Language::English)?
+                .with_share_checks(&shares, HashAlgorithm::Sha256);
+        let manifest = Manifest::new(seed);
+
+        let check = check_transcription(&line(&shares[1][2]), Some(&manifest))?;
+        assert!(check.valid);
+        assert_eq!(check.matches_split, Some(true));
+        assert_eq!(
+            check.metadata.map(|metadata| metadata.member_index),
+            Some(2)
+        );
+        let check = check_transcription(&line(&strangers[1][2]), Some(&manifest))?;
+        assert!(!check.valid);
+        assert!(check_transcription(&line(&strangers[1][2]), None)?.valid);
+
+        // A mistyped word is pointed at without quoting the share
+        let mut words = line(&shares[0][1])
+            .split(' ')
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        words[6] = if words[6] == "able" { "acid" } else { "able" }.to_string();
+        let check = check_transcription(&words.join(" "), Some(&manifest))?;
+        assert!(!check.valid);
+        assert!(check.suggestions[0].starts_with("Re-check word"));
+        assert!(!serde_json::to_string(&check)?.contains(&words[0]));
+
+        let lines = [line(&shares[0][0]), "not a share".to_string()]
+            .iter()
+            .enumerate()
+            .map(|(index, text)| input::ShareLine {
+                source: "shares.txt".to_string(),
+                line: index + 1,
+                text: text.clone(),
+            })
+            .collect::<Vec<_>>();
+        let validations = validate_lines(&lines);
+        assert!(validations[0].diagnostics.is_valid());
+        assert!(!validations[1].diagnostics.is_valid());
+        assert_eq!(validations[1].location, "shares.txt:2");
+
+        // Nothing that could reveal a seed is there to run
+        for args in [
+            vec!["check-transcription"],
+            vec!["inspect", "ur:sskr/x"],
+            vec!["validate", "shares.txt"],
+        ] {
+            assert!(command()
+                .try_get_matches_from([&["sskr-tool-verify"], &args[..]].concat())
+                .is_ok());
+        }
+        for subcommand in ["split", "recover", "reshare", "verify", "tui", "bundle"] {
+            assert!(command()
+                .try_get_matches_from(["sskr-tool-verify", subcommand, "2of3"])
+                .is_err());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn test_gui_recovery() -> Result<(), Error> {
+        use sskr_tool::gui::Recovery;
+
+        let secret: Vec<u8> = (0..16).collect();
+        let shares = split::split_raw("2of3,2of3", 2, &secret)?;
+        let line = |share: &Vec<u8>| share_format::encode(share, ShareFormat::Bytewords);
+        let allowed = Policy::default();
+        assert_eq!(
+            Recovery::of("\n# Nothing yet\n", &allowed),
+            Recovery::default()
+        );
+
+        // Progress comes from the good shares, and each bad line says what's
+        // wrong with it
+        let mut text = format!(
+            "{}\nnot a share\n{}\n",
+            line(&shares[0][0]),
+            line(&shares[0][2])
+        );
+        let recovery = Recovery::of(&text, &allowed);
+        assert_eq!(recovery.problems.len(), 1);
+        assert!(recovery.problems[0].0.starts_with("Line 2: "));
+        assert!(recovery
+            .progress
+            .contains(&"Group 1: 2 of 2 shares, complete".to_string()));
+        assert!(recovery
+            .progress
+            .contains(&"1 more group(s) needed".to_string()));
+        assert!(recovery.recovered.is_none());
+
+        text += &line(&shares[1][1]);
+        text += "\n";
+        text += &line(&shares[1][0]);
+        let mnemonic = Mnemonic::from_entropy(&secret, Language::English)?;
+        assert_eq!(
+            Recovery::of(&text, &allowed)
+                .recovered
+                .as_deref()
+                .map(String::as_str),
+            Some(mnemonic.phrase())
+        );
+
+        // A policy keeping the mnemonic off the display withholds it
+        let denied = Policy {
+            display: policy::Permissions {
+                mnemonic: false,
+                ..policy::Permissions::default()
+            },
+            ..Policy::default()
+        };
+        assert_eq!(
+            Recovery::of(&text, &denied)
+                .recovered
+                .as_deref()
+                .map(String::as_str),
+            Some(policy::REDACTED)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_spec_check() -> Result<(), Error> {
+        let layout = split::spec_layout("family=2of3,3of5,1of1,2/4", 3)?;
+        assert_eq!(layout.share_count(), 13);
+        assert_eq!(layout.fewest_shares(), 5);
+        assert_eq!(layout.groups[0].label.as_deref(), Some("family"));
+        assert_eq!(
+            layout.quorums().collect::<Vec<_>>(),
+            vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 2, 3], vec![1, 2, 3]]
+        );
+        assert_eq!(split::spec_layout("2of3,3of5", 2)?.quorums().count(), 1);
+        assert!(split::spec_layout("2of3,3of5", 3).is_err());
+        assert!(split::spec_layout("3of2", 1).is_err());
+
+        let mut script = vec![];
+        clap_complete::generate(Shell::Bash, &mut Cli::command(), "sskr-tool", &mut script);
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("migrate") && script.contains("--check"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_algorithms() -> Result<(), Error> {
+        use sskr_tool::hash::Hash;
+
+        assert_eq!(
+            HashAlgorithm::Blake3.hash(b"abc").hex(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        let (_mnemonic, groups) = split::split_random_phrase(
+            "2of3",
+            1,
+            MnemonicType::Words12,
+            &false,
+            Language::English,
+        )?;
+        let shares: Vec<Vec<Vec<u8>>> = vec![groups[0]
+            .iter()
+            .map(|share| recover::decode_share(share))
+            .collect::<Result<_, _>>()?];
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?
+            .with_share_checks(&shares, HashAlgorithm::Blake3);
+        let dir = std::env::temp_dir().join(format!("sskr-tool-hash-{}", process::id()));
+        artifacts::write_share_files(
+            &dir,
+            &seed,
+            &groups,
+            &naming::NameTemplate::default(),
+            ExistingShares::Refuse,
+            HashAlgorithm::Blake3,
+            false,
+        )?;
+
+        // The manifest names the algorithm, and checks find it there
+        let manifest = Manifest::load(&dir.join(media::MANIFEST_FILE))?;
+        let recorded = &manifest.seeds[0];
+        assert!(matches!(recorded.artifacts[0].hash, Hash::Blake3(_)));
+        let json = manifest.to_json()?;
+        assert!(json.contains("\"blake3\": \"") && !json.contains("sha256"));
+        assert!(media::verify_media(&dir, None)?
+            .iter()
+            .all(|check| check.outcome == media::MediaOutcome::Intact));
+        let checks = recorded.share_checks.as_ref().unwrap();
+        assert_eq!(checks.algorithm, HashAlgorithm::Blake3);
+        assert!(checks.matches(&shares[0][1])?);
+        assert!(!checks.matches(&shares[0][1][1..])?);
+
+        // Manifests from before the choice are SHA-256, and still read
+        let old = r#"{"salt": "00", "digests": []}"#;
+        let old: drill::ShareChecks = serde_json::from_str(old)?;
+        assert_eq!(old.algorithm, HashAlgorithm::Sha256);
+        let old: manifest::ArtifactHash = serde_json::from_str(r#"{"path": "a", "sha256": "00"}"#)?;
+        assert_eq!(old.hash, Hash::Sha256("00".to_string()));
+
+        let release = dir.join("release.tar.gz");
+        std::fs::write(&release, "release")?;
+        let bundle = bundle::Bundle::new(
+            Some(manifest),
+            groups[0].clone(),
+            &release,
+            HashAlgorithm::Blake3,
+        )?;
+        let path = dir.join("bundle.json");
+        bundle.save(&path)?;
+        let verification = bundle::Bundle::load(&path)?.verify(Some(&release))?;
+        assert_eq!(
+            verification.software.hash.algorithm(),
+            HashAlgorithm::Blake3
+        );
+        assert_eq!(verification.release_matches, Some(true));
+        assert!(matches!(bundle.seal, Hash::Blake3(_)));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_seed_ur() -> Result<(), Error> {
+        assert_eq!(seed_ur::parse_date("1970-01-01")?, 0);
+        assert_eq!(seed_ur::parse_date("2024-02-29")?, 19782);
+        assert_eq!(seed_ur::format_date(-1), "1969-12-31");
+        for invalid in ["2023-02-29", "2024-13-01", "2024-1", "yesterday"] {
+            assert!(seed_ur::parse_date(invalid).is_err());
+        }
+
+        // A bare seed is a map holding only its payload
+        let secret: Vec<u8> = (0..16).collect();
+        let bare = seed_ur::encode(&secret, &SeedMetadata::default(), SeedUrType::Seed);
+        assert!(bare.starts_with("ur:seed/oyadgdaeadaoaxaaahamatayasbkbdbnbtbabs"));
+        let (decoded, metadata) = seed_ur::decode(&bare)?;
+        assert_eq!((*decoded).clone(), secret);
+        assert_eq!(metadata, SeedMetadata::default());
+
+        let metadata = SeedMetadata {
+            name: Some("Family".to_string()),
+            note: Some("Cold storage".to_string()),
+            creation_date: Some(19782),
+        };
+        for ur_type in [SeedUrType::Seed, SeedUrType::CryptoSeed] {
+            let ur = seed_ur::encode(&secret, &metadata, ur_type);
+            let (decoded, read) = seed_ur::decode(&ur.to_uppercase())?;
+            assert_eq!((*decoded).clone(), secret);
+            assert_eq!(read, metadata);
         }
+        assert!(seed_ur::encode(&secret, &metadata, SeedUrType::CryptoSeed)
+            .starts_with("ur:crypto-seed/"));
+        assert!(seed_ur::decode("ur:sskr/aeadaolazmjendeoti").is_err());
+        assert!(!seed_ur::terminal_qr(&bare)?.is_empty());
+        Ok(())
     }
-}
 
-fn split_success(
-    spec: &String,
-    group_threshold: &usize,
-    mnemonic: Mnemonic,
-    groups: Vec<Vec<String>>,
-) {
-    println!("Entropy:  0x{}", hex::encode(mnemonic.entropy()));
-    println!("Mnemonic: {}", mnemonic.phrase());
-    println!();
-    println!(
-        "SSKR shares - need to recover at least {} group(s) to recover mnemonic\n",
-        group_threshold
-    );
-    for ((group_num, group), group_spec) in groups.iter().enumerate().zip(spec.split(",")) {
-        println!(
-            "Group {} - need {} shares to recover group",
-            group_num + 1,
-            group_spec.replace("of", " of ")
+    #[test]
+    fn test_policy() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!("sskr-tool-policy-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("policy.toml");
+        std::fs::write(
+            &path,
+            "[display]\nmnemonic = false\n\n[disk]\nshares = false\n\n[log]\nentropy = false\n",
+        )?;
+        let policy = Policy::load(&path)?;
+
+        // Only what the file names is denied
+        assert!(!policy.allows(Field::Mnemonic, Channel::Display));
+        assert!(policy.allows(Field::Entropy, Channel::Display));
+        assert!(policy.allows(Field::Mnemonic, Channel::Disk));
+        assert!(!policy.allows(Field::Shares, Channel::Disk));
+        assert!(policy.allows(Field::Shares, Channel::Display));
+        assert!(!policy.allows(Field::Entropy, Channel::Log));
+        assert_eq!(
+            policy.shown(Field::Mnemonic, Channel::Display, "abandon ability"),
+            policy::REDACTED
         );
-        for (share_num, share) in group.iter().enumerate() {
-            println!(
-                "  {}{}: {}",
-                if group.len() > 9 && share_num < 9 {
-                    " "
-                } else {
-                    ""
-                },
-                share_num + 1,
-                share
-            );
-        }
-        println!();
-    }
-}
+        assert_eq!(
+            policy.shown(Field::Fingerprints, Channel::Display, "73c5da0a"),
+            "73c5da0a"
+        );
+        assert_eq!(
+            policy
+                .check(Field::Shares, Channel::Disk)
+                .unwrap_err()
+                .to_string(),
+            "The policy doesn't allow writing shares to disk"
+        );
+        assert!(policy.check(Field::Fingerprints, Channel::Log).is_ok());
 
-fn recover(filename: &String, minimal: &bool) {
-    let file_contents = read_to_string(filename);
+        // Without a policy everything is allowed, and mistakes aren't ignored
+        assert_eq!(Policy::default(), toml::from_str("")?);
+        std::fs::write(&path, "[display]\nmnemonics = false\n")?;
+        assert!(Policy::load(&path).is_err());
+        std::fs::write(&path, "[screen]\nmnemonic = false\n")?;
+        assert!(Policy::load(&path).is_err());
 
-    if let Err(error) = file_contents {
-        eprintln!("Error reading file \"{}\": {}", filename, error);
-        process::exit(1);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
     }
 
-    let lines = file_contents.unwrap().lines().map(String::from).collect();
+    #[test]
+    fn test_given_secret_is_not_echoed() -> Result<(), Error> {
+        let given = |arguments: &[&str]| -> Result<bool, Error> {
+            let cli = Cli::try_parse_from([&["sskr-tool", "split"], arguments].concat())?;
+            let Commands::Split(args) = cli.command else {
+                unreachable!()
+            };
+            Ok(secret_given(&args) && !args.echo_secret)
+        };
+        let phrase = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        assert!(given(&["2of3", phrase])?);
+        assert!(given(&["2of3,2of3", "1", phrase])?);
+        assert!(given(&["2of3", "--no-echo"])?);
+        assert!(given(&[
+            "2of3",
+            "--entropy-hex",
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f"
+        ])?);
+        assert!(given(&[
+            "2of3",
+            "--raw-secret",
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f"
+        ])?);
+        assert!(!given(&["2of3", phrase, "--echo-secret"])?);
+        // Generated ones are shown, having no other copy
+        assert!(!given(&["2of3"])?);
+        assert!(!given(&["2of3,2of3", "1"])?);
+        assert!(!given(&["2of3", "--entropy", "coin:HTTH"])?);
 
-    match recover::recover(lines, minimal) {
-        Ok(mnemonic) => recover_success(mnemonic),
-        Err(error) => {
-            eprintln!("Error recovering mnemonic: {:?}", error);
-            process::exit(1);
-        }
+        let mnemonic = Mnemonic::from_phrase(phrase, Language::English)?;
+        let policy = Policy::default().without_secret_display();
+        assert_eq!(
+            policy.shown(Field::Mnemonic, Channel::Display, mnemonic.phrase()),
+            policy::REDACTED
+        );
+        assert!(!policy.allows(Field::Entropy, Channel::Display));
+        assert!(policy.allows(Field::Shares, Channel::Display));
+        let raw = [0x7f; 16];
+        assert_eq!(raw_secret_line(&raw, &policy), "Secret:   [redacted]");
+        assert_eq!(
+            raw_secret_line(&raw, &Policy::default()),
+            "Secret:   0x7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f"
+        );
+        assert!(policy.allows(Field::Mnemonic, Channel::Disk));
+
+        let withheld = serde_json::to_string(&SecretOutput::new(&mnemonic).withheld())?;
+        assert!(!withheld.contains("legal"));
+        assert!(!withheld.contains("7f7f"));
+        assert!(withheld.contains("\"language\":\"english\""));
+        Ok(())
     }
-}
 
-fn recover_success(mnemonic: Mnemonic) {
-    println!("Entropy:  0x{}", hex::encode(mnemonic.entropy()));
-    println!("Mnemonic: {}", mnemonic.phrase());
-}
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_split_and_recover() -> Result<(), Error> {
+        use sskr_tool::wasm::{recover_document, split_document};
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use anyhow::Error;
-    use rand::prelude::SliceRandom;
-    use rand::seq::IteratorRandom;
-    use rand::Rng;
+        // The page gets the same documents as --output json
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let split: serde_json::Value =
+            serde_json::from_str(&split_document("2of3,2of3", Some(2), Some(phrase))?)?;
+        assert_eq!(split["mnemonic"], phrase);
+        assert_eq!(split["groups"].as_array().map(Vec::len), Some(2));
+        let share = |group: usize, member: usize| {
+            split["groups"][group]["shares"][member]["bytewords"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
 
-    static TEST_ITERATIONS: usize = 50000;
+        // Shares are pasted as in a share file, in any format
+        let pasted = format!(
+            "# Alice and Bob\n{}\n\n{}\n{}\n{}\n",
+            share(0, 0),
+            share(0, 2),
+            split["groups"][1]["shares"][1]["ur"]
+                .as_str()
+                .unwrap_or_default(),
+            share(1, 0)
+        );
+        let recovered: serde_json::Value = serde_json::from_str(&recover_document(&pasted)?)?;
+        assert_eq!(recovered["mnemonic"], phrase);
+        assert_eq!(recovered["entropy"], "0x00000000000000000000000000000000");
+
+        // Errors come back instead of ending the process
+        let error = recover_document(&share(0, 0)).unwrap_err();
+        assert!(!error.to_string().is_empty());
+        assert!(split_document("2of3,2of3", None, Some(phrase)).is_err());
+        let generated: serde_json::Value =
+            serde_json::from_str(&split_document("2of3", None, None)?)?;
+        assert_eq!(
+            generated["mnemonic"]
+                .as_str()
+                .map(|phrase| phrase.split(' ').count()),
+            Some(12)
+        );
+        Ok(())
+    }
 
     #[test]
-    fn test_roundtrip_all_full_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, _sizes, group_threshold) = gen_random_params();
-            let (mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            ensure_recoverable(&mnemonic, groups.into_iter().flatten().collect())?;
+    fn test_split_entropy_hex() -> Result<(), Error> {
+        let phrase = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        let entropy = "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f";
+        for input in [entropy, " 0x7F7F7F7F7F7F7F7F7F7F7F7F7F7F7F7F "] {
+            let mnemonic = split::entropy_hex_mnemonic(input, Language::English)?;
+            assert_eq!(mnemonic.phrase(), phrase);
         }
+        let words = split::entropy_hex_mnemonic(&"00".repeat(32), Language::English)?;
+        assert_eq!(words.phrase().split(' ').count(), 24);
+
+        // Lengths no mnemonic has point at --raw-secret, and hex has to be hex
+        let error = split::entropy_hex_mnemonic(&"00".repeat(18), Language::English).unwrap_err();
+        assert!(error.to_string().contains("--raw-secret"));
+        assert!(split::entropy_hex_mnemonic("7g7f", Language::English).is_err());
+
+        // It takes the place of the mnemonic, so both can't be given
+        let parameters = |arguments: &[&str]| -> Result<(usize, Option<String>), Error> {
+            let cli = Cli::try_parse_from([&["sskr-tool", "split"], arguments].concat())?;
+            let Commands::Split(args) = cli.command else {
+                unreachable!()
+            };
+            let (group_threshold, phrase) = split_parameters(&args)?;
+            Ok((group_threshold, phrase.map(String::from)))
+        };
+        assert_eq!(parameters(&["2of3", "--entropy-hex", entropy])?, (1, None));
+        assert!(parameters(&["2of3", phrase, "--entropy-hex", entropy]).is_err());
+        assert!(parameters(&["2of3", "1", phrase, "--entropy-hex", entropy]).is_err());
+        assert!(parameters(&["2of3", "--entropy-hex", entropy, "--words", "24"]).is_err());
+        assert!(parameters(&["2of3", "--entropy-hex", entropy, "--raw-secret", entropy]).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_all_sufficient_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, sizes, group_threshold) = gen_random_params();
-            let (mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            ensure_recoverable(
-                &mnemonic,
-                groups
-                    .into_iter()
-                    .zip(sizes.into_iter())
-                    .map(|(group, (m, _n))| {
-                        group
-                            .into_iter()
-                            .choose_multiple(&mut rand::thread_rng(), m)
-                    })
-                    .flatten()
-                    .collect(),
-            )?;
-        }
+    fn test_example() -> Result<(), Error> {
+        // The same shares every time, so recover --example matches what
+        // split --example printed
+        let shares = example::shares()?;
+        assert_eq!(shares, example::shares()?);
+        assert_eq!(shares[0].len(), 3);
+        assert_eq!(ShareMetadataOutput::new(&shares[0][0])?.identifier, "7eb5");
+        let lines = example::recovery_lines()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            recover::recover(lines, &false, Language::English)?.phrase(),
+            example::mnemonic()?.phrase()
+        );
+
+        let parse = |arguments: &[&str]| Cli::try_parse_from(arguments);
+        assert!(parse(&["sskr-tool", "split", "--example"]).is_ok());
+        assert!(parse(&[
+            "sskr-tool",
+            "--output",
+            "json",
+            "split",
+            "--example",
+            "--minimal"
+        ])
+        .is_ok());
+        assert!(parse(&["sskr-tool", "split", "--example", "2of3"]).is_err());
+        assert!(parse(&["sskr-tool", "split", "--example", "--out-dir", "shares"]).is_err());
+        assert!(parse(&["sskr-tool", "recover", "--example"]).is_ok());
+        assert!(parse(&["sskr-tool", "recover", "--example", "shares.txt"]).is_err());
+        assert!(parse(&["sskr-tool", "recover"]).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_all_insufficient_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, sizes, group_threshold) = gen_random_params();
-            let (_mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            let mut shares: Vec<String> = groups
-                .into_iter()
-                .zip(sizes.into_iter())
-                .map(|(group, (m, _n))| {
-                    group
-                        .into_iter()
-                        .choose_multiple(&mut rand::thread_rng(), m - 1)
-                })
-                .flatten()
-                .collect();
-            if shares.len() == 0 {
-                continue;
-            }
-            shares.shuffle(&mut rand::thread_rng());
-            ensure_unrecoverable(shares);
-        }
+    fn test_duress_keys() -> Result<(), Error> {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        use duress::Press;
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        let plain = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        let keys: DuressKeys = duress::DEFAULT_KEYS.parse()?;
+        assert_eq!(keys.to_string(), "ctrl-x ctrl-x");
+        let mut watch = keys.watch();
+        assert_eq!(watch.press(&ctrl('x')), Press::Swallowed);
+        assert_eq!(watch.press(&ctrl('x')), Press::Wipe);
+
+        // A wrong key starts the sequence over, and plain letters pass through
+        assert_eq!(watch.press(&ctrl('x')), Press::Swallowed);
+        assert_eq!(watch.press(&plain('x')), Press::Pass);
+        assert_eq!(watch.press(&ctrl('x')), Press::Swallowed);
+        assert_eq!(watch.press(&ctrl('X')), Press::Wipe);
+
+        let keys: DuressKeys = "Ctrl-Alt-w F12".parse()?;
+        assert_eq!(keys.to_string(), "ctrl-alt-w f12");
+        let mut watch = keys.watch();
+        let ctrl_alt = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        assert_eq!(
+            watch.press(&KeyEvent::new(KeyCode::Char('w'), ctrl_alt)),
+            Press::Swallowed
+        );
+        assert_eq!(
+            watch.press(&KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE)),
+            Press::Wipe
+        );
+
+        let keys: DuressKeys = "none".parse()?;
+        assert_eq!(keys.to_string(), "none");
+        assert_eq!(keys.watch().press(&ctrl('x')), Press::Pass);
+        assert!("".parse::<DuressKeys>().is_err());
+        assert!("hyper-x".parse::<DuressKeys>().is_err());
+        assert!("f13".parse::<DuressKeys>().is_err());
+        assert!(Cli::try_parse_from(["sskr-tool", "tui", "--duress-keys", "ctrl-"]).is_err());
+
+        // A wiped ceremony is back at its first screen, nothing typed kept
+        let mut wizard = tui::Wizard::new();
+        wizard.key(KeyCode::Enter);
+        "abandon".chars().for_each(|c| wizard.key(KeyCode::Char(c)));
+        wizard.wipe();
+        assert_eq!(wizard.screen(), tui::Wizard::new().screen());
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_enough_full_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, _sizes, group_threshold) = gen_random_params();
-            let (mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            ensure_recoverable(
-                &mnemonic,
-                groups
-                    .into_iter()
-                    .choose_multiple(&mut rand::thread_rng(), group_threshold)
-                    .into_iter()
-                    .flatten()
-                    .collect(),
-            )?;
-        }
+    fn test_transcribed_shares() -> Result<(), Error> {
+        let (mnemonic, shares) = split::split_shares(
+            "3of3",
+            1,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        let lines = split::to_bytewords(&shares, &false);
+        let minimal = split::to_bytewords(&shares, &true);
+
+        // Case and runs of spaces and tabs don't matter
+        let messy = lines[0][0].to_uppercase().replace(' ', " \t  ");
+        assert!(share_format::checksum_matches(
+            &messy,
+            ShareFormat::Bytewords
+        )?);
+        let spaced = minimal[0][0]
+            .as_bytes()
+            .chunks(8)
+            .map(|chunk| String::from_utf8_lossy(chunk).to_uppercase())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert!(share_format::checksum_matches(
+            &spaced,
+            ShareFormat::Minimal
+        )?);
+
+        // Numbered as split prints them, and wrapped across lines as copied
+        // from paper
+        let wrap = |share: &str, per_line: usize| {
+            share
+                .split(' ')
+                .collect::<Vec<_>>()
+                .chunks(per_line)
+                .map(|words| words.join(" "))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let wrapped = wrap(&lines[0][1].to_uppercase(), 6);
+        let (minimal_head, minimal_tail) = minimal[0][2].split_at(10);
+        let contents = format!(
+            "1: {}\n2:\t{}\n\n# the third\n3. {}\n{}\n",
+            messy, wrapped, minimal_head, minimal_tail
+        );
+        let dir = std::env::temp_dir().join(format!("sskr-tool-transcribed-{}", process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let file = dir.join("shares.txt");
+        std::fs::write(&file, &contents)?;
+        let located = input::read_located_share_lines(&[file.display().to_string()])?;
+        assert_eq!(
+            located.iter().map(|line| line.line).collect::<Vec<_>>(),
+            vec![1, 2, wrapped.lines().count() + 4]
+        );
+        assert_eq!(input::share_lines(&contents).len(), 3);
+        ensure_recoverable(
+            &mnemonic,
+            located.into_iter().map(|line| line.text).collect(),
+        )?;
+
+        // A share whose checksum nothing completes is left on its own line
+        let mut words = lines[0][0].split(' ').collect::<Vec<_>>();
+        words[10] = if words[10] == "able" { "acid" } else { "able" };
+        let corrupted = words.join(" ");
+        std::fs::write(&file, format!("{}\n{}\n", corrupted, lines[0][1]))?;
+        let read = input::read_share_lines(&[file.display().to_string()])?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(read, vec![corrupted, lines[0][1].clone()]);
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_enough_sufficient_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, sizes, group_threshold) = gen_random_params();
-            let (mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            let mut shares: Vec<String> = groups
-                .into_iter()
-                .zip(sizes.into_iter())
-                .map(|(group, (m, _n))| {
-                    group
-                        .into_iter()
-                        .choose_multiple(&mut rand::thread_rng(), m)
-                })
-                .choose_multiple(&mut rand::thread_rng(), group_threshold)
-                .into_iter()
-                .flatten()
-                .collect();
-            shares.shuffle(&mut rand::thread_rng());
-            ensure_recoverable(&mnemonic, shares)?;
-        }
+    fn test_audit_against_manifest() -> Result<(), Error> {
+        let spec = "family=2of3,lawyers=2of2";
+        let phrase = split::random_mnemonic(MnemonicType::Words12, Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares(spec, 1, phrase.phrase(), Some(Language::English))?;
+        let groups = split::to_bytewords(&shares, &false);
+        let seed = SeedManifest::from_shares(&groups, &false, Language::English)?
+            .with_details(&Some("Family savings".to_string()), &None, &None)?
+            .with_group_labels(&split::spec_labels(spec)?)
+            .with_share_checks(&shares, HashAlgorithm::Sha256)
+            .with_creation_time();
+        let manifest = Manifest::from_json(&Manifest::new(seed.clone()).to_json()?)?;
+        assert!(manifest.seeds[0].created.is_some());
+
+        // A share altered but re-encoded, so its own checksum still passes
+        let mut altered = shares[0][1].clone();
+        let last = altered.len() - 1;
+        altered[last] ^= 1;
+        let (_other, foreign) =
+            split::split_shares("2of3", 1, phrase.phrase(), Some(Language::English))?;
+        let located = [
+            groups[0][1].clone(),
+            share_format::encode(&altered, ShareFormat::Bytewords),
+            share_format::encode(&foreign[0][0], ShareFormat::Minimal),
+            "tuna acid".to_string(),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| input::ShareLine {
+            source: "returned.txt".to_string(),
+            line: index + 1,
+            text,
+        })
+        .collect::<Vec<_>>();
+
+        let audits = audit::audit(&manifest, &located)?;
+        assert_eq!(
+            audits.iter().map(|audit| audit.verdict).collect::<Vec<_>>(),
+            vec![
+                audit::Verdict::Genuine,
+                audit::Verdict::Tampered,
+                audit::Verdict::Unknown,
+                audit::Verdict::Unreadable
+            ]
+        );
+        let shown = audits[0].to_string();
+        assert!(shown.starts_with("returned.txt:1: genuine\n"));
+        assert!(shown.contains("\"Family savings\" (identifier"));
+        assert!(shown.contains("family (group 1), share 2"));
+        assert!(shown.contains("Split on "));
+        let json = serde_json::to_value(&audits)?;
+        assert_eq!(json[1]["verdict"], "tampered");
+        assert_eq!(json[1]["member"], 2);
+        assert!(json[3].get("identifier").is_none());
+
+        // Without commitments, only whether the share fits the groups is known
+        let unchecked = Manifest::new(SeedManifest {
+            share_checks: None,
+            ..seed
+        });
+        let audits = audit::audit(&unchecked, &located[..2])?;
+        assert_eq!(audits[0].verdict, audit::Verdict::Unchecked);
+        assert_eq!(audits[1].verdict, audit::Verdict::Unchecked);
+        assert!(!audits[0].is_genuine());
+        assert!(Cli::try_parse_from(["sskr-tool", "audit", "returned.txt"]).is_err());
+        assert!(
+            Cli::try_parse_from(["sskr-tool", "audit", "--manifest", "manifest.json"]).is_err()
+        );
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_enough_sufficient_groups_minus_one() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, sizes, group_threshold) = gen_random_params();
-            let (_mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            let mut shares = groups
-                .into_iter()
-                .zip(sizes.into_iter())
-                .map(|(group, (m, _n))| {
-                    group
-                        .into_iter()
-                        .choose_multiple(&mut rand::thread_rng(), m)
-                })
-                .choose_multiple(&mut rand::thread_rng(), group_threshold)
-                .into_iter()
-                .flatten()
-                .collect::<Vec<String>>();
-            if shares.len() == 1 {
-                continue;
-            }
-            shares.shuffle(&mut rand::thread_rng());
-            ensure_unrecoverable(shares.split_last().unwrap().1.to_vec());
-        }
+    fn test_tones() -> Result<(), Error> {
+        let (_mnemonic, shares) = split::split_shares(
+            "2of3",
+            1,
+            split::random_mnemonic(gen_random_words(), Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        let share = &shares[0][1];
+        let wav = tones::to_wav(&tones::encode_samples(share));
+        let (samples, rate) = tones::from_wav(&wav)?;
+        assert_eq!(rate, tones::SAMPLE_RATE);
+        assert_eq!(&tones::decode_samples(&samples, rate)?, share);
+
+        // As recorded on another machine: at 44.1 kHz, quieter, with noise and
+        // with the recording started early
+        let mut rng = rand::thread_rng();
+        let recorded_rate = 44100;
+        let stretch = tones::SAMPLE_RATE as f64 / recorded_rate as f64;
+        let mut recorded = (0..15000)
+            .map(|_| rng.gen_range(-0.05..0.05))
+            .collect::<Vec<f64>>();
+        recorded.extend(
+            (0..((samples.len() - 1) as f64 / stretch) as usize).map(|index| {
+                let at = index as f64 * stretch;
+                let (before, after) = (samples[at as usize], samples[at as usize + 1]);
+                let sample = before + (after - before) * at.fract();
+                sample * 0.3 + rng.gen_range(-0.05..0.05)
+            }),
+        );
+        assert_eq!(&tones::decode_samples(&recorded, recorded_rate)?, share);
+
+        // Cut off halfway, or with no tones at all
+        let cut = &recorded[..recorded.len() / 2];
+        assert!(tones::decode_samples(cut, recorded_rate).is_err());
+        assert!(tones::decode_samples(&recorded[..15000], recorded_rate).is_err());
+        assert!(tones::from_wav(b"RIFF....WAVEdata").is_err());
+        assert!(tones::from_wav(&wav[..12]).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_enough_insufficient_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, sizes, group_threshold) = gen_random_params();
-            let (_mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            let mut shares: Vec<String> = groups
-                .into_iter()
-                .zip(sizes.into_iter())
-                .map(|(group, (m, _n))| {
-                    group
-                        .into_iter()
-                        .choose_multiple(&mut rand::thread_rng(), m - 1)
-                })
-                .choose_multiple(&mut rand::thread_rng(), group_threshold)
-                .into_iter()
-                .flatten()
-                .collect();
-            if shares.len() == 0 {
-                continue;
-            }
-            shares.shuffle(&mut rand::thread_rng());
-            ensure_unrecoverable(shares);
+    fn test_error_kinds() -> Result<(), Error> {
+        use error::SskrToolError;
+        let secret: Vec<u8> = (0..16).collect();
+        let shares = split::split_raw("2of3,2of3,2of3", 2, &secret)?;
+        let line = |share: &Vec<u8>| share_format::encode(share, ShareFormat::Bytewords);
+        let kind = |error: &Error| error.downcast_ref::<SskrToolError>().cloned();
+
+        let error = recover::recover_secret(vec![line(&shares[0][0]), line(&shares[0][1])], &false)
+            .unwrap_err();
+        assert_eq!(
+            kind(&error),
+            Some(SskrToolError::InsufficientGroups {
+                needed: 2,
+                have: vec![1]
+            })
+        );
+        assert_eq!(error::exit_code(&error), error::NOT_ENOUGH_SHARES);
+        assert!(error.to_string().ends_with("only 1 are satisfied (1)"));
+
+        let identifier = split::share_position(&shares[0][0])?.0;
+        let mut other = split::split_raw("2of3", 1, &secret)?;
+        split::set_identifier(&mut other, !identifier);
+        let error = recover::recover_secret(vec![line(&shares[0][0]), line(&other[0][0])], &false)
+            .unwrap_err();
+        let Some(SskrToolError::MismatchedIdentifier { expected, found }) = kind(&error) else {
+            panic!("expected mismatched identifiers, got {:?}", error);
+        };
+        assert_eq!((expected, found), (identifier, !identifier));
+        assert_eq!(error::exit_code(&error), error::MISMATCHED_SHARES);
+
+        // The last word changed, so only the checksum is wrong
+        let encoded = line(&shares[1][0]);
+        let mut words: Vec<&str> = encoded.split(' ').collect();
+        let last = words.len() - 1;
+        words[last] = if words[last] == "able" {
+            "acid"
+        } else {
+            "able"
+        };
+        let error = recover::decode_share(&words.join(" ")).unwrap_err();
+        assert!(matches!(
+            kind(&error),
+            Some(SskrToolError::InvalidChecksum { .. })
+        ));
+        assert_eq!(error::exit_code(&error), error::INVALID_CHECKSUM);
+
+        let error = split::spec_layout("3of2", 1).unwrap_err();
+        assert_eq!(
+            kind(&error),
+            Some(SskrToolError::ThresholdAboveCount {
+                group: "3of2".to_string(),
+                threshold: 3,
+                count: 2
+            })
+        );
+        assert_eq!(error::exit_code(&error), error::INVALID_SPLIT);
+
+        // Bad entropy, word counts and specs a scheme can't use are the split's
+        for error in [
+            split::entropy_hex_mnemonic("0xzz", Language::English).unwrap_err(),
+            split::entropy_hex_mnemonic("00112233", Language::English).unwrap_err(),
+            split::parse_word_count("13").unwrap_err(),
+            split::parse_word_count("twelve").unwrap_err(),
+            split::single_group("2of3,2of3").unwrap_err(),
+        ] {
+            assert_eq!(error::exit_code(&error), error::INVALID_SPLIT, "{}", error);
         }
+        // A secret no mnemonic has doesn't end up in the error
+        let odd: Vec<u8> = (0xa0..0xb2).collect();
+        let lines = split::to_bytewords(&split::split_raw("2of3", 1, &odd)?, &false);
+        let error = recover::recover(lines[0].clone(), &false, Language::English).unwrap_err();
+        assert!(matches!(
+            kind(&error),
+            Some(SskrToolError::MnemonicFromEntropy(_))
+        ));
+        assert!(!error.to_string().contains(&hex::encode(&odd)));
+
+        let mut shares = split::split_raw("2of3", 1, &[0; 16])?;
+        split::set_identifier(&mut shares, 0x4321);
+        let mut other = split::split_raw("2of3", 1, &[0; 16])?;
+        split::set_identifier(&mut other, 0x1234);
+        let mixed = [other[0][0].clone(), shares[0][1].clone()];
+        let mismatch = split::regroup(&mixed).unwrap_err();
+        assert_eq!(kind(&mismatch), Some(SskrToolError::MixedSplits));
+        assert_eq!(error::exit_code(&mismatch), error::MISMATCHED_SHARES);
+
+        // Found through context too, and anything else exits with 1
+        let error = error.context("Spec");
+        assert_eq!(error::exit_code(&error), error::INVALID_SPLIT);
+        assert_eq!(error::exit_code(&anyhow::anyhow!("Unable to read")), 1);
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_not_enough_full_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, _sizes, group_threshold) = gen_random_params();
-            let (_mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            let mut shares: Vec<String> = groups
-                .into_iter()
-                .choose_multiple(&mut rand::thread_rng(), group_threshold - 1)
-                .into_iter()
-                .flatten()
-                .collect();
-            if shares.len() == 0 {
-                continue;
-            }
-            shares.shuffle(&mut rand::thread_rng());
-            ensure_unrecoverable(shares);
-        }
+    fn test_escrow_lodgement() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!("sskr-tool-escrow-{}", process::id()));
+        fs::create_dir_all(&dir)?;
+        let key_path = dir.join("depositor.key");
+        fs::write(&key_path, format!("{}\n", *escrow::generate_signing_key()))?;
+        let signing_key = escrow::load_signing_key(&key_path)?;
+        let signer = hex::encode(signing_key.verifying_key().as_bytes());
+        let agent = age::x25519::Identity::generate();
+        let recipient = agent.to_public().to_string();
+
+        let secret: Vec<u8> = (0..16).collect();
+        let shares = split::split_raw("2of3,2of2", 2, &secret)?;
+        let groups = split::to_bytewords(&shares, &false);
+        // Shares given twice are lodged once
+        let mut lines = groups.concat();
+        lines.push(groups[1][0].clone());
+
+        let lodgement = dir.join("lodgement");
+        let manifest = escrow::export(
+            &lines,
+            &[2],
+            &recipient,
+            &signing_key,
+            HashAlgorithm::Sha256,
+            &lodgement,
+        )?;
+        assert_eq!(
+            manifest.groups,
+            vec![escrow::LodgedGroup {
+                group: 2,
+                member_threshold: 2,
+                members: vec![1, 2]
+            }]
+        );
+        assert_eq!(escrow::verify(&lodgement, None)?, manifest);
+        assert_eq!(escrow::verify(&lodgement, Some(&signer))?, manifest);
+
+        // Only the agent can read the shares
+        let payload = fs::read(lodgement.join(escrow::PAYLOAD_FILE))?;
+        let plaintext = age::decrypt(&agent, &payload)?;
+        assert_eq!(String::from_utf8(plaintext)?, groups[1].join("\n") + "\n");
+
+        // Signed by someone else, or altered after signing
+        let other = ed25519_dalek::SigningKey::from_bytes(&[7; 32]).verifying_key();
+        let other = hex::encode(other.as_bytes());
+        assert!(escrow::verify(&lodgement, Some(&other)).is_err());
+        let path = lodgement.join(escrow::MANIFEST_FILE);
+        let json = fs::read_to_string(&path)?;
+        let altered = json.replace("\"members\": [\n        1,", "\"members\": [\n        3,");
+        assert_ne!(altered, json);
+        fs::write(&path, altered)?;
+        assert!(escrow::verify(&lodgement, None).is_err());
+
+        // Groups with no shares given, and shares of different splits
+        let export = |lines: &[String], groups: &[usize]| {
+            escrow::export(
+                lines,
+                groups,
+                &recipient,
+                &signing_key,
+                HashAlgorithm::Sha256,
+                &lodgement,
+            )
+        };
+        assert!(export(&groups[0], &[2]).is_err());
+        let mut foreign = split::split_raw("2of3", 1, &secret)?;
+        split::set_identifier(&mut foreign, !split::share_position(&shares[0][0])?.0);
+        let foreign = split::to_bytewords(&foreign, &false);
+        assert!(export(&[groups[0][0].clone(), foreign[0][0].clone()], &[1]).is_err());
+        assert!(export(&lines, &[1]).is_ok());
+        assert!(Cli::try_parse_from([
+            "sskr-tool",
+            "escrow",
+            "export",
+            "shares.txt",
+            "--group",
+            "17",
+            "--recipient",
+            &recipient,
+            "--signing-key",
+            "depositor.key",
+            "--out-dir",
+            "lodgement"
+        ])
+        .is_err());
+        fs::remove_dir_all(&dir)?;
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_not_enough_sufficient_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, sizes, group_threshold) = gen_random_params();
-            let (_mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            let mut shares: Vec<String> = groups
-                .into_iter()
-                .zip(sizes.into_iter())
-                .map(|(group, (m, _n))| {
-                    group
-                        .into_iter()
-                        .choose_multiple(&mut rand::thread_rng(), m)
-                })
-                .choose_multiple(&mut rand::thread_rng(), group_threshold - 1)
-                .into_iter()
-                .flatten()
-                .collect();
-            if shares.len() == 0 {
-                continue;
-            }
-            shares.shuffle(&mut rand::thread_rng());
-            ensure_unrecoverable(shares);
+    fn test_split_profiles() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!("sskr-tool-profiles-{}", process::id()));
+        let store = dir.join("profiles");
+        fs::create_dir_all(&dir)?;
+        let contents = "# Quarterly rotation
+spec = \"family=2of3,lawyers=2of2\"
+group_threshold = 2
+custodians = [[\"Alice\", \"Bob\", \"Carol\"], [\"Dewey\", \"Lee\"]]
+words = 24
+share_format = \"cbor-hex\"
+out_dir = \"shares\"
+name_template = \"{custodian}\"
+checksums = true
+paranoid = true
+";
+        let file = dir.join("quarterly.toml");
+        fs::write(&file, contents)?;
+        profile::save(&store, "quarterly", &file)?;
+        assert_eq!(profile::list(&store)?, vec!["quarterly"]);
+        assert_eq!(profile::export(&store, "quarterly")?, contents);
+
+        let split_with = |args: &[&str]| -> Result<(SplitArgs, bool), Error> {
+            let matches =
+                Cli::command().try_get_matches_from(["sskr-tool", "split"].iter().chain(args))?;
+            let mut cli = Cli::from_arg_matches(&matches)?;
+            with_profile(&mut cli, &matches, || Ok(store.clone()))?;
+            let Commands::Split(args) = cli.command else {
+                panic!("not a split");
+            };
+            Ok((*args, cli.paranoid))
+        };
+        // What the command line gives wins, the rest comes from the profile
+        let (args, paranoid) = split_with(&["--profile", "quarterly", "--words", "12"])?;
+        assert_eq!(args.spec(), "family=2of3,lawyers=2of2");
+        assert_eq!(args.group_threshold.as_deref(), Some("2"));
+        assert_eq!(args.words.word_count(), 12);
+        assert_eq!(args.share_format, SplitFormat::CborHex);
+        assert_eq!(args.out_dir.as_deref(), Some("shares"));
+        assert_eq!(args.custodians[1], vec!["Dewey", "Lee"]);
+        assert!(args.checksums && paranoid && !args.confirm);
+        let (args, _) = split_with(&["--profile", "quarterly", "--share-format", "bytewords"])?;
+        assert_eq!(args.share_format, SplitFormat::Bytewords);
+        assert_eq!(args.words.word_count(), 24);
+        // The custodians are named for the profile's spec, not another one
+        let (args, _) = split_with(&["2of3", "--profile", "quarterly"])?;
+        assert_eq!(
+            (args.spec(), args.group_threshold.as_deref()),
+            ("2of3", None)
+        );
+        assert!(args.custodians.is_empty());
+        assert!(split_with(&["--profile", "monthly"]).is_err());
+        assert!(split_with(&["--profile", "quarterly", "--batch", "seeds.toml"]).is_err());
+
+        // Shares are named after their custodians
+        let (_mnemonic, shares) = split::split_shares(
+            "family=2of3,lawyers=2of2",
+            2,
+            split::random_mnemonic(MnemonicType::Words12, Language::English).phrase(),
+            Some(Language::English),
+        )?;
+        let seed = SeedManifest::from_shares(
+            &split::to_bytewords(&shares, &false),
+            &false,
+            Language::English,
+        )?
+        .with_custodians(&profile::load(&store, "quarterly")?.custodians);
+        let name = naming::ShareName {
+            seed: &seed,
+            group: 2,
+            member: 1,
+            members: 2,
+        };
+        assert_eq!(naming::render_share_name("{custodian}", &name)?, "Dewey");
+
+        // Custodians that don't fit the spec, unknown keys and values, and
+        // names that aren't file names
+        for invalid in [
+            "spec = \"2of3\"\ncustodians = [[\"Alice\", \"Bob\"]]",
+            "spec = \"2of3\"\ncustodians = [[\"Alice\", \"Bob\", \" \"]]",
+            "spec = \"2of3,2of2\"\ncustodians = [[\"Alice\", \"Bob\", \"Carol\"]]",
+            "spec = \"2of3\"\nshare_format = \"base64\"",
+            "spec = \"2of3\"\nwords = 13",
+            "spec = \"2of3\"\nthreshold = 2",
+            "spec = \"2of3,2of2\"",
+        ] {
+            assert!(profile::Profile::parse(invalid).is_err(), "{}", invalid);
         }
+        assert!(profile::save(&store, "../quarterly", &file).is_err());
+        fs::remove_dir_all(&dir)?;
         Ok(())
     }
 
     #[test]
-    fn test_roundtrip_not_enough_insufficient_groups() -> Result<(), Error> {
-        for _ in 0..TEST_ITERATIONS {
-            let (spec, sizes, group_threshold) = gen_random_params();
-            let (_mnemonic, groups) = split::split_random_phrase(&spec, group_threshold)?;
-            let mut shares: Vec<String> = groups
-                .into_iter()
-                .zip(sizes.into_iter())
-                .map(|(group, (m, _n))| {
-                    group
-                        .into_iter()
-                        .choose_multiple(&mut rand::thread_rng(), m - 1)
-                })
-                .choose_multiple(&mut rand::thread_rng(), group_threshold - 1)
-                .into_iter()
-                .flatten()
-                .collect();
-            if shares.len() == 0 {
-                continue;
-            }
-            shares.shuffle(&mut rand::thread_rng());
-            ensure_unrecoverable(shares);
-        }
+    fn test_secrets_are_wiped_and_kept_off_the_command_line() -> Result<(), Error> {
+        let mnemonic = split::random_mnemonic(gen_random_words(), Language::English);
+        let (_mnemonic, shares) =
+            split::split_shares("2of3", 1, mnemonic.phrase(), Some(Language::English))?;
+        let lines = split::to_bytewords(&shares, &false)[0].clone();
+        let secret: Zeroizing<Vec<u8>> = recover::recover_secret(lines, &false)?;
+        assert_eq!(secret.as_slice(), mnemonic.entropy());
+
+        // --no-echo takes the place of the mnemonic argument
+        let parse = |arguments: &[&str]| Cli::try_parse_from([&["sskr-tool"], arguments].concat());
+        assert!(parse(&["split", "2of3", "--no-echo"]).is_ok());
+        assert!(parse(&["split", "2of3,2of3", "1", "abandon", "--no-echo"]).is_err());
+        assert!(parse(&["split", "2of3", "--no-echo", "--entropy", "coin:0"]).is_err());
+        assert!(parse(&["verify", "shares.txt", "--no-echo"]).is_ok());
+        assert!(parse(&["verify", "shares.txt"]).is_err());
+        let Commands::Split(args) = parse(&["split", "2of3", "abandon", "--no-echo"])?.command
+        else {
+            unreachable!()
+        };
+        assert!(split_parameters(&args).is_err());
         Ok(())
     }
 
     fn ensure_recoverable(expected: &Mnemonic, shares: Vec<String>) -> Result<(), Error> {
-        let recovered = recover::recover(shares)?;
+        let recovered = recover::recover(shares, &false, Language::English)?;
         assert_eq!(recovered.phrase(), expected.phrase());
         Ok(())
     }
 
     fn ensure_unrecoverable(shares: Vec<String>) {
-        let recovered = recover::recover(shares);
+        let recovered = recover::recover(shares, &false, Language::English);
         assert!(recovered.is_err());
     }
 
+    fn gen_random_words() -> MnemonicType {
+        *[
+            MnemonicType::Words12,
+            MnemonicType::Words15,
+            MnemonicType::Words18,
+            MnemonicType::Words21,
+            MnemonicType::Words24,
+        ]
+        .choose(&mut rand::thread_rng())
+        .unwrap()
+    }
+
     fn gen_random_params() -> (String, Vec<(usize, usize)>, usize) {
         let total_groups = rand::thread_rng().gen_range(1..=16);
         let group_threshold = rand::thread_rng().gen_range(1..=total_groups);
@@ -0,0 +1,271 @@
+use crate::drill::ShareChecks;
+use crate::dual_control::DualControl;
+use crate::hash::{Hash, HashAlgorithm};
+use crate::language::MnemonicLanguage;
+use crate::policy::{self, Channel, Field};
+use crate::recover::decode_share;
+use crate::sskr_shares::share_metadata;
+use anyhow::{bail, Context, Error};
+use bip39::Language;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MANIFEST_VERSION: u32 = 2;
+
+static FINGERPRINT_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[0-9a-f]{8}$").unwrap());
+
+// A custody arrangement: one or more seeds, each split into its own share set,
+// typically held by the same custodians.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub version: u32,
+    pub seeds: Vec<SeedManifest>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SeedManifest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    pub identifier: String,
+    // When the split was made, as a Unix time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    pub group_threshold: usize,
+    pub groups: Vec<ManifestGroup>,
+    pub minimal: bool,
+    pub language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dual_control: Option<DualControl>,
+    // The check words of the passphrase a split with --encrypt used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase_check: Option<String>,
+    // The salt of that passphrase, when it didn't fit in the shares
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase_salt: Option<String>,
+    // For custodians to drill on their share against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_checks: Option<ShareChecks>,
+    // Files written next to the manifest, so `verify-media` can tell when one
+    // of them has rotted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<ArtifactHash>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArtifactHash {
+    // Relative to the directory of the manifest
+    pub path: String,
+    #[serde(flatten)]
+    pub hash: Hash,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestGroup {
+    pub group: usize,
+    // The name given to the group in the spec, e.g. "lawyers"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub member_threshold: usize,
+    pub member_count: usize,
+    // Who holds each member's share, in order, when a profile names them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custodians: Vec<String>,
+}
+
+// Version 1 manifests described exactly one seed at the top level
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnyManifest {
+    Current(Manifest),
+    SingleSeed(Box<SeedManifest>),
+}
+
+impl Manifest {
+    pub fn new(seed: SeedManifest) -> Manifest {
+        Manifest {
+            version: MANIFEST_VERSION,
+            seeds: vec![seed],
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Manifest, Error> {
+        Ok(match serde_json::from_str(json)? {
+            AnyManifest::Current(manifest) => manifest,
+            AnyManifest::SingleSeed(seed) => Manifest::new(*seed),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Manifest, Error> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read manifest \"{}\"", path.display()))?;
+        Manifest::from_json(&json)
+            .with_context(|| format!("Invalid manifest \"{}\"", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, self.to_json()? + "\n")
+            .with_context(|| format!("Unable to write manifest \"{}\"", path.display()))
+    }
+
+    pub fn add_seed(&mut self, seed: SeedManifest) -> Result<(), Error> {
+        if self.seeds.iter().any(|s| s.identifier == seed.identifier) {
+            bail!(
+                "Manifest already describes a seed with identifier {}",
+                seed.identifier
+            );
+        }
+        self.seeds.push(seed);
+        Ok(())
+    }
+}
+
+impl SeedManifest {
+    pub fn from_shares(
+        groups: &[Vec<String>],
+        minimal: &bool,
+        language: Language,
+    ) -> Result<SeedManifest, Error> {
+        let mut identifier = None;
+        let mut group_threshold = 0;
+        let mut manifest_groups = vec![];
+
+        for group in groups {
+            let Some(first_share) = group.first() else {
+                bail!("Cannot describe an empty group");
+            };
+            let (id, meta) = share_metadata(&decode_share(first_share)?, minimal)?;
+
+            identifier = Some(id);
+            group_threshold = meta[1];
+            manifest_groups.push(ManifestGroup {
+                group: meta[0] + 1,
+                label: None,
+                member_threshold: meta[4],
+                member_count: group.len(),
+                custodians: vec![],
+            });
+        }
+
+        let Some(identifier) = identifier else {
+            bail!("Cannot describe a split without any groups");
+        };
+
+        Ok(SeedManifest {
+            name: None,
+            purpose: None,
+            fingerprint: None,
+            identifier: format!("{:04x}", identifier),
+            created: None,
+            group_threshold,
+            groups: manifest_groups,
+            minimal: *minimal,
+            language: MnemonicLanguage::from_bip39(language).name().to_string(),
+            dual_control: None,
+            passphrase_check: None,
+            passphrase_salt: None,
+            share_checks: None,
+            artifacts: vec![],
+        })
+    }
+
+    pub fn with_details(
+        mut self,
+        name: &Option<String>,
+        purpose: &Option<String>,
+        fingerprint: &Option<String>,
+    ) -> Result<SeedManifest, Error> {
+        if let Some(fingerprint) = fingerprint {
+            // A manifest is made to be written down
+            policy::check(Field::Fingerprints, Channel::Disk)?;
+            let fingerprint = fingerprint.to_lowercase();
+            if !FINGERPRINT_REGEX.is_match(&fingerprint) {
+                bail!(
+                    "Invalid fingerprint \"{}\", expected 8 hex digits",
+                    fingerprint
+                );
+            }
+            self.fingerprint = Some(fingerprint);
+        }
+        self.name = name.clone();
+        self.purpose = purpose.clone();
+        Ok(self)
+    }
+
+    // Names the groups in order, as the spec of the split did
+    pub fn with_group_labels(mut self, labels: &[Option<String>]) -> SeedManifest {
+        for (group, label) in self.groups.iter_mut().zip(labels) {
+            group.label = label.clone();
+        }
+        self
+    }
+
+    // Names the custodians of each group's members, in order
+    pub fn with_custodians(mut self, custodians: &[Vec<String>]) -> SeedManifest {
+        for (group, names) in self.groups.iter_mut().zip(custodians) {
+            group.custodians = names.clone();
+        }
+        self
+    }
+
+    pub fn with_share_checks(
+        mut self,
+        shares: &[Vec<Vec<u8>>],
+        algorithm: HashAlgorithm,
+    ) -> SeedManifest {
+        self.share_checks = Some(ShareChecks::new(shares, algorithm));
+        self
+    }
+
+    pub fn with_creation_time(mut self) -> SeedManifest {
+        self.created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|duration| duration.as_secs());
+        self
+    }
+
+    // How to refer to a group: by its name if it has one
+    pub fn group_title(&self, group: usize) -> String {
+        match self
+            .groups
+            .iter()
+            .find(|described| described.group == group)
+            .and_then(|described| described.label.as_ref())
+        {
+            Some(label) => format!("{} (group {})", label, group),
+            None => format!("Group {}", group),
+        }
+    }
+
+    // The command that recovers the seed from a file of its shares
+    pub fn recover_command(&self) -> String {
+        format!(
+            "sskr-tool recover shares.txt{}{}",
+            if self.minimal { " --minimal" } else { "" },
+            if self.language == "english" {
+                String::new()
+            } else {
+                format!(" --language {}", self.language)
+            }
+        )
+    }
+
+    pub fn title(&self) -> String {
+        match &self.name {
+            Some(name) => format!("\"{}\" (identifier {})", name, self.identifier),
+            None => format!("Identifier {}", self.identifier),
+        }
+    }
+}
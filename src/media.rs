@@ -0,0 +1,61 @@
+use crate::manifest::Manifest;
+use anyhow::{bail, Error};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaOutcome {
+    Intact,
+    // Readable, but not what was written
+    Corrupted,
+    Missing,
+    Unreadable,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MediaCheck {
+    pub identifier: String,
+    pub path: String,
+    pub outcome: MediaOutcome,
+}
+
+// Re-reads every file the manifest on the media recorded when it was written
+// and compares it with its hash. The manifest itself has to parse, so a
+// rotted manifest fails the check as a whole.
+pub fn verify_media(dir: &Path, manifest: Option<&Path>) -> Result<Vec<MediaCheck>, Error> {
+    let manifest_path = manifest.map_or_else(|| dir.join(MANIFEST_FILE), Path::to_path_buf);
+    let manifest = Manifest::load(&manifest_path)?;
+    if manifest.seeds.iter().all(|seed| seed.artifacts.is_empty()) {
+        bail!(
+            "Manifest \"{}\" records no files to check, it was not written with the shares",
+            manifest_path.display()
+        );
+    }
+
+    let mut checks = vec![];
+    for seed in &manifest.seeds {
+        for artifact in &seed.artifacts {
+            let path = dir.join(&artifact.path);
+            let outcome = if !path.exists() {
+                MediaOutcome::Missing
+            } else {
+                match fs::read(&path) {
+                    Ok(contents) if artifact.hash.matches(&contents) => MediaOutcome::Intact,
+                    Ok(_) => MediaOutcome::Corrupted,
+                    Err(_) => MediaOutcome::Unreadable,
+                }
+            };
+            checks.push(MediaCheck {
+                identifier: seed.identifier.clone(),
+                path: artifact.path.clone(),
+                outcome,
+            });
+        }
+    }
+
+    Ok(checks)
+}
@@ -0,0 +1,245 @@
+use crate::bundle::BUNDLE_FORMAT;
+use crate::journal;
+use crate::manifest::{Manifest, MANIFEST_VERSION};
+use crate::output::SplitOutput;
+use crate::recover::decode_share;
+use anyhow::{bail, Context, Error};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// The fields of a split document that aren't derived from its shares, kept as
+// they are when the shares are rebuilt
+const SECRET_FIELDS: [&str; 5] = ["entropy", "mnemonic", "language", "bytewords", "codex32"];
+const SHARE_FIELDS: [&str; 2] = ["envelope", "segments"];
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Manifest,
+    SplitDocument,
+    Journal,
+    Bundle,
+}
+
+impl ArtifactKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArtifactKind::Manifest => "custody manifest",
+            ArtifactKind::SplitDocument => "split document (--output json)",
+            ArtifactKind::Journal => "session journal",
+            ArtifactKind::Bundle => "archival bundle",
+        }
+    }
+}
+
+// What an artifact is, and what it becomes in the current format. `contents`
+// is only set when something changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub kind: ArtifactKind,
+    pub changes: Vec<String>,
+    pub contents: Option<String>,
+}
+
+// Recognizes an artifact written by this tool, of any release, and upgrades
+// it to what the current release writes. Journals and bundles are sealed by
+// hashes over their contents, and have only had one format so far, so they
+// are only checked for being one this release reads.
+pub fn migrate(contents: &str) -> Result<Migration, Error> {
+    if contents.starts_with("# sskr-tool session journal") {
+        return current_journal(contents);
+    }
+    let value: Value = serde_json::from_str(contents)
+        .context("Not an artifact of this tool: not a session journal, nor JSON")?;
+    let Some(object) = value.as_object() else {
+        bail!("Not an artifact of this tool: the JSON isn't an object");
+    };
+
+    if let Some(format) = object.get("format").and_then(Value::as_str) {
+        if format != BUNDLE_FORMAT {
+            bail!(
+                "Unsupported bundle format \"{}\", it is newer than this release",
+                format
+            );
+        }
+        return Ok(Migration {
+            kind: ArtifactKind::Bundle,
+            changes: vec![],
+            contents: None,
+        });
+    }
+    if object.contains_key("seeds")
+        || (object.contains_key("identifier") && !object.contains_key("spec"))
+    {
+        return migrate_manifest(contents, object);
+    }
+    // Codex32 splits have had one format so far
+    if object.contains_key("spec") && object.contains_key("threshold") {
+        return Ok(Migration {
+            kind: ArtifactKind::SplitDocument,
+            changes: vec![],
+            contents: None,
+        });
+    }
+    if object.contains_key("spec") && object.contains_key("groups") {
+        return migrate_split_document(object);
+    }
+    bail!("Not an artifact of this tool: the JSON isn't a manifest, split document or bundle")
+}
+
+fn current_journal(contents: &str) -> Result<Migration, Error> {
+    let header = contents.lines().next().unwrap_or_default();
+    if header != journal::HEADER {
+        bail!(
+            "Unsupported journal \"{}\", it is newer than this release",
+            header
+        );
+    }
+    Ok(Migration {
+        kind: ArtifactKind::Journal,
+        changes: vec![],
+        contents: None,
+    })
+}
+
+// Version 1 manifests described one seed at the top level, without a version
+fn migrate_manifest(contents: &str, object: &Map<String, Value>) -> Result<Migration, Error> {
+    let version = object.get("version").and_then(Value::as_u64).unwrap_or(1) as u32;
+    if version > MANIFEST_VERSION {
+        bail!(
+            "Manifest version {} is newer than this release, which writes version {}",
+            version,
+            MANIFEST_VERSION
+        );
+    }
+    let manifest = Manifest::from_json(contents).context("Invalid manifest")?;
+    if version == MANIFEST_VERSION {
+        return Ok(Migration {
+            kind: ArtifactKind::Manifest,
+            changes: vec![],
+            contents: None,
+        });
+    }
+    Ok(Migration {
+        kind: ArtifactKind::Manifest,
+        changes: vec![format!(
+            "version {} to {}, the seed moved into \"seeds\"",
+            version, MANIFEST_VERSION
+        )],
+        contents: Some(manifest.to_json()? + "\n"),
+    })
+}
+
+// Documents of earlier releases lack fields added to shares since, like hex,
+// cbor_hex and checksum. They are rebuilt from the shares' bytewords, keeping
+// the secret and the envelopes and segments as they were.
+fn migrate_split_document(object: &Map<String, Value>) -> Result<Migration, Error> {
+    let spec = object["spec"].as_str().context("The spec isn't a string")?;
+    let group_threshold = object
+        .get("group_threshold")
+        .and_then(Value::as_u64)
+        .context("The document has no group threshold")? as usize;
+    let groups = object["groups"]
+        .as_array()
+        .context("The groups aren't a list")?;
+
+    let mut shares = vec![];
+    for group in groups {
+        let mut members = vec![];
+        for share in group["shares"]
+            .as_array()
+            .context("A group has no shares")?
+        {
+            let bytewords = share["bytewords"]
+                .as_str()
+                .context("A share has no bytewords")?;
+            members.push(decode_share(bytewords)?);
+        }
+        shares.push(members);
+    }
+
+    let rebuilt = serde_json::to_value(SplitOutput::shares(spec, group_threshold, &shares)?)?;
+    let Value::Object(mut rebuilt) = rebuilt else {
+        bail!("A split document is an object");
+    };
+    if object.get("identifier") != rebuilt.get("identifier") {
+        bail!("The shares don't match the identifier of the document");
+    }
+    for field in SECRET_FIELDS {
+        if let Some(value) = object.get(field) {
+            rebuilt.insert(field.to_string(), value.clone());
+        }
+    }
+    if let Some(Value::Array(rebuilt_groups)) = rebuilt.get_mut("groups") {
+        for (rebuilt_group, group) in rebuilt_groups.iter_mut().zip(groups) {
+            let old_shares = group["shares"].as_array().into_iter().flatten();
+            let Some(new_shares) = rebuilt_group["shares"].as_array_mut() else {
+                continue;
+            };
+            for (new_share, old_share) in new_shares.iter_mut().zip(old_shares) {
+                for field in SHARE_FIELDS {
+                    if let Some(value) = old_share.get(field) {
+                        new_share[field] = value.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    let rebuilt = Value::Object(rebuilt);
+    if rebuilt == Value::Object(object.clone()) {
+        return Ok(Migration {
+            kind: ArtifactKind::SplitDocument,
+            changes: vec![],
+            contents: None,
+        });
+    }
+    let added = added_fields(object, &rebuilt);
+    let change = if added.is_empty() {
+        "shares rebuilt as this release writes them".to_string()
+    } else {
+        format!("shares rebuilt with {}", added.join(", "))
+    };
+    Ok(Migration {
+        kind: ArtifactKind::SplitDocument,
+        changes: vec![change],
+        contents: Some(serde_json::to_string_pretty(&rebuilt)? + "\n"),
+    })
+}
+
+// The fields of the first share the rebuilt document has and the old one
+// lacked, as "checksum" or "metadata.secret_length"
+fn added_fields(old: &Map<String, Value>, new: &Value) -> Vec<String> {
+    let first_share = |groups: Option<&Value>| {
+        let mut fields = vec![];
+        if let Some(share) = groups.map(|groups| &groups[0]["shares"][0]) {
+            field_paths(share, "", &mut fields);
+        }
+        fields
+    };
+    let old_fields = first_share(old.get("groups"));
+    let added: Vec<String> = first_share(new.get("groups"))
+        .into_iter()
+        .filter(|field| !old_fields.contains(field))
+        .collect();
+    // The fields of a new field are new as well, and not listed
+    added
+        .iter()
+        .filter(|field| {
+            !added
+                .iter()
+                .any(|parent| field.starts_with(&format!("{}.", parent)))
+        })
+        .cloned()
+        .collect()
+}
+
+fn field_paths(value: &Value, prefix: &str, paths: &mut Vec<String>) {
+    if let Value::Object(object) = value {
+        for (key, value) in object {
+            let path = format!("{}{}", prefix, key);
+            field_paths(value, &format!("{}.", path), paths);
+            paths.push(path);
+        }
+    }
+}
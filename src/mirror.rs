@@ -0,0 +1,64 @@
+use crate::manifest::{Manifest, SeedManifest};
+use anyhow::{bail, Context, Error};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Writes the record of a split (its manifest, which holds no shares or
+// secrets) to every device, given as the directory it is mounted on. Each copy
+// is flushed to the device and read back before it counts as written.
+pub fn mirror_record(devices: &[String], seed: &SeedManifest) -> Result<Vec<PathBuf>, Error> {
+    let contents = Manifest::new(seed.clone()).to_json()? + "\n";
+    let file_name = format!("sskr-split-{}.json", seed.identifier);
+
+    // Check every device up front, so a typo doesn't leave some written
+    let mut mount_points: Vec<PathBuf> = vec![];
+    for device in devices {
+        let mount_point = Path::new(device)
+            .canonicalize()
+            .with_context(|| format!("Device \"{}\" is not mounted", device))?;
+        if !mount_point.is_dir() {
+            bail!("Device \"{}\" is not a mounted directory", device);
+        }
+        if mount_points.contains(&mount_point) {
+            bail!(
+                "\"{}\" is given twice, mirrors need separate devices",
+                mount_point.display()
+            );
+        }
+        let path = mount_point.join(&file_name);
+        if path.exists() && fs::read_to_string(&path).ok().as_ref() != Some(&contents) {
+            bail!(
+                "\"{}\" already holds a different record, refusing to overwrite it",
+                path.display()
+            );
+        }
+        mount_points.push(mount_point);
+    }
+
+    let mut paths = vec![];
+    for mount_point in mount_points {
+        let path = mount_point.join(&file_name);
+        write_synced(&path, &contents)
+            .with_context(|| format!("Unable to write \"{}\"", path.display()))?;
+
+        let read_back = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read back \"{}\"", path.display()))?;
+        if read_back != contents || Manifest::from_json(&read_back)?.seeds != [seed.clone()] {
+            bail!(
+                "\"{}\" doesn't read back as written, the device may be failing",
+                path.display()
+            );
+        }
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+fn write_synced(path: &Path, contents: &str) -> Result<(), Error> {
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use crate::manifest::SeedManifest;
+use anyhow::{bail, Error};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+pub const DEFAULT_SHARE_TEMPLATE: &str = "share-g{group}-m{member}-of-{members}";
+
+// Characters some filesystem in use (FAT, NTFS, APFS, ext4) doesn't allow in
+// a file name
+const RESERVED_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+// Names Windows keeps for devices, whatever their extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+// Well under the 255 bytes filesystems allow, leaving room for an extension
+const MAX_NAME_BYTES: usize = 200;
+
+static PLACEHOLDER_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{([a-z]*)\}").unwrap());
+
+pub struct ShareName<'a> {
+    pub seed: &'a SeedManifest,
+    pub group: usize,
+    pub member: usize,
+    pub members: usize,
+}
+
+// A name template, and whether the names it renders are kept to ASCII
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTemplate {
+    pub template: String,
+    pub ascii: bool,
+}
+
+impl NameTemplate {
+    pub fn new(template: &str) -> NameTemplate {
+        NameTemplate {
+            template: template.to_string(),
+            ascii: false,
+        }
+    }
+
+    pub fn ascii(self, ascii: bool) -> NameTemplate {
+        NameTemplate { ascii, ..self }
+    }
+
+    pub fn render(&self, share: &ShareName) -> Result<String, Error> {
+        let name = render_share_name(&self.template, share)?;
+        Ok(match self.ascii {
+            true => portable_name(&transliterate(&name)),
+            false => name,
+        })
+    }
+}
+
+impl Default for NameTemplate {
+    fn default() -> NameTemplate {
+        NameTemplate::new(DEFAULT_SHARE_TEMPLATE)
+    }
+}
+
+// Renders the file name (without extension) of a per-share artifact.
+// Supported placeholders: {identifier}, {name}, {fingerprint}, {group},
+// {label}, {member}, {members}, {threshold} and {custodian}.
+//
+// The names are the same on every OS the files may be restored to years
+// later: characters a filesystem doesn't allow in the values of placeholders
+// become "_", text is NFC-normalized the way macOS and Linux would otherwise
+// disagree on, and Windows device names and overlong names are avoided.
+pub fn render_share_name(template: &str, share: &ShareName) -> Result<String, Error> {
+    let literal = PLACEHOLDER_REGEX.replace_all(template, "");
+    if literal
+        .chars()
+        .any(|c| RESERVED_CHARS.contains(&c) || c.is_control())
+    {
+        bail!(
+            "Name template \"{}\" does not produce a valid file name",
+            template
+        );
+    }
+
+    let mut error = None;
+
+    let rendered = PLACEHOLDER_REGEX.replace_all(template, |captures: &regex::Captures| {
+        let value = match &captures[1] {
+            "identifier" => Ok(share.seed.identifier.clone()),
+            "name" => share
+                .seed
+                .name
+                .clone()
+                .ok_or("{name} needs a seed name (--seed-name)"),
+            "fingerprint" => share
+                .seed
+                .fingerprint
+                .clone()
+                .ok_or("{fingerprint} needs a fingerprint (--fingerprint)"),
+            "group" => Ok(share.group.to_string()),
+            // Groups without a name in the spec fall back to their number
+            "label" => Ok(share
+                .seed
+                .groups
+                .get(share.group - 1)
+                .and_then(|group| group.label.clone())
+                .unwrap_or_else(|| format!("group{}", share.group))),
+            "member" => Ok(share.member.to_string()),
+            "members" => Ok(share.members.to_string()),
+            "threshold" => Ok(share
+                .seed
+                .groups
+                .get(share.group - 1)
+                .map_or(0, |group| group.member_threshold)
+                .to_string()),
+            // Custodians a profile doesn't name are identified by their position
+            "custodian" => Ok(share
+                .seed
+                .groups
+                .get(share.group - 1)
+                .and_then(|group| group.custodians.get(share.member - 1))
+                .cloned()
+                .unwrap_or_else(|| format!("group{}-member{}", share.group, share.member))),
+            _ => Err("unknown placeholder"),
+        };
+        match value {
+            Ok(value) => sanitize_value(&value),
+            Err(message) => {
+                error.get_or_insert(format!("{} in \"{}\"", message, &captures[0]));
+                String::new()
+            }
+        }
+    });
+
+    if let Some(error) = error {
+        bail!("Invalid name template: {}", error);
+    }
+    let name = portable_name(&rendered);
+    if name.is_empty() {
+        bail!(
+            "Name template \"{}\" does not produce a valid file name",
+            template
+        );
+    }
+    Ok(name)
+}
+
+fn sanitize_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match RESERVED_CHARS.contains(&c) || c.is_control() {
+            true => '_',
+            false => c,
+        })
+        .collect()
+}
+
+// Windows drops trailing dots and spaces, and Unix hides names with a leading
+// dot, so neither is kept
+fn portable_name(name: &str) -> String {
+    let name = name.nfc().collect::<String>();
+    let mut name = name
+        .trim()
+        .trim_end_matches(['.', ' '])
+        .trim_start_matches('.')
+        .to_string();
+    let stem = name.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        name.insert(stem.len(), '_');
+    }
+    if name.len() > MAX_NAME_BYTES {
+        let mut end = MAX_NAME_BYTES;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+    }
+    name
+}
+
+// Accents are dropped and letters without a decomposition spelled out, the
+// same way every time; anything else outside ASCII becomes "_"
+fn transliterate(name: &str) -> String {
+    let mut ascii = String::new();
+    for c in name.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            c if c.is_ascii() => ascii.push(c),
+            'ß' => ascii.push_str("ss"),
+            'æ' => ascii.push_str("ae"),
+            'Æ' => ascii.push_str("AE"),
+            'œ' => ascii.push_str("oe"),
+            'Œ' => ascii.push_str("OE"),
+            'þ' => ascii.push_str("th"),
+            'Þ' => ascii.push_str("Th"),
+            'ø' => ascii.push('o'),
+            'Ø' => ascii.push('O'),
+            'ł' => ascii.push('l'),
+            'Ł' => ascii.push('L'),
+            'đ' | 'ð' => ascii.push('d'),
+            'Đ' | 'Ð' => ascii.push('D'),
+            'ı' => ascii.push('i'),
+            _ => ascii.push('_'),
+        }
+    }
+    ascii
+}
+
+// Names that differ only in case are the same file on Windows and macOS
+pub fn ensure_unique_names(names: &[String]) -> Result<(), Error> {
+    let mut seen = HashMap::new();
+    for name in names {
+        if let Some(other) = seen.insert(name.to_lowercase(), name) {
+            if other == name {
+                bail!(
+                    "Name template produces \"{}\" more than once, include {{group}} and {{member}}",
+                    name
+                );
+            }
+            bail!(
+                "Name template produces \"{}\" and \"{}\", which are the same file on Windows and macOS",
+                other,
+                name
+            );
+        }
+    }
+    Ok(())
+}
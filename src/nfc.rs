@@ -0,0 +1,128 @@
+use crate::share_format::{decode_as, encode, ShareFormat};
+use crate::smartcard::CardTransport;
+use anyhow::{bail, Error};
+use zeroize::Zeroizing;
+
+// NTAG21x user memory starts at page 4, pages are 4 bytes
+const FIRST_PAGE: u8 = 4;
+const PAGE_SIZE: usize = 4;
+// The smallest of the family, NTAG213, has 36 pages of user memory
+const MAX_PAGES: usize = 36;
+
+const NDEF_TLV: u8 = 0x03;
+const TERMINATOR_TLV: u8 = 0xfe;
+const NULL_TLV: u8 = 0x00;
+
+// A share on an NFC tag, as an NDEF URI record holding the ur:sskr string,
+// so a phone that reads the tag shows the share as it is. The tag is reached
+// through the storage card commands of PC/SC readers (PC/SC part 3).
+pub struct NfcTag<T: CardTransport> {
+    transport: T,
+}
+
+impl<T: CardTransport> NfcTag<T> {
+    pub fn new(transport: T) -> NfcTag<T> {
+        NfcTag { transport }
+    }
+
+    // Writes the share and reads it back to compare before returning
+    pub fn write_share(&mut self, share: &[u8]) -> Result<(), Error> {
+        let message = ndef_uri_record(&encode(share, ShareFormat::Ur));
+        if message.len() > 0xfe {
+            bail!("The share is too long for an NDEF record on a tag");
+        }
+        let mut data = vec![NDEF_TLV, message.len() as u8];
+        data.extend(message);
+        data.push(TERMINATOR_TLV);
+        data.resize(data.len().div_ceil(PAGE_SIZE) * PAGE_SIZE, NULL_TLV);
+        if data.len() > MAX_PAGES * PAGE_SIZE {
+            bail!("The share doesn't fit on the tag");
+        }
+
+        for (page, bytes) in data.chunks(PAGE_SIZE).enumerate() {
+            let mut update = vec![0xff, 0xd6, 0x00, FIRST_PAGE + page as u8, PAGE_SIZE as u8];
+            update.extend(bytes);
+            response(&self.transport.transmit(&update)?)?;
+        }
+
+        if *self.read_share()? != share {
+            bail!("The share read back from the tag doesn't match what was written");
+        }
+        Ok(())
+    }
+
+    pub fn read_share(&mut self) -> Result<Zeroizing<Vec<u8>>, Error> {
+        let mut memory = Zeroizing::new(vec![]);
+        // Four pages come back per read, until the NDEF message is complete
+        for page in (0..MAX_PAGES).step_by(4) {
+            let read = [0xff, 0xb0, 0x00, FIRST_PAGE + page as u8, 16];
+            memory.extend(response(&self.transport.transmit(&read)?)?);
+            if let Some(uri) = ndef_message(&memory)? {
+                return Ok(Zeroizing::new(decode_as(&uri, ShareFormat::Ur)?));
+            }
+        }
+        bail!("The tag holds no NDEF message")
+    }
+}
+
+fn ndef_uri_record(uri: &str) -> Vec<u8> {
+    // Message begin and end, short record, NFC Forum well-known type "U",
+    // and URI identifier code 0 (no abbreviation)
+    let mut record = vec![0xd1, 0x01, uri.len() as u8 + 1, b'U', 0x00];
+    record.extend(uri.as_bytes());
+    record
+}
+
+// The URI of the first NDEF message in the TLVs read so far, or None while
+// more of the tag has to be read
+fn ndef_message(memory: &[u8]) -> Result<Option<String>, Error> {
+    let mut offset = 0;
+    loop {
+        let Some(&tlv) = memory.get(offset) else {
+            return Ok(None);
+        };
+        match tlv {
+            NULL_TLV => {
+                offset += 1;
+                continue;
+            }
+            TERMINATOR_TLV => bail!("The tag holds no NDEF message"),
+            _ => {}
+        }
+        let Some(&length) = memory.get(offset + 1) else {
+            return Ok(None);
+        };
+        if length == 0xff {
+            bail!("The NDEF message is too long to hold a share");
+        }
+        let value = offset + 2..offset + 2 + length as usize;
+        let Some(value) = memory.get(value) else {
+            return Ok(None);
+        };
+        if tlv == NDEF_TLV {
+            return uri_record(value).map(Some);
+        }
+        // Lock and memory control TLVs
+        offset += 2 + length as usize;
+    }
+}
+
+fn uri_record(message: &[u8]) -> Result<String, Error> {
+    match message {
+        [header, 0x01, length, b'U', 0x00, uri @ ..]
+            if header & 0x1f == 0x11 && *length as usize == uri.len() + 1 =>
+        {
+            Ok(String::from_utf8(uri.to_vec())?)
+        }
+        [] => bail!("The tag's NDEF message is empty"),
+        _ => bail!("The tag's NDEF message isn't a share written by this tool"),
+    }
+}
+
+fn response(response: &[u8]) -> Result<Vec<u8>, Error> {
+    match response.split_at(response.len().saturating_sub(2)) {
+        (data, [0x90, 0x00]) => Ok(data.to_vec()),
+        (_, [0x63, 0x00]) => bail!("The reader couldn't reach the tag, hold it still"),
+        (_, status) => bail!("Tag error {}", hex::encode(status)),
+    }
+}
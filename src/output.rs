@@ -0,0 +1,333 @@
+use crate::bytewords::{byteword_string, BytewordStyle};
+use crate::hint::HintCard;
+use crate::language::MnemonicLanguage;
+use crate::limits::METADATA_SIZE_BYTES;
+use crate::policy::{self, Field};
+use crate::share_format::{
+    encode, segments, share_checksum, EncodedShare, ShareChecksum, ShareFormat,
+};
+use crate::split::{spec_labels, to_bytewords};
+use crate::sskr_shares::share_metadata;
+use anyhow::Error;
+use bip39::Mnemonic;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SecretOutput {
+    pub entropy: String,
+    // Secrets of lengths no mnemonic has are given as bytewords instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytewords: Option<String>,
+    // The secret as a codex32 string, when asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codex32: Option<String>,
+    // The secret as a ur:seed or ur:crypto-seed, when asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_ur: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SplitOutput {
+    // Left out when the secret is not to be shown, as when resharing
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<SecretOutput>,
+    pub spec: String,
+    pub identifier: String,
+    pub group_threshold: usize,
+    pub groups: Vec<GroupOutput>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GroupOutput {
+    pub group: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub member_threshold: usize,
+    pub member_count: usize,
+    pub shares: Vec<ShareOutput>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ShareOutput {
+    pub member: usize,
+    pub bytewords: String,
+    pub minimal: String,
+    pub ur: String,
+    pub hex: String,
+    pub cbor_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub envelope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint_card: Option<HintCard>,
+    pub checksum: ShareChecksum,
+    pub metadata: ShareMetadataOutput,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ShareMetadataOutput {
+    pub identifier: String,
+    pub group_index: usize,
+    pub group_threshold: usize,
+    pub group_count: usize,
+    pub member_index: usize,
+    pub member_threshold: usize,
+    pub secret_length: usize,
+}
+
+// Secrets and shares are given as the policy allows them shown
+impl SecretOutput {
+    pub fn new(mnemonic: &Mnemonic) -> SecretOutput {
+        SecretOutput {
+            entropy: policy::shown(
+                Field::Entropy,
+                format_args!("0x{}", hex::encode(mnemonic.entropy())),
+            ),
+            mnemonic: Some(policy::shown(Field::Mnemonic, mnemonic.phrase())),
+            language: Some(
+                MnemonicLanguage::from_bip39(mnemonic.language())
+                    .name()
+                    .to_string(),
+            ),
+            bytewords: None,
+            codex32: None,
+            seed_ur: None,
+        }
+    }
+
+    pub fn raw(secret: &[u8]) -> SecretOutput {
+        SecretOutput {
+            entropy: policy::shown(Field::Entropy, format_args!("0x{}", hex::encode(secret))),
+            mnemonic: None,
+            language: None,
+            bytewords: Some(policy::shown(
+                Field::Entropy,
+                byteword_string(secret, BytewordStyle::Standard),
+            )),
+            codex32: None,
+            seed_ur: None,
+        }
+    }
+
+    // The secret left out, whatever the policy, for a mnemonic the user gave
+    pub fn withheld(self) -> SecretOutput {
+        let redacted = |value: Option<String>| value.map(|_| policy::REDACTED.to_string());
+        SecretOutput {
+            entropy: policy::REDACTED.to_string(),
+            mnemonic: redacted(self.mnemonic),
+            bytewords: redacted(self.bytewords),
+            codex32: redacted(self.codex32),
+            seed_ur: redacted(self.seed_ur),
+            ..self
+        }
+    }
+
+    // Adds the secret exported as codex32 or ur:seed
+    pub fn with_exports(
+        mut self,
+        codex32: Option<String>,
+        seed_ur: Option<String>,
+    ) -> SecretOutput {
+        self.codex32 = codex32.map(|codex32| policy::shown(Field::Entropy, codex32));
+        self.seed_ur = seed_ur.map(|seed_ur| policy::shown(Field::Entropy, seed_ur));
+        self
+    }
+}
+
+impl SplitOutput {
+    pub fn new(
+        spec: &str,
+        group_threshold: usize,
+        mnemonic: &Mnemonic,
+        groups: &[Vec<Vec<u8>>],
+    ) -> Result<SplitOutput, Error> {
+        SplitOutput::with_secret(
+            spec,
+            group_threshold,
+            Some(SecretOutput::new(mnemonic)),
+            groups,
+        )
+        .map(SplitOutput::redacted)
+    }
+
+    pub fn raw(
+        spec: &str,
+        group_threshold: usize,
+        secret: &[u8],
+        groups: &[Vec<Vec<u8>>],
+    ) -> Result<SplitOutput, Error> {
+        SplitOutput::with_secret(
+            spec,
+            group_threshold,
+            Some(SecretOutput::raw(secret)),
+            groups,
+        )
+        .map(SplitOutput::redacted)
+    }
+
+    // The shares alone, as when resharing or rebuilding an older document
+    pub fn shares(
+        spec: &str,
+        group_threshold: usize,
+        groups: &[Vec<Vec<u8>>],
+    ) -> Result<SplitOutput, Error> {
+        SplitOutput::with_secret(spec, group_threshold, None, groups)
+    }
+
+    // The shares in every encoding, unless the policy allows them shown
+    pub fn redacted(mut self) -> SplitOutput {
+        if policy::current().allows(Field::Shares, policy::Channel::Display) {
+            return self;
+        }
+        for share in self.groups.iter_mut().flat_map(|group| &mut group.shares) {
+            for encoded in [
+                &mut share.bytewords,
+                &mut share.minimal,
+                &mut share.ur,
+                &mut share.hex,
+                &mut share.cbor_hex,
+            ] {
+                *encoded = policy::REDACTED.to_string();
+            }
+        }
+        self
+    }
+
+    // Adds the shares as Gordian Envelopes, given in the same order
+    pub fn with_envelopes(mut self, envelopes: &[Vec<String>]) -> SplitOutput {
+        for (group, envelopes) in self.groups.iter_mut().zip(envelopes) {
+            for (share, envelope) in group.shares.iter_mut().zip(envelopes) {
+                share.envelope = Some(policy::shown(Field::Shares, envelope));
+            }
+        }
+        self
+    }
+
+    // Adds each share split into ur:sskr segments
+    pub fn with_segments(
+        mut self,
+        shares: &[Vec<Vec<u8>>],
+        count: usize,
+    ) -> Result<SplitOutput, Error> {
+        for (group, shares) in self.groups.iter_mut().zip(shares) {
+            for (output, share) in group.shares.iter_mut().zip(shares) {
+                output.segments = Some(
+                    segments(share, count)?
+                        .into_iter()
+                        .map(|segment| policy::shown(Field::Shares, segment))
+                        .collect(),
+                );
+            }
+        }
+        Ok(self)
+    }
+
+    // Adds each share's hint card, given in the same order
+    pub fn with_hint_cards(mut self, cards: &[Vec<HintCard>]) -> SplitOutput {
+        for (group, cards) in self.groups.iter_mut().zip(cards) {
+            for (share, card) in group.shares.iter_mut().zip(cards) {
+                share.hint_card = Some(card.clone());
+            }
+        }
+        self
+    }
+
+    fn with_secret(
+        spec: &str,
+        group_threshold: usize,
+        secret: Option<SecretOutput>,
+        groups: &[Vec<Vec<u8>>],
+    ) -> Result<SplitOutput, Error> {
+        let bytewords = to_bytewords(groups, &false);
+        let minimal = to_bytewords(groups, &true);
+        let labels = spec_labels(spec)?;
+        let mut identifier = String::new();
+        let mut group_outputs = vec![];
+
+        for (group_num, group) in groups.iter().enumerate() {
+            let mut shares = vec![];
+            for (share_num, share) in group.iter().enumerate() {
+                let metadata = ShareMetadataOutput::new(share)?;
+                identifier = metadata.identifier.clone();
+                shares.push(ShareOutput {
+                    member: share_num + 1,
+                    bytewords: bytewords[group_num][share_num].clone(),
+                    minimal: minimal[group_num][share_num].clone(),
+                    ur: encode(share, ShareFormat::Ur),
+                    hex: encode(share, ShareFormat::Hex),
+                    cbor_hex: encode(share, ShareFormat::CborHex),
+                    envelope: None,
+                    segments: None,
+                    hint_card: None,
+                    checksum: share_checksum(share),
+                    metadata,
+                });
+            }
+            group_outputs.push(GroupOutput {
+                group: group_num + 1,
+                label: labels.get(group_num).cloned().flatten(),
+                member_threshold: shares
+                    .first()
+                    .map_or(0, |share| share.metadata.member_threshold),
+                member_count: shares.len(),
+                shares,
+            });
+        }
+
+        Ok(SplitOutput {
+            secret,
+            spec: spec.to_string(),
+            identifier,
+            group_threshold,
+            groups: group_outputs,
+        })
+    }
+}
+
+impl ShareMetadataOutput {
+    pub fn new(share: &[u8]) -> Result<ShareMetadataOutput, Error> {
+        let (identifier, meta) = share_metadata(share, &false)?;
+        Ok(ShareMetadataOutput {
+            identifier: format!("{:04x}", identifier),
+            group_index: meta[0],
+            group_threshold: meta[1],
+            group_count: meta[2],
+            member_index: meta[3],
+            member_threshold: meta[4],
+            secret_length: share.len() - METADATA_SIZE_BYTES,
+        })
+    }
+}
+
+// Parsing already checked the metadata, so describing a parsed share can't fail
+impl From<&EncodedShare> for ShareMetadataOutput {
+    fn from(share: &EncodedShare) -> ShareMetadataOutput {
+        let meta = share.metadata();
+        ShareMetadataOutput {
+            identifier: format!("{:04x}", share.identifier()),
+            group_index: meta[0],
+            group_threshold: meta[1],
+            group_count: meta[2],
+            member_index: meta[3],
+            member_threshold: meta[4],
+            secret_length: share.share().len() - METADATA_SIZE_BYTES,
+        }
+    }
+}
+
+pub fn print_json<T: Serialize>(value: &T) -> Result<(), Error> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use crate::output::ShareMetadataOutput;
+use crate::policy::{self, Channel, Field};
+use crate::share_format::{encode, ShareFormat};
+use anyhow::{bail, Context, Error};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use qrcode::{Color, QrCode};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+// The last bytewords of every share are a checksum of the ones before them
+const CHECKSUM_WORDS: usize = 4;
+const WORDS_PER_ROW: usize = 4;
+
+// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const COLUMN_WIDTH: f32 = 120.0;
+const ROW_HEIGHT: f32 = 20.0;
+const QR_SIZE: f32 = 120.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PaperFormat {
+    Pdf,
+    Html,
+}
+
+impl PaperFormat {
+    fn from_path(path: &Path) -> Result<PaperFormat, Error> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("pdf") => Ok(PaperFormat::Pdf),
+            Some("html") | Some("htm") => Ok(PaperFormat::Html),
+            _ => bail!(
+                "Can't tell the paper format of \"{}\", use a .pdf or .html file",
+                path.display()
+            ),
+        }
+    }
+}
+
+// Everything printed on the page of one share
+struct Sheet {
+    metadata: ShareMetadataOutput,
+    member_count: usize,
+    words: Vec<String>,
+    ur: String,
+}
+
+impl Sheet {
+    fn is_checksum(&self, word_num: usize) -> bool {
+        word_num + CHECKSUM_WORDS >= self.words.len()
+    }
+
+    fn header(&self) -> Vec<String> {
+        let metadata = &self.metadata;
+        vec![
+            format!("Share set {}", metadata.identifier),
+            format!(
+                "Group {} of {} - {} of {} groups needed to recover",
+                metadata.group_index + 1,
+                metadata.group_count,
+                metadata.group_threshold,
+                metadata.group_count
+            ),
+            format!(
+                "Share {} of {} - {} of {} shares needed to recover the group",
+                metadata.member_index + 1,
+                self.member_count,
+                metadata.member_threshold,
+                self.member_count
+            ),
+            format!(
+                "{} words - the last {} (highlighted) are the checksum",
+                self.words.len(),
+                CHECKSUM_WORDS
+            ),
+        ]
+    }
+
+    fn footer(&self) -> String {
+        format!(
+            "{} - group {}, share {}",
+            self.metadata.identifier,
+            self.metadata.group_index + 1,
+            self.metadata.member_index + 1
+        )
+    }
+
+    // Dark modules of the QR code of the share as a UR, which is uppercased so
+    // it fits the denser alphanumeric mode
+    fn qr_modules(&self) -> Result<(usize, Vec<(usize, usize)>), Error> {
+        let code =
+            QrCode::new(self.ur.to_uppercase()).context("Unable to encode share as a QR code")?;
+        let width = code.width();
+        let dark = code
+            .to_colors()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, color)| *color == Color::Dark)
+            .map(|(i, _)| (i % width, i / width))
+            .collect();
+        Ok((width, dark))
+    }
+}
+
+// Renders each share on its own printable page, as PDF or HTML depending on
+// the file extension. Returns the number of pages.
+pub fn write_paper(path: &Path, groups: &[Vec<Vec<u8>>], qr: &bool) -> Result<usize, Error> {
+    policy::check(Field::Shares, Channel::Disk)?;
+    let format = PaperFormat::from_path(path)?;
+    if path.exists() {
+        bail!(
+            "\"{}\" already exists, refusing to overwrite it",
+            path.display()
+        );
+    }
+
+    let mut sheets = vec![];
+    for group in groups {
+        for share in group {
+            sheets.push(Sheet {
+                metadata: ShareMetadataOutput::new(share)?,
+                member_count: group.len(),
+                words: encode(share, ShareFormat::Bytewords)
+                    .split(' ')
+                    .map(str::to_string)
+                    .collect(),
+                ur: encode(share, ShareFormat::Ur),
+            });
+        }
+    }
+
+    let contents = match format {
+        PaperFormat::Pdf => render_pdf(&sheets, qr)?,
+        PaperFormat::Html => render_html(&sheets, qr)?.into_bytes(),
+    };
+    fs::write(path, contents).with_context(|| format!("Unable to write {}", path.display()))?;
+
+    Ok(sheets.len())
+}
+
+fn render_pdf(sheets: &[Sheet], qr: &bool) -> Result<Vec<u8>, Error> {
+    let mut pdf = Pdf::new();
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let (regular, bold) = (Name(b"F1"), Name(b"F2"));
+
+    // Each page takes two objects, the page and its content stream
+    let page_ids = (0..sheets.len())
+        .map(|i| Ref::new(5 + 2 * i as i32))
+        .collect::<Vec<_>>();
+
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(sheets.len() as i32);
+    // Base fonts every PDF reader has, so nothing needs to be embedded
+    pdf.type1_font(regular_id).base_font(Name(b"Helvetica"));
+    pdf.type1_font(bold_id).base_font(Name(b"Helvetica-Bold"));
+
+    for (sheet, page_id) in sheets.iter().zip(page_ids) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        page.resources()
+            .fonts()
+            .pair(regular, regular_id)
+            .pair(bold, bold_id);
+        page.finish();
+
+        let mut content = Content::new();
+        let text = |content: &mut Content, font: Name, size: f32, x: f32, y: f32, text: &str| {
+            content.begin_text();
+            content.set_font(font, size);
+            content.next_line(x, y);
+            content.show(Str(text.as_bytes()));
+            content.end_text();
+        };
+
+        let mut y = PAGE_HEIGHT - MARGIN - 6.0;
+        text(&mut content, bold, 20.0, MARGIN, y, "SSKR share");
+        y -= 24.0;
+        for line in sheet.header() {
+            text(&mut content, regular, 11.0, MARGIN, y, &line);
+            y -= 16.0;
+        }
+
+        if *qr {
+            let (width, dark) = sheet.qr_modules()?;
+            let module = QR_SIZE / width as f32;
+            let (left, top) = (PAGE_WIDTH - MARGIN - QR_SIZE, PAGE_HEIGHT - MARGIN);
+            for (x, y) in dark {
+                content.rect(
+                    left + x as f32 * module,
+                    top - (y + 1) as f32 * module,
+                    module,
+                    module,
+                );
+            }
+            content.fill_nonzero();
+        }
+
+        // The words, numbered from 1, with the checksum words bold on grey
+        y = PAGE_HEIGHT - MARGIN - QR_SIZE - 2.0 * ROW_HEIGHT;
+        let rows = sheet.words.len().div_ceil(WORDS_PER_ROW);
+        for (word_num, word) in sheet.words.iter().enumerate() {
+            let x = MARGIN + (word_num % WORDS_PER_ROW) as f32 * COLUMN_WIDTH;
+            let row_y = y - (word_num / WORDS_PER_ROW) as f32 * ROW_HEIGHT;
+            let font = if sheet.is_checksum(word_num) {
+                content.set_fill_gray(0.85);
+                content.rect(x - 4.0, row_y - 6.0, COLUMN_WIDTH - 8.0, ROW_HEIGHT - 2.0);
+                content.fill_nonzero();
+                content.set_fill_gray(0.0);
+                bold
+            } else {
+                regular
+            };
+            text(
+                &mut content,
+                regular,
+                9.0,
+                x,
+                row_y,
+                &format!("{:02}", word_num + 1),
+            );
+            text(&mut content, font, 12.0, x + 18.0, row_y, word);
+        }
+
+        // Blank lines in the same layout, to copy the words onto by hand
+        y -= rows as f32 * ROW_HEIGHT + ROW_HEIGHT;
+        text(
+            &mut content,
+            regular,
+            11.0,
+            MARGIN,
+            y,
+            "Copy the words by hand, then check them against the printed ones:",
+        );
+        y -= 1.5 * ROW_HEIGHT;
+        content.set_line_width(0.5);
+        for word_num in 0..sheet.words.len() {
+            let x = MARGIN + (word_num % WORDS_PER_ROW) as f32 * COLUMN_WIDTH;
+            let row_y = y - (word_num / WORDS_PER_ROW) as f32 * (ROW_HEIGHT + 4.0);
+            text(
+                &mut content,
+                regular,
+                9.0,
+                x,
+                row_y,
+                &format!("{:02}", word_num + 1),
+            );
+            content.move_to(x + 18.0, row_y - 2.0);
+            content.line_to(x + COLUMN_WIDTH - 12.0, row_y - 2.0);
+        }
+        content.stroke();
+
+        text(
+            &mut content,
+            regular,
+            8.0,
+            MARGIN,
+            MARGIN / 2.0,
+            &sheet.footer(),
+        );
+        pdf.stream(content_id, &content.finish());
+    }
+
+    Ok(pdf.finish())
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: Helvetica, Arial, sans-serif; margin: 0; }
+.page { box-sizing: border-box; width: 210mm; min-height: 297mm; padding: 20mm; page-break-after: always; break-after: page; position: relative; }
+.page:last-child { page-break-after: auto; break-after: auto; }
+h1 { margin: 0 0 4mm 0; font-size: 20pt; }
+.header p { margin: 1mm 0; }
+.qr { position: absolute; top: 20mm; right: 20mm; width: 42mm; height: 42mm; }
+.words { display: grid; grid-template-columns: repeat(4, 1fr); gap: 2mm 4mm; margin: 14mm 0 8mm 0; font-size: 12pt; }
+.words span { padding: 1mm; }
+.words small, .copy small { color: #555; margin-right: 2mm; }
+.checksum { background: #ddd; font-weight: bold; }
+.copy { display: grid; grid-template-columns: repeat(4, 1fr); gap: 6mm 4mm; }
+.copy span { border-bottom: 0.5pt solid #000; height: 6mm; }
+footer { position: absolute; bottom: 10mm; font-size: 8pt; }
+";
+
+fn render_html(sheets: &[Sheet], qr: &bool) -> Result<String, Error> {
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(html, "<title>SSKR shares</title>")?;
+    writeln!(html, "<style>\n{}</style>\n</head>\n<body>", HTML_STYLE)?;
+
+    for sheet in sheets {
+        writeln!(html, "<section class=\"page\">")?;
+        writeln!(html, "<h1>SSKR share</h1>\n<div class=\"header\">")?;
+        for line in sheet.header() {
+            writeln!(html, "<p>{}</p>", line)?;
+        }
+        writeln!(html, "</div>")?;
+
+        if *qr {
+            let (width, dark) = sheet.qr_modules()?;
+            let path = dark
+                .iter()
+                .map(|(x, y)| format!("M{} {}h1v1h-1z", x, y))
+                .collect::<String>();
+            writeln!(
+                html,
+                "<svg class=\"qr\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\"><path d=\"{}\"/></svg>",
+                width, width, path
+            )?;
+        }
+
+        writeln!(html, "<div class=\"words\">")?;
+        for (word_num, word) in sheet.words.iter().enumerate() {
+            let class = if sheet.is_checksum(word_num) {
+                " class=\"checksum\""
+            } else {
+                ""
+            };
+            writeln!(
+                html,
+                "<span{}><small>{:02}</small>{}</span>",
+                class,
+                word_num + 1,
+                word
+            )?;
+        }
+        writeln!(html, "</div>")?;
+
+        writeln!(
+            html,
+            "<p>Copy the words by hand, then check them against the printed ones:</p>"
+        )?;
+        writeln!(html, "<div class=\"copy\">")?;
+        for word_num in 0..sheet.words.len() {
+            writeln!(html, "<span><small>{:02}</small></span>", word_num + 1)?;
+        }
+        writeln!(html, "</div>")?;
+
+        writeln!(html, "<footer>{}</footer>\n</section>", sheet.footer())?;
+    }
+
+    writeln!(html, "</body>\n</html>")?;
+    Ok(html)
+}
@@ -0,0 +1,224 @@
+use crate::limits::METADATA_SIZE_BYTES;
+use crate::share_format::decode;
+use anyhow::{bail, ensure, Error};
+use bc_crypto::hash::hmac_sha256;
+use bip39::Mnemonic;
+use sskr::sskr_combine;
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+// The x coordinates Shamir shares the secret and its digest at
+const SECRET_INDEX: u8 = 255;
+const DIGEST_INDEX: u8 = 254;
+const DIGEST_SIZE: usize = 4;
+
+// A share's x coordinate and its bytes
+type Point<'a> = (u8, &'a [u8]);
+
+// What the checks of a split went through
+#[derive(Debug, PartialEq)]
+pub struct Checked {
+    pub shares: usize,
+    pub combinations: usize,
+}
+
+// Re-verifies a split before it is declared done: every share as it is shown
+// or written decodes back to the share, and enough combinations to use every
+// share at least once recover the secret, both through the sskr crate and
+// through `combine` below, which shares no code with it.
+pub fn check_split(
+    shares: &[Vec<Vec<u8>>],
+    encoded: &[Vec<String>],
+    secret: &[u8],
+) -> Result<Checked, Error> {
+    for (group, (shares, encoded)) in shares.iter().zip(encoded).enumerate() {
+        ensure!(
+            shares.len() == encoded.len(),
+            "Group {} has {} shares but {} were encoded",
+            group + 1,
+            shares.len(),
+            encoded.len()
+        );
+        for (member, (share, encoded)) in shares.iter().zip(encoded).enumerate() {
+            let (_, decoded) = decode(encoded)?;
+            if Zeroizing::new(decoded).as_slice() != share.as_slice() {
+                bail!(
+                    "Share {} of group {} doesn't decode back to itself",
+                    member + 1,
+                    group + 1
+                );
+            }
+        }
+    }
+
+    let combinations = quorums(shares)?;
+    for quorum in &combinations {
+        check_combination(quorum, secret)?;
+    }
+    Ok(Checked {
+        shares: shares.iter().map(Vec::len).sum(),
+        combinations: combinations.len(),
+    })
+}
+
+// Recombines the shares a recovery used on the independent code path
+pub fn check_recovery(shares: &[&[u8]], secret: &[u8]) -> Result<(), Error> {
+    let independent = combine(shares)?;
+    ensure!(
+        independent.as_slice() == secret,
+        "The shares recover a different secret on the independent code path"
+    );
+    Ok(())
+}
+
+// The mnemonic shown for a secret reads back to the same secret
+pub fn check_mnemonic(mnemonic: &Mnemonic) -> Result<(), Error> {
+    let parsed = Mnemonic::from_phrase(mnemonic.phrase(), mnemonic.language())?;
+    ensure!(
+        parsed.entropy() == mnemonic.entropy(),
+        "The mnemonic doesn't read back to the secret"
+    );
+    Ok(())
+}
+
+fn check_combination(quorum: &[&[u8]], secret: &[u8]) -> Result<(), Error> {
+    let combined = sskr_combine(quorum)
+        .map_err(|error| anyhow::anyhow!("A combination of shares doesn't recover: {}", error))?;
+    ensure!(
+        combined.data() == secret,
+        "A combination of shares recovers a different secret"
+    );
+    check_recovery(quorum, secret)
+}
+
+// One combination for every share: the share's group with a window of its
+// member threshold starting at the share, and the groups after it with their
+// first shares. Windows wrap around, so every share is used, and no more than
+// one combination is made per share even for 16 groups of 16.
+fn quorums(groups: &[Vec<Vec<u8>>]) -> Result<Vec<Vec<&[u8]>>, Error> {
+    let Some(first) = groups.first().and_then(|group| group.first()) else {
+        bail!("No shares to check");
+    };
+    ensure!(first.len() > METADATA_SIZE_BYTES, "Share is too short");
+    let group_threshold = (first[2] >> 4) as usize + 1;
+    let mut quorums = vec![];
+    for (index, group) in groups.iter().enumerate() {
+        for start in 0..group.len() {
+            let mut quorum = window(group, start);
+            for other in 1..group_threshold {
+                quorum.extend(window(&groups[(index + other) % groups.len()], 0));
+            }
+            quorums.push(quorum);
+        }
+    }
+    Ok(quorums)
+}
+
+// The member threshold of the group's shares, starting at one of them
+fn window(group: &[Vec<u8>], start: usize) -> Vec<&[u8]> {
+    let member_threshold = (group[0][3] & 0xf) as usize + 1;
+    (0..member_threshold)
+        .map(|offset| group[(start + offset) % group.len()].as_slice())
+        .collect()
+}
+
+// SSKR combination written out from the spec (BCR-2020-011) rather than taken
+// from the sskr crate: member shares are interpolated to their group's share,
+// and group shares to the secret, each level checked against its digest.
+pub fn combine(shares: &[&[u8]]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let mut groups: BTreeMap<u8, (usize, Vec<Point>)> = BTreeMap::new();
+    let mut group_threshold = None;
+    for share in shares {
+        ensure!(share.len() > METADATA_SIZE_BYTES, "Share is too short");
+        let threshold = (share[2] >> 4) as usize + 1;
+        ensure!(
+            *group_threshold.get_or_insert(threshold) == threshold,
+            "Shares disagree on the group threshold"
+        );
+        let group = groups
+            .entry(share[3] >> 4)
+            .or_insert(((share[3] & 0xf) as usize + 1, vec![]));
+        let member = share[4] & 0xf;
+        if group.1.iter().all(|(other, _)| *other != member) {
+            group.1.push((member, &share[METADATA_SIZE_BYTES..]));
+        }
+    }
+
+    let mut group_shares = vec![];
+    for (index, (member_threshold, members)) in &groups {
+        if members.len() >= *member_threshold {
+            group_shares.push((*index, recover_level(&members[..*member_threshold])?));
+        }
+    }
+    let group_threshold = group_threshold.unwrap_or_default();
+    ensure!(
+        group_threshold > 0 && group_shares.len() >= group_threshold,
+        "Not enough groups to recover"
+    );
+    let points: Vec<Point> = group_shares[..group_threshold]
+        .iter()
+        .map(|(index, share)| (*index, share.as_slice()))
+        .collect();
+    recover_level(&points)
+}
+
+// One level of Shamir's scheme: a single point is the secret itself, more are
+// interpolated at the secret and at the digest, whose first bytes are an HMAC
+// of the secret keyed by the rest of the digest
+fn recover_level(points: &[Point]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    if let [(_, only)] = points {
+        return Ok(Zeroizing::new(only.to_vec()));
+    }
+    let secret = interpolate(points, SECRET_INDEX);
+    let digest = interpolate(points, DIGEST_INDEX);
+    let expected = hmac_sha256(&digest[DIGEST_SIZE..], secret.as_slice());
+    ensure!(
+        digest[..DIGEST_SIZE] == expected[..DIGEST_SIZE],
+        "The shares don't match their digest"
+    );
+    Ok(secret)
+}
+
+// Lagrange interpolation of the points at x, byte by byte, in GF(2^8)
+fn interpolate(points: &[Point], x: u8) -> Zeroizing<Vec<u8>> {
+    let length = points[0].1.len();
+    let mut result = Zeroizing::new(vec![0u8; length]);
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        let mut basis = 1u8;
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                basis = multiply(basis, multiply(x ^ xj, inverse(xi ^ xj)));
+            }
+        }
+        for (byte, y) in result.iter_mut().zip(yi.iter()) {
+            *byte ^= multiply(basis, *y);
+        }
+    }
+    result
+}
+
+// Multiplication modulo x^8 + x^4 + x^3 + x + 1, the AES polynomial
+fn multiply(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+// a^254, which is 1/a for every a but 0
+fn inverse(a: u8) -> u8 {
+    let mut result = 1;
+    for _ in 0..254 {
+        result = multiply(result, a);
+    }
+    result
+}
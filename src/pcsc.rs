@@ -0,0 +1,255 @@
+use crate::smartcard::CardTransport;
+use anyhow::{anyhow, bail, Error};
+use libloading::Library;
+use std::ffi::{c_char, c_void, CString};
+use std::ptr;
+
+// The PC/SC types differ between pcsc-lite, Windows and macOS
+#[cfg(not(any(windows, target_os = "macos")))]
+mod types {
+    pub type Dword = std::ffi::c_ulong;
+    pub type Long = std::ffi::c_long;
+    pub type Handle = std::ffi::c_long;
+    pub const LIBRARY: &str = "libpcsclite.so.1";
+}
+#[cfg(windows)]
+mod types {
+    pub type Dword = u32;
+    pub type Long = i32;
+    pub type Handle = usize;
+    pub const LIBRARY: &str = "winscard.dll";
+}
+#[cfg(target_os = "macos")]
+mod types {
+    pub type Dword = u32;
+    pub type Long = i32;
+    pub type Handle = i32;
+    pub const LIBRARY: &str = "/System/Library/Frameworks/PCSC.framework/PCSC";
+}
+use types::{Dword, Handle, Long, LIBRARY};
+
+// Windows has ANSI and wide versions of the functions that take strings
+#[cfg(windows)]
+const LIST_READERS: &[u8] = b"SCardListReadersA\0";
+#[cfg(not(windows))]
+const LIST_READERS: &[u8] = b"SCardListReaders\0";
+#[cfg(windows)]
+const CONNECT: &[u8] = b"SCardConnectA\0";
+#[cfg(not(windows))]
+const CONNECT: &[u8] = b"SCardConnect\0";
+
+const SCARD_SCOPE_SYSTEM: Dword = 2;
+const SCARD_SHARE_SHARED: Dword = 2;
+const SCARD_PROTOCOL_T0: Dword = 1;
+const SCARD_PROTOCOL_T1: Dword = 2;
+const SCARD_LEAVE_CARD: Dword = 0;
+// Status codes, as unsigned whatever the width of LONG
+const SCARD_E_NO_SMARTCARD: u32 = 0x8010_000c;
+const SCARD_E_NO_SERVICE: u32 = 0x8010_001d;
+const SCARD_E_NO_READERS_AVAILABLE: u32 = 0x8010_002e;
+const SCARD_W_UNRESPONSIVE_CARD: u32 = 0x8010_0066;
+const SCARD_W_REMOVED_CARD: u32 = 0x8010_0069;
+// Short APDU responses hold up to 256 bytes and the two status bytes
+const MAX_RESPONSE: usize = 258;
+
+#[repr(C)]
+struct IoRequest {
+    protocol: Dword,
+    length: Dword,
+}
+
+type EstablishContext =
+    unsafe extern "system" fn(Dword, *const c_void, *const c_void, *mut Handle) -> Long;
+type ReleaseContext = unsafe extern "system" fn(Handle) -> Long;
+type ListReaders =
+    unsafe extern "system" fn(Handle, *const c_char, *mut c_char, *mut Dword) -> Long;
+type Connect =
+    unsafe extern "system" fn(Handle, *const c_char, Dword, Dword, *mut Handle, *mut Dword) -> Long;
+type Transmit = unsafe extern "system" fn(
+    Handle,
+    *const IoRequest,
+    *const u8,
+    Dword,
+    *mut IoRequest,
+    *mut u8,
+    *mut Dword,
+) -> Long;
+type Disconnect = unsafe extern "system" fn(Handle, Dword) -> Long;
+
+// A card or tag on a PC/SC reader. The PC/SC library of the system is loaded
+// when connecting rather than linked, so the tool still runs where there is
+// none, and only the card options fail.
+pub struct PcscReader {
+    library: Library,
+    context: Handle,
+    card: Option<Handle>,
+    protocol: Dword,
+}
+
+impl PcscReader {
+    // Connects to the card or tag on the first reader whose name contains
+    // the given name, or on the first reader
+    pub fn connect(name: Option<&str>) -> Result<PcscReader, Error> {
+        // SAFETY: the system's PC/SC library has no initialisers that are
+        // unsafe to run
+        let library = unsafe { Library::new(LIBRARY) }
+            .map_err(|error| anyhow!("Unable to load the PC/SC library {}: {}", LIBRARY, error))?;
+        let mut context: Handle = 0;
+        // SAFETY: the signature is the one PC/SC defines, and the context
+        // outlives the call
+        check(
+            unsafe {
+                symbol::<EstablishContext>(&library, b"SCardEstablishContext\0")?(
+                    SCARD_SCOPE_SYSTEM,
+                    ptr::null(),
+                    ptr::null(),
+                    &mut context,
+                )
+            },
+            "Unable to reach the PC/SC service",
+        )?;
+        let mut reader = PcscReader {
+            library,
+            context,
+            card: None,
+            protocol: 0,
+        };
+
+        let readers = reader.readers()?;
+        let Some(chosen) = readers
+            .iter()
+            .find(|reader| name.is_none_or(|name| reader.contains(name)))
+        else {
+            bail!(
+                "No card reader{} is connected",
+                name.map(|name| format!(" named \"{}\"", name))
+                    .unwrap_or_default()
+            );
+        };
+        let chosen = CString::new(chosen.as_str())?;
+        let (mut card, mut protocol): (Handle, Dword) = (0, 0);
+        // SAFETY: as above, with the reader name NUL terminated
+        check(
+            unsafe {
+                symbol::<Connect>(&reader.library, CONNECT)?(
+                    reader.context,
+                    chosen.as_ptr(),
+                    SCARD_SHARE_SHARED,
+                    SCARD_PROTOCOL_T0 | SCARD_PROTOCOL_T1,
+                    &mut card,
+                    &mut protocol,
+                )
+            },
+            "Unable to connect to the card or tag on the reader",
+        )?;
+        reader.card = Some(card);
+        reader.protocol = protocol;
+        Ok(reader)
+    }
+
+    // The names of the connected readers
+    pub fn readers(&self) -> Result<Vec<String>, Error> {
+        let list = symbol::<ListReaders>(&self.library, LIST_READERS)?;
+        let mut length: Dword = 0;
+        // SAFETY: a null buffer asks for the length the names need
+        let code = unsafe { list(self.context, ptr::null(), ptr::null_mut(), &mut length) };
+        if code as u32 == SCARD_E_NO_READERS_AVAILABLE {
+            return Ok(vec![]);
+        }
+        check(code, "Unable to list card readers")?;
+        let mut names = vec![0u8; length as usize];
+        // SAFETY: the buffer holds the length given
+        check(
+            unsafe {
+                list(
+                    self.context,
+                    ptr::null(),
+                    names.as_mut_ptr() as *mut c_char,
+                    &mut length,
+                )
+            },
+            "Unable to list card readers",
+        )?;
+        names.truncate(length as usize);
+        Ok(reader_names(&names))
+    }
+}
+
+impl CardTransport for PcscReader {
+    fn transmit(&mut self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+        let Some(card) = self.card else {
+            bail!("Not connected to a card");
+        };
+        let request = IoRequest {
+            protocol: self.protocol,
+            length: std::mem::size_of::<IoRequest>() as Dword,
+        };
+        let mut response = vec![0u8; MAX_RESPONSE];
+        let mut length = response.len() as Dword;
+        // SAFETY: both buffers hold the lengths given
+        check(
+            unsafe {
+                symbol::<Transmit>(&self.library, b"SCardTransmit\0")?(
+                    card,
+                    &request,
+                    apdu.as_ptr(),
+                    apdu.len() as Dword,
+                    ptr::null_mut(),
+                    response.as_mut_ptr(),
+                    &mut length,
+                )
+            },
+            "Unable to exchange data with the card or tag",
+        )?;
+        response.truncate(length as usize);
+        Ok(response)
+    }
+}
+
+impl Drop for PcscReader {
+    fn drop(&mut self) {
+        // SAFETY: the card and context came from the same library, and are
+        // given back once
+        unsafe {
+            if let (Some(card), Ok(disconnect)) = (
+                self.card,
+                symbol::<Disconnect>(&self.library, b"SCardDisconnect\0"),
+            ) {
+                disconnect(card, SCARD_LEAVE_CARD);
+            }
+            if let Ok(release) = symbol::<ReleaseContext>(&self.library, b"SCardReleaseContext\0") {
+                release(self.context);
+            }
+        }
+    }
+}
+
+fn symbol<T: Copy>(library: &Library, name: &[u8]) -> Result<T, Error> {
+    // SAFETY: every type asked for is the signature PC/SC gives the name
+    unsafe { library.get::<T>(name) }
+        .map(|symbol| *symbol)
+        .map_err(|error| anyhow!("The PC/SC library lacks a function: {}", error))
+}
+
+// Reader names come as one buffer of NUL terminated strings, ending in an
+// empty one
+pub fn reader_names(buffer: &[u8]) -> Vec<String> {
+    buffer
+        .split(|byte| *byte == 0)
+        .take_while(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).to_string())
+        .collect()
+}
+
+fn check(code: Long, context: &str) -> Result<(), Error> {
+    let reason = match code as u32 {
+        0 => return Ok(()),
+        SCARD_E_NO_SMARTCARD => "there is no card or tag on the reader".to_string(),
+        SCARD_E_NO_SERVICE => "the PC/SC service (pcscd) isn't running".to_string(),
+        SCARD_E_NO_READERS_AVAILABLE => "no card reader is connected".to_string(),
+        SCARD_W_UNRESPONSIVE_CARD => "the card or tag doesn't respond".to_string(),
+        SCARD_W_REMOVED_CARD => "the card or tag was taken off the reader".to_string(),
+        code => format!("PC/SC error {:#010x}", code),
+    };
+    bail!("{}: {}", context, reason)
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, bail, Context, Error};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const REDACTED: &str = "[redacted]";
+
+// A policy file says which sensitive fields may go where, for organizations
+// that want one rule for every subcommand rather than flags on each:
+//
+//     [display]
+//     mnemonic = false
+//
+//     [disk]
+//     shares = false
+//
+//     [log]
+//     entropy = false
+//
+// Anything left out is allowed, as it is without a policy. Fields not allowed
+// on the display are shown as [redacted]; writing them to disk or to the
+// session journal is refused.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    pub display: Permissions,
+    pub disk: Permissions,
+    pub log: Permissions,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Permissions {
+    pub mnemonic: bool,
+    // The secret in any other encoding: hex, bytewords, codex32 or ur:seed
+    pub entropy: bool,
+    pub shares: bool,
+    // The BIP-32 fingerprint, and the xpub and addresses shown with it
+    pub fingerprints: bool,
+}
+
+impl Default for Permissions {
+    fn default() -> Permissions {
+        Permissions {
+            mnemonic: true,
+            entropy: true,
+            shares: true,
+            fingerprints: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Mnemonic,
+    Entropy,
+    Shares,
+    Fingerprints,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Channel {
+    Display,
+    Disk,
+    // Messages on stderr and the session journal
+    Log,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Field::Mnemonic => "the mnemonic",
+            Field::Entropy => "the entropy",
+            Field::Shares => "shares",
+            Field::Fingerprints => "fingerprints",
+        })
+    }
+}
+
+impl Policy {
+    pub fn load(path: &Path) -> Result<Policy, Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read policy file \"{}\"", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Invalid policy file \"{}\"", path.display()))
+    }
+
+    pub fn allows(&self, field: Field, channel: Channel) -> bool {
+        let permissions = match channel {
+            Channel::Display => &self.display,
+            Channel::Disk => &self.disk,
+            Channel::Log => &self.log,
+        };
+        match field {
+            Field::Mnemonic => permissions.mnemonic,
+            Field::Entropy => permissions.entropy,
+            Field::Shares => permissions.shares,
+            Field::Fingerprints => permissions.fingerprints,
+        }
+    }
+
+    pub fn check(&self, field: Field, channel: Channel) -> Result<(), Error> {
+        if !self.allows(field, channel) {
+            match channel {
+                Channel::Display => bail!("The policy doesn't allow showing {}", field),
+                Channel::Disk => bail!("The policy doesn't allow writing {} to disk", field),
+                Channel::Log => bail!("The policy doesn't allow logging {}", field),
+            }
+        }
+        Ok(())
+    }
+
+    // Keeps the mnemonic and entropy off the display, as when the user gave
+    // them and has them already
+    pub fn without_secret_display(mut self) -> Policy {
+        self.display.mnemonic = false;
+        self.display.entropy = false;
+        self
+    }
+
+    // The value, or [redacted] where the policy keeps it off the channel
+    pub fn shown(&self, field: Field, channel: Channel, value: impl fmt::Display) -> String {
+        if self.allows(field, channel) {
+            value.to_string()
+        } else {
+            REDACTED.to_string()
+        }
+    }
+}
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+// The policy of this run, set once before any subcommand runs
+pub fn install(policy: Policy) -> Result<(), Error> {
+    POLICY
+        .set(policy)
+        .map_err(|_| anyhow!("A policy is already in force"))
+}
+
+// The policy in force, which allows everything unless one was installed
+pub fn current() -> &'static Policy {
+    POLICY.get_or_init(Policy::default)
+}
+
+pub fn check(field: Field, channel: Channel) -> Result<(), Error> {
+    current().check(field, channel)
+}
+
+pub fn shown(field: Field, value: impl fmt::Display) -> String {
+    current().shown(field, Channel::Display, value)
+}
+
+pub fn logged(field: Field, value: impl fmt::Display) -> String {
+    current().shown(field, Channel::Log, value)
+}
@@ -0,0 +1,208 @@
+use crate::hash::HashAlgorithm;
+use crate::language::MnemonicLanguage;
+use crate::share_format::{BytewordStyle, SplitFormat, WordSeparator};
+use crate::split::{default_group_threshold, parse_word_count, spec_layout, SpecLayout};
+use anyhow::{bail, Context, Error};
+use bip39::MnemonicType;
+use clap::ValueEnum;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PROFILES_ENV: &str = "SSKR_TOOL_PROFILES";
+
+// A profile is a split's options saved under a name, so a ceremony repeated
+// every quarter runs the same way each time with `split --profile quarterly`:
+//
+//     spec = "family=2of3,lawyers=2of2"
+//     group_threshold = 2
+//     custodians = [["Alice", "Bob", "Carol"], ["Dewey & Co", "Lee LLP"]]
+//     out_dir = "shares"
+//     share_format = "bytewords"
+//     checksums = true
+//     confirm = true
+//     paranoid = true
+//
+// Every key but the spec is optional, and options given on the command line
+// take precedence over the profile's. The custodians of each group are given
+// in the order of the spec's groups and of their members.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub spec: String,
+    pub group_threshold: Option<usize>,
+    #[serde(default)]
+    pub custodians: Vec<Vec<String>>,
+    #[serde(default, deserialize_with = "word_count")]
+    pub words: Option<MnemonicType>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub language: Option<MnemonicLanguage>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub share_format: Option<SplitFormat>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub style: Option<BytewordStyle>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub separator: Option<WordSeparator>,
+    pub out_dir: Option<String>,
+    pub manifest: Option<String>,
+    pub paper: Option<String>,
+    pub name_template: Option<String>,
+    #[serde(default)]
+    pub ascii_names: bool,
+    #[serde(default)]
+    pub hint_cards: bool,
+    #[serde(default)]
+    pub mirror: Vec<String>,
+    pub seed_name: Option<String>,
+    pub purpose: Option<String>,
+    pub hash: Option<HashAlgorithm>,
+    // Hardening
+    #[serde(default)]
+    pub checksums: bool,
+    #[serde(default)]
+    pub confirm: bool,
+    #[serde(default)]
+    pub dual_control: bool,
+    #[serde(default)]
+    pub encrypt: bool,
+    #[serde(default)]
+    pub paranoid: bool,
+}
+
+impl Profile {
+    pub fn parse(contents: &str) -> Result<Profile, Error> {
+        let profile: Profile = toml::from_str(contents)?;
+        let group_threshold = match profile.group_threshold {
+            Some(threshold) => threshold,
+            None => default_group_threshold(&profile.spec)?,
+        };
+        check_custodians(
+            &spec_layout(&profile.spec, group_threshold)?,
+            &profile.custodians,
+        )?;
+        Ok(profile)
+    }
+
+    pub fn load(path: &Path) -> Result<Profile, Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read profile \"{}\"", path.display()))?;
+        Profile::parse(&contents).with_context(|| format!("Invalid profile \"{}\"", path.display()))
+    }
+}
+
+// Every group has a custodian for each of its members, if any are named
+pub fn check_custodians(layout: &SpecLayout, custodians: &[Vec<String>]) -> Result<(), Error> {
+    if custodians.is_empty() {
+        return Ok(());
+    }
+    if custodians.len() != layout.groups.len() {
+        bail!(
+            "Custodians are named for {} groups, but the spec has {}",
+            custodians.len(),
+            layout.groups.len()
+        );
+    }
+    for (index, (names, group)) in custodians.iter().zip(&layout.groups).enumerate() {
+        if names.len() != group.count {
+            bail!(
+                "Group {} has {} shares, but {} custodians are named for it",
+                index + 1,
+                group.count,
+                names.len()
+            );
+        }
+        if names.iter().any(|name| name.trim().is_empty()) {
+            bail!("Group {} has a custodian with an empty name", index + 1);
+        }
+    }
+    Ok(())
+}
+
+// Where profiles are saved: $SSKR_TOOL_PROFILES, or sskr-tool/profiles in the
+// user's configuration directory
+pub fn profiles_dir() -> Result<PathBuf, Error> {
+    if let Some(dir) = env::var_os(PROFILES_ENV) {
+        return Ok(PathBuf::from(dir));
+    }
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .with_context(|| format!("No configuration directory, set {}", PROFILES_ENV))?;
+    Ok(config.join("sskr-tool").join("profiles"))
+}
+
+fn profile_path(dir: &Path, name: &str) -> Result<PathBuf, Error> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(valid) {
+        bail!(
+            "Invalid profile name \"{}\", use letters, digits, - and _",
+            name
+        );
+    }
+    Ok(dir.join(format!("{}.toml", name)))
+}
+
+// Checks the profile file and saves it, comments and all, under the name,
+// replacing any profile of that name
+pub fn save(dir: &Path, name: &str, file: &Path) -> Result<Profile, Error> {
+    let path = profile_path(dir, name)?;
+    let contents = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read profile \"{}\"", file.display()))?;
+    let profile = Profile::parse(&contents)
+        .with_context(|| format!("Invalid profile \"{}\"", file.display()))?;
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Unable to create directory \"{}\"", dir.display()))?;
+    fs::write(&path, contents)
+        .with_context(|| format!("Unable to write \"{}\"", path.display()))?;
+    Ok(profile)
+}
+
+pub fn load(dir: &Path, name: &str) -> Result<Profile, Error> {
+    Profile::load(&profile_path(dir, name)?)
+}
+
+// The saved profile as it was written, to copy or edit and save again
+pub fn export(dir: &Path, name: &str) -> Result<String, Error> {
+    let path = profile_path(dir, name)?;
+    fs::read_to_string(&path).with_context(|| format!("No profile \"{}\"", name))
+}
+
+// The names of the saved profiles, in order
+pub fn list(dir: &Path) -> Result<Vec<String>, Error> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut names = fs::read_dir(dir)
+        .with_context(|| format!("Unable to read directory \"{}\"", dir.display()))?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+// Enums are written the way their command line options take them
+fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    T::from_str(&value, true)
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+fn word_count<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<MnemonicType>, D::Error> {
+    let words = usize::deserialize(deserializer)?;
+    parse_word_count(&words.to_string())
+        .map(Some)
+        .map_err(D::Error::custom)
+}
@@ -0,0 +1,162 @@
+use crate::bytewords::is_byteword;
+use crate::share_format::decode;
+use anyhow::{bail, Context, Error};
+use std::io::{BufRead, Write};
+use zeroize::Zeroizing;
+
+// Tries at a share, or at a sampled word, before the read-back gives up
+const MAX_TRIES: usize = 3;
+
+// What the custodian is asked to read back from their copy of each share
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReadBack {
+    // The whole share, in any format it can be read in
+    Full,
+    // This many words, at positions of `choose`'s choosing
+    Sample(usize),
+}
+
+// Asks for every share of a split to be read back from the copy just made of
+// it, before the split is trusted. Transcription errors are the most common
+// way SSKR backups fail, and this is the last time they are cheap to fix.
+// `printed` holds the shares as they were shown, so sampled words can be
+// asked by their position in the copy.
+pub fn run(
+    shares: &[Vec<Vec<u8>>],
+    printed: &[Vec<String>],
+    mode: ReadBack,
+    mut choose: impl FnMut(usize, usize) -> Vec<usize>,
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<usize, Error> {
+    let mut confirmed = 0;
+    for (group, (shares, printed)) in shares.iter().zip(printed).enumerate() {
+        for (member, (share, printed)) in shares.iter().zip(printed).enumerate() {
+            let name = format!("Share {} of group {}", member + 1, group + 1);
+            match mode {
+                ReadBack::Full => read_share(&name, share, printed, input, prompt)?,
+                ReadBack::Sample(count) => {
+                    let words = printed_words(printed);
+                    let mut positions = choose(words.len(), count.min(words.len()));
+                    positions.sort();
+                    for position in positions {
+                        read_word(&name, position, &words[position], input, prompt)?;
+                    }
+                }
+            }
+            writeln!(prompt, "{} matches", name)?;
+            confirmed += 1;
+        }
+    }
+    Ok(confirmed)
+}
+
+fn read_share(
+    name: &str,
+    share: &[u8],
+    printed: &str,
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<(), Error> {
+    for _ in 0..MAX_TRIES {
+        let line = read_line(&format!("{}: ", name), input, prompt)?;
+        if decode(&line).is_ok_and(|(_, typed)| Zeroizing::new(typed).as_slice() == share) {
+            return Ok(());
+        }
+        let differing = differing_words(&printed_words(printed), &printed_words(&line));
+        if differing.is_empty() {
+            writeln!(prompt, "That doesn't match the share, try again")?;
+        } else {
+            writeln!(
+                prompt,
+                "That doesn't match the share at word {}, try again",
+                differing
+                    .iter()
+                    .map(|position| (position + 1).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+    }
+    bail!("{} doesn't match its copy, make the copy again", name)
+}
+
+fn read_word(
+    name: &str,
+    position: usize,
+    expected: &str,
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<(), Error> {
+    for _ in 0..MAX_TRIES {
+        let word = read_line(&format!("{}, word {}: ", name, position + 1), input, prompt)?;
+        if minimal(&word) == minimal(expected) {
+            return Ok(());
+        }
+        writeln!(prompt, "That doesn't match the share, try again")?;
+    }
+    bail!(
+        "{} doesn't match its copy at word {}, make the copy again",
+        name,
+        position + 1
+    )
+}
+
+// The words of a share as it is written down: bytewords separated by spaces
+// or hyphens, or the letter pairs of minimal bytewords, after any UR prefix
+fn printed_words(line: &str) -> Vec<String> {
+    let line = line.trim().to_lowercase();
+    let body = match line.starts_with("ur:") {
+        true => line.rsplit('/').next().unwrap_or_default(),
+        false => &line,
+    };
+    if body.contains([' ', '-']) {
+        body.split([' ', '-'])
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect()
+    } else {
+        body.chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| pair.iter().collect())
+            .collect()
+    }
+}
+
+fn differing_words(expected: &[String], typed: &[String]) -> Vec<usize> {
+    (0..expected.len().max(typed.len()))
+        .filter(|&position| {
+            expected.get(position).map(|word| minimal(word))
+                != typed.get(position).map(|word| minimal(word))
+        })
+        .collect()
+}
+
+// A byteword written out in full counts the same as its minimal form
+fn minimal(word: &str) -> String {
+    let word = word.trim().to_lowercase();
+    match (is_byteword(&word), word.chars().next(), word.chars().last()) {
+        (true, Some(first), Some(last)) => format!("{}{}", first, last),
+        _ => word,
+    }
+}
+
+fn read_line(
+    message: &str,
+    input: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<Zeroizing<String>, Error> {
+    write!(prompt, "{}", message)?;
+    prompt.flush()?;
+
+    let mut line = Zeroizing::new(String::new());
+    if input
+        .read_line(&mut line)
+        .context("Unable to read answer")?
+        == 0
+    {
+        bail!("The read-back was ended before every share was confirmed");
+    }
+    Ok(Zeroizing::new(line.trim().to_string()))
+}
@@ -1,42 +1,205 @@
-use crate::bytewords::*;
+use crate::diagnostics::{unreadable, Problem};
+use crate::error::SskrToolError;
+use crate::limits::{is_valid_secret_len, MAX_SECRET_LEN, METADATA_SIZE_BYTES, MIN_SECRET_LEN};
+use crate::share_format::{decode, encode, EncodedShare};
 use crate::sskr_shares::*;
-use anyhow::{anyhow, bail, Error};
+use anyhow::{bail, Error};
 use bip39::{Language, Mnemonic};
-use dcbor::CBOR;
+use rayon::prelude::*;
 use sskr::sskr_combine;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
 
-pub fn recover(lines: Vec<String>, minimal: &bool) -> Result<Mnemonic, Error> {
-    let mut shares: Vec<Vec<u8>> = vec![];
+pub fn recover(lines: Vec<String>, minimal: &bool, language: Language) -> Result<Mnemonic, Error> {
+    let secret = recover_secret(lines, minimal)?;
 
-    // Get shares from raw strings
-    for line in lines {
-        // Parse bytewords and strip byteword-level checksum
-        let bytes = byteword_string_to_bytes(line.trim(), minimal)?;
+    // The secret is left out, as errors reach stderr and logs unredacted
+    Mnemonic::from_entropy(&secret, language)
+        .map_err(|e| SskrToolError::MnemonicFromEntropy(e.to_string()).into())
+}
 
-        // Unwrap data from CBOR container
-        let cbor = CBOR::from_data(bytes.as_slice())?;
-        let cbor_bytes = cbor.expect_tagged_value(309)?;
-        let share = cbor_bytes.expect_byte_string()?;
+// A line that can't be used to recover, by its position in the lines given
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineProblem {
+    pub index: usize,
+    pub message: String,
+    // The earlier line holding a different share for the same place
+    pub conflicts_with: Option<usize>,
+    pub problem: Problem,
+}
 
-        // Retain share data
-        shares.push(share.to_vec());
+// Checks every line, on its own and against the others, and reports each one
+// that can't be used instead of stopping at the first. Where the lines
+// disagree, the identifier and thresholds most of them share are taken to be
+// the right ones.
+pub fn diagnose_lines(lines: &[String]) -> Vec<LineProblem> {
+    let mut problems = vec![];
+    let mut decoded = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        match decode_share(line).and_then(|share| Ok((share_metadata(&share, &false)?, share))) {
+            Ok(((id, meta), share)) => decoded.push((index, id, meta, share)),
+            Err(error) => problems.push(LineProblem {
+                index,
+                message: format!("{:#}", error),
+                conflicts_with: None,
+                problem: unreadable(index, line),
+            }),
+        }
     }
 
-    // Parse out metadata from each share
+    let identifier = most_common(decoded.iter().map(|(_, id, _, _)| *id));
+    let split = most_common(
+        decoded
+            .iter()
+            .filter(|(_, id, _, _)| Some(*id) == identifier)
+            .map(|(_, _, meta, _)| (meta[1], meta[2])),
+    );
+    let mut accepted: Vec<&(usize, u16, [usize; 5], Vec<u8>)> = vec![];
+    for share in &decoded {
+        let (index, id, meta, bytes) = share;
+        let problem = |message: String, problem| LineProblem {
+            index: *index,
+            message,
+            conflicts_with: match problem {
+                Problem::Conflict { other, .. } => Some(other),
+                _ => None,
+            },
+            problem,
+        };
+        if Some(*id) != identifier {
+            problems.push(problem(
+                format!(
+                    "Identifier {:04x} doesn't match the {:04x} of the other shares",
+                    id,
+                    identifier.unwrap_or_default()
+                ),
+                Problem::OtherSet { share: *index },
+            ));
+            continue;
+        }
+        if Some((meta[1], meta[2])) != split {
+            problems.push(problem(
+                format!(
+                    "Group threshold {} of {} groups doesn't match the other shares",
+                    meta[1], meta[2]
+                ),
+                Problem::OtherSplit { share: *index },
+            ));
+            continue;
+        }
+        let group_threshold = most_common(
+            decoded
+                .iter()
+                .filter(|(_, other_id, other, _)| other_id == id && other[0] == meta[0])
+                .map(|(_, _, other, _)| other[4]),
+        );
+        if Some(meta[4]) != group_threshold {
+            problems.push(problem(
+                format!(
+                    "Member threshold {} doesn't match the other shares of group {}",
+                    meta[4],
+                    meta[0] + 1
+                ),
+                Problem::OtherSplit { share: *index },
+            ));
+            continue;
+        }
+        match accepted
+            .iter()
+            .find(|(_, _, other, _)| other[0] == meta[0] && other[3] == meta[3])
+        {
+            // The same share twice only counts once
+            Some((_, _, _, other)) if other == bytes => continue,
+            Some((other_index, _, _, _)) => {
+                problems.push(problem(
+                    format!(
+                        "Conflicts with another share for group {}, member {}",
+                        meta[0] + 1,
+                        meta[3] + 1
+                    ),
+                    Problem::Conflict {
+                        share: *index,
+                        other: *other_index,
+                        group: meta[0] + 1,
+                        member: meta[3] + 1,
+                    },
+                ));
+                continue;
+            }
+            None => accepted.push(share),
+        }
+    }
+
+    problems.sort_by_key(|problem| problem.index);
+    problems
+}
+
+// The value most of the items have, the first one seen on a tie
+fn most_common<T: PartialEq + Copy>(items: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: Vec<(T, usize)> = vec![];
+    for item in items {
+        match counts.iter_mut().find(|(value, _)| *value == item) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((item, 1)),
+        }
+    }
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(value, _)| *value)
+}
+
+// The raw secret, which may be of a length no BIP-39 mnemonic has
+pub fn recover_secret(lines: Vec<String>, minimal: &bool) -> Result<Zeroizing<Vec<u8>>, Error> {
+    // Parse the lines in parallel, then go through them in the order given,
+    // so the first bad line is the one reported
+    let parsed: Vec<Result<EncodedShare, Error>> = lines
+        .par_iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| EncodedShare::parse_styled(line, minimal))
+        .collect();
+    recover_shares(&parsed.into_iter().collect::<Result<Vec<_>, _>>()?)
+}
+
+pub fn recover_shares(parsed: &[EncodedShare]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let mut shares: Vec<&[u8]> = vec![];
     let mut share_ids: Vec<u16> = vec![];
     let mut share_meta: Vec<[usize; 5]> = vec![];
-    for share in shares.clone() {
-        let (id, meta) = share_metadata(&share, minimal)?;
+
+    for parsed in parsed {
+        let (share, id, meta) = (parsed.share(), parsed.identifier(), parsed.metadata());
+
+        // The same share given twice counts once, but two different shares
+        // claiming the same place mean one of them was mistyped or tampered with
+        let same_place = share_meta
+            .iter()
+            .position(|other| other[0] == meta[0] && other[3] == meta[3]);
+        if let Some(i) = same_place.filter(|i| share_ids[*i] == id) {
+            if shares[i] == share {
+                continue;
+            }
+            bail!(SskrToolError::ConflictingShares {
+                group: meta[0] + 1,
+                member: meta[3] + 1
+            });
+        }
+
+        shares.push(share);
         share_ids.push(id);
         share_meta.push(meta);
     }
 
-    let identifier = share_ids[0];
+    let Some(&identifier) = share_ids.first() else {
+        bail!(SskrToolError::NoShares);
+    };
 
     // Make sure identifier is the same for all shares
-    if share_ids.iter().any(|id| id != &identifier) {
-        bail!("Mismatched identifiers, shares don't go together");
+    if let Some(&found) = share_ids.iter().find(|id| *id != &identifier) {
+        bail!(SskrToolError::MismatchedIdentifier {
+            expected: identifier,
+            found
+        });
     }
 
     let group_threshold = share_meta[0][1];
@@ -47,22 +210,14 @@ pub fn recover(lines: Vec<String>, minimal: &bool) -> Result<Mnemonic, Error> {
         .iter()
         .any(|meta| meta[1] != group_threshold || meta[2] != group_count)
     {
-        bail!("Mismatched group threshold or count, shares don't go together");
+        bail!(SskrToolError::MismatchedGroups);
     }
 
-    // Group shares by group in the form { group_num => Vec<(share_index, share)> }
-    let mut shares_by_group: HashMap<usize, Vec<(usize, Vec<u8>)>> = HashMap::new();
-    for (i, share) in shares.clone().iter().enumerate() {
-        let share_group_num = share_meta[i][0];
-
-        if !shares_by_group.contains_key(&share_group_num) {
-            shares_by_group.insert(share_group_num, vec![]);
-        }
-
-        shares_by_group
-            .get_mut(&share_group_num)
-            .unwrap()
-            .push((i, share.to_vec()));
+    // Group shares by group in the form { group_num => Vec<share_index> },
+    // ordered so groups are checked and reported the same way on every run
+    let mut shares_by_group: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, meta) in share_meta.iter().enumerate() {
+        shares_by_group.entry(meta[0]).or_default().push(i);
     }
 
     // See how many groups are recoverable
@@ -71,15 +226,11 @@ pub fn recover(lines: Vec<String>, minimal: &bool) -> Result<Mnemonic, Error> {
     // Look through the groups one-by-one to validate and gather information
     for (group_num, shares) in &shares_by_group {
         // Make sure the member threshold is the same for all shares in the group
-        let member_threshold = share_meta[shares[0].0][4];
-        if shares
-            .iter()
-            .any(|(i, _share)| share_meta[*i][4] != member_threshold)
-        {
-            bail!(
-                "Mismatched share member thresholds in group {}, shares don't go together",
-                group_num + 1
-            );
+        let member_threshold = share_meta[shares[0]][4];
+        if shares.iter().any(|i| share_meta[*i][4] != member_threshold) {
+            bail!(SskrToolError::MismatchedMemberThreshold {
+                group: group_num + 1
+            });
         }
 
         // See whether this group is recoverable
@@ -90,41 +241,92 @@ pub fn recover(lines: Vec<String>, minimal: &bool) -> Result<Mnemonic, Error> {
 
     // Make sure there are enough groups to recover the secret
     if recoverable_groups.len() < group_threshold {
-        bail!(
-            "Not enough groups, need to satisfy at least {} but only {} are satisfied ({})",
-            group_threshold,
-            recoverable_groups.len(),
-            recoverable_groups
-                .iter()
-                .map(|g| (g + 1).to_string())
-                .collect::<Vec<String>>()
-                .join(" and ")
-        )
+        bail!(SskrToolError::InsufficientGroups {
+            needed: group_threshold,
+            have: recoverable_groups.iter().map(|g| g + 1).collect()
+        })
     }
 
-    let mut shares_for_recovery: Vec<Vec<u8>> = vec![];
-
-    // Gather shares from enough theoretically-recoverable groups
-    for group_num in recoverable_groups.iter().take(group_threshold) {
-        // Get just the shares without the group number
-        let group_shares = shares_by_group[&group_num]
+    // Combine the shares of enough recoverable groups, borrowing them rather
+    // than leaving more copies around. A group holding a bad share fails its
+    // digest, so other choices of groups are tried, stopping at the first
+    // that recovers.
+    let mut first_error = None;
+    for subset in subsets(recoverable_groups.len(), group_threshold) {
+        let shares_for_recovery: Vec<&[u8]> = subset
             .iter()
-            .map(|(_i, share)| share.to_vec())
-            .collect::<Vec<Vec<u8>>>();
+            .flat_map(|index| &shares_by_group[&recoverable_groups[*index]])
+            .map(|i| shares[*i])
+            .collect();
+        // The combined secret is copied out of sskr's own type, which isn't wiped
+        match sskr_combine(&shares_for_recovery) {
+            Ok(secret) => return Ok(Zeroizing::new(secret.data().to_vec())),
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(SskrToolError::CombinationFailed(
+        first_error
+            .expect("there is at least one choice of groups")
+            .to_string(),
+    )
+    .into())
+}
 
-        for share in group_shares {
-            shares_for_recovery.push(share);
+// The ways of choosing `size` of `count` items, as indices in increasing
+// order, generated one at a time
+pub(crate) fn subsets(count: usize, size: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut next = (size <= count).then(|| (0..size).collect::<Vec<usize>>());
+    std::iter::from_fn(move || {
+        let current = next.take()?;
+        // Advance the rightmost index that can still move, resetting the
+        // ones after it to follow it
+        let mut following = current.clone();
+        if let Some(position) = (0..size).rev().find(|&i| following[i] < count - size + i) {
+            following[position] += 1;
+            for i in position + 1..size {
+                following[i] = following[i - 1] + 1;
+            }
+            next = Some(following);
         }
+        Some(current)
+    })
+}
+
+// Every line is read in whichever format it is in, so shares given back in
+// different formats can be combined
+pub fn decode_share(line: &str) -> Result<Vec<u8>, Error> {
+    Ok(decode(line)?.1)
+}
+
+// Shares from newer SSKR revisions or other implementations may set bits this
+// version reserves. The share is returned with them cleared, in the format it
+// was given in, along with a warning for each deviation. Secrets of lengths
+// SSKR can't combine are still refused, there is nothing to fall back on.
+pub fn lenient_share(line: &str) -> Result<(String, Vec<String>), Error> {
+    let (format, mut share) = decode(line)?;
+    let mut warnings = vec![];
+
+    if share.len() > METADATA_SIZE_BYTES && share[4] >> 4 != 0 {
+        warnings.push(format!(
+            "reserved bits are set (0x{:x}), ignoring them",
+            share[4] >> 4
+        ));
+        share[4] &= 0x0f;
     }
 
-    let secret = sskr_combine(&shares_for_recovery)
-        .map_err(|e| anyhow!("Error during SSKR combination: {}", e))?;
+    let secret_len = share.len().saturating_sub(METADATA_SIZE_BYTES);
+    if !is_valid_secret_len(secret_len) {
+        bail!(SskrToolError::UncombinableSecret {
+            length: secret_len,
+            min: MIN_SECRET_LEN,
+            max: MAX_SECRET_LEN
+        });
+    }
 
-    Mnemonic::from_entropy(secret.data(), Language::English).map_err(|e| {
-        anyhow!(
-            "Recovered entropy 0x{} but unable to make mnemonic: {}",
-            hex::encode(secret.data()),
-            e
-        )
-    })
+    if warnings.is_empty() {
+        return Ok((line.to_string(), warnings));
+    }
+    Ok((encode(&share, format), warnings))
 }
@@ -0,0 +1,236 @@
+use crate::duress::{DuressKeys, Ending, Press};
+use crate::input::ShareLine;
+use crate::output::ShareMetadataOutput;
+use crate::share_format::{decode, encode, ShareFormat};
+use anyhow::{anyhow, Error};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use zeroize::Zeroizing;
+
+const GLYPH_HEIGHT: usize = 5;
+// Each glyph is five columns wide, plus one column between glyphs
+const GLYPH_WIDTH: usize = 6;
+
+#[rustfmt::skip]
+const GLYPHS: &[(char, [&str; GLYPH_HEIGHT])] = &[
+    ('a', [" ### ", "#   #", "#####", "#   #", "#   #"]),
+    ('b', ["#### ", "#   #", "#### ", "#   #", "#### "]),
+    ('c', [" ####", "#    ", "#    ", "#    ", " ####"]),
+    ('d', ["#### ", "#   #", "#   #", "#   #", "#### "]),
+    ('e', ["#####", "#    ", "#### ", "#    ", "#####"]),
+    ('f', ["#####", "#    ", "#### ", "#    ", "#    "]),
+    ('g', [" ####", "#    ", "#  ##", "#   #", " ####"]),
+    ('h', ["#   #", "#   #", "#####", "#   #", "#   #"]),
+    ('i', ["#####", "  #  ", "  #  ", "  #  ", "#####"]),
+    ('j', ["#####", "   # ", "   # ", "#  # ", " ##  "]),
+    ('k', ["#   #", "#  # ", "###  ", "#  # ", "#   #"]),
+    ('l', ["#    ", "#    ", "#    ", "#    ", "#####"]),
+    ('m', ["#   #", "## ##", "# # #", "#   #", "#   #"]),
+    ('n', ["#   #", "##  #", "# # #", "#  ##", "#   #"]),
+    ('o', [" ### ", "#   #", "#   #", "#   #", " ### "]),
+    ('p', ["#### ", "#   #", "#### ", "#    ", "#    "]),
+    ('q', [" ### ", "#   #", "# # #", "#  # ", " ## #"]),
+    ('r', ["#### ", "#   #", "#### ", "#  # ", "#   #"]),
+    ('s', [" ####", "#    ", " ### ", "    #", "#### "]),
+    ('t', ["#####", "  #  ", "  #  ", "  #  ", "  #  "]),
+    ('u', ["#   #", "#   #", "#   #", "#   #", " ### "]),
+    ('v', ["#   #", "#   #", "#   #", " # # ", "  #  "]),
+    ('w', ["#   #", "#   #", "# # #", "## ##", "#   #"]),
+    ('x', ["#   #", " # # ", "  #  ", " # # ", "#   #"]),
+    ('y', ["#   #", " # # ", "  #  ", "  #  ", "  #  "]),
+    ('z', ["#####", "   # ", "  #  ", " #   ", "#####"]),
+    ('0', [" ### ", "#  ##", "# # #", "##  #", " ### "]),
+    ('1', ["  #  ", " ##  ", "  #  ", "  #  ", " ### "]),
+    ('2', [" ### ", "#   #", "  ## ", " #   ", "#####"]),
+    ('3', ["#### ", "    #", " ### ", "    #", "#### "]),
+    ('4', ["#   #", "#   #", "#####", "    #", "    #"]),
+    ('5', ["#####", "#    ", "#### ", "    #", "#### "]),
+    ('6', [" ### ", "#    ", "#### ", "#   #", " ### "]),
+    ('7', ["#####", "    #", "   # ", "  #  ", "  #  "]),
+    ('8', [" ### ", "#   #", " ### ", "#   #", " ### "]),
+    ('9', [" ### ", "#   #", " ####", "    #", " ### "]),
+    (':', ["     ", "  #  ", "     ", "  #  ", "     "]),
+    ('/', ["    #", "   # ", "  #  ", " #   ", "#    "]),
+    ('-', ["     ", "     ", "#####", "     ", "     "]),
+    (' ', ["     ", "     ", "     ", "     ", "     "]),
+];
+
+const UNKNOWN_GLYPH: [&str; GLYPH_HEIGHT] = [" ### ", "#   #", "  ## ", "     ", "  #  "];
+
+// One share as shown on its own screen
+pub struct Card {
+    pub title: String,
+    pub text: Zeroizing<String>,
+}
+
+// A card for every share line, in the given format or the one it was read in
+pub fn cards(lines: &[ShareLine], format: Option<ShareFormat>) -> Result<Vec<Card>, Error> {
+    lines
+        .iter()
+        .map(|line| {
+            let (detected, share) =
+                decode(&line.text).map_err(|error| anyhow!("{}: {}", line, error))?;
+            let share = Zeroizing::new(share);
+            let metadata = ShareMetadataOutput::new(&share)?;
+            Ok(Card {
+                title: format!(
+                    "Share set {} - group {} of {}, share {} (need {})",
+                    metadata.identifier,
+                    metadata.group_index + 1,
+                    metadata.group_count,
+                    metadata.member_index + 1,
+                    metadata.member_threshold
+                ),
+                text: Zeroizing::new(encode(&share, format.unwrap_or(detected))),
+            })
+        })
+        .collect()
+}
+
+// The text broken into rows of at most `columns` characters, at spaces and
+// after hyphens where it can be, so words aren't split across rows
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let columns = columns.max(1);
+    let mut rows: Vec<String> = vec![];
+    let mut row = String::new();
+
+    for word in text.split_inclusive([' ', '-']) {
+        let word = word.trim_end_matches(' ');
+        for piece in word.chars().collect::<Vec<char>>().chunks(columns) {
+            let piece = piece.iter().collect::<String>();
+            let separator = usize::from(!row.is_empty() && !row.ends_with('-'));
+            if row.chars().count() + separator + piece.chars().count() > columns {
+                rows.push(std::mem::take(&mut row));
+            } else if separator == 1 {
+                row.push(' ');
+            }
+            row.push_str(&piece);
+        }
+    }
+    if !row.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
+
+// The text in letters five lines high, wrapped to fit the width of the
+// screen, with a blank line between rows
+pub fn big_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for row in wrap(&text.to_lowercase(), width / GLYPH_WIDTH) {
+        let glyphs = row
+            .chars()
+            .map(|c| {
+                GLYPHS
+                    .iter()
+                    .find(|(glyph, _)| *glyph == c)
+                    .map_or(UNKNOWN_GLYPH, |(_, lines)| *lines)
+            })
+            .collect::<Vec<_>>();
+        for line in 0..GLYPH_HEIGHT {
+            lines.push(
+                glyphs
+                    .iter()
+                    .map(|glyph| glyph[line].replace('#', "█"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+// Puts the terminal back however the review ends
+pub(crate) struct Screen;
+
+impl Screen {
+    pub(crate) fn enter() -> Result<Screen, Error> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            Clear(ClearType::All),
+            Show,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Shows the cards one at a time on the alternate screen, which is cleared
+// when the review ends, so no share is left in the scrollback
+pub fn run(cards: &[Card], duress: &DuressKeys) -> Result<Ending, Error> {
+    if cards.is_empty() {
+        return Ok(Ending::Done);
+    }
+    let _screen = Screen::enter()?;
+    let mut watch = duress.watch();
+    let mut current = 0;
+
+    loop {
+        draw(&cards[current], current, cards.len())?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match watch.press(&key) {
+            Press::Wipe => return Ok(Ending::Wiped),
+            Press::Swallowed => continue,
+            Press::Pass => {}
+        }
+        match key.code {
+            KeyCode::Right | KeyCode::Down | KeyCode::PageDown | KeyCode::Char(' ' | 'n' | 'l') => {
+                current = (current + 1).min(cards.len() - 1)
+            }
+            KeyCode::Left
+            | KeyCode::Up
+            | KeyCode::PageUp
+            | KeyCode::Backspace
+            | KeyCode::Char('p' | 'h') => current = current.saturating_sub(1),
+            KeyCode::Home => current = 0,
+            KeyCode::End => current = cards.len() - 1,
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(Ending::Done),
+            _ => {}
+        }
+    }
+}
+
+fn draw(card: &Card, index: usize, count: usize) -> Result<(), Error> {
+    let (width, height) = terminal::size()?;
+    let mut stdout = io::stdout();
+    queue!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+
+    // Raw mode doesn't turn newlines into carriage returns, so every line is
+    // placed explicitly
+    let mut lines = vec![
+        format!("[{}/{}] {}", index + 1, count, card.title),
+        String::new(),
+    ];
+    lines.extend(big_text(&card.text, width as usize));
+    for (row, line) in lines
+        .iter()
+        .enumerate()
+        .take(height.saturating_sub(1) as usize)
+    {
+        queue!(stdout, MoveTo(0, row as u16))?;
+        write!(stdout, "{}", line)?;
+    }
+    queue!(stdout, MoveTo(0, height.saturating_sub(1)))?;
+    write!(stdout, "<- previous   -> next   q quit")?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+// Shares typed in by keyboard wedge scanners, or pasted from documents, pick up
+// characters that no share contains. These are cleaned up before parsing.
+
+// Scanners send key positions of a US layout. On a host with another layout
+// those positions produce other characters, received => intended.
+const QWERTZ: &[(char, char)] = &[
+    ('y', 'z'),
+    ('z', 'y'),
+    ('Y', 'Z'),
+    ('Z', 'Y'),
+    ('Ö', ':'),
+    ('-', '/'),
+];
+const AZERTY: &[(char, char)] = &[
+    ('q', 'a'),
+    ('a', 'q'),
+    ('z', 'w'),
+    ('w', 'z'),
+    (',', 'm'),
+    ('Q', 'A'),
+    ('A', 'Q'),
+    ('Z', 'W'),
+    ('W', 'Z'),
+    ('?', 'M'),
+    ('M', ':'),
+    ('!', '/'),
+];
+
+pub fn sanitize(input: &str) -> String {
+    let mut cleaned = String::with_capacity(input.len());
+
+    for c in input.chars() {
+        let c = match c {
+            // Quotes from word processors, or the ones the share was copied in
+            '"' | '\'' | '`' | '‘' | '’' | '‚' | '“' | '”' | '„' | '«' | '»' => {
+                continue
+            }
+            // Zero-width characters and byte order marks
+            '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}' => continue,
+            // Full-width forms of ASCII characters
+            '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+            c if c.is_whitespace() => ' ',
+            c if c.is_control() => continue,
+            c => c,
+        };
+        cleaned.push(c);
+    }
+
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub const LAYOUTS: &[(&str, &[(char, char)])] = &[("QWERTZ", QWERTZ), ("AZERTY", AZERTY)];
+
+// The sanitized input as if it had been typed through the given layout
+pub fn remap(input: &str, layout: &[(char, char)]) -> String {
+    sanitize(input)
+        .chars()
+        .map(|c| {
+            layout
+                .iter()
+                .find(|(received, _)| *received == c)
+                .map_or(c, |(_, intended)| *intended)
+        })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+// The input as received plus its readings under the keyboard layouts that
+// commonly swap letters, all sanitized. Only one of them can pass the share's
+// checksum, so trying each in turn is safe.
+pub fn candidates(input: &str) -> Vec<String> {
+    let mut candidates = vec![sanitize(input).to_lowercase()];
+
+    for (_name, layout) in LAYOUTS {
+        let remapped = remap(input, layout);
+        if !candidates.contains(&remapped) {
+            candidates.push(remapped);
+        }
+    }
+
+    candidates
+}
@@ -0,0 +1,127 @@
+use crate::input::STDIN;
+use crate::recover::decode_share;
+use crate::sskr_shares::share_metadata;
+use crate::status::status;
+use anyhow::{Context, Error};
+use std::collections::BTreeSet;
+use std::io::{self, BufReader, ErrorKind, Read};
+use std::iter;
+use std::time::Duration;
+
+// How long a read may block before it is retried, scanners sit idle for a
+// long time between shares
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub fn open_device(device: &str, baud: u32) -> Result<Box<dyn Read>, Error> {
+    if device == STDIN {
+        return Ok(Box::new(io::stdin()));
+    }
+    let port = serialport::new(device, baud)
+        .timeout(READ_TIMEOUT)
+        .open()
+        .with_context(|| format!("Unable to open serial device \"{}\"", device))?;
+    Ok(Box::new(port))
+}
+
+// Reads shares one line at a time as they are scanned, checking each one as it
+// arrives. Misreads, duplicates and shares from another set are reported and
+// skipped rather than aborting, so the scan can simply be repeated. Stops as
+// soon as the accepted shares are enough to recover.
+pub fn collect_shares(
+    reader: impl Read,
+    minimal: &bool,
+    mut report: impl FnMut(&str),
+) -> Result<Vec<String>, Error> {
+    let mut collector = Collector::new(minimal);
+    let mut line = vec![];
+
+    // A final line may arrive without a line ending before the input closes
+    let bytes = BufReader::new(reader).bytes().chain(iter::once(Ok(b'\n')));
+
+    for byte in bytes {
+        let byte = match byte {
+            Ok(byte) => byte,
+            Err(error) if error.kind() == ErrorKind::TimedOut => continue,
+            Err(error) => return Err(error).context("Unable to read from device"),
+        };
+
+        // Scanners end lines with either a carriage return or a line feed
+        if byte != b'\r' && byte != b'\n' {
+            line.push(byte);
+            continue;
+        }
+
+        let text = String::from_utf8_lossy(&line).trim().to_string();
+        line.clear();
+        if !text.is_empty() && collector.offer(text, &mut report)? {
+            break;
+        }
+    }
+
+    Ok(collector.shares)
+}
+
+// The shares of a set as they come in one at a time, from a scanner or
+// tags on a reader
+pub struct Collector<'a> {
+    minimal: &'a bool,
+    shares: Vec<String>,
+    identifier: Option<u16>,
+    members: BTreeSet<(usize, usize)>,
+}
+
+impl<'a> Collector<'a> {
+    pub fn new(minimal: &'a bool) -> Collector<'a> {
+        Collector {
+            minimal,
+            shares: vec![],
+            identifier: None,
+            members: BTreeSet::new(),
+        }
+    }
+
+    // Takes the share unless it's unreadable, a duplicate or from another
+    // set, and tells whether the shares taken are enough to recover
+    pub fn offer(&mut self, text: String, report: &mut impl FnMut(&str)) -> Result<bool, Error> {
+        let (id, meta) =
+            match decode_share(&text).and_then(|share| share_metadata(&share, self.minimal)) {
+                Ok(decoded) => decoded,
+                Err(error) => {
+                    report(&format!("Rejected unreadable share: {}", error));
+                    return Ok(false);
+                }
+            };
+
+        if *self.identifier.get_or_insert(id) != id {
+            report(&format!(
+                "Rejected share from a different set (identifier {:04x})",
+                id
+            ));
+            return Ok(false);
+        }
+        if !self.members.insert((meta[0], meta[3])) {
+            report(&format!(
+                "Rejected duplicate share: group {}, member {}",
+                meta[0] + 1,
+                meta[3] + 1
+            ));
+            return Ok(false);
+        }
+
+        self.shares.push(text);
+        let progress = status(self.shares.clone(), self.minimal, None)?;
+        let set = &progress[0];
+        report(&format!(
+            "Accepted share: group {}, member {} ({} of {} groups satisfied)",
+            meta[0] + 1,
+            meta[3] + 1,
+            set.groups_satisfied,
+            meta[1]
+        ));
+        Ok(set.recoverable)
+    }
+
+    pub fn into_shares(self) -> Vec<String> {
+        self.shares
+    }
+}
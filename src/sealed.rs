@@ -0,0 +1,72 @@
+use anyhow::{anyhow, bail, Context, Error};
+use bc_crypto::{
+    aead_chacha20_poly1305_decrypt_with_aad, aead_chacha20_poly1305_encrypt_with_aad,
+    SYMMETRIC_AUTH_SIZE, SYMMETRIC_KEY_SIZE, SYMMETRIC_NONCE_SIZE,
+};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+// Identifies a sealed file and its layout, and is authenticated along with
+// the contents
+const MAGIC: &[u8] = b"sskr-tool sealed file v1\n";
+const HEADER_SIZE: usize = MAGIC.len() + SYMMETRIC_NONCE_SIZE + SYMMETRIC_AUTH_SIZE;
+
+// A file too long for SSKR is encrypted (ChaCha20-Poly1305) with a random key,
+// and only the 32 byte key is split. The sealed file can be kept anywhere; it
+// is useless without enough shares to recover the key.
+pub fn seal(contents: &[u8]) -> (Zeroizing<[u8; SYMMETRIC_KEY_SIZE]>, Vec<u8>) {
+    let key = Zeroizing::new(rand::random::<[u8; SYMMETRIC_KEY_SIZE]>());
+    let nonce = rand::random::<[u8; SYMMETRIC_NONCE_SIZE]>();
+    let (ciphertext, auth) =
+        aead_chacha20_poly1305_encrypt_with_aad(contents, &key, &nonce, &MAGIC);
+
+    let mut sealed = MAGIC.to_vec();
+    sealed.extend(nonce);
+    sealed.extend(auth);
+    sealed.extend(ciphertext);
+    (key, sealed)
+}
+
+pub fn open(sealed: &[u8], key: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    if !sealed.starts_with(MAGIC) || sealed.len() < HEADER_SIZE {
+        bail!("Not a file sealed by sskr-tool");
+    }
+    let key: &[u8; SYMMETRIC_KEY_SIZE] = key.try_into().map_err(|_| {
+        anyhow!(
+            "The shares hold a {} byte secret, not the {} byte key of a sealed file",
+            key.len(),
+            SYMMETRIC_KEY_SIZE
+        )
+    })?;
+    let (nonce, rest) = sealed[MAGIC.len()..].split_at(SYMMETRIC_NONCE_SIZE);
+    let (auth, ciphertext) = rest.split_at(SYMMETRIC_AUTH_SIZE);
+
+    let contents = aead_chacha20_poly1305_decrypt_with_aad(
+        ciphertext,
+        key,
+        nonce.try_into()?,
+        MAGIC,
+        auth.try_into()?,
+    )
+    .map_err(|_| anyhow!("The shares don't open this sealed file, or it was changed"))?;
+    Ok(Zeroizing::new(contents))
+}
+
+// Where the sealed copy of a file goes unless told otherwise: next to it
+pub fn default_path(input: &Path) -> PathBuf {
+    let mut name = input.as_os_str().to_owned();
+    name.push(".sealed");
+    PathBuf::from(name)
+}
+
+// Neither the sealed file nor the recovered one replaces an existing file
+pub fn write_new(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .with_context(|| format!("Unable to write \"{}\"", path.display()))
+}
@@ -0,0 +1,187 @@
+use crate::bytewords::{byteword_string, byteword_string_to_bytes, BytewordStyle};
+use anyhow::{bail, ensure, Context, Error};
+use dcbor::{CBOREncodable, Map, CBOR};
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use zeroize::Zeroizing;
+
+// A seed as Gordian SeedTool and compatible wallets import it by QR code
+// (BCR-2020-006): the secret with an optional name, note and creation date,
+// as a CBOR map in a UR.
+
+const PAYLOAD: u64 = 1;
+const CREATION_DATE: u64 = 2;
+const NAME: u64 = 3;
+const NOTE: u64 = 4;
+// ur:seed dates are seconds since the Unix epoch, ur:crypto-seed dates days
+const DATE_TAG: u64 = 1;
+const FULL_DATE_TAG: u64 = 100;
+const SECONDS_PER_DAY: i64 = 86400;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SeedUrType {
+    // As current Gordian tools write it
+    #[default]
+    Seed,
+    // For wallets from before ur:seed
+    CryptoSeed,
+}
+
+impl SeedUrType {
+    fn prefix(&self) -> &'static str {
+        match self {
+            SeedUrType::Seed => "ur:seed/",
+            SeedUrType::CryptoSeed => "ur:crypto-seed/",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SeedMetadata {
+    pub name: Option<String>,
+    pub note: Option<String>,
+    // Days since 1970-01-01
+    pub creation_date: Option<i64>,
+}
+
+pub fn encode(secret: &[u8], metadata: &SeedMetadata, ur_type: SeedUrType) -> Zeroizing<String> {
+    let mut map = Map::new();
+    map.insert(CBOR::Unsigned(PAYLOAD), CBOR::byte_string(secret));
+    if let Some(days) = metadata.creation_date {
+        let date = match ur_type {
+            SeedUrType::Seed => CBOR::tagged_value(DATE_TAG, integer(days * SECONDS_PER_DAY)),
+            SeedUrType::CryptoSeed => CBOR::tagged_value(FULL_DATE_TAG, integer(days)),
+        };
+        map.insert(CBOR::Unsigned(CREATION_DATE), date);
+    }
+    if let Some(name) = &metadata.name {
+        map.insert(CBOR::Unsigned(NAME), CBOR::Text(name.clone()));
+    }
+    if let Some(note) = &metadata.note {
+        map.insert(CBOR::Unsigned(NOTE), CBOR::Text(note.clone()));
+    }
+    let cbor = Zeroizing::new(CBOR::Map(map).cbor_data());
+    Zeroizing::new(format!(
+        "{}{}",
+        ur_type.prefix(),
+        byteword_string(&cbor, BytewordStyle::Minimal)
+    ))
+}
+
+// Reads a ur:seed or ur:crypto-seed back
+pub fn decode(input: &str) -> Result<(Zeroizing<Vec<u8>>, SeedMetadata), Error> {
+    let input = input.trim().to_lowercase();
+    let Some((ur_type, body)) = [SeedUrType::Seed, SeedUrType::CryptoSeed]
+        .into_iter()
+        .find_map(|ur_type| Some((ur_type, input.strip_prefix(ur_type.prefix())?)))
+    else {
+        bail!("Not a ur:seed or ur:crypto-seed string");
+    };
+    let bytes = Zeroizing::new(byteword_string_to_bytes(body, BytewordStyle::Minimal)?);
+    let CBOR::Map(map) = CBOR::from_data(&bytes)? else {
+        bail!("A seed is a CBOR map");
+    };
+
+    let mut secret = None;
+    let mut metadata = SeedMetadata::default();
+    for (key, value) in map.iter() {
+        match (key, value) {
+            (CBOR::Unsigned(PAYLOAD), CBOR::ByteString(payload)) => {
+                secret = Some(Zeroizing::new(payload.clone()))
+            }
+            (CBOR::Unsigned(CREATION_DATE), CBOR::Tagged(tag, date)) => {
+                let value = match **date {
+                    CBOR::Unsigned(value) => i64::try_from(value)?,
+                    CBOR::Negative(value) => value,
+                    _ => bail!("Invalid seed creation date"),
+                };
+                metadata.creation_date = Some(match (ur_type, tag.value()) {
+                    (_, DATE_TAG) => value.div_euclid(SECONDS_PER_DAY),
+                    (SeedUrType::CryptoSeed, FULL_DATE_TAG) => value,
+                    _ => bail!("Invalid seed creation date"),
+                });
+            }
+            (CBOR::Unsigned(NAME), CBOR::Text(name)) => metadata.name = Some(name.clone()),
+            (CBOR::Unsigned(NOTE), CBOR::Text(note)) => metadata.note = Some(note.clone()),
+            _ => bail!("Unexpected field in seed"),
+        }
+    }
+    let secret = secret.context("The seed has no payload")?;
+    Ok((secret, metadata))
+}
+
+// The UR as a QR code to scan off the terminal. The light modules are the ones
+// drawn, so the code reads the right way round on a dark terminal. Upper case
+// fits the QR code's alphanumeric mode.
+pub fn terminal_qr(ur: &str) -> Result<Zeroizing<String>, Error> {
+    let code = QrCode::new(ur.to_uppercase()).context("Unable to encode the seed as a QR code")?;
+    Ok(Zeroizing::new(
+        code.render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .build(),
+    ))
+}
+
+// A date as YYYY-MM-DD, as days since 1970-01-01
+pub fn parse_date(input: &str) -> Result<i64, Error> {
+    let invalid = || format!("Invalid date \"{}\", expected YYYY-MM-DD", input);
+    let parts: Vec<&str> = input.trim().split('-').collect();
+    let [year, month, day] = parts[..] else {
+        bail!(invalid());
+    };
+    let (year, month, day): (i64, i64, i64) = (
+        year.parse().with_context(invalid)?,
+        month.parse().with_context(invalid)?,
+        day.parse().with_context(invalid)?,
+    );
+    ensure!((1..=12).contains(&month), invalid());
+    let days = days_from_civil(year, month, day);
+    // Days past the end of the month come out as days of the next one
+    ensure!(
+        day >= 1 && civil_from_days(days) == (year, month, day),
+        invalid()
+    );
+    Ok(days)
+}
+
+pub fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn integer(value: i64) -> CBOR {
+    match u64::try_from(value) {
+        Ok(value) => CBOR::Unsigned(value),
+        Err(_) => CBOR::Negative(value),
+    }
+}
+
+// The proleptic Gregorian calendar in days since 1970-01-01, after Howard
+// Hinnant's chrono-compatible date algorithms
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
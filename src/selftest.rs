@@ -0,0 +1,195 @@
+use crate::bytewords::{
+    byteword_checksum, byteword_string, byteword_string_to_bytes, BytewordStyle,
+};
+use anyhow::{bail, Error};
+use bc_crypto::RandomNumberGenerator;
+use serde::Serialize;
+use sskr::{sskr_combine, sskr_generate_using, GroupSpec, Secret, Spec};
+
+#[derive(Serialize, Debug)]
+pub struct VectorResult {
+    pub name: String,
+    // Where the expected values are published
+    pub source: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// The generator the reference Shamir vectors are made with: every request
+// for random bytes gets 0x00, 0x11, 0x22 and so on
+struct VectorRandomNumberGenerator;
+
+impl RandomNumberGenerator for VectorRandomNumberGenerator {
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_random_data(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn random_data(&mut self, size: usize) -> Vec<u8> {
+        let mut data = vec![0u8; size];
+        self.fill_random_data(&mut data);
+        data
+    }
+
+    fn fill_random_data(&mut self, data: &mut [u8]) {
+        let mut byte = 0u8;
+        for x in data.iter_mut() {
+            *x = byte;
+            byte = byte.wrapping_add(17);
+        }
+    }
+}
+
+const SHAMIR_SOURCE: &str = "Blockchain Commons bc-shamir 0.1.2 tests";
+const BYTEWORDS_SOURCE: &str = "Blockchain Commons BCR-2020-012";
+const CRC32_SOURCE: &str = "Blockchain Commons bc-crypto 0.1.4 tests";
+
+// A single group split with the vector generator. The group level of SSKR
+// takes no randomness for one group, so the member shares are exactly the
+// published Shamir shares, behind the 5 byte SSKR header.
+struct ShamirVector {
+    name: &'static str,
+    secret: &'static str,
+    threshold: usize,
+    shares: &'static [&'static str],
+    recover_from: &'static [usize],
+}
+
+const SHAMIR_VECTORS: [ShamirVector; 2] = [
+    ShamirVector {
+        name: "SSKR 3 of 5, 16 byte secret",
+        secret: "0ff784df000c4380a5ed683f7e6e3dcf",
+        threshold: 3,
+        shares: &[
+            "00112233445566778899aabbccddeeff",
+            "d43099fe444807c46921a4f33a2a798b",
+            "d9ad4e3bec2e1a7485698823abf05d36",
+            "0d8cf5f6ec337bc764d1866b5d07ca42",
+            "1aa7fe3199bc5092ef3816b074cabdf2",
+        ],
+        recover_from: &[1, 2, 4],
+    },
+    ShamirVector {
+        name: "SSKR 2 of 7, 32 byte secret",
+        secret: "204188bfa6b440a1bdfd6753ff55a8241e07af5c5be943db917e3efabc184b1a",
+        threshold: 2,
+        shares: &[
+            "2dcd14c2252dc8489af3985030e74d5a48e8eff1478ab86e65b43869bf39d556",
+            "a1dfdd798388aada635b9974472b4fc59a32ae520c42c9f6a0af70149b882487",
+            "2ee99daf727c0c7773b89a18de64497ff7476dacd1015a45f482a893f7402cef",
+            "a2fb5414d4d96ee58a109b3ca9a84be0259d2c0f9ac92bdd3199e0eed3f1dd3e",
+            "2b851d188b8f5b3653659cc0f7fa45102dadf04b708767385cd803862fcb3c3f",
+            "a797d4a32d2a39a4aacd9de48036478fff77b1e83b4f16a099c34bfb0b7acdee",
+            "28a19475dcde9f09ba2e9e881979413592027216e60c8513cdee937c67b2c586",
+        ],
+        recover_from: &[3, 4],
+    },
+];
+
+fn check_shamir(vector: &ShamirVector) -> Result<(), Error> {
+    let secret = hex::decode(vector.secret)?;
+    let spec = Spec::new(
+        1,
+        vec![GroupSpec::new(vector.threshold, vector.shares.len())?],
+    )?;
+    let shares = sskr_generate_using(
+        &spec,
+        &Secret::new(&secret)?,
+        &mut VectorRandomNumberGenerator,
+    )?
+    .concat();
+
+    for (member, (share, expected)) in shares.iter().zip(vector.shares).enumerate() {
+        // Identifier 0011 (the first two generated bytes), group 1 of 1,
+        // then the member threshold and index
+        let header = [0x00, 0x11, 0x00, (vector.threshold - 1) as u8, member as u8];
+        if share[..5] != header {
+            bail!(
+                "Share {} has header {}, expected {}",
+                member + 1,
+                hex::encode(&share[..5]),
+                hex::encode(header)
+            );
+        }
+        if hex::encode(&share[5..]) != *expected {
+            bail!(
+                "Share {} is {}, expected {}",
+                member + 1,
+                hex::encode(&share[5..]),
+                expected
+            );
+        }
+    }
+
+    let recovered = sskr_combine(
+        &vector
+            .recover_from
+            .iter()
+            .map(|index| &shares[*index])
+            .collect::<Vec<_>>(),
+    )?;
+    if recovered.data() != secret.as_slice() {
+        bail!("Recovered {}", hex::encode(recovered.data()));
+    }
+    Ok(())
+}
+
+fn check_bytewords() -> Result<(), Error> {
+    let bytes = [0x00, 0x01, 0x02, 0x80, 0xff];
+    for (style, expected) in [
+        (
+            BytewordStyle::Standard,
+            "able acid also lava zoom jade need echo taxi",
+        ),
+        (BytewordStyle::Minimal, "aeadaolazmjendeoti"),
+    ] {
+        let encoded = byteword_string(&bytes, style);
+        if encoded != expected {
+            bail!("Encoded as \"{}\", expected \"{}\"", encoded, expected);
+        }
+        if byteword_string_to_bytes(expected, style)? != bytes {
+            bail!("\"{}\" doesn't decode to the expected bytes", expected);
+        }
+    }
+    Ok(())
+}
+
+fn check_crc32() -> Result<(), Error> {
+    let checksum = byteword_checksum(b"Hello, world!");
+    if checksum != [0xeb, 0xe6, 0xc6, 0xe6] {
+        bail!("Checksum is {}, expected ebe6c6e6", hex::encode(checksum));
+    }
+    Ok(())
+}
+
+// Runs the reference vectors bundled in the binary against this build. A
+// failure means this build can't be trusted to make or read shares other
+// SSKR tools can.
+pub fn run() -> Vec<VectorResult> {
+    let mut checks: Vec<(String, &str, Result<(), Error>)> = SHAMIR_VECTORS
+        .iter()
+        .map(|vector| (vector.name.to_string(), SHAMIR_SOURCE, check_shamir(vector)))
+        .collect();
+    checks.push((
+        "Bytewords, standard and minimal".to_string(),
+        BYTEWORDS_SOURCE,
+        check_bytewords(),
+    ));
+    checks.push((
+        "Bytewords checksum (CRC-32)".to_string(),
+        CRC32_SOURCE,
+        check_crc32(),
+    ));
+
+    checks
+        .into_iter()
+        .map(|(name, source, result)| VectorResult {
+            name,
+            source: source.to_string(),
+            passed: result.is_ok(),
+            error: result.err().map(|error| error.to_string()),
+        })
+        .collect()
+}
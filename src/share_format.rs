@@ -0,0 +1,611 @@
+pub use crate::bytewords::BytewordStyle;
+use crate::bytewords::{
+    byteword_checksum, byteword_string, byteword_string_no_checksum, byteword_string_to_bytes,
+    byteword_string_to_bytes_unchecked, correct_byteword_string, is_byteword, is_byteword_string,
+    split_words, Correction,
+};
+use crate::envelope::{self, ShareEnvelope};
+use crate::sanitize::{candidates, sanitize};
+use crate::sskr_shares::share_metadata;
+use anyhow::{bail, Error};
+use clap::ValueEnum;
+use dcbor::{CBOREncodable, CBOR};
+use serde::Serialize;
+use std::fmt;
+use zeroize::Zeroizing;
+
+pub(crate) const SSKR_TAG: u64 = 309;
+// The CBOR header of tag 309
+const SSKR_TAG_HEADER: [u8; 3] = [0xd9, 0x01, 0x35];
+const UR_PREFIX: &str = "ur:sskr/";
+
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareFormat {
+    /// Tagged CBOR as space-separated bytewords
+    Bytewords,
+    /// Tagged CBOR as minimal (two letter) bytewords
+    Minimal,
+    /// Tagged CBOR as hyphen-separated bytewords, the URI style
+    Hyphenated,
+    /// A ur:sskr string
+    Ur,
+    /// The share as hex, without a checksum
+    Hex,
+    /// Tagged CBOR as hex, without a checksum
+    #[serde(rename = "cbor-hex")]
+    CborHex,
+    /// A ur:envelope string, the share in a Gordian Envelope
+    Envelope,
+}
+
+impl ShareFormat {
+    pub fn detect(input: &str) -> ShareFormat {
+        let input = input.trim();
+        if input.to_lowercase().starts_with(envelope::UR_PREFIX) {
+            ShareFormat::Envelope
+        } else if input.to_lowercase().starts_with(UR_PREFIX) {
+            ShareFormat::Ur
+        } else if input.contains(char::is_whitespace) {
+            ShareFormat::Bytewords
+        } else if input.contains('-') {
+            ShareFormat::Hyphenated
+        } else if !input.is_empty() && input.chars().all(|c| c.is_ascii_hexdigit()) {
+            // Minimal bytewords shares start with "ta" (the CBOR tag), so
+            // they are never mistaken for hex
+            if input
+                .to_lowercase()
+                .starts_with(&hex::encode(SSKR_TAG_HEADER))
+            {
+                ShareFormat::CborHex
+            } else {
+                ShareFormat::Hex
+            }
+        } else {
+            ShareFormat::Minimal
+        }
+    }
+}
+
+impl ShareFormat {
+    // The style of the bytewords, for the formats that write the tagged CBOR
+    // as bytewords
+    fn byteword_style(&self) -> BytewordStyle {
+        match self {
+            ShareFormat::Minimal => BytewordStyle::Minimal,
+            ShareFormat::Hyphenated => BytewordStyle::Uri,
+            _ => BytewordStyle::Standard,
+        }
+    }
+}
+
+// What separates the words of standard bytewords when they are written out,
+// for printing templates and engraving layouts that need hyphens or one word
+// per line. Shares are read back with any of them.
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WordSeparator {
+    #[default]
+    Space,
+    Hyphen,
+    Newline,
+}
+
+impl WordSeparator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WordSeparator::Space => " ",
+            WordSeparator::Hyphen => "-",
+            WordSeparator::Newline => "\n",
+        }
+    }
+
+    // Standard bytewords with this separator between the words instead
+    pub fn separate(&self, words: &str) -> String {
+        words.split(' ').collect::<Vec<_>>().join(self.as_str())
+    }
+}
+
+// What split writes shares as. Hex is for metal backups and other SSKR tools
+// that store shares that way; it has no checksum of its own.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum SplitFormat {
+    /// Bytewords, in the style of --style
+    #[default]
+    Bytewords,
+    /// The share as hex
+    Hex,
+    /// Tagged CBOR as hex
+    CborHex,
+}
+
+impl SplitFormat {
+    pub fn share_format(&self, style: BytewordStyle) -> ShareFormat {
+        match self {
+            SplitFormat::Bytewords => ShareFormat::from(style),
+            SplitFormat::Hex => ShareFormat::Hex,
+            SplitFormat::CborHex => ShareFormat::CborHex,
+        }
+    }
+}
+
+impl From<BytewordStyle> for ShareFormat {
+    fn from(style: BytewordStyle) -> ShareFormat {
+        match style {
+            BytewordStyle::Standard => ShareFormat::Bytewords,
+            BytewordStyle::Uri => ShareFormat::Hyphenated,
+            BytewordStyle::Minimal => ShareFormat::Minimal,
+        }
+    }
+}
+
+impl fmt::Display for ShareFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+pub fn encode(share: &[u8], format: ShareFormat) -> String {
+    match format {
+        ShareFormat::Bytewords | ShareFormat::Minimal | ShareFormat::Hyphenated => {
+            let cbor = CBOR::tagged_value(SSKR_TAG, CBOR::byte_string(share));
+            byteword_string(cbor.cbor_data().as_slice(), format.byteword_style())
+        }
+        ShareFormat::Ur => {
+            // The UR type already identifies the payload, so the CBOR is left untagged
+            let cbor = CBOR::byte_string(share);
+            format!(
+                "{}{}",
+                UR_PREFIX,
+                byteword_string(cbor.cbor_data().as_slice(), BytewordStyle::Minimal)
+            )
+        }
+        ShareFormat::Hex => hex::encode(share),
+        ShareFormat::CborHex => {
+            hex::encode(CBOR::tagged_value(SSKR_TAG, CBOR::byte_string(share)).cbor_data())
+        }
+        ShareFormat::Envelope => ShareEnvelope::new(share).encode(),
+    }
+}
+
+// A share read from text: decoded in one of the formats, with its checksum
+// matching, and with metadata SSKR can combine. Parsing is the only way to
+// make one, so code taking an EncodedShare can't be handed text that skipped
+// those checks, however the text came in.
+#[derive(Clone, PartialEq)]
+pub struct EncodedShare {
+    format: ShareFormat,
+    share: Zeroizing<Vec<u8>>,
+    identifier: u16,
+    metadata: [usize; 5],
+}
+
+impl EncodedShare {
+    pub fn parse(input: &str) -> Result<EncodedShare, Error> {
+        EncodedShare::parse_styled(input, &false)
+    }
+
+    // Errors about the metadata quote the share as minimal bytewords
+    pub(crate) fn parse_styled(input: &str, minimal: &bool) -> Result<EncodedShare, Error> {
+        let (format, share) = decode(input)?;
+        let (identifier, metadata) = share_metadata(&share, minimal)?;
+        Ok(EncodedShare {
+            format,
+            share: Zeroizing::new(share),
+            identifier,
+            metadata,
+        })
+    }
+
+    // The format the share was read in
+    pub fn format(&self) -> ShareFormat {
+        self.format
+    }
+
+    pub fn share(&self) -> &[u8] {
+        &self.share
+    }
+
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    // As share_metadata gives it: the group index, group threshold and group
+    // count, then the member index and member threshold
+    pub fn metadata(&self) -> [usize; 5] {
+        self.metadata
+    }
+
+    pub fn encode(&self, format: ShareFormat) -> String {
+        encode(&self.share, format)
+    }
+
+    // Converting is the one place where a backup artifact is rewritten, so
+    // the result is decoded again and compared against the share before it is
+    // handed back
+    pub fn convert(&self, to: ShareFormat) -> Result<String, Error> {
+        let converted = self.encode(to);
+        if *decode_as(&converted, to)? != **self.share {
+            bail!(
+                "Conversion from {} to {} did not round-trip, refusing to print the result",
+                self.format,
+                to
+            );
+        }
+        Ok(converted)
+    }
+}
+
+// The share itself is left out, like anywhere else it could end up logged
+impl fmt::Debug for EncodedShare {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EncodedShare")
+            .field("format", &self.format)
+            .field("identifier", &format_args!("{:04x}", self.identifier))
+            .field("metadata", &self.metadata)
+            .finish_non_exhaustive()
+    }
+}
+
+// The checksum the last four bytewords of a share stand for, which is the
+// CRC-32 (ISO-HDLC, as in zlib) of the CBOR payload the words before encode.
+// The payload is given as hex so the checksum can be recomputed elsewhere.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ShareChecksum {
+    pub payload: String,
+    pub crc32: String,
+    pub bytewords: String,
+}
+
+pub fn share_checksum(share: &[u8]) -> ShareChecksum {
+    let cbor = CBOR::tagged_value(SSKR_TAG, CBOR::byte_string(share)).cbor_data();
+    let checksum = byteword_checksum(&cbor);
+    ShareChecksum {
+        payload: hex::encode(&cbor),
+        crc32: hex::encode(checksum),
+        bytewords: byteword_string_no_checksum(&checksum, BytewordStyle::Standard),
+    }
+}
+
+// How many bytewords of a share come before the share itself, spelling out
+// the CBOR tag and the length of the byte string
+pub fn bytewords_header_len(share_len: usize) -> usize {
+    CBOR::tagged_value(SSKR_TAG, CBOR::byte_string(vec![0u8; share_len]))
+        .cbor_data()
+        .len()
+        - share_len
+}
+
+pub fn decode(input: &str) -> Result<(ShareFormat, Vec<u8>), Error> {
+    first_candidate(input, |candidate| {
+        let format = ShareFormat::detect(candidate);
+        Ok((format, decode_exact(candidate, format)?))
+    })
+}
+
+pub fn decode_as(input: &str, format: ShareFormat) -> Result<Vec<u8>, Error> {
+    first_candidate(input, |candidate| decode_exact(candidate, format))
+}
+
+// Tries the input as received first, then as misread through other keyboard
+// layouts. The error reported is always the one for the input as received.
+fn first_candidate<T>(input: &str, decode: impl Fn(&str) -> Result<T, Error>) -> Result<T, Error> {
+    let mut first_error = None;
+    for candidate in candidates(input) {
+        match decode(&candidate) {
+            Ok(decoded) => return Ok(decoded),
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(first_error.unwrap())
+}
+
+fn decode_exact(input: &str, format: ShareFormat) -> Result<Vec<u8>, Error> {
+    match format {
+        ShareFormat::Bytewords | ShareFormat::Minimal | ShareFormat::Hyphenated => {
+            // Parse bytewords and strip byteword-level checksum
+            let bytes = byteword_string_to_bytes(input, format.byteword_style())?;
+
+            // Unwrap data from CBOR container
+            let cbor = CBOR::from_data(bytes.as_slice())?;
+            let cbor_bytes = cbor.expect_tagged_value(SSKR_TAG)?;
+            let share = cbor_bytes.expect_byte_string()?;
+
+            Ok(share.to_vec())
+        }
+        ShareFormat::Ur => {
+            if !input.to_lowercase().starts_with(UR_PREFIX) {
+                bail!("Not a ur:sskr string: \"{}\"", input);
+            }
+            let body = input[UR_PREFIX.len()..].to_lowercase();
+            if let Some((sequence, count)) = segment_position(&body) {
+                bail!(
+                    "Segment {} of a share split into {} segments, all of its segments are needed",
+                    sequence,
+                    count
+                );
+            }
+            let bytes = byteword_string_to_bytes(&body, BytewordStyle::Minimal)?;
+
+            // Older encoders tag the payload even inside a UR, so accept both
+            let cbor = CBOR::from_data(bytes.as_slice())?;
+            let share = match cbor.expect_tagged_value(SSKR_TAG) {
+                Ok(tagged) => tagged.expect_byte_string()?.to_vec(),
+                Err(_) => cbor.expect_byte_string()?.to_vec(),
+            };
+
+            Ok(share)
+        }
+        ShareFormat::Hex => Ok(hex::decode(input)?),
+        ShareFormat::CborHex => {
+            let cbor = CBOR::from_data(hex::decode(input)?.as_slice())?;
+            Ok(cbor
+                .expect_tagged_value(SSKR_TAG)?
+                .expect_byte_string()?
+                .to_vec())
+        }
+        ShareFormat::Envelope => Ok(ShareEnvelope::decode(input)?.share),
+    }
+}
+
+// A share in bytewords, spaced or hyphenated, with words that aren't bytewords,
+// as corrected to the closest bytewords that pass the checksum, along with the
+// (word, replacement) pairs. Shares that already decode are returned as they are.
+pub fn auto_correct(input: &str) -> Result<(String, Vec<Correction>), Error> {
+    let error = match decode(input) {
+        Ok(_) => return Ok((input.to_string(), vec![])),
+        Err(error) => error,
+    };
+    let cleaned = sanitize(input).to_lowercase();
+    let format = ShareFormat::detect(&cleaned);
+    if !matches!(format, ShareFormat::Bytewords | ShareFormat::Hyphenated)
+        || split_words(&cleaned).all(is_byteword)
+    {
+        return Err(error);
+    }
+
+    let (corrected, corrections) = correct_byteword_string(&cleaned, format.byteword_style())?;
+    decode_exact(&corrected, format)?;
+    Ok((corrected, corrections))
+}
+
+// Whether the byteword checksum of an input in the given format matches, as
+// long as its words can be read at all
+pub fn checksum_matches(input: &str, format: ShareFormat) -> Result<bool, Error> {
+    let input = input.trim();
+    let (body, style) = match format {
+        ShareFormat::Bytewords | ShareFormat::Minimal | ShareFormat::Hyphenated => {
+            (input.to_string(), format.byteword_style())
+        }
+        ShareFormat::Ur => match input.to_lowercase().strip_prefix(UR_PREFIX) {
+            Some(body) => (body.to_string(), BytewordStyle::Minimal),
+            None => bail!("Not a ur:sskr string: \"{}\"", input),
+        },
+        ShareFormat::Envelope => match input.to_lowercase().strip_prefix(envelope::UR_PREFIX) {
+            Some(body) => (body.to_string(), BytewordStyle::Minimal),
+            None => bail!("Not a ur:envelope string: \"{}\"", input),
+        },
+        ShareFormat::Hex | ShareFormat::CborHex => bail!("Hex shares have no checksum"),
+    };
+    Ok(byteword_string_to_bytes_unchecked(&body, style)?.1)
+}
+
+pub fn convert(input: &str, to: ShareFormat) -> Result<(ShareFormat, String), Error> {
+    let share = EncodedShare::parse(input)?;
+    Ok((share.format(), share.convert(to)?))
+}
+
+// A share split across several ur:sskr parts ("ur:sskr/1-3/..."), for labels
+// or QR codes too small for the whole share. These are the plain fragments
+// of a multi-part UR, so other UR decoders can join them too.
+pub fn segments(share: &[u8], count: usize) -> Result<Vec<String>, Error> {
+    let message = CBOR::byte_string(share).cbor_data();
+    if count < 2 || count > message.len() {
+        bail!(
+            "A share can be split into 2 to {} segments, not {}",
+            message.len(),
+            count
+        );
+    }
+    let checksum = u32::from_be_bytes(byteword_checksum(&message));
+    let fragment_len = message.len().div_ceil(count);
+
+    Ok((0..count)
+        .map(|index| {
+            let mut fragment = message
+                .iter()
+                .skip(index * fragment_len)
+                .take(fragment_len)
+                .copied()
+                .collect::<Vec<u8>>();
+            fragment.resize(fragment_len, 0);
+            let part = CBOR::Array(vec![
+                CBOR::Unsigned(index as u64 + 1),
+                CBOR::Unsigned(count as u64),
+                CBOR::Unsigned(message.len() as u64),
+                CBOR::Unsigned(checksum as u64),
+                CBOR::byte_string(fragment),
+            ]);
+            format!(
+                "{}{}-{}/{}",
+                UR_PREFIX,
+                index + 1,
+                count,
+                byteword_string(part.cbor_data().as_slice(), BytewordStyle::Minimal)
+            )
+        })
+        .collect())
+}
+
+// The "sequence-count" of a multi-part UR body
+fn segment_position(body: &str) -> Option<(usize, usize)> {
+    let (position, _) = body.split_once('/')?;
+    let (sequence, count) = position.split_once('-')?;
+    Some((sequence.parse().ok()?, count.parse().ok()?))
+}
+
+struct Segment {
+    sequence: usize,
+    count: usize,
+    message_len: usize,
+    checksum: u64,
+    fragment: Vec<u8>,
+}
+
+impl Segment {
+    // What every segment of the same share has in common
+    fn share(&self) -> (usize, usize, u64) {
+        (self.count, self.message_len, self.checksum)
+    }
+}
+
+fn parse_segment(input: &str) -> Option<Segment> {
+    let body = input.trim().to_lowercase();
+    let body = body.strip_prefix(UR_PREFIX)?;
+    let (sequence, count) = segment_position(body)?;
+    let (_, words) = body.split_once('/')?;
+    let bytes = byteword_string_to_bytes(words, BytewordStyle::Minimal).ok()?;
+    match CBOR::from_data(bytes.as_slice()).ok()? {
+        CBOR::Array(part) => match part.as_slice() {
+            [CBOR::Unsigned(seq), CBOR::Unsigned(len), CBOR::Unsigned(message_len), CBOR::Unsigned(checksum), fragment]
+                if *seq as usize == sequence && *len as usize == count =>
+            {
+                Some(Segment {
+                    sequence,
+                    count,
+                    message_len: *message_len as usize,
+                    checksum: *checksum,
+                    fragment: fragment.expect_byte_string().ok()?.to_vec(),
+                })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Joins the segments among the lines back into whole ur:sskr shares, each
+// placed where its first segment was. Returns the index of the line each
+// result comes from. Lines that aren't segments, and segments of shares with
+// some segments missing, are left as they are.
+pub fn join_segments(lines: &[&str]) -> Vec<(usize, String)> {
+    let mut joined = vec![];
+    // Segments of the same share agree on the count, length and checksum
+    let mut shares: Vec<Vec<(usize, Segment)>> = vec![];
+
+    for (index, line) in lines.iter().enumerate() {
+        let Some(segment) = parse_segment(line) else {
+            joined.push((index, line.to_string()));
+            continue;
+        };
+        match shares
+            .iter_mut()
+            .find(|segments| segments[0].1.share() == segment.share())
+        {
+            Some(segments) => segments.push((index, segment)),
+            None => shares.push(vec![(index, segment)]),
+        }
+    }
+
+    for mut segments in shares {
+        let (count, message_len, checksum) = segments[0].1.share();
+        segments.sort_by_key(|(_, segment)| segment.sequence);
+        segments.dedup_by(|(_, a), (_, b)| a.sequence == b.sequence && a.fragment == b.fragment);
+        let first = segments.iter().map(|(index, _)| *index).min().unwrap();
+
+        let complete = segments.len() == count
+            && segments
+                .iter()
+                .enumerate()
+                .all(|(position, (_, segment))| segment.sequence == position + 1);
+        let message = complete.then(|| {
+            let mut message = segments
+                .iter()
+                .flat_map(|(_, segment)| segment.fragment.clone())
+                .collect::<Vec<u8>>();
+            message.truncate(message_len);
+            message
+        });
+
+        match message {
+            Some(message)
+                if message.len() == message_len
+                    && u32::from_be_bytes(byteword_checksum(&message)) as u64 == checksum =>
+            {
+                joined.push((
+                    first,
+                    format!(
+                        "{}{}",
+                        UR_PREFIX,
+                        byteword_string(&message, BytewordStyle::Minimal)
+                    ),
+                ));
+            }
+            // Decoding the segments on their own says what is missing
+            _ => joined.extend(
+                segments
+                    .into_iter()
+                    .map(|(index, _)| (index, lines[index].to_string())),
+            ),
+        }
+    }
+
+    joined.sort_by_key(|(index, _)| *index);
+    joined
+}
+
+// Joins a share in bytewords wrapped across several lines, as transcribed
+// from paper, to the fewest following lines that make its checksum (the last
+// four words) match. Lines that already make a share, and lines nothing
+// completes, are left as they are. Returns the index of the line each result
+// comes from, like join_segments.
+pub fn join_wrapped(lines: &[&str]) -> Vec<(usize, String)> {
+    let mut joined = vec![];
+    let mut index = 0;
+
+    while index < lines.len() {
+        let (text, used) =
+            wrapped_share(&lines[index..]).unwrap_or_else(|| (lines[index].to_string(), 1));
+        joined.push((index, text));
+        index += used;
+    }
+
+    joined
+}
+
+// The share starting at the first line and the number of lines it takes
+fn wrapped_share(lines: &[&str]) -> Option<(String, usize)> {
+    let mut share = sanitize(lines[0]);
+    // A line of a single byteword starts a share written one word per line,
+    // not minimal bytewords
+    let format = if is_byteword(&share.to_lowercase()) {
+        ShareFormat::Bytewords
+    } else {
+        ShareFormat::detect(&share)
+    };
+    if !matches!(
+        format,
+        ShareFormat::Bytewords | ShareFormat::Minimal | ShareFormat::Hyphenated
+    ) {
+        return None;
+    }
+    let style = format.byteword_style();
+    if !is_byteword_string(&share, style) || checksum_matches(&share, format).unwrap_or(false) {
+        return None;
+    }
+
+    for (count, line) in lines.iter().enumerate().skip(1) {
+        let line = sanitize(line);
+        if !is_byteword_string(&line, style) {
+            return None;
+        }
+        share.push_str(style.separator());
+        share.push_str(&line);
+        if checksum_matches(&share, format).unwrap_or(false) {
+            return Some((share, count + 1));
+        }
+    }
+    None
+}
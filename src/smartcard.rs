@@ -0,0 +1,173 @@
+use crate::share_format::SSKR_TAG;
+use anyhow::{anyhow, bail, Error};
+use dcbor::{CBOREncodable, CBOR};
+use zeroize::Zeroizing;
+
+// The OpenPGP card application, version independent
+const OPENPGP_AID: [u8; 6] = [0xd2, 0x76, 0x00, 0x01, 0x24, 0x01];
+const SW_OK: [u8; 2] = [0x90, 0x00];
+
+// What carries APDUs to a card and its responses back, e.g. a PC/SC reader.
+// Responses end in the two status bytes.
+pub trait CardTransport {
+    fn transmit(&mut self, apdu: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+impl<T: CardTransport + ?Sized> CardTransport for &mut T {
+    fn transmit(&mut self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).transmit(apdu)
+    }
+}
+
+// The private use data objects of an OpenPGP card. Slots 1 and 2 can be read
+// by anyone, slot 3 only after the user PIN and slot 4 only after the admin
+// PIN. Writing slots 1 and 3 takes the user PIN, slots 2 and 4 the admin PIN.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PrivateSlot {
+    One,
+    Two,
+    Three,
+    Four,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pin {
+    User,
+    Admin,
+}
+
+impl PrivateSlot {
+    pub fn from_number(number: u8) -> Result<PrivateSlot, Error> {
+        match number {
+            1 => Ok(PrivateSlot::One),
+            2 => Ok(PrivateSlot::Two),
+            3 => Ok(PrivateSlot::Three),
+            4 => Ok(PrivateSlot::Four),
+            _ => bail!(
+                "OpenPGP cards have private data slots 1 to 4, not {}",
+                number
+            ),
+        }
+    }
+
+    fn tag(&self) -> [u8; 2] {
+        match self {
+            PrivateSlot::One => [0x01, 0x01],
+            PrivateSlot::Two => [0x01, 0x02],
+            PrivateSlot::Three => [0x01, 0x03],
+            PrivateSlot::Four => [0x01, 0x04],
+        }
+    }
+
+    fn write_pin(&self) -> Pin {
+        match self {
+            PrivateSlot::One | PrivateSlot::Three => Pin::User,
+            PrivateSlot::Two | PrivateSlot::Four => Pin::Admin,
+        }
+    }
+
+    fn read_pin(&self) -> Option<Pin> {
+        match self {
+            PrivateSlot::One | PrivateSlot::Two => None,
+            PrivateSlot::Three => Some(Pin::User),
+            PrivateSlot::Four => Some(Pin::Admin),
+        }
+    }
+}
+
+pub struct OpenPgpCard<T: CardTransport> {
+    transport: T,
+}
+
+impl<T: CardTransport> OpenPgpCard<T> {
+    pub fn open(mut transport: T) -> Result<OpenPgpCard<T>, Error> {
+        let mut select = vec![0x00, 0xa4, 0x04, 0x00, OPENPGP_AID.len() as u8];
+        select.extend(OPENPGP_AID);
+        select.push(0x00);
+        response(&transport.transmit(&select)?)
+            .map_err(|error| anyhow!("No OpenPGP application on the card: {}", error))?;
+        Ok(OpenPgpCard { transport })
+    }
+
+    // Stores the share as tagged CBOR, so anything reading the slot can tell
+    // what it holds. The slot is read back and compared before returning.
+    pub fn write_share(&mut self, slot: PrivateSlot, share: &[u8], pin: &str) -> Result<(), Error> {
+        self.verify(slot.write_pin(), pin)?;
+        let data = CBOR::tagged_value(SSKR_TAG, CBOR::byte_string(share)).cbor_data();
+        if data.len() > 254 {
+            bail!("The share is too long for a private data slot");
+        }
+
+        let [p1, p2] = slot.tag();
+        let mut put = vec![0x00, 0xda, p1, p2, data.len() as u8];
+        put.extend(&data);
+        response(&self.transport.transmit(&put)?)?;
+
+        if *self.read_share(slot, Some(pin))? != share {
+            bail!("The share read back from the card doesn't match what was written");
+        }
+        Ok(())
+    }
+
+    pub fn read_share(
+        &mut self,
+        slot: PrivateSlot,
+        pin: Option<&str>,
+    ) -> Result<Zeroizing<Vec<u8>>, Error> {
+        if let Some(needed) = slot.read_pin() {
+            let Some(pin) = pin else {
+                bail!("Reading this slot takes a PIN");
+            };
+            self.verify(needed, pin)?;
+        }
+
+        let [p1, p2] = slot.tag();
+        let data = Zeroizing::new(response(
+            &self.transport.transmit(&[0x00, 0xca, p1, p2, 0x00])?,
+        )?);
+        if data.is_empty() {
+            bail!("The slot is empty");
+        }
+        let cbor = CBOR::from_data(&data)
+            .map_err(|_| anyhow!("The slot doesn't hold a share written by this tool"))?;
+        Ok(Zeroizing::new(
+            cbor.expect_tagged_value(SSKR_TAG)?
+                .expect_byte_string()?
+                .to_vec(),
+        ))
+    }
+
+    fn verify(&mut self, which: Pin, pin: &str) -> Result<(), Error> {
+        // PW1 in the mode for other commands than signing, or PW3
+        let reference = match which {
+            Pin::User => 0x82,
+            Pin::Admin => 0x83,
+        };
+        let mut verify = Zeroizing::new(vec![0x00, 0x20, 0x00, reference, pin.len() as u8]);
+        verify.extend(pin.as_bytes());
+        response(&self.transport.transmit(&verify)?).map_err(|error| match which {
+            Pin::User => anyhow!("User PIN not accepted: {}", error),
+            Pin::Admin => anyhow!("Admin PIN not accepted: {}", error),
+        })?;
+        Ok(())
+    }
+}
+
+// The data of a response, or what its status words mean
+fn response(response: &[u8]) -> Result<Vec<u8>, Error> {
+    let Some(split) = response.len().checked_sub(2) else {
+        bail!("The card gave no status");
+    };
+    let (data, status) = response.split_at(split);
+    match status {
+        s if s == SW_OK => Ok(data.to_vec()),
+        [0x63, tries] if tries & 0xf0 == 0xc0 => {
+            bail!("wrong PIN, {} tries left", tries & 0x0f)
+        }
+        [0x69, 0x82] => bail!("security status not satisfied"),
+        [0x69, 0x83] => bail!("the PIN is blocked"),
+        [0x6a, 0x80] => bail!("the card rejected the data"),
+        [0x6a, 0x82] | [0x6a, 0x88] => bail!("not found"),
+        _ => bail!("card error {}", hex::encode(status)),
+    }
+}
@@ -0,0 +1,175 @@
+use crate::error::SskrToolError;
+use std::ops::Range;
+
+// The grammar of group specs:
+//
+//   spec       = group *(separator group)
+//   separator  = "," / ";"
+//   group      = [name "="] threshold ("of" / "/") count *annotation
+//   annotation = "[" *(any character but "]") "]"
+//
+// Whitespace is allowed between any two tokens and "of" can be in any case.
+// A name runs up to the "=" and keeps its case and inner spaces. Annotations
+// are parsed so that specs written for later versions fail to parse cleanly
+// rather than in the middle of a group; what they may say is up to the
+// callers.
+//
+// Positions are byte offsets into the spec, reported in errors as columns.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecGroup {
+    pub name: Option<String>,
+    pub threshold: usize,
+    pub count: usize,
+    pub annotations: Vec<Annotation>,
+    // From the threshold to the count, without the name
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+impl SpecGroup {
+    // The group as written in the spec, for messages about it
+    pub fn text<'a>(&self, spec: &'a str) -> &'a str {
+        &spec[self.span.clone()]
+    }
+}
+
+pub fn parse(spec: &str) -> Result<Vec<SpecGroup>, SskrToolError> {
+    let mut parser = Parser { spec, at: 0 };
+    let mut groups = vec![parser.group()?];
+    while let Some(c) = parser.peek() {
+        if c != ',' && c != ';' {
+            return Err(parser.error(
+                parser.at..parser.at + c.len_utf8(),
+                "expected \",\" or \";\" between groups",
+            ));
+        }
+        parser.at += 1;
+        groups.push(parser.group()?);
+    }
+    Ok(groups)
+}
+
+// The error for a part of a spec, such as an annotation no version supports
+pub fn spec_error(spec: &str, span: Range<usize>, problem: &str) -> SskrToolError {
+    SskrToolError::InvalidSpec {
+        spec: spec.to_string(),
+        start: span.start,
+        end: span.end,
+        problem: problem.to_string(),
+    }
+}
+
+struct Parser<'a> {
+    spec: &'a str,
+    at: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.spec[self.at..]
+    }
+
+    // The next character that isn't whitespace, skipping to it
+    fn peek(&mut self) -> Option<char> {
+        let rest = self.rest();
+        self.at += rest.len() - rest.trim_start().len();
+        self.rest().chars().next()
+    }
+
+    // The next character, or the end of the spec, for errors about it
+    fn next_span(&mut self) -> Range<usize> {
+        let len = self.peek().map_or(0, char::len_utf8);
+        self.at..self.at + len
+    }
+
+    fn error(&self, span: Range<usize>, problem: &str) -> SskrToolError {
+        spec_error(self.spec, span, problem)
+    }
+
+    fn group(&mut self) -> Result<SpecGroup, SskrToolError> {
+        let name = self.name();
+        self.peek();
+        let start = self.at;
+        let threshold = self.number("a threshold, such as the 2 of 2of3")?;
+        self.of()?;
+        let count = self.number("a number of shares, such as the 3 of 2of3")?;
+        let span = start..self.at;
+        let mut annotations = vec![];
+        while self.peek() == Some('[') {
+            annotations.push(self.annotation()?);
+        }
+        Ok(SpecGroup {
+            name,
+            threshold,
+            count,
+            annotations,
+            span,
+        })
+    }
+
+    // A name is whatever comes before an "=" in the group
+    fn name(&mut self) -> Option<String> {
+        let group = self
+            .rest()
+            .split([',', ';', '['])
+            .next()
+            .unwrap_or_default();
+        let (name, _group) = group.split_once('=')?;
+        self.at += name.len() + 1;
+        Some(name.trim().to_string())
+    }
+
+    fn number(&mut self, expected: &str) -> Result<usize, SskrToolError> {
+        self.peek();
+        let start = self.at;
+        let digits = self.rest().len()
+            - self
+                .rest()
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        if digits == 0 {
+            let span = self.next_span();
+            return Err(self.error(span, &format!("expected {}", expected)));
+        }
+        self.at += digits;
+        self.spec[start..self.at]
+            .parse()
+            .map_err(|_| self.error(start..self.at, "number too large"))
+    }
+
+    // Between the threshold and count
+    fn of(&mut self) -> Result<(), SskrToolError> {
+        self.peek();
+        let rest = self.rest();
+        if rest.starts_with('/') {
+            self.at += 1;
+        } else if rest
+            .get(..2)
+            .is_some_and(|of| of.eq_ignore_ascii_case("of"))
+        {
+            self.at += 2;
+        } else {
+            let span = self.next_span();
+            return Err(self.error(span, "expected \"of\" or \"/\" after the threshold"));
+        }
+        Ok(())
+    }
+
+    fn annotation(&mut self) -> Result<Annotation, SskrToolError> {
+        let start = self.at;
+        let Some(end) = self.rest().find(']') else {
+            return Err(self.error(start..self.spec.len(), "annotation is missing its \"]\""));
+        };
+        self.at += end + 1;
+        Ok(Annotation {
+            text: self.spec[start + 1..self.at - 1].trim().to_string(),
+            span: start..self.at,
+        })
+    }
+}
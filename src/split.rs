@@ -1,90 +1,444 @@
-use crate::bytewords::byteword_string;
-use anyhow::{anyhow, bail, Error};
+use crate::entropy::{mix_os_entropy, EntropySource};
+use crate::envelope::ShareEnvelope;
+use crate::error::SskrToolError;
+use crate::language::detect_language;
+use crate::limits::{MAX_GROUPS, MAX_MEMBERS, MAX_SECRET_LEN, MIN_SECRET_LEN};
+use crate::recover::{decode_share, recover, subsets};
+use crate::share_format::{encode, BytewordStyle, ShareFormat};
+use crate::spec::{self, spec_error, SpecGroup};
+use crate::sskr_shares::share_metadata;
+use anyhow::{bail, Error};
 use bip39::{Language, Mnemonic, MnemonicType};
-use dcbor::{CBOREncodable, CBOR};
-use lazy_static::lazy_static;
-use regex::Regex;
+use sha2::{Digest, Sha256};
 use sskr::{sskr_generate, GroupSpec, Secret, Spec};
+use zeroize::Zeroizing;
 
-lazy_static! {
-    static ref SPEC_REGEX: Regex = Regex::new(r"^((\d+of\d+),)*\d+of\d+$").unwrap();
-    static ref SPEC_GROUP_REGEX: Regex = Regex::new(r"(?<m>\d+)of(?<n>\d+)").unwrap();
+// The entropy lengths of 12 to 24 word mnemonics, in bytes
+pub const BIP39_ENTROPY_LENGTHS: [usize; 5] = [16, 20, 24, 28, 32];
+
+// Raw SSKR shares, grouped in the same order as the groups in the spec
+pub type ShareGroups = Vec<Vec<Vec<u8>>>;
+
+// The order to list the shares of a split in. Splits always come out in
+// group order, which depends on nothing but the spec, so printing the same
+// shares twice gives the same output on any platform.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ShareOrder {
+    // Groups as in the spec, and each group's members by index
+    #[default]
+    Group,
+    // Member 1 of every group, then member 2 of every group, and so on, to
+    // hand shares out in rounds
+    Member,
 }
 
 pub fn split(
-    spec: &String,
+    spec: &str,
     group_threshold: usize,
-    phrase: &String,
-    minimal: &bool
+    phrase: &str,
+    minimal: &bool,
+    language: Option<Language>,
 ) -> Result<(Mnemonic, Vec<Vec<String>>), Error> {
+    let (mnemonic, groups) = split_shares(spec, group_threshold, phrase, language)?;
+    let byteword_groups = to_bytewords(&groups, minimal);
+    Ok((mnemonic, byteword_groups))
+}
+
+pub fn split_shares(
+    spec: &str,
+    group_threshold: usize,
+    phrase: &str,
+    language: Option<Language>,
+) -> Result<(Mnemonic, ShareGroups), Error> {
     let sskr_spec = parse_spec(spec, group_threshold)?;
-    let mnemonic = Mnemonic::from_phrase(phrase, Language::English)?;
+    let language = match language {
+        Some(language) => language,
+        None => detect_language(phrase)?,
+    };
+    let mnemonic = Mnemonic::from_phrase(phrase, language)?;
     let entropy = mnemonic.entropy();
     let secret = Secret::new(entropy)?;
     let groups = sskr_generate(&sskr_spec, &secret)?;
-    let byteword_groups = to_bytewords(&groups, minimal);
-    Ok((mnemonic, byteword_groups))
+    Ok((mnemonic, groups))
+}
+
+// Splits a secret that isn't a mnemonic, which SSKR allows for any even
+// length from 16 to 32 bytes
+pub fn split_raw(spec: &str, group_threshold: usize, secret: &[u8]) -> Result<ShareGroups, Error> {
+    if secret.len() < MIN_SECRET_LEN || secret.len() > MAX_SECRET_LEN {
+        bail!(SskrToolError::SecretLength {
+            length: secret.len(),
+            min: MIN_SECRET_LEN,
+            max: MAX_SECRET_LEN
+        });
+    }
+    if !secret.len().is_multiple_of(2) {
+        bail!(SskrToolError::OddSecretLength {
+            length: secret.len()
+        });
+    }
+
+    let sskr_spec = parse_spec(spec, group_threshold)?;
+    Ok(sskr_generate(&sskr_spec, &Secret::new(secret)?)?)
 }
 
 pub fn split_random_phrase(
-    spec: &String,
+    spec: &str,
     group_threshold: usize,
-    minimal: &bool
+    words: MnemonicType,
+    minimal: &bool,
+    language: Language,
 ) -> Result<(Mnemonic, Vec<Vec<String>>), Error> {
-    let mnemonic = Mnemonic::new(MnemonicType::Words12, Language::English);
-    split(spec, group_threshold, &mnemonic.phrase().to_string(), minimal)
+    let mnemonic = random_mnemonic(words, language);
+    split(
+        spec,
+        group_threshold,
+        mnemonic.phrase(),
+        minimal,
+        Some(language),
+    )
+}
+
+// Recovers the secret from the shares and splits it again under a new spec.
+// The new share set never gets the old one's identifier, so the two can't be
+// mixed up.
+pub fn reshare(
+    lines: Vec<String>,
+    minimal: &bool,
+    spec: &str,
+    group_threshold: usize,
+    language: Language,
+) -> Result<(Mnemonic, ShareGroups), Error> {
+    let Some(first) = lines.first() else {
+        bail!(SskrToolError::NoShares);
+    };
+    let (old_identifier, _) = share_metadata(&decode_share(first)?, minimal)?;
+    let mnemonic = recover(lines, minimal, language)?;
+
+    loop {
+        let (mnemonic, groups) =
+            split_shares(spec, group_threshold, mnemonic.phrase(), Some(language))?;
+        if share_metadata(&groups[0][0], &false)?.0 != old_identifier {
+            return Ok((mnemonic, groups));
+        }
+    }
+}
+
+// The identifier only lives in the metadata of the shares, it plays no part in
+// the splitting itself, so it can be chosen after the fact
+pub fn set_identifier(groups: &mut ShareGroups, identifier: u16) {
+    for share in groups.iter_mut().flatten() {
+        share[..2].copy_from_slice(&identifier.to_be_bytes());
+    }
+}
+
+pub fn parse_identifier(input: &str) -> Result<u16, Error> {
+    let input = input.trim();
+    let digits = input.strip_prefix("0x").unwrap_or(input);
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!(SskrToolError::InvalidIdentifier {
+            input: input.to_string()
+        });
+    }
+    Ok(u16::from_str_radix(digits, 16)?)
+}
+
+// The first two bytes of the SHA-256 of the seed, so anyone can work out the
+// identifier a seed gives
+pub fn seeded_identifier(seed: &str) -> u16 {
+    let hash = Sha256::digest(seed.as_bytes());
+    u16::from_be_bytes([hash[0], hash[1]])
+}
+
+pub fn random_mnemonic(words: MnemonicType, language: Language) -> Mnemonic {
+    Mnemonic::new(words, language)
+}
+
+// A new mnemonic from user-supplied entropy instead of the OS
+pub fn entropy_mnemonic(
+    source: &EntropySource,
+    mix_os: &bool,
+    words: MnemonicType,
+    language: Language,
+) -> Result<Mnemonic, Error> {
+    let mut entropy = source.entropy(words.entropy_bits() / 8)?;
+    if *mix_os {
+        mix_os_entropy(&mut entropy);
+    }
+    Mnemonic::from_entropy(&entropy, language)
+        .map_err(|e| SskrToolError::MnemonicFromEntropy(e.to_string()).into())
+}
+
+// The mnemonic for entropy another tool gave as hex
+pub fn entropy_hex_mnemonic(input: &str, language: Language) -> Result<Mnemonic, Error> {
+    let input = input.trim();
+    let entropy = Zeroizing::new(
+        hex::decode(input.strip_prefix("0x").unwrap_or(input)).map_err(|_| {
+            SskrToolError::InvalidEntropy {
+                input: input.to_string(),
+            }
+        })?,
+    );
+    if !BIP39_ENTROPY_LENGTHS.contains(&entropy.len()) {
+        bail!(SskrToolError::EntropyLength {
+            length: entropy.len()
+        });
+    }
+    Mnemonic::from_entropy(&entropy, language)
+        .map_err(|e| SskrToolError::MnemonicFromEntropy(e.to_string()).into())
+}
+
+pub fn parse_word_count(input: &str) -> Result<MnemonicType, Error> {
+    let count = input.parse().map_err(|_| SskrToolError::InvalidWordCount)?;
+    MnemonicType::for_word_count(count)
+        .map_err(|_| SskrToolError::UnsupportedWordCount { count }.into())
+}
+
+pub fn to_bytewords(groups: &[Vec<Vec<u8>>], minimal: &bool) -> Vec<Vec<String>> {
+    to_styled_bytewords(groups, BytewordStyle::from_minimal(minimal))
+}
+
+pub fn to_styled_bytewords(groups: &[Vec<Vec<u8>>], style: BytewordStyle) -> Vec<Vec<String>> {
+    to_format(groups, ShareFormat::from(style))
+}
+
+pub fn to_format(groups: &[Vec<Vec<u8>>], format: ShareFormat) -> Vec<Vec<String>> {
+    groups
+        .iter()
+        .map(|shares| shares.iter().map(|share| encode(share, format)).collect())
+        .collect()
+}
+
+pub fn ordered(groups: &ShareGroups, order: ShareOrder) -> Vec<&[u8]> {
+    match order {
+        ShareOrder::Group => groups.iter().flatten().map(Vec::as_slice).collect(),
+        ShareOrder::Member => {
+            let members = groups.iter().map(Vec::len).max().unwrap_or(0);
+            (0..members)
+                .flat_map(|member| groups.iter().filter_map(move |group| group.get(member)))
+                .map(Vec::as_slice)
+                .collect()
+        }
+    }
 }
 
-fn to_bytewords(groups: &Vec<Vec<Vec<u8>>>, minimal: &bool) -> Vec<Vec<String>> {
+// Where a share belongs in group order: by identifier, then group, then member
+pub fn share_position(share: &[u8]) -> Result<(u16, usize, usize), Error> {
+    let (identifier, meta) = share_metadata(share, &false)?;
+    Ok((identifier, meta[0], meta[3]))
+}
+
+// Groups shares given in any order the way the split that made them did, so
+// shares read back from files compare equal to the split's output
+pub fn regroup(shares: &[Vec<u8>]) -> Result<ShareGroups, Error> {
+    let mut positioned = shares
+        .iter()
+        .map(|share| Ok((share_position(share)?, share)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    positioned.sort_by_key(|(position, _share)| *position);
+    if let (Some(((first, _, _), _)), Some(((last, _, _), _))) =
+        (positioned.first(), positioned.last())
+    {
+        if first != last {
+            bail!(SskrToolError::MixedSplits);
+        }
+    }
+
+    let mut groups: ShareGroups = vec![];
+    let mut last_group = None;
+    for ((_identifier, group, _member), share) in positioned {
+        if last_group != Some(group) {
+            groups.push(vec![]);
+            last_group = Some(group);
+        }
+        groups.last_mut().unwrap().push(share.clone());
+    }
+    Ok(groups)
+}
+
+// The shares wrapped in Gordian Envelopes, with the same metadata asserted
+// on every one of them
+pub fn to_envelopes(
+    groups: &[Vec<Vec<u8>>],
+    label: Option<&str>,
+    date: Option<i64>,
+) -> Vec<Vec<String>> {
     groups
         .iter()
         .map(|shares| {
             shares
                 .iter()
                 .map(|share| {
-                    let cbor = CBOR::tagged_value(309, CBOR::byte_string(share));
-                    byteword_string(cbor.cbor_data().as_slice(), minimal)
+                    ShareEnvelope {
+                        share: share.clone(),
+                        label: label.map(str::to_string),
+                        date,
+                    }
+                    .encode()
                 })
                 .collect()
         })
         .collect()
 }
 
-fn parse_spec(spec: &String, group_threshold: usize) -> Result<Spec, Error> {
-    if !SPEC_REGEX.is_match(spec) {
-        bail!("Invalid group spec");
+// The group threshold to use when none is given, which only makes sense when
+// there is a single group
+pub fn default_group_threshold(spec: &str) -> Result<usize, Error> {
+    let groups = spec_groups(spec)?.len();
+    if groups > 1 {
+        bail!(SskrToolError::GroupThresholdNeeded { groups });
     }
+    Ok(1)
+}
 
-    let mut group_specs: Vec<GroupSpec> = vec![];
+// The names given to the groups of a spec, in order, for groups without one
+pub fn spec_labels(spec: &str) -> Result<Vec<Option<String>>, Error> {
+    Ok(spec_groups(spec)?
+        .into_iter()
+        .map(|group| group.name)
+        .collect())
+}
 
-    for part in spec.split(",") {
-        let Some(group_match) = SPEC_GROUP_REGEX.captures(&part) else {
-            bail!("Invalid group \"{}\" in spec", &part);
-        };
+// The threshold and count of a spec of a single group, for schemes that have
+// no groups, like codex32
+pub fn single_group(spec: &str) -> Result<(usize, usize), Error> {
+    let groups = spec_groups(spec)?;
+    let [group] = &groups[..] else {
+        bail!(SskrToolError::SingleGroupNeeded {
+            groups: groups.len()
+        });
+    };
+    check_group(spec, group)?;
+    Ok((group.threshold, group.count))
+}
 
-        let m = group_match["m"].parse()?;
-        let n = group_match["n"].parse()?;
+// The shares a spec makes, as a split would make them, for checking a spec
+// before splitting anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecLayout {
+    pub group_threshold: usize,
+    pub groups: Vec<GroupLayout>,
+}
 
-        if m > n {
-            bail!(
-                "Invalid group \"{}\" in spec ({} is greater than {})",
-                &part,
-                m,
-                n
-            );
-        }
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupLayout {
+    pub label: Option<String>,
+    pub threshold: usize,
+    pub count: usize,
+}
 
-        if m == 1 && n > 1 {
-            bail!(
-                "Invalid group \"{}\" in spec: 1 of N groups (where N > 1) not supported",
-                &part
-            );
+impl SpecLayout {
+    pub fn share_count(&self) -> usize {
+        self.groups.iter().map(|group| group.count).sum()
+    }
+
+    // The sets of groups that are enough to recover, as indices of groups,
+    // generated one at a time as there can be thousands of them
+    pub fn quorums(&self) -> impl Iterator<Item = Vec<usize>> {
+        subsets(self.groups.len(), self.group_threshold)
+    }
+
+    // The fewest shares that recover, from the groups needing the fewest
+    pub fn fewest_shares(&self) -> usize {
+        let mut thresholds: Vec<usize> = self.groups.iter().map(|group| group.threshold).collect();
+        thresholds.sort();
+        thresholds.iter().take(self.group_threshold).sum()
+    }
+}
+
+// Checks a spec and group threshold the way splitting does
+pub fn spec_layout(spec: &str, group_threshold: usize) -> Result<SpecLayout, Error> {
+    let sskr_spec = parse_spec(spec, group_threshold)?;
+    let groups = sskr_spec
+        .groups()
+        .iter()
+        .zip(spec_labels(spec)?)
+        .map(|(group, label)| GroupLayout {
+            label,
+            threshold: group.member_threshold(),
+            count: group.member_count(),
+        })
+        .collect();
+    Ok(SpecLayout {
+        group_threshold: sskr_spec.group_threshold(),
+        groups,
+    })
+}
+
+// Specs are written as "2of3,3of5" or "2/3; 3/5", in any case and with any
+// whitespace. Groups can be named, as in "family=2of3,lawyers=3of5"; names
+// keep their case. See the spec module for the grammar.
+fn spec_groups(spec: &str) -> Result<Vec<SpecGroup>, Error> {
+    let groups = spec::parse(spec)?;
+    if let Some(extra) = groups.get(MAX_GROUPS) {
+        bail!(spec_error(
+            spec,
+            extra.span.clone(),
+            &format!("a split has at most {} groups", MAX_GROUPS)
+        ));
+    }
+
+    for (index, group) in groups.iter().enumerate() {
+        // No annotation means anything yet
+        if let Some(annotation) = group.annotations.first() {
+            bail!(spec_error(
+                spec,
+                annotation.span.clone(),
+                &format!("unknown annotation \"{}\"", annotation.text)
+            ));
+        }
+        let Some(name) = &group.name else {
+            continue;
+        };
+        if name.is_empty() {
+            bail!(SskrToolError::EmptyGroupName {
+                group: group.text(spec).to_string()
+            });
+        }
+        if groups[..index]
+            .iter()
+            .any(|other| other.name.as_ref() == Some(name))
+        {
+            bail!(SskrToolError::DuplicateGroupName { name: name.clone() });
         }
+    }
+
+    Ok(groups)
+}
+
+fn check_group(spec: &str, group: &SpecGroup) -> Result<(), Error> {
+    let text = group.text(spec).to_string();
+    if group.threshold > group.count {
+        bail!(SskrToolError::ThresholdAboveCount {
+            group: text,
+            threshold: group.threshold,
+            count: group.count
+        });
+    }
+    if group.count > MAX_MEMBERS {
+        bail!(spec_error(
+            spec,
+            group.span.clone(),
+            &format!("a group has at most {} shares", MAX_MEMBERS)
+        ));
+    }
+    if group.threshold == 1 && group.count > 1 {
+        bail!(SskrToolError::OneOfMany { group: text });
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_spec(spec: &str, group_threshold: usize) -> Result<Spec, Error> {
+    let mut group_specs: Vec<GroupSpec> = vec![];
 
-        group_specs.push(
-            GroupSpec::new(m, n)
-                .map_err(|e| anyhow!("Error making group spec for group \"{}\": {}", &part, e))?,
-        );
+    for group in spec_groups(spec)? {
+        check_group(spec, &group)?;
+        group_specs.push(GroupSpec::new(group.threshold, group.count).map_err(|e| {
+            SskrToolError::UnsupportedGroup {
+                group: group.text(spec).to_string(),
+                reason: e.to_string(),
+            }
+        })?);
     }
 
     Ok(Spec::new(group_threshold, group_specs)?)
@@ -1,23 +1,22 @@
 use crate::bytewords::*;
+use crate::error::SskrToolError;
+use crate::limits::METADATA_SIZE_BYTES;
 use anyhow::{bail, Error};
-use sskr::METADATA_SIZE_BYTES;
 
 pub fn share_metadata(source: &[u8], minimal: &bool) -> Result<(u16, [usize; 5]), Error> {
     if source.len() < METADATA_SIZE_BYTES {
-        bail!(
-            "Share is too short: \"{}\"",
-            byteword_string_no_checksum(&source, minimal)
-        );
+        bail!(SskrToolError::ShareTooShort {
+            share: byteword_string_no_checksum(source, BytewordStyle::from_minimal(minimal))
+        });
     }
 
     let group_threshold = ((source[2] >> 4) + 1) as usize;
     let group_count = ((source[2] & 0xf) + 1) as usize;
 
     if group_threshold > group_count {
-        bail!(
-            "Share has invalid group threshold: \"{}\"",
-            byteword_string_no_checksum(&source, minimal)
-        );
+        bail!(SskrToolError::InvalidGroupThreshold {
+            share: byteword_string_no_checksum(source, BytewordStyle::from_minimal(minimal))
+        });
     }
 
     let identifier = ((source[0] as u16) << 8) | source[1] as u16;
@@ -25,10 +24,9 @@ pub fn share_metadata(source: &[u8], minimal: &bool) -> Result<(u16, [usize; 5])
     let member_threshold = ((source[3] & 0xf) + 1) as usize;
     let reserved = source[4] >> 4;
     if reserved != 0 {
-        bail!(
-            "Share has invalid reserved bits: \"{}\"",
-            byteword_string_no_checksum(&source, minimal)
-        );
+        bail!(SskrToolError::ReservedBits {
+            share: byteword_string_no_checksum(source, BytewordStyle::from_minimal(minimal))
+        });
     }
     let member_index = (source[4] & 0xf) as usize;
 
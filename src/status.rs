@@ -0,0 +1,124 @@
+use crate::diagnostics::{shortfall, suggestions};
+use crate::manifest::Manifest;
+use crate::recover::decode_share;
+use crate::sskr_shares::share_metadata;
+use anyhow::Error;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Serialize, Debug)]
+pub struct SetStatus {
+    pub identifier: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub group_threshold: Option<usize>,
+    pub groups: Vec<GroupStatus>,
+    pub groups_satisfied: usize,
+    pub groups_missing: Option<usize>,
+    pub recoverable: bool,
+    // The shares to collect next
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GroupStatus {
+    pub group: usize,
+    pub member_threshold: Option<usize>,
+    pub present: usize,
+    pub missing: Option<usize>,
+    pub satisfied: bool,
+}
+
+#[derive(Default)]
+struct SetShares {
+    group_threshold: Option<usize>,
+    group_count: usize,
+    // group index => (member threshold, member indices present)
+    groups: BTreeMap<usize, (Option<usize>, BTreeSet<usize>)>,
+}
+
+// Works out how far each share set is from being recoverable, using only share
+// metadata, so it never combines shares or touches the secret. Sets described
+// by the manifest are reported even when none of their shares are present.
+pub fn status(
+    lines: Vec<String>,
+    minimal: &bool,
+    manifest: Option<&Manifest>,
+) -> Result<Vec<SetStatus>, Error> {
+    let mut sets: BTreeMap<String, SetShares> = BTreeMap::new();
+
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let share = decode_share(line)?;
+        let (id, meta) = share_metadata(&share, minimal)?;
+        let set = sets.entry(format!("{:04x}", id)).or_default();
+
+        set.group_threshold = Some(meta[1]);
+        set.group_count = meta[2];
+        let group = set.groups.entry(meta[0]).or_default();
+        group.0 = Some(meta[4]);
+        group.1.insert(meta[3]);
+    }
+
+    let seeds = manifest.map_or(&[][..], |manifest| &manifest.seeds[..]);
+    for seed in seeds {
+        let set = sets.entry(seed.identifier.clone()).or_default();
+        set.group_threshold = Some(seed.group_threshold);
+        set.group_count = set.group_count.max(seed.groups.len());
+        for group in &seed.groups {
+            set.groups.entry(group.group - 1).or_default().0 = Some(group.member_threshold);
+        }
+    }
+
+    Ok(sets
+        .into_iter()
+        .map(|(identifier, set)| {
+            let name = seeds
+                .iter()
+                .find(|seed| seed.identifier == identifier)
+                .and_then(|seed| seed.name.clone());
+            set_status(identifier, name, set)
+        })
+        .collect())
+}
+
+fn set_status(identifier: String, name: Option<String>, set: SetShares) -> SetStatus {
+    let groups: Vec<GroupStatus> = (0..set.group_count)
+        .map(|group_index| {
+            let (member_threshold, present) = set
+                .groups
+                .get(&group_index)
+                .map_or((None, 0), |(threshold, members)| {
+                    (*threshold, members.len())
+                });
+            let missing = member_threshold.map(|threshold| threshold.saturating_sub(present));
+            GroupStatus {
+                group: group_index + 1,
+                member_threshold,
+                present,
+                missing,
+                satisfied: missing == Some(0),
+            }
+        })
+        .collect();
+
+    let groups_satisfied = groups.iter().filter(|group| group.satisfied).count();
+    let groups_missing = set
+        .group_threshold
+        .map(|threshold| threshold.saturating_sub(groups_satisfied));
+
+    let mut status = SetStatus {
+        identifier,
+        name,
+        group_threshold: set.group_threshold,
+        groups,
+        groups_satisfied,
+        groups_missing,
+        recoverable: groups_missing == Some(0),
+        suggestions: vec![],
+    };
+    if let Some(problem) = shortfall(&status) {
+        status.suggestions = suggestions(&problem, &[]);
+    }
+    status
+}
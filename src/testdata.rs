@@ -0,0 +1,268 @@
+use crate::diagnostics::{shortfall, Problem};
+use crate::example;
+use crate::recover::diagnose_lines;
+use crate::share_format::{encode, ShareFormat};
+use crate::split::{parse_spec, set_identifier};
+use crate::status::status;
+use anyhow::{Context, Error};
+use bc_crypto::make_fake_random_number_generator;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sskr::{sskr_generate_using, Secret};
+use std::fs;
+use std::path::Path;
+
+// A corpus of shares as people get them wrong, each case with the problems
+// the diagnostics should find in it, so that how well mistakes are explained
+// is checked as any other behaviour is. A corpus file lists the cases in TOML:
+//
+//   [[case]]
+//   name = "Misspelled word"
+//   mistake = "\"tuna\" typed as \"tunx\" in the first share"
+//   shares = ["tunx acid ...", "tuna acid ..."]
+//   expect = [{ problem = "not_bytewords", share = 0, words = [1] }]
+//
+// Problems are written as the JSON output names them, in the order they are
+// found: the problems of each line, then what the usable lines are short of.
+// Fields left out of an expected problem match anything.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Corpus {
+    #[serde(rename = "case")]
+    pub cases: Vec<Case>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Case {
+    pub name: String,
+    #[serde(default)]
+    pub mistake: String,
+    pub shares: Vec<String>,
+    #[serde(default)]
+    pub expect: Vec<Value>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CaseOutcome {
+    pub name: String,
+    pub passed: bool,
+    pub expected: Vec<Value>,
+    pub found: Vec<Problem>,
+}
+
+impl Corpus {
+    pub fn load(path: &Path) -> Result<Corpus, Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read corpus \"{}\"", path.display()))?;
+        Corpus::parse(&contents).with_context(|| format!("Invalid corpus \"{}\"", path.display()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Corpus, Error> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, Error> {
+        Ok(toml::to_string(self)?)
+    }
+
+    pub fn run(&self) -> Vec<CaseOutcome> {
+        self.cases
+            .iter()
+            .map(|case| {
+                let found = diagnose(&case.shares);
+                let actual: Vec<Value> = found
+                    .iter()
+                    .map(|problem| serde_json::to_value(problem).unwrap_or_default())
+                    .collect();
+                CaseOutcome {
+                    name: case.name.clone(),
+                    passed: case.expect.len() == actual.len()
+                        && case
+                            .expect
+                            .iter()
+                            .zip(&actual)
+                            .all(|(expected, actual)| matches(expected, actual)),
+                    expected: case.expect.clone(),
+                    found,
+                }
+            })
+            .collect()
+    }
+}
+
+// What `recover` reports about the lines: each line it leaves out, then what
+// the lines it keeps need to recover
+pub fn diagnose(lines: &[String]) -> Vec<Problem> {
+    let line_problems = diagnose_lines(lines);
+    let usable: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| line_problems.iter().all(|problem| problem.index != *index))
+        .map(|(_, line)| line.clone())
+        .collect();
+    let mut problems: Vec<Problem> = line_problems
+        .into_iter()
+        .map(|problem| problem.problem)
+        .collect();
+    if let Ok(sets) = status(usable, &false, None) {
+        problems.extend(sets.iter().filter_map(shortfall));
+    }
+    problems
+}
+
+fn matches(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).is_some_and(|actual| matches(value, actual))),
+        _ => expected == actual,
+    }
+}
+
+// The corpus this version is checked against, made from the shares of
+// `split --example`, so it is the same on every run
+pub fn corpus() -> Result<Corpus, Error> {
+    let shares = &example::shares()?[0];
+    let words = |share: &[u8]| encode(share, ShareFormat::Bytewords);
+    let first = words(&shares[0]);
+    let third = words(&shares[2]);
+    let edited = |share: &str, edit: &dyn Fn(&mut Vec<String>)| {
+        let mut words: Vec<String> = share.split(' ').map(str::to_string).collect();
+        edit(&mut words);
+        words.join(" ")
+    };
+
+    // Another seed split the same way, under another identifier
+    let mut other = sskr_generate_using(
+        &parse_spec(example::SPEC, example::GROUP_THRESHOLD)?,
+        &Secret::new([0xa5; 16])?,
+        &mut make_fake_random_number_generator(),
+    )?;
+    set_identifier(&mut other, 0x5eed);
+    // The first share with a byte of its secret changed and the checksum
+    // made to match, as a tampered share would be
+    let mut tampered = shares[0].clone();
+    tampered[8] ^= 0x01;
+    // The third share claiming its group needs all three shares
+    let mut resplit = shares[2].clone();
+    resplit[3] = (resplit[3] & 0xf0) | 0x02;
+
+    let cases = vec![
+        Case {
+            name: "Enough good shares".to_string(),
+            mistake: "None, the first and third shares as split".to_string(),
+            shares: vec![first.clone(), third.clone()],
+            expect: vec![],
+        },
+        Case {
+            name: "Misspelled word".to_string(),
+            mistake: "The fifth word of the first share misspelled".to_string(),
+            shares: vec![
+                edited(&first, &|words| words[4].replace_range(3.., "x")),
+                third.clone(),
+            ],
+            expect: vec![
+                json!({ "problem": "not_bytewords", "share": 0, "words": [5] }),
+                json!({ "problem": "not_enough_shares", "groups": [[1, 1]] }),
+            ],
+        },
+        Case {
+            name: "Letters swapped".to_string(),
+            mistake: "Two letters of the second word of the third share swapped".to_string(),
+            shares: vec![
+                first.clone(),
+                edited(&third, &|words| words[1] = swapped(&words[1])),
+            ],
+            expect: vec![
+                json!({ "problem": "not_bytewords", "share": 1, "words": [2] }),
+                json!({ "problem": "not_enough_shares", "groups": [[1, 1]] }),
+            ],
+        },
+        Case {
+            name: "Wrong word".to_string(),
+            mistake: "The tenth word of the first share written as another byteword".to_string(),
+            shares: vec![
+                edited(&first, &|words| {
+                    words[9] = if words[9] == "able" { "acid" } else { "able" }.to_string()
+                }),
+                third.clone(),
+            ],
+            expect: vec![
+                json!({ "problem": "checksum_mismatch", "share": 0, "suspects": [10] }),
+                json!({ "problem": "not_enough_shares", "groups": [[1, 1]] }),
+            ],
+        },
+        Case {
+            name: "Words swapped".to_string(),
+            mistake: "The seventh and eighth words of the first share swapped".to_string(),
+            shares: vec![edited(&first, &|words| words.swap(6, 7)), third.clone()],
+            expect: vec![
+                json!({ "problem": "checksum_mismatch", "share": 0, "suspects": [] }),
+                json!({ "problem": "not_enough_shares", "groups": [[1, 1]] }),
+            ],
+        },
+        Case {
+            name: "Dropped checksum".to_string(),
+            mistake: "The four checksum words left off the third share".to_string(),
+            shares: vec![
+                first.clone(),
+                edited(&third, &|words| words.truncate(words.len() - 4)),
+            ],
+            expect: vec![
+                json!({ "problem": "checksum_mismatch", "share": 1, "suspects": [] }),
+                json!({ "problem": "not_enough_shares", "groups": [[1, 1]] }),
+            ],
+        },
+        Case {
+            name: "Mixed seeds".to_string(),
+            mistake: "The third share of another seed's split given with the first".to_string(),
+            shares: vec![first.clone(), words(&other[0][2])],
+            expect: vec![
+                json!({ "problem": "other_set", "share": 1 }),
+                json!({ "problem": "not_enough_shares", "groups": [[1, 1]] }),
+            ],
+        },
+        Case {
+            name: "Another split".to_string(),
+            mistake: "The third share altered to need all three shares of its group".to_string(),
+            shares: vec![first.clone(), words(&resplit)],
+            expect: vec![
+                json!({ "problem": "other_split", "share": 1 }),
+                json!({ "problem": "not_enough_shares", "groups": [[1, 1]] }),
+            ],
+        },
+        Case {
+            name: "Tampered share".to_string(),
+            mistake: "The first share given twice, once with its secret altered".to_string(),
+            shares: vec![first.clone(), words(&tampered), third.clone()],
+            expect: vec![json!({
+                "problem": "conflict", "share": 1, "other": 0, "group": 1, "member": 1
+            })],
+        },
+        Case {
+            name: "Same share twice".to_string(),
+            mistake: "The first share given twice and no other".to_string(),
+            shares: vec![first.clone(), first.clone()],
+            expect: vec![json!({
+                "problem": "not_enough_shares", "groups_missing": 1, "groups": [[1, 1]]
+            })],
+        },
+        Case {
+            name: "Not a share".to_string(),
+            mistake: "A line of notes among the shares".to_string(),
+            shares: vec![
+                first.clone(),
+                "third share is in the safe".to_string(),
+                third.clone(),
+            ],
+            expect: vec![json!({ "problem": "unreadable", "share": 1 })],
+        },
+    ];
+    Ok(Corpus { cases })
+}
+
+fn swapped(word: &str) -> String {
+    let mut letters: Vec<char> = word.chars().collect();
+    letters.swap(1, 2);
+    letters.into_iter().collect()
+}
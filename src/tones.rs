@@ -0,0 +1,224 @@
+use crate::bytewords::byteword_checksum;
+use crate::share_format::SSKR_TAG;
+use anyhow::{bail, Context, Error};
+use dcbor::{CBOREncodable, CBOR};
+use std::f64::consts::TAU;
+use std::fs;
+use std::path::Path;
+
+// Experimental: a share as audio tones, for moving it between machines that
+// may share no media at all. One machine plays the tones through its speaker
+// while the other records them with its microphone.
+//
+// After a sync tone, the share's tagged CBOR and the CRC-32 the byteword
+// checksum uses are sent a 4-bit nibble at a time, each as one of 16 tones
+// (16-FSK), high nibble first. The decoder finds where the sync tone ends and
+// reads the strongest tone in the middle of each symbol from there, so a
+// recording can start anywhere before the tones and run on after them.
+pub const SAMPLE_RATE: u32 = 16000;
+const AMPLITUDE: f64 = 0.5;
+const LEAD_SECONDS: f64 = 0.25;
+const SYNC_SECONDS: f64 = 0.5;
+const SYMBOL_SECONDS: f64 = 0.06;
+const SYNC_HZ: f64 = 700.0;
+const BASE_HZ: f64 = 1000.0;
+const STEP_HZ: f64 = 125.0;
+// The decoder slides a window this long in steps this long to find the sync
+const SCAN_SECONDS: f64 = 0.02;
+const HOP_SECONDS: f64 = 0.0025;
+// Less than this much of the sync tone means noise set it off
+const MIN_SYNC_SECONDS: f64 = 0.3;
+// The share of a window's energy the strongest tone needs to count as sent
+const MIN_PURITY: f64 = 0.2;
+
+const WAV_HEADER_LEN: usize = 44;
+
+fn nibble_hz(nibble: u8) -> f64 {
+    BASE_HZ + STEP_HZ * nibble as f64
+}
+
+// The tones for the share, as 16-bit samples at SAMPLE_RATE
+pub fn encode_samples(share: &[u8]) -> Vec<i16> {
+    let cbor = CBOR::tagged_value(SSKR_TAG, CBOR::byte_string(share)).cbor_data();
+    let payload = [cbor.as_slice(), &byteword_checksum(&cbor)].concat();
+
+    let mut tones = vec![(0.0, LEAD_SECONDS), (SYNC_HZ, SYNC_SECONDS)];
+    for byte in payload {
+        tones.push((nibble_hz(byte >> 4), SYMBOL_SECONDS));
+        tones.push((nibble_hz(byte & 0x0f), SYMBOL_SECONDS));
+    }
+    tones.push((0.0, LEAD_SECONDS));
+
+    // The phase carries on from tone to tone, so changing tone doesn't click
+    let mut samples = vec![];
+    let mut phase: f64 = 0.0;
+    for (hz, seconds) in tones {
+        for _ in 0..(seconds * SAMPLE_RATE as f64).round() as usize {
+            samples.push((phase.sin() * AMPLITUDE * i16::MAX as f64) as i16);
+            phase = (phase + TAU * hz / SAMPLE_RATE as f64) % TAU;
+        }
+    }
+    samples
+}
+
+pub fn decode_samples(samples: &[f64], sample_rate: u32) -> Result<Vec<u8>, Error> {
+    let rate = sample_rate as f64;
+    let symbol = (SYMBOL_SECONDS * rate) as usize;
+    let start = sync_end(samples, rate)
+        .context("No sync tone found, the recording doesn't hold the start of a share")?;
+
+    let mut nibbles = vec![];
+    let mut position = start;
+    while position + symbol <= samples.len() {
+        // The middle of the symbol, away from where the tones change
+        let window = &samples[position + symbol / 5..position + symbol * 4 / 5];
+        match strongest(window, rate, &(0..16).map(nibble_hz).collect::<Vec<_>>()) {
+            Some(nibble) => nibbles.push(nibble as u8),
+            None => break,
+        }
+        position += symbol;
+    }
+
+    let bytes = nibbles
+        .chunks_exact(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect::<Vec<u8>>();
+    if bytes.len() < 5 {
+        bail!("The tones stop right after the sync tone, the recording was cut off");
+    }
+    let (cbor, checksum) = bytes.split_at(bytes.len() - 4);
+    if checksum != byteword_checksum(cbor) {
+        bail!("The checksum of the tones doesn't match, the recording is too noisy or was cut off");
+    }
+    let cbor = CBOR::from_data(cbor)?;
+    Ok(cbor
+        .expect_tagged_value(SSKR_TAG)?
+        .expect_byte_string()?
+        .to_vec())
+}
+
+// Where the first long enough run of the sync tone ends
+fn sync_end(samples: &[f64], rate: f64) -> Option<usize> {
+    let window = (SCAN_SECONDS * rate) as usize;
+    let hop = ((HOP_SECONDS * rate) as usize).max(1);
+    let min_run = (MIN_SYNC_SECONDS / HOP_SECONDS) as usize;
+    let mut frequencies = vec![SYNC_HZ];
+    frequencies.extend((0..16).map(nibble_hz));
+
+    let mut run = 0;
+    let mut position = 0;
+    while position + window <= samples.len() {
+        let is_sync =
+            strongest(&samples[position..position + window], rate, &frequencies) == Some(0);
+        if is_sync {
+            run += 1;
+        } else if run >= min_run {
+            // The last window mostly in the sync tone ends about half a
+            // window after it starts
+            return Some(position - hop + window / 2);
+        } else {
+            run = 0;
+        }
+        position += hop;
+    }
+    None
+}
+
+// The index of the strongest of the frequencies in the window, as long as it
+// stands out from the noise
+fn strongest(window: &[f64], rate: f64, frequencies: &[f64]) -> Option<usize> {
+    let energy = window.iter().map(|sample| sample * sample).sum::<f64>();
+    if energy == 0.0 {
+        return None;
+    }
+    let (index, power) = frequencies
+        .iter()
+        .map(|hz| goertzel(window, rate, *hz))
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    // A pure tone's power comes to half its energy times the window length
+    (power / (energy * window.len() as f64) >= MIN_PURITY / 2.0).then_some(index)
+}
+
+// The power of one frequency in the window
+fn goertzel(window: &[f64], rate: f64, hz: f64) -> f64 {
+    let coefficient = 2.0 * (TAU * hz / rate).cos();
+    let (mut previous, mut before) = (0.0, 0.0);
+    for sample in window {
+        let current = sample + coefficient * previous - before;
+        before = previous;
+        previous = current;
+    }
+    previous * previous + before * before - coefficient * previous * before
+}
+
+// A mono 16-bit PCM WAV file
+pub fn to_wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(WAV_HEADER_LEN + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+// The samples of the first channel of a 16-bit PCM WAV file, as recorders
+// write them, and the sample rate
+pub fn from_wav(wav: &[u8]) -> Result<(Vec<f64>, u32), Error> {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        bail!("Not a WAV file");
+    }
+    let mut format = None;
+    let mut position = 12;
+    while position + 8 <= wav.len() {
+        let id = &wav[position..position + 4];
+        let len = u32::from_le_bytes(wav[position + 4..position + 8].try_into()?) as usize;
+        let body = &wav[position + 8..(position + 8 + len).min(wav.len())];
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let field = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+                let rate = u32::from_le_bytes(body[4..8].try_into()?);
+                format = Some((field(0), field(2), rate, field(14)));
+            }
+            b"data" => {
+                let Some((1, channels @ 1.., rate, 16)) = format else {
+                    bail!("Only 16-bit PCM WAV files can be read");
+                };
+                let samples = body
+                    .chunks_exact(2 * channels as usize)
+                    .map(|frame| i16::from_le_bytes([frame[0], frame[1]]) as f64 / i16::MAX as f64)
+                    .collect();
+                return Ok((samples, rate));
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        position += 8 + len + len % 2;
+    }
+    bail!("The WAV file holds no audio")
+}
+
+pub fn write_wav(path: &Path, share: &[u8]) -> Result<(), Error> {
+    fs::write(path, to_wav(&encode_samples(share)))
+        .with_context(|| format!("Unable to write \"{}\"", path.display()))
+}
+
+pub fn read_wav(path: &Path) -> Result<Vec<u8>, Error> {
+    let wav = fs::read(path).with_context(|| format!("Unable to read \"{}\"", path.display()))?;
+    let (samples, rate) =
+        from_wav(&wav).with_context(|| format!("Unable to read \"{}\"", path.display()))?;
+    decode_samples(&samples, rate)
+}
@@ -0,0 +1,685 @@
+use crate::checklist::{Checklist, Outcome};
+use crate::diagnostics::{suggestions, unreadable};
+use crate::duress::{DuressKeys, Ending, Press};
+use crate::language::detect_language;
+use crate::policy::{self, Channel, Field, Policy};
+use crate::recover::{decode_share, recover_secret};
+use crate::review::Screen;
+use crate::share_format::{encode, ShareFormat};
+use crate::split::split_shares;
+use crate::status::status;
+use anyhow::Error;
+use bip39::{Language, Mnemonic, MnemonicType};
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::queue;
+use crossterm::terminal::{self, Clear, ClearType};
+use std::io::{self, Write};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+// SSKR allows up to 16 groups of up to 16 shares
+const MAX_GROUPS: usize = 16;
+const MAX_SHARES: usize = 16;
+// Words of a share per row when it is shown for copying
+const WORDS_PER_ROW: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Group {
+    threshold: usize,
+    count: usize,
+}
+
+enum Step {
+    // The checklist is gone through before anything else
+    Checklist,
+    Start {
+        choice: usize,
+    },
+    Words {
+        choice: usize,
+    },
+    Source {
+        choice: usize,
+    },
+    EnterMnemonic {
+        error: Option<String>,
+    },
+    ShowMnemonic,
+    Spec {
+        selected: usize,
+        error: Option<String>,
+    },
+    // The screen is blank until the custodian of the next share is alone at it
+    Handoff {
+        next: usize,
+    },
+    ShowShare {
+        current: usize,
+    },
+    SplitDone,
+    EnterShare {
+        message: Option<String>,
+    },
+    Recovered,
+    Done,
+}
+
+// A split or recovery ceremony, one screen at a time. Custodians come to the
+// screen in turn, so a share is only ever shown on its own and only while its
+// custodian is the one looking, and shares being recovered are never echoed.
+pub struct Wizard {
+    step: Step,
+    words: usize,
+    // None detects the language of a mnemonic typed in, and is English for
+    // the rest
+    language: Option<Language>,
+    input: Zeroizing<String>,
+    mnemonic: Option<Mnemonic>,
+    groups: Vec<Group>,
+    group_threshold: usize,
+    // (title, share) in the order they are handed out
+    shares: Vec<(String, Zeroizing<String>)>,
+    lines: Zeroizing<Vec<String>>,
+    // The mnemonic, or the secret as hex when it isn't one
+    recovered: Option<(Field, Zeroizing<String>)>,
+    checklist: Option<Checklist>,
+    outcome: Outcome,
+    policy: Policy,
+}
+
+impl Default for Wizard {
+    fn default() -> Wizard {
+        Wizard::new()
+    }
+}
+
+impl Wizard {
+    pub fn new() -> Wizard {
+        Wizard {
+            step: Step::Start { choice: 0 },
+            words: 12,
+            language: None,
+            input: Zeroizing::new(String::new()),
+            mnemonic: None,
+            groups: vec![Group {
+                threshold: 2,
+                count: 3,
+            }],
+            group_threshold: 1,
+            shares: vec![],
+            lines: Zeroizing::new(vec![]),
+            recovered: None,
+            checklist: None,
+            outcome: Outcome::Abandoned,
+            policy: policy::current().clone(),
+        }
+    }
+
+    // A ceremony that starts with the steps of the checklist
+    pub fn with_checklist(checklist: Checklist) -> Wizard {
+        Wizard {
+            step: Step::Checklist,
+            checklist: Some(checklist),
+            ..Wizard::new()
+        }
+    }
+
+    // The language of the mnemonics generated, typed in and recovered
+    pub fn with_language(self, language: Language) -> Wizard {
+        Wizard {
+            language: Some(language),
+            ..self
+        }
+    }
+
+    // What may be shown, instead of the policy in force
+    pub fn with_policy(self, policy: Policy) -> Wizard {
+        Wizard { policy, ..self }
+    }
+
+    // Drops everything entered, generated or recovered, which wipes it
+    pub fn wipe(&mut self) {
+        *self = Wizard {
+            language: self.language,
+            policy: self.policy.clone(),
+            ..Wizard::new()
+        };
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(self.step, Step::Done)
+    }
+
+    pub fn checklist(&self) -> Option<&Checklist> {
+        self.checklist.as_ref()
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    // The spec the groups built so far make, as split takes it
+    pub fn spec(&self) -> String {
+        self.groups
+            .iter()
+            .map(|group| format!("{}of{}", group.threshold, group.count))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn key(&mut self, key: KeyCode) {
+        if key == KeyCode::Esc {
+            self.step = Step::Done;
+            return;
+        }
+        match &mut self.step {
+            Step::Checklist => {
+                if let (KeyCode::Enter, Some(checklist)) = (key, &mut self.checklist) {
+                    checklist.confirm();
+                    if checklist.is_complete() {
+                        self.step = Step::Start { choice: 0 };
+                    }
+                }
+            }
+            Step::Start { choice } => match menu(key, choice, 2) {
+                Some(0) => self.step = Step::Words { choice: 0 },
+                Some(_) => self.step = Step::EnterShare { message: None },
+                None => {}
+            },
+            Step::Words { choice } => {
+                if let Some(choice) = menu(key, choice, WORD_COUNTS.len()) {
+                    self.words = WORD_COUNTS[choice];
+                    self.step = Step::Source { choice: 0 };
+                }
+            }
+            Step::Source { choice } => match menu(key, choice, 2) {
+                Some(0) => {
+                    let words =
+                        MnemonicType::for_word_count(self.words).unwrap_or(MnemonicType::Words12);
+                    self.mnemonic = Some(Mnemonic::new(
+                        words,
+                        self.language.unwrap_or(Language::English),
+                    ));
+                    self.step = Step::ShowMnemonic;
+                }
+                Some(_) => self.step = Step::EnterMnemonic { error: None },
+                None => {}
+            },
+            Step::EnterMnemonic { error } => {
+                if let Some(phrase) = text_input(key, &mut self.input) {
+                    let phrase = Zeroizing::new(phrase.to_lowercase());
+                    let parsed = match self.language {
+                        Some(language) => Ok(language),
+                        None => detect_language(&phrase),
+                    }
+                    .and_then(|language| Mnemonic::from_phrase(&phrase, language));
+                    match parsed {
+                        Ok(mnemonic) => {
+                            self.mnemonic = Some(mnemonic);
+                            self.step = Step::Spec {
+                                selected: 0,
+                                error: None,
+                            };
+                        }
+                        Err(_) => *error = Some("That is not a valid mnemonic".to_string()),
+                    }
+                }
+            }
+            Step::ShowMnemonic => {
+                if key == KeyCode::Enter {
+                    self.step = Step::Spec {
+                        selected: 0,
+                        error: None,
+                    };
+                }
+            }
+            Step::Spec { selected, .. } => {
+                let selected = *selected;
+                self.step = self.spec_key(key, selected);
+            }
+            Step::Handoff { next } => {
+                if key == KeyCode::Enter {
+                    self.step = Step::ShowShare { current: *next };
+                }
+            }
+            Step::ShowShare { current } => {
+                if key == KeyCode::Enter {
+                    self.step = match *current + 1 {
+                        next if next < self.shares.len() => Step::Handoff { next },
+                        _ => {
+                            self.outcome = Outcome::Split;
+                            Step::SplitDone
+                        }
+                    };
+                }
+            }
+            Step::SplitDone | Step::Recovered => {
+                if key == KeyCode::Enter {
+                    self.step = Step::Done;
+                }
+            }
+            Step::EnterShare { .. } => {
+                if let Some(line) = text_input(key, &mut self.input) {
+                    let message = Some(self.add_share(&line));
+                    self.step = match self.recovered {
+                        Some(_) => {
+                            self.outcome = Outcome::Recovered;
+                            Step::Recovered
+                        }
+                        None => Step::EnterShare { message },
+                    };
+                }
+            }
+            Step::Done => {}
+        }
+    }
+
+    fn spec_key(&mut self, key: KeyCode, mut selected: usize) -> Step {
+        let groups = &mut self.groups;
+        let group = &mut groups[selected];
+        match key {
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected = (selected + 1).min(groups.len() - 1),
+            KeyCode::Left => group.threshold = (group.threshold - 1).max(1),
+            KeyCode::Right => group.threshold = (group.threshold + 1).min(group.count),
+            KeyCode::Char('-') => {
+                group.count = (group.count - 1).max(1);
+                group.threshold = group.threshold.min(group.count);
+            }
+            KeyCode::Char('+') => group.count = (group.count + 1).min(MAX_SHARES),
+            KeyCode::Char('a') if groups.len() < MAX_GROUPS => {
+                groups.push(Group {
+                    threshold: 2,
+                    count: 3,
+                });
+                selected = groups.len() - 1;
+            }
+            KeyCode::Char('d') | KeyCode::Delete if groups.len() > 1 => {
+                groups.remove(selected);
+                selected = selected.min(groups.len() - 1);
+            }
+            KeyCode::Char('<') => self.group_threshold = (self.group_threshold - 1).max(1),
+            KeyCode::Char('>') => self.group_threshold += 1,
+            KeyCode::Enter => {
+                return match self.split() {
+                    Ok(()) => Step::Handoff { next: 0 },
+                    Err(error) => Step::Spec {
+                        selected,
+                        error: Some(error.to_string()),
+                    },
+                }
+            }
+            _ => {}
+        }
+        self.group_threshold = self.group_threshold.min(self.groups.len());
+        Step::Spec {
+            selected,
+            error: None,
+        }
+    }
+
+    fn split(&mut self) -> Result<(), Error> {
+        let mnemonic = self.mnemonic.as_ref().expect("a mnemonic is chosen first");
+        let (_, groups) = split_shares(
+            &self.spec(),
+            self.group_threshold,
+            mnemonic.phrase(),
+            Some(mnemonic.language()),
+        )?;
+        self.shares = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group_num, shares)| {
+                let group = self.groups[group_num];
+                shares.iter().enumerate().map(move |(share_num, share)| {
+                    (
+                        format!(
+                            "Group {} (any {} of {}), share {}",
+                            group_num + 1,
+                            group.threshold,
+                            group.count,
+                            share_num + 1
+                        ),
+                        Zeroizing::new(encode(share, ShareFormat::Bytewords)),
+                    )
+                })
+            })
+            .collect();
+        Ok(())
+    }
+
+    // What to tell the custodian about the share they typed
+    fn add_share(&mut self, line: &str) -> String {
+        let share = match decode_share(line) {
+            Ok(share) => Zeroizing::new(share),
+            Err(_) => {
+                let problem = unreadable(0, line);
+                return match suggestions(&problem, &["the share".to_string()]).first() {
+                    Some(suggestion) => format!("That is not a valid share. {}.", suggestion),
+                    None => "That is not a valid share, check it and type it again".to_string(),
+                };
+            }
+        };
+        let duplicate = self
+            .lines
+            .iter()
+            .any(|line| decode_share(line).is_ok_and(|other| Zeroizing::new(other) == share));
+        if duplicate {
+            return "That share was already entered".to_string();
+        }
+
+        self.lines.push(line.to_string());
+        match status(self.lines.to_vec(), &false, None) {
+            Ok(sets) if sets.len() > 1 => {
+                self.lines.pop();
+                return "That share is from another share set".to_string();
+            }
+            Ok(sets) if sets.iter().any(|set| set.recoverable) => {}
+            Ok(_) => return "Share accepted".to_string(),
+            Err(error) => {
+                self.lines.pop();
+                return error.to_string();
+            }
+        }
+
+        match recover_secret(self.lines.to_vec(), &false) {
+            Ok(secret) => {
+                self.recovered = Some(
+                    match Mnemonic::from_entropy(
+                        &secret,
+                        self.language.unwrap_or(Language::English),
+                    ) {
+                        Ok(mnemonic) => (
+                            Field::Mnemonic,
+                            Zeroizing::new(mnemonic.phrase().to_string()),
+                        ),
+                        Err(_) => (
+                            Field::Entropy,
+                            Zeroizing::new(format!("0x{}", hex::encode(secret.as_slice()))),
+                        ),
+                    },
+                );
+                "Recovered".to_string()
+            }
+            Err(error) => error.to_string(),
+        }
+    }
+
+    // The lines of the current screen; the last one lists the keys
+    pub fn screen(&self) -> Vec<String> {
+        let mut lines = vec![];
+        let keys = match &self.step {
+            Step::Checklist => {
+                lines.push("Ceremony checklist".to_string());
+                lines.push(String::new());
+                lines.extend(self.checklist.iter().flat_map(Checklist::lines));
+                "enter confirm the step   esc quit"
+            }
+            Step::Start { choice } => {
+                lines.push("SSKR ceremony".to_string());
+                lines.push(String::new());
+                lines.extend(options(
+                    &["Split a mnemonic", "Recover a mnemonic"],
+                    *choice,
+                ));
+                "up/down choose   enter select   esc quit"
+            }
+            Step::Words { choice } => {
+                lines.push("How many words should the new mnemonic have?".to_string());
+                lines.push(String::new());
+                let counts = WORD_COUNTS.map(|count| format!("{} words", count));
+                lines.extend(options(&counts.each_ref().map(String::as_str), *choice));
+                "up/down choose   enter select   esc quit"
+            }
+            Step::Source { choice } => {
+                lines.push("Where does the mnemonic come from?".to_string());
+                lines.push(String::new());
+                let generate = format!("Generate a new {} word mnemonic", self.words);
+                lines.extend(options(
+                    &[&generate, "Type in an existing mnemonic"],
+                    *choice,
+                ));
+                "up/down choose   enter select   esc quit"
+            }
+            Step::EnterMnemonic { error } => {
+                lines.push("Type the mnemonic, it is not shown:".to_string());
+                lines.push(String::new());
+                lines.push(format!("> {}", "*".repeat(self.input.chars().count())));
+                lines.extend(error.iter().map(|error| format!("\n{}", error)));
+                "enter done   esc quit"
+            }
+            Step::ShowMnemonic => {
+                lines.push("The new mnemonic, to set up the wallet with:".to_string());
+                lines.push(String::new());
+                if let Some(mnemonic) = &self.mnemonic {
+                    lines.extend(self.revealed(Field::Mnemonic, mnemonic.phrase()));
+                }
+                "enter continue to the groups   esc quit"
+            }
+            Step::Spec { selected, error } => {
+                lines.push("Groups of custodians".to_string());
+                lines.push(String::new());
+                for (index, group) in self.groups.iter().enumerate() {
+                    lines.push(format!(
+                        "{} Group {}: any {} of {} shares",
+                        if index == *selected { ">" } else { " " },
+                        index + 1,
+                        group.threshold,
+                        group.count
+                    ));
+                }
+                lines.push(String::new());
+                lines.push(format!(
+                    "Recovery needs {} of the {} group(s)",
+                    self.group_threshold,
+                    self.groups.len()
+                ));
+                if let Some(error) = error {
+                    lines.push(String::new());
+                    lines.push(error.clone());
+                }
+                "up/down group   left/right needed   +/- shares   a add   d delete   </> groups needed   enter split"
+            }
+            Step::Handoff { next } => {
+                lines.push(format!(
+                    "Next: {} of {}",
+                    self.shares[*next].0,
+                    self.shares.len()
+                ));
+                lines.push(String::new());
+                lines.push(
+                    "Only the custodian of this share should see the screen now.".to_string(),
+                );
+                "enter show the share   esc quit"
+            }
+            Step::ShowShare { current } => {
+                let (title, share) = &self.shares[*current];
+                lines.push(format!("[{}/{}] {}", current + 1, self.shares.len(), title));
+                lines.push(String::new());
+                lines.extend(self.revealed(Field::Shares, share));
+                "enter hide the share   esc quit"
+            }
+            Step::SplitDone => {
+                lines.push(format!(
+                    "All {} shares have been handed out.",
+                    self.shares.len()
+                ));
+                "enter finish"
+            }
+            Step::EnterShare { message } => {
+                lines.push("Type a share, it is not shown:".to_string());
+                lines.push(String::new());
+                lines.push(format!("> {}", "*".repeat(self.input.chars().count())));
+                if let Some(message) = message {
+                    lines.push(String::new());
+                    lines.push(message.clone());
+                }
+                lines.push(String::new());
+                lines.extend(self.progress());
+                "enter add the share   esc quit"
+            }
+            Step::Recovered => {
+                lines.push("Recovered mnemonic:".to_string());
+                lines.push(String::new());
+                if let Some((field, recovered)) = &self.recovered {
+                    lines.extend(self.revealed(*field, recovered));
+                }
+                "enter clear the screen and finish"
+            }
+            Step::Done => "",
+        };
+        lines.push(keys.to_string());
+        lines
+    }
+
+    fn progress(&self) -> Vec<String> {
+        let Ok(sets) = status(self.lines.to_vec(), &false, None) else {
+            return vec![];
+        };
+        let mut lines = vec![format!("{} share(s) entered", self.lines.len())];
+        for set in sets {
+            for group in set.groups {
+                lines.push(format!(
+                    "  Group {}: {} of {} shares",
+                    group.group,
+                    group.present,
+                    group
+                        .member_threshold
+                        .map_or("?".to_string(), |threshold| threshold.to_string())
+                ));
+            }
+            if let Some(missing) = set.groups_missing {
+                lines.push(format!("  {} more group(s) needed", missing));
+            }
+            lines.extend(
+                set.suggestions
+                    .iter()
+                    .map(|suggestion| format!("  - {}", suggestion)),
+            );
+        }
+        lines
+    }
+
+    // The words in numbered rows, or [redacted] where the policy keeps the
+    // field off the screen
+    fn revealed(&self, field: Field, text: &str) -> Vec<String> {
+        if self.policy.allows(field, Channel::Display) {
+            word_rows(text)
+        } else {
+            vec![policy::REDACTED.to_string()]
+        }
+    }
+}
+
+// Moves through a menu of `count` options, and gives the choice on Enter
+fn menu(key: KeyCode, choice: &mut usize, count: usize) -> Option<usize> {
+    match key {
+        KeyCode::Up => *choice = choice.saturating_sub(1),
+        KeyCode::Down => *choice = (*choice + 1).min(count - 1),
+        KeyCode::Enter => return Some(*choice),
+        _ => {}
+    }
+    None
+}
+
+// Edits hidden input, and gives what was typed on Enter
+fn text_input(key: KeyCode, input: &mut Zeroizing<String>) -> Option<Zeroizing<String>> {
+    match key {
+        KeyCode::Char(c) => input.push(c),
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Enter => {
+            return Some(std::mem::replace(input, Zeroizing::new(String::new())));
+        }
+        _ => {}
+    }
+    None
+}
+
+fn options(options: &[&str], choice: usize) -> Vec<String> {
+    options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| format!("{} {}", if index == choice { ">" } else { " " }, option))
+        .collect()
+}
+
+// Numbered words, a few to a row, so they can be copied without losing place
+fn word_rows(text: &str) -> Vec<String> {
+    let words = text.split(' ').collect::<Vec<_>>();
+    words
+        .chunks(WORDS_PER_ROW)
+        .enumerate()
+        .map(|(row, words)| {
+            words
+                .iter()
+                .enumerate()
+                .map(|(column, word)| {
+                    format!("{:>2}. {:<9}", row * WORDS_PER_ROW + column + 1, word)
+                })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+// Runs the wizard full-screen on the alternate screen, which is cleared when
+// it ends, so nothing shown is left in the scrollback
+pub fn run(wizard: &mut Wizard, duress: &DuressKeys) -> Result<Ending, Error> {
+    let _screen = Screen::enter()?;
+    let mut watch = duress.watch();
+    while !wizard.is_done() {
+        draw(&wizard.screen())?;
+        // Redrawn every second while nothing is pressed, to keep the time of
+        // the current checklist step going
+        let timing = wizard
+            .checklist()
+            .is_some_and(|checklist| !checklist.is_complete());
+        if timing && !event::poll(Duration::from_secs(1))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match watch.press(&key) {
+            Press::Wipe => {
+                wizard.wipe();
+                return Ok(Ending::Wiped);
+            }
+            Press::Swallowed => {}
+            Press::Pass => wizard.key(key.code),
+        }
+    }
+    Ok(Ending::Done)
+}
+
+fn draw(lines: &[String]) -> Result<(), Error> {
+    let (_, height) = terminal::size()?;
+    let mut stdout = io::stdout();
+    queue!(stdout, Clear(ClearType::All))?;
+    let Some((keys, lines)) = lines.split_last() else {
+        return Ok(());
+    };
+    // Raw mode doesn't turn newlines into carriage returns, so every line is
+    // placed explicitly
+    for (row, line) in lines
+        .iter()
+        .flat_map(|line| line.split('\n'))
+        .enumerate()
+        .take(height.saturating_sub(1) as usize)
+    {
+        queue!(stdout, MoveTo(0, row as u16))?;
+        write!(stdout, "{}", line)?;
+    }
+    queue!(stdout, MoveTo(0, height.saturating_sub(1)))?;
+    write!(stdout, "{}", keys)?;
+    stdout.flush()?;
+    Ok(())
+}
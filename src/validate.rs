@@ -0,0 +1,179 @@
+use crate::diagnostics::{suggestions, unreadable, Problem};
+use crate::drill::ShareChecks;
+use crate::output::ShareMetadataOutput;
+use crate::sanitize::{remap, sanitize, LAYOUTS};
+use crate::share_format::{checksum_matches, decode, ShareFormat};
+use crate::split::BIP39_ENTROPY_LENGTHS;
+use anyhow::{bail, Error};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+// How long every answer of a blind check takes, valid or not
+pub const BLIND_RESPONSE_TIME: Duration = Duration::from_secs(2);
+
+// Everything that can be said about a single share without combining it with
+// others, for front ends that check shares one at a time as they are entered
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ShareDiagnostics {
+    pub format: ShareFormat,
+    // None when the input can't even be read as bytewords
+    pub checksum_ok: Option<bool>,
+    pub metadata: Option<ShareMetadataOutput>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+    // What to check when the share isn't usable
+    pub suggestions: Vec<String>,
+}
+
+impl ShareDiagnostics {
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+pub fn validate_share(input: &str) -> ShareDiagnostics {
+    let cleaned = sanitize(input).to_lowercase();
+    let mut warnings = vec![];
+
+    if sanitize(input) != input.trim() {
+        warnings.push("Removed or replaced characters that can't be part of a share".to_string());
+    }
+
+    let readings = [(None, cleaned.clone())].into_iter().chain(
+        LAYOUTS
+            .iter()
+            .map(|(name, layout)| (Some(*name), remap(input, layout))),
+    );
+    let mut first_error = None;
+
+    for (layout, candidate) in readings {
+        let (format, share) = match decode(&candidate) {
+            Ok(decoded) => decoded,
+            Err(error) => {
+                first_error.get_or_insert(error);
+                continue;
+            }
+        };
+
+        if let Some(layout) = layout {
+            warnings.push(format!(
+                "Only readable as typed on a {} keyboard layout, check the scanner's layout setting",
+                layout
+            ));
+        }
+
+        return match ShareMetadataOutput::new(&share) {
+            Ok(metadata) => {
+                warnings.extend(metadata_warnings(&metadata));
+                ShareDiagnostics {
+                    format,
+                    checksum_ok: Some(true),
+                    metadata: Some(metadata),
+                    warnings,
+                    error: None,
+                    suggestions: vec![],
+                }
+            }
+            Err(error) => ShareDiagnostics {
+                format,
+                checksum_ok: Some(true),
+                metadata: None,
+                warnings,
+                error: Some(error.to_string()),
+                suggestions: suggestions(&Problem::Unreadable { share: 0 }, &the_share()),
+            },
+        };
+    }
+
+    let format = ShareFormat::detect(&cleaned);
+    ShareDiagnostics {
+        format,
+        checksum_ok: checksum_matches(&cleaned, format).ok(),
+        metadata: None,
+        warnings,
+        error: first_error.map(|error| error.to_string()),
+        suggestions: suggestions(&unreadable(0, input), &the_share()),
+    }
+}
+
+fn the_share() -> [String; 1] {
+    ["the share".to_string()]
+}
+
+fn metadata_warnings(metadata: &ShareMetadataOutput) -> Vec<String> {
+    let mut warnings = vec![];
+
+    if !BIP39_ENTROPY_LENGTHS.contains(&metadata.secret_length) {
+        warnings.push(format!(
+            "Secret is {} bytes, which is not the entropy length of a BIP-39 mnemonic",
+            metadata.secret_length
+        ));
+    }
+    if metadata.group_threshold == 1 && metadata.member_threshold == 1 {
+        warnings.push("This share alone is enough to recover the secret".to_string());
+    }
+
+    warnings
+}
+
+// Checks shares answering only whether each is valid, for a custodian checking
+// their copy somewhere that isn't fully trusted. Nothing about the share is
+// told, every answer takes the same time so the time doesn't tell where a
+// share went wrong either, and only so many shares can be tried.
+pub struct BlindCheck<'a> {
+    checks: &'a [&'a ShareChecks],
+    response_time: Duration,
+    attempts_left: usize,
+}
+
+impl<'a> BlindCheck<'a> {
+    // With checks a share is only valid when it is one of their shares
+    pub fn new(checks: &'a [&'a ShareChecks], attempts: usize) -> BlindCheck<'a> {
+        BlindCheck {
+            checks,
+            response_time: BLIND_RESPONSE_TIME,
+            attempts_left: attempts,
+        }
+    }
+
+    pub fn with_response_time(self, response_time: Duration) -> BlindCheck<'a> {
+        BlindCheck {
+            response_time,
+            ..self
+        }
+    }
+
+    pub fn attempts_left(&self) -> usize {
+        self.attempts_left
+    }
+
+    pub fn check(&mut self, input: &str) -> Result<bool, Error> {
+        if self.attempts_left == 0 {
+            bail!("No attempts left");
+        }
+        self.attempts_left -= 1;
+
+        let start = Instant::now();
+        let valid = self.is_valid(input);
+        if let Some(rest) = self.response_time.checked_sub(start.elapsed()) {
+            std::thread::sleep(rest);
+        }
+        Ok(valid)
+    }
+
+    fn is_valid(&self, input: &str) -> bool {
+        let Ok((_, share)) = decode(&sanitize(input).to_lowercase()) else {
+            return false;
+        };
+        if ShareMetadataOutput::new(&share).is_err() {
+            return false;
+        }
+        // Every check is tried, not just up to the one that matches
+        self.checks.is_empty()
+            || self
+                .checks
+                .iter()
+                .map(|checks| checks.matches(&share).unwrap_or(false))
+                .fold(false, |matched, matches| matched | matches)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::recover::decode_share;
+use crate::sskr_shares::share_metadata;
+use anyhow::{bail, Error};
+use serde::Serialize;
+use sskr::sskr_combine;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupOutcome {
+    // Recovers the expected secret together with other verified groups
+    Verified,
+    // Has enough shares, but no combination with it recovers the expected secret
+    Mismatch,
+    NotEnoughShares,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GroupVerification {
+    pub group: usize,
+    pub member_threshold: usize,
+    pub present: usize,
+    pub outcome: GroupOutcome,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Verification {
+    pub identifier: String,
+    pub group_threshold: usize,
+    pub groups: Vec<GroupVerification>,
+    pub matches: bool,
+}
+
+// Checks whether the shares recover exactly the expected secret, group by
+// group, without handing the recovered secret back. Every combination of
+// group-threshold many satisfied groups is tried, so a single mistranscribed
+// group is pinned down instead of failing the whole check.
+pub fn verify(lines: Vec<String>, minimal: &bool, expected: &[u8]) -> Result<Verification, Error> {
+    let mut identifier = None;
+    let mut group_threshold = 0;
+    // group index => (member threshold, shares)
+    let mut groups: BTreeMap<usize, (usize, Vec<Vec<u8>>)> = BTreeMap::new();
+
+    for line in &lines {
+        let share = decode_share(line)?;
+        let (id, meta) = share_metadata(&share, minimal)?;
+        if *identifier.get_or_insert(id) != id {
+            bail!("Mismatched identifiers, shares don't go together");
+        }
+        group_threshold = meta[1];
+
+        let group = groups.entry(meta[0]).or_insert((meta[4], vec![]));
+        if !group.1.contains(&share) {
+            group.1.push(share);
+        }
+    }
+    let Some(identifier) = identifier else {
+        bail!("No shares to verify");
+    };
+
+    let satisfied = groups
+        .iter()
+        .filter(|(_, (threshold, shares))| shares.len() >= *threshold)
+        .map(|(group, _)| *group)
+        .collect::<Vec<_>>();
+    let mut verified = vec![];
+
+    for combination in combinations(&satisfied, group_threshold) {
+        let shares = combination
+            .iter()
+            .flat_map(|group| groups[group].1.clone())
+            .collect::<Vec<_>>();
+        if sskr_combine(&shares).is_ok_and(|secret| secret.data() == expected) {
+            verified.extend(combination);
+        }
+    }
+
+    Ok(Verification {
+        identifier: format!("{:04x}", identifier),
+        group_threshold,
+        groups: groups
+            .iter()
+            .map(|(group, (member_threshold, shares))| GroupVerification {
+                group: group + 1,
+                member_threshold: *member_threshold,
+                present: shares.len(),
+                outcome: if verified.contains(group) {
+                    GroupOutcome::Verified
+                } else if satisfied.contains(group) {
+                    GroupOutcome::Mismatch
+                } else {
+                    GroupOutcome::NotEnoughShares
+                },
+            })
+            .collect(),
+        matches: !verified.is_empty(),
+    })
+}
+
+fn combinations(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    let mut result = vec![];
+    for (i, item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], size - 1) {
+            rest.insert(0, *item);
+            result.push(rest);
+        }
+    }
+    result
+}
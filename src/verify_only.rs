@@ -0,0 +1,239 @@
+use crate::input::{read_hidden, read_located_share_lines, ShareLine};
+use crate::inspect::Inspection;
+use crate::manifest::Manifest;
+use crate::output::{print_json, OutputFormat, ShareMetadataOutput};
+use crate::sanitize::sanitize;
+use crate::share_format::{self, EncodedShare};
+use crate::validate::{validate_share, ShareDiagnostics};
+use anyhow::{Context, Error};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+// The sskr-tool-verify binary, built with `--features verify-only`, to hand
+// to custodians along with their share. It checks and inspects shares one at
+// a time and nothing else: with no command that splits or combines shares, it
+// can't reveal a seed however it is used.
+
+/// Checks SSKR shares one at a time, for the custodians holding them. It
+/// can't split or recover, so it never reveals a seed.
+#[derive(Parser, Debug)]
+#[command(name = "sskr-tool-verify", version)]
+struct Cli {
+    /// How to format the output; "json" prints a single structured document
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Checks a share copied by hand, typed in without echo, against its
+    /// checksum and, given the split's manifest, against the shares the split
+    /// made.
+    CheckTranscription(CheckTranscriptionArgs),
+
+    /// Prints what can be said about a share in public: its split, group and
+    /// member, never the share itself.
+    Inspect(InspectArgs),
+
+    /// Checks the shares in files, one per line, reporting which can be read
+    /// and why the others can't.
+    Validate(ValidateArgs),
+}
+
+#[derive(Args, Debug)]
+struct CheckTranscriptionArgs {
+    /// The manifest of the split, so only its own shares pass
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct InspectArgs {
+    /// The share to inspect, in any supported format (detected automatically)
+    share: String,
+
+    /// A manifest of the split, to show the seed name and group name it records
+    #[clap(long, value_name = "FILE")]
+    manifest: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// Files containing SSKR shares, one per line. `-` reads from stdin and a
+    /// directory reads every .txt file in it
+    #[clap(value_name = "FILE", required = true)]
+    files: Vec<String>,
+}
+
+// What a custodian learns from checking their copy. The error of an
+// unreadable share is left out, since it can quote the share typed in.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionCheck {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ShareMetadataOutput>,
+    // Whether the share is one the manifest's split made, when that can be told
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches_split: Option<bool>,
+    pub warnings: Vec<String>,
+    pub suggestions: Vec<String>,
+}
+
+pub fn check_transcription(
+    input: &str,
+    manifest: Option<&Manifest>,
+) -> Result<TranscriptionCheck, Error> {
+    let diagnostics = validate_share(input);
+    let mut check = TranscriptionCheck {
+        valid: diagnostics.is_valid(),
+        metadata: diagnostics.metadata.clone(),
+        matches_split: None,
+        warnings: diagnostics.warnings,
+        suggestions: diagnostics.suggestions,
+    };
+    let (Some(manifest), Some(metadata)) = (manifest, &diagnostics.metadata) else {
+        return Ok(check);
+    };
+
+    let seed = manifest
+        .seeds
+        .iter()
+        .find(|seed| seed.identifier == metadata.identifier);
+    match seed.map(|seed| &seed.share_checks) {
+        // Shares only readable under another keyboard layout aren't checked
+        Some(Some(checks)) => {
+            if let Ok((_, share)) = share_format::decode(&sanitize(input).to_lowercase()) {
+                check.matches_split = Some(checks.matches(&Zeroizing::new(share))?);
+            }
+        }
+        Some(None) => check
+            .warnings
+            .push("The manifest has no share checks, any readable share passes".to_string()),
+        None => {
+            check.matches_split = Some(false);
+            check.suggestions.push(format!(
+                "Check the manifest is the split's, it doesn't describe shares with identifier {}",
+                metadata.identifier
+            ));
+        }
+    }
+    if check.matches_split == Some(false) {
+        check.valid = false;
+    }
+    Ok(check)
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ShareValidation {
+    pub location: String,
+    #[serde(flatten)]
+    pub diagnostics: ShareDiagnostics,
+}
+
+pub fn validate_lines(lines: &[ShareLine]) -> Vec<ShareValidation> {
+    lines
+        .iter()
+        .map(|line| ShareValidation {
+            location: line.to_string(),
+            diagnostics: validate_share(&line.text),
+        })
+        .collect()
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+// Runs the command line, returning whether every share checked is valid
+pub fn run(args: impl IntoIterator<Item = impl Into<OsString> + Clone>) -> Result<bool, Error> {
+    let cli = Cli::parse_from(args);
+    match &cli.command {
+        Commands::CheckTranscription(args) => {
+            let manifest = args
+                .manifest
+                .as_ref()
+                .map(|path| Manifest::load(Path::new(path)))
+                .transpose()
+                .context("Error reading manifest")?;
+            let share = read_hidden("Share as written down (not echoed): ")?;
+            let check = check_transcription(&share, manifest.as_ref())?;
+            match cli.output {
+                OutputFormat::Text => print_transcription_check(&check),
+                OutputFormat::Json => print_json(&check)?,
+            }
+            Ok(check.valid)
+        }
+        Commands::Inspect(args) => {
+            let share = EncodedShare::parse(&args.share).context("Invalid share")?;
+            let mut inspection = Inspection::new(&share).public();
+            if let Some(path) = &args.manifest {
+                let manifest = Manifest::load(Path::new(path)).context("Error reading manifest")?;
+                if let Some(seed) = manifest
+                    .seeds
+                    .iter()
+                    .find(|seed| seed.identifier == inspection.metadata.identifier)
+                {
+                    inspection = inspection.with_seed(seed);
+                }
+            }
+            match cli.output {
+                OutputFormat::Text => print!("{}", inspection),
+                OutputFormat::Json => print_json(&inspection)?,
+            }
+            Ok(true)
+        }
+        Commands::Validate(args) => {
+            let validations = validate_lines(&read_located_share_lines(&args.files)?);
+            match cli.output {
+                OutputFormat::Text => validations.iter().for_each(print_validation),
+                OutputFormat::Json => print_json(&validations)?,
+            }
+            Ok(validations
+                .iter()
+                .all(|validation| validation.diagnostics.is_valid()))
+        }
+    }
+}
+
+fn print_transcription_check(check: &TranscriptionCheck) {
+    match (&check.metadata, check.valid) {
+        (Some(metadata), true) => println!(
+            "The transcription is correct: group {}, share {} of split {}",
+            metadata.group_index + 1,
+            metadata.member_index + 1,
+            metadata.identifier
+        ),
+        (Some(_), false) => println!("The transcription reads, but isn't a share the split made"),
+        (None, _) => println!("The transcription can't be read as a share"),
+    }
+    for line in check.warnings.iter().chain(&check.suggestions) {
+        println!("  - {}", line);
+    }
+}
+
+fn print_validation(validation: &ShareValidation) {
+    let diagnostics = &validation.diagnostics;
+    match (&diagnostics.metadata, &diagnostics.error) {
+        (Some(metadata), None) => println!(
+            "{}: valid, group {}, share {} of split {}",
+            validation.location,
+            metadata.group_index + 1,
+            metadata.member_index + 1,
+            metadata.identifier
+        ),
+        (_, error) => println!(
+            "{}: invalid, {}",
+            validation.location,
+            error.as_deref().unwrap_or("unreadable")
+        ),
+    }
+    for line in diagnostics.warnings.iter().chain(&diagnostics.suggestions) {
+        println!("  - {}", line);
+    }
+}
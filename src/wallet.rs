@@ -0,0 +1,127 @@
+use crate::policy::{self, Field};
+use anyhow::{Context, Error};
+use bip39::{Mnemonic, Seed};
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv, Xpub};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{Address, Network};
+use serde::Serialize;
+use std::str::FromStr;
+
+pub const PREVIEW_ADDRESSES: u32 = 5;
+
+// Enough of the wallet a mnemonic stands for to recognize it: the master key
+// fingerprint hardware wallets show, and with a derivation path, the extended
+// public key there and its first receive addresses
+#[derive(Serialize, Debug, PartialEq)]
+pub struct WalletPreview {
+    pub fingerprint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xpub: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AddressType {
+    Legacy,
+    NestedSegwit,
+    Segwit,
+    Taproot,
+}
+
+// The address type and network follow from the purpose and coin type of a
+// BIP-44 style path, as wallets derive them
+fn address_type(path: &DerivationPath) -> Option<(AddressType, Network)> {
+    let hardened = |child: &ChildNumber| match child {
+        ChildNumber::Hardened { index } => Some(*index),
+        ChildNumber::Normal { .. } => None,
+    };
+    let purpose = path.into_iter().next().and_then(hardened)?;
+    let network = match path.into_iter().nth(1).and_then(hardened)? {
+        0 => Network::Bitcoin,
+        1 => Network::Testnet,
+        _ => return None,
+    };
+    let address_type = match purpose {
+        44 => AddressType::Legacy,
+        49 => AddressType::NestedSegwit,
+        84 => AddressType::Segwit,
+        86 => AddressType::Taproot,
+        _ => return None,
+    };
+    Some((address_type, network))
+}
+
+impl WalletPreview {
+    // The fingerprint, xpub and addresses as the policy allows them shown
+    pub fn redacted(self) -> WalletPreview {
+        WalletPreview {
+            fingerprint: policy::shown(Field::Fingerprints, self.fingerprint),
+            path: self.path,
+            xpub: self
+                .xpub
+                .map(|xpub| policy::shown(Field::Fingerprints, xpub)),
+            addresses: self
+                .addresses
+                .into_iter()
+                .map(|address| policy::shown(Field::Fingerprints, address))
+                .collect(),
+        }
+    }
+}
+
+pub fn preview(
+    mnemonic: &Mnemonic,
+    passphrase: &str,
+    derive: Option<&str>,
+) -> Result<WalletPreview, Error> {
+    let secp = Secp256k1::new();
+    let seed = Seed::new(mnemonic, passphrase);
+    let path = match derive {
+        Some(path) => Some(
+            DerivationPath::from_str(path.trim())
+                .with_context(|| format!("Invalid derivation path \"{}\"", path))?,
+        ),
+        None => None,
+    };
+    let (address_type, network) = match path.as_ref().and_then(address_type) {
+        Some((address_type, network)) => (Some(address_type), network),
+        None => (None, Network::Bitcoin),
+    };
+
+    let master = Xpriv::new_master(network, seed.as_bytes())?;
+    let mut preview = WalletPreview {
+        fingerprint: master.fingerprint(&secp).to_string(),
+        path: None,
+        xpub: None,
+        addresses: vec![],
+    };
+    let Some(path) = path else {
+        return Ok(preview);
+    };
+
+    let account = Xpub::from_priv(&secp, &master.derive_priv(&secp, &path)?);
+    preview.path = Some(format!("m/{}", path));
+    preview.xpub = Some(account.to_string());
+
+    if let Some(address_type) = address_type {
+        for index in 0..PREVIEW_ADDRESSES {
+            let receive = [
+                ChildNumber::from_normal_idx(0)?,
+                ChildNumber::from_normal_idx(index)?,
+            ];
+            let key = account.derive_pub(&secp, &receive)?.to_pub();
+            let address = match address_type {
+                AddressType::Legacy => Address::p2pkh(key, network),
+                AddressType::NestedSegwit => Address::p2shwpkh(&key, network),
+                AddressType::Segwit => Address::p2wpkh(&key, network),
+                AddressType::Taproot => Address::p2tr(&secp, key.into(), None, network),
+            };
+            preview.addresses.push(address.to_string());
+        }
+    }
+
+    Ok(preview)
+}
@@ -0,0 +1,66 @@
+use crate::input::share_lines;
+use crate::output::{SecretOutput, SplitOutput};
+use crate::recover::recover_secret;
+use crate::split::{default_group_threshold, random_mnemonic, split_shares};
+use anyhow::Error;
+use bip39::{Language, Mnemonic, MnemonicType};
+use wasm_bindgen::prelude::*;
+
+// Splitting and recovering for a page that runs offline in a browser, on the
+// same code as the command line. Each gives the JSON document the matching
+// subcommand prints with `--output json`, so the page and the tool can be
+// checked against each other.
+
+// Splits the mnemonic, or a new 12 word one when none is given. The group
+// threshold can be left out when the spec has a single group.
+pub fn split_document(
+    spec: &str,
+    group_threshold: Option<usize>,
+    mnemonic: Option<&str>,
+) -> Result<String, Error> {
+    let group_threshold = match group_threshold {
+        Some(group_threshold) => group_threshold,
+        None => default_group_threshold(spec)?,
+    };
+    let generated;
+    let phrase = match mnemonic {
+        Some(phrase) => phrase,
+        None => {
+            generated = random_mnemonic(MnemonicType::Words12, Language::English);
+            generated.phrase()
+        }
+    };
+    let (mnemonic, groups) = split_shares(spec, group_threshold, phrase, None)?;
+    let document = SplitOutput::new(spec, group_threshold, &mnemonic, &groups)?;
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+// Recovers from shares given one per line in any format, laid out as in a
+// share file
+pub fn recover_document(shares: &str) -> Result<String, Error> {
+    let secret = recover_secret(share_lines(shares), &false)?;
+    let document = match Mnemonic::from_entropy(&secret, Language::English) {
+        Ok(mnemonic) => SecretOutput::new(&mnemonic),
+        Err(_) => SecretOutput::raw(&secret),
+    };
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+#[wasm_bindgen]
+pub fn split(
+    spec: &str,
+    group_threshold: Option<usize>,
+    mnemonic: Option<String>,
+) -> Result<String, JsError> {
+    split_document(spec, group_threshold, mnemonic.as_deref()).map_err(js_error)
+}
+
+#[wasm_bindgen]
+pub fn recover(shares: &str) -> Result<String, JsError> {
+    recover_document(shares).map_err(js_error)
+}
+
+// The error with its causes, as the command line shows them
+fn js_error(error: Error) -> JsError {
+    JsError::new(&format!("{:#}", error))
+}
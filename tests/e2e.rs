@@ -0,0 +1,425 @@
+// Whole ceremonies, driving the binary through a pseudo-terminal the way a
+// person at the keyboard would, prompts, hidden input and typos included.
+// These are slow and need a PTY, so they only run with `--features e2e`.
+#![cfg(feature = "e2e")]
+
+use portable_pty::{native_pty_system, Child, CommandBuilder, PtySize};
+use sskr_tool::share_format::{self, ShareFormat};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+const MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+// Blind checks alone take two seconds an answer
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+struct Session {
+    child: Box<dyn Child + Send + Sync>,
+    writer: Box<dyn Write + Send>,
+    output: Receiver<Vec<u8>>,
+    // What the program has written that no expectation has consumed yet
+    unread: String,
+}
+
+impl Session {
+    fn spawn(args: &[&str], dir: &Path) -> Session {
+        let pty = native_pty_system()
+            .openpty(PtySize {
+                rows: 50,
+                cols: 200,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .unwrap();
+        let mut command = CommandBuilder::new(env!("CARGO_BIN_EXE_sskr-tool"));
+        command.args(args);
+        command.cwd(dir);
+        command.env("RUST_BACKTRACE", "0");
+        let child = pty.slave.spawn_command(command).unwrap();
+        drop(pty.slave);
+
+        let mut reader = pty.master.try_clone_reader().unwrap();
+        let (sender, output) = channel();
+        std::thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(read @ 1..) = reader.read(&mut buffer) {
+                if sender.send(buffer[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Session {
+            child,
+            writer: pty.master.take_writer().unwrap(),
+            output,
+            unread: String::new(),
+        }
+    }
+
+    // Waits for the text, and returns everything written up to and including it
+    fn expect(&mut self, text: &str) -> String {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            if let Some(found) = self.unread.find(text) {
+                let seen = self.unread[..found + text.len()].to_string();
+                self.unread.drain(..found + text.len());
+                return seen;
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.output.recv_timeout(left) {
+                Ok(bytes) => self
+                    .unread
+                    .push_str(&String::from_utf8_lossy(&bytes).replace('\r', "")),
+                Err(_) => panic!("Expected \"{}\", got:\n{}", text, self.unread),
+            }
+        }
+    }
+
+    fn send_line(&mut self, line: &str) {
+        write!(self.writer, "{}\r", line).unwrap();
+        self.writer.flush().unwrap();
+    }
+
+    fn send_keys(&mut self, keys: &str) {
+        self.writer.write_all(keys.as_bytes()).unwrap();
+        self.writer.flush().unwrap();
+    }
+
+    // Hidden prompts are written before echo is turned off, so what is typed
+    // right after them would still be echoed
+    fn send_hidden(&mut self, line: &str) {
+        std::thread::sleep(Duration::from_millis(200));
+        self.send_line(line);
+    }
+
+    // Ends input the way Ctrl-D does at a terminal
+    fn send_eof(&mut self) {
+        self.writer.write_all(b"\x04").unwrap();
+        self.writer.flush().unwrap();
+    }
+
+    fn exit_code(mut self) -> u32 {
+        let status = self.child.wait().unwrap();
+        status.exit_code()
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sskr-tool-e2e-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn share_file(dir: &Path, group: usize, member: usize, count: usize) -> String {
+    let path = dir.join(format!(
+        "shares/share-g{}-m{}-of-{}.txt",
+        group, member, count
+    ));
+    std::fs::read_to_string(path).unwrap().trim().to_string()
+}
+
+// A byteword swapped for another, as a slip of the pen would
+fn mistyped(share: &str, position: usize) -> String {
+    let mut words = share.split(' ').collect::<Vec<_>>();
+    words[position] = if words[position] == "able" {
+        "acid"
+    } else {
+        "able"
+    };
+    words.join(" ")
+}
+
+// A byteword misspelled into something that isn't one
+fn misspelled(share: &str, position: usize) -> String {
+    let mut words = share.split(' ').map(str::to_string).collect::<Vec<_>>();
+    words[position] = format!("{}q", &words[position][..3]);
+    words.join(" ")
+}
+
+#[test]
+fn ceremony_split_transcribe_drill_recover() {
+    let dir = temp_dir("ceremony");
+
+    // Split with the mnemonic typed in hidden, then read every share back
+    // from its copy, fumbling one on the first try
+    let mut split = Session::spawn(
+        &[
+            "split",
+            "2of3,2of2",
+            "1",
+            "--no-echo",
+            "--out-dir",
+            "shares",
+            "--confirm",
+        ],
+        &dir,
+    );
+    split.expect("Mnemonic (not echoed): ");
+    split.send_hidden(MNEMONIC);
+    let shown = split.expect("Share 1 of group 1: ");
+    // The mnemonic typed in isn't printed back
+    assert!(shown.contains("Mnemonic: [redacted]"));
+    assert!(!shown.contains(MNEMONIC));
+    let shares = [(1, 1, 3), (1, 2, 3), (1, 3, 3), (2, 1, 2), (2, 2, 2)]
+        .map(|(group, member, count)| share_file(&dir, group, member, count));
+
+    split.send_line(&mistyped(&shares[0], 10));
+    split.expect("That doesn't match the share at word 11, try again");
+    split.expect("Share 1 of group 1: ");
+    split.send_line(&shares[0]);
+    for (index, share) in shares.iter().enumerate().skip(1) {
+        let (group, member) = if index < 3 {
+            (1, index + 1)
+        } else {
+            (2, index - 2)
+        };
+        split.expect(&format!("Share {} of group {}: ", member, group));
+        // Copies on metal are often kept as minimal bytewords
+        match index {
+            3 => {
+                let bytes = share_format::decode(share).unwrap().1;
+                split.send_line(&share_format::encode(&bytes, ShareFormat::Minimal));
+            }
+            _ => split.send_line(share),
+        }
+    }
+    split.expect("All 5 shares match their copies");
+    assert_eq!(split.exit_code(), 0);
+
+    // A custodian drills their share against the manifest, typing one word
+    // that isn't a byteword on the way: the nine words naming the share, then
+    // the four the manifest checks
+    let words = shares[1].split(' ').collect::<Vec<_>>();
+    let mut drill = Session::spawn(&["drill", "--manifest", "shares/manifest.json"], &dir);
+    drill.expect("How many words does your share have? ");
+    drill.send_line(&words.len().to_string());
+    for asked in 0..9 + 4 {
+        drill.expect("Word ");
+        let position = drill
+            .expect(": ")
+            .trim_end_matches(": ")
+            .parse::<usize>()
+            .unwrap();
+        if asked == 0 {
+            drill.send_line("tunx");
+            drill.expect("is not a byteword");
+            drill.expect(&format!("Word {}: ", position));
+        }
+        drill.send_line(words[position - 1]);
+    }
+    drill.expect("It matches a share recorded in the manifest.");
+    assert_eq!(drill.exit_code(), 0);
+
+    // Recovery from shares typed in at the terminal, one of them misspelled
+    let mut recover = Session::spawn(&["recover", "-", "--auto-correct"], &dir);
+    recover.send_line(&misspelled(&shares[2], 12));
+    recover.send_line(&shares[4]);
+    recover.send_line(&shares[3]);
+    recover.send_eof();
+    recover.expect("corrected");
+    recover.expect(MNEMONIC);
+    assert_eq!(recover.exit_code(), 0);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn ceremony_with_a_copy_that_cant_be_confirmed() {
+    let dir = temp_dir("unconfirmed");
+    let mut split = Session::spawn(&["split", "2of3", "--out-dir", "shares", "--confirm"], &dir);
+    split.expect("Share 1 of group 1: ");
+    let share = share_file(&dir, 1, 1, 3);
+    for _ in 0..3 {
+        split.send_line(&mistyped(&share, 20));
+        split.expect("That doesn't match the share at word 21");
+    }
+    split.expect("Read-back failed: Share 1 of group 1 doesn't match its copy");
+    assert_eq!(split.exit_code(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blind_check_of_a_share() {
+    let dir = temp_dir("blind");
+    let mut split = Session::spawn(&["split", "2of3", "--out-dir", "shares"], &dir);
+    split.expect("Shares (one file per share):");
+    assert_eq!(split.exit_code(), 0);
+    let share = share_file(&dir, 1, 2, 3);
+
+    let mut check = Session::spawn(
+        &[
+            "check",
+            "--manifest",
+            "shares/manifest.json",
+            "--attempts",
+            "2",
+        ],
+        &dir,
+    );
+    check.expect("Share (not echoed, empty to stop): ");
+    check.send_hidden(&share);
+    let answer = check.expect("valid");
+    // Nothing is echoed or told about the share
+    assert!(!answer.contains(&share[..20]));
+    assert!(!answer.contains("invalid"));
+    check.expect("Share (not echoed, empty to stop): ");
+    check.send_hidden(&mistyped(&share, 9));
+    check.expect("invalid");
+    assert_eq!(check.exit_code(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn recovery_ceremony_in_the_tui() {
+    let dir = temp_dir("tui");
+    let mut split = Session::spawn(&["split", "2of3", MNEMONIC, "--out-dir", "shares"], &dir);
+    split.expect("Shares (one file per share):");
+    assert_eq!(split.exit_code(), 0);
+
+    let mut tui = Session::spawn(&["tui"], &dir);
+    tui.expect("Recover a mnemonic");
+    // Down, then Enter
+    tui.send_keys("\x1b[B\r");
+    tui.expect("Type a share, it is not shown:");
+    for member in [3, 1] {
+        tui.send_line(&share_file(&dir, 1, member, 3));
+    }
+    tui.expect("Recovered mnemonic:");
+    tui.expect("12. about");
+    tui.send_keys("\r");
+    assert_eq!(tui.exit_code(), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn encrypted_split_needs_the_passphrase() {
+    let dir = temp_dir("encrypted");
+    let mut split = Session::spawn(
+        &[
+            "split",
+            "2of3",
+            MNEMONIC,
+            "--out-dir",
+            "shares",
+            "--encrypt",
+        ],
+        &dir,
+    );
+    split.expect("Encryption passphrase (not echoed): ");
+    split.send_hidden("correct horse battery");
+    split.expect("Repeat the passphrase: ");
+    split.send_hidden("correct horse battery");
+    split.expect("Passphrase check: ");
+    let check = split.expect(" (").trim_end_matches(" (").to_string();
+    assert_eq!(split.exit_code(), 0);
+    let shares = [1, 3].map(|member| share_file(&dir, 1, member, 3));
+
+    // The shares alone recover something else
+    let mut recover = Session::spawn(&["recover", "-"], &dir);
+    for share in &shares {
+        recover.send_line(share);
+    }
+    recover.send_eof();
+    recover.expect("Mnemonic: ");
+    let shown = recover.expect("\n");
+    assert_eq!(recover.exit_code(), 0);
+    assert!(!shown.contains(MNEMONIC));
+
+    let mut recover = Session::spawn(&["recover", "-", "--decrypt"], &dir);
+    for share in &shares {
+        recover.send_line(share);
+    }
+    recover.send_eof();
+    recover.expect("Encryption passphrase (not echoed): ");
+    recover.send_hidden("correct horse battery");
+    recover.expect(&format!("Passphrase check: {}", check));
+    recover.expect(MNEMONIC);
+    assert_eq!(recover.exit_code(), 0);
+
+    // The manifest knows the check words, and turns a wrong passphrase away
+    let mut recover = Session::spawn(
+        &[
+            "recover",
+            "shares/share-g1-m1-of-3.txt",
+            "shares/share-g1-m2-of-3.txt",
+            "--manifest",
+            "shares/manifest.json",
+        ],
+        &dir,
+    );
+    recover.expect("Encryption passphrase (not echoed): ");
+    recover.send_hidden("correct horse batterY");
+    recover.expect("Wrong passphrase");
+    assert_eq!(recover.exit_code(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dual_control_needs_both_operators() {
+    let dir = temp_dir("dual-control");
+    let mut split = Session::spawn(
+        &[
+            "split",
+            "2of3",
+            MNEMONIC,
+            "--out-dir",
+            "shares",
+            "--dual-control",
+        ],
+        &dir,
+    );
+    for (operator, passphrase) in [(1, "first keyholder"), (2, "second keyholder")] {
+        split.expect(&format!(
+            "Operator {}, choose a passphrase (not echoed): ",
+            operator
+        ));
+        split.send_hidden(passphrase);
+        split.expect(&format!(
+            "Operator {}, repeat the passphrase (not echoed): ",
+            operator
+        ));
+        split.send_hidden(passphrase);
+    }
+    split.expect("Passphrase check: ");
+    assert_eq!(split.exit_code(), 0);
+    let shares = ["shares/share-g1-m1-of-3.txt", "shares/share-g1-m3-of-3.txt"];
+
+    // The shares alone recover something else
+    let mut recover = Session::spawn(&["recover", shares[0], shares[1]], &dir);
+    recover.expect("Mnemonic: ");
+    let shown = recover.expect("\n");
+    assert_eq!(recover.exit_code(), 0);
+    assert!(!shown.contains(MNEMONIC));
+
+    let recover_with = |passphrases: [&str; 2]| {
+        let mut recover = Session::spawn(
+            &[
+                "recover",
+                shares[0],
+                shares[1],
+                "--manifest",
+                "shares/manifest.json",
+            ],
+            &dir,
+        );
+        recover.expect("under dual control");
+        for (operator, passphrase) in passphrases.iter().enumerate() {
+            recover.expect(&format!(
+                "Operator {}, enter your passphrase (not echoed): ",
+                operator + 1
+            ));
+            recover.send_hidden(passphrase);
+        }
+        recover
+    };
+    let mut recover = recover_with(["first keyholder", "second keyholder"]);
+    recover.expect(MNEMONIC);
+    assert_eq!(recover.exit_code(), 0);
+    let mut recover = recover_with(["second keyholder", "first keyholder"]);
+    recover.expect("Wrong passphrase");
+    assert_eq!(recover.exit_code(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,240 @@
+// The stable API, `sskr_tool::api::prelude`, rendered one item per line from
+// rustdoc's JSON output and compared with a snapshot, so a change to it shows
+// up in review instead of in a downstream build. Rustdoc only writes JSON on
+// nightly, so this only runs with `--features public-api`.
+#![cfg(feature = "public-api")]
+
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+
+fn rustdoc_json() -> Value {
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("public-api");
+    let status = Command::new("cargo")
+        .args(["+nightly", "rustdoc", "--lib", "--target-dir"])
+        .arg(&target)
+        .args(["--", "-Z", "unstable-options", "--output-format", "json"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .status()
+        .expect("cargo +nightly rustdoc should run");
+    assert!(
+        status.success(),
+        "rustdoc failed, is a nightly toolchain installed?"
+    );
+    let json = std::fs::read_to_string(target.join("doc/sskr_tool.json")).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+struct Api<'a> {
+    index: &'a Value,
+    paths: &'a Value,
+    lines: Vec<String>,
+}
+
+impl<'a> Api<'a> {
+    fn item(&self, id: &Value) -> &'a Value {
+        &self.index[id.to_string()]
+    }
+
+    fn add(&mut self, name: &str, item: &Value) {
+        let inner = &item["inner"];
+        if let Some(function) = inner.get("function") {
+            self.lines
+                .push(format!("pub fn {}{}", name, self.signature(function)));
+        } else if let Some(constant) = inner.get("constant") {
+            self.lines.push(format!(
+                "pub const {}: {}",
+                name,
+                self.render(&constant["type"])
+            ));
+        } else if let Some(alias) = inner.get("type_alias") {
+            self.lines.push(format!(
+                "pub type {} = {}",
+                name,
+                self.render(&alias["type"])
+            ));
+        } else if let Some(structure) = inner.get("struct") {
+            self.lines.push(format!("pub struct {}", name));
+            for field in array(&structure["kind"]["plain"]["fields"]) {
+                let field = self.item(field);
+                self.lines.push(format!(
+                    "pub {}::{}: {}",
+                    name,
+                    field["name"].as_str().unwrap(),
+                    self.render(&field["inner"]["struct_field"])
+                ));
+            }
+            self.add_impls(name, &structure["impls"]);
+        } else if let Some(enumeration) = inner.get("enum") {
+            self.lines.push(format!("pub enum {}", name));
+            for variant in array(&enumeration["variants"]) {
+                let variant = self.item(variant);
+                self.lines.push(format!(
+                    "pub {}::{}",
+                    name,
+                    variant["name"].as_str().unwrap()
+                ));
+            }
+            self.add_impls(name, &enumeration["impls"]);
+        } else {
+            panic!("Can't render {}: {}", name, item);
+        }
+    }
+
+    // Inherent methods and the traits implemented by hand or derived, leaving
+    // out auto traits and blanket implementations, which follow from the rest
+    fn add_impls(&mut self, name: &str, impls: &Value) {
+        for id in array(impls) {
+            let implementation = &self.item(id)["inner"]["impl"];
+            if implementation["is_synthetic"] == true || !implementation["blanket_impl"].is_null() {
+                continue;
+            }
+            let path = &implementation["trait"];
+            match path.as_object() {
+                // Derived along with PartialEq, and only used by the compiler
+                Some(_) if path["path"] == "StructuralPartialEq" => {}
+                // Listed under both types when both are in the prelude
+                Some(_) => self.lines.push(format!(
+                    "impl {} for {}",
+                    self.render_path(path),
+                    self.render(&implementation["for"])
+                )),
+                None => {
+                    for method in array(&implementation["items"]) {
+                        let method = self.item(method);
+                        if method["visibility"] == "public" {
+                            self.lines.push(format!(
+                                "pub fn {}::{}{}",
+                                name,
+                                method["name"].as_str().unwrap(),
+                                self.signature(&method["inner"]["function"])
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn signature(&self, function: &Value) -> String {
+        let inputs = array(&function["sig"]["inputs"])
+            .iter()
+            .map(|input| match input[0].as_str().unwrap() {
+                "self" => render_self(&input[1]),
+                name => format!("{}: {}", name, self.render(&input[1])),
+            })
+            .collect::<Vec<_>>();
+        match &function["sig"]["output"] {
+            Value::Null => format!("({})", inputs.join(", ")),
+            output => format!("({}) -> {}", inputs.join(", "), self.render(output)),
+        }
+    }
+
+    fn render(&self, ty: &Value) -> String {
+        let (kind, inner) = ty.as_object().unwrap().iter().next().unwrap();
+        match kind.as_str() {
+            "primitive" | "generic" => inner.as_str().unwrap().to_string(),
+            "resolved_path" => self.render_path(inner),
+            "borrowed_ref" => format!(
+                "&{}{}",
+                if inner["is_mutable"] == true {
+                    "mut "
+                } else {
+                    ""
+                },
+                self.render(&inner["type"])
+            ),
+            "slice" => format!("[{}]", self.render(inner)),
+            "array" => format!(
+                "[{}; {}]",
+                self.render(&inner["type"]),
+                inner["len"].as_str().unwrap()
+            ),
+            "tuple" => format!(
+                "({})",
+                array(inner)
+                    .iter()
+                    .map(|ty| self.render(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => panic!("Can't render type {}", ty),
+        }
+    }
+
+    fn render_path(&self, path: &Value) -> String {
+        let arguments = match path["args"].get("angle_bracketed") {
+            Some(arguments) if !array(&arguments["args"]).is_empty() => format!(
+                "<{}>",
+                array(&arguments["args"])
+                    .iter()
+                    .map(|argument| match argument.get("type") {
+                        Some(ty) => self.render(ty),
+                        None => argument["lifetime"].as_str().unwrap().to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => String::new(),
+        };
+        format!("{}{}", self.path(path), arguments)
+    }
+
+    // Paths into the standard library and this crate by name alone, and into
+    // other crates in full, however the source happens to spell them
+    fn path(&self, path: &Value) -> String {
+        match self.paths.get(path["id"].to_string()) {
+            Some(summary) => {
+                let segments = array(&summary["path"])
+                    .iter()
+                    .map(|segment| segment.as_str().unwrap())
+                    .collect::<Vec<_>>();
+                match segments[0] {
+                    "sskr_tool" | "std" | "core" | "alloc" => segments.last().unwrap().to_string(),
+                    _ => segments.join("::"),
+                }
+            }
+            None => path["path"].as_str().unwrap().to_string(),
+        }
+    }
+}
+
+fn array(value: &Value) -> &Vec<Value> {
+    value.as_array().unwrap()
+}
+
+fn render_self(receiver: &Value) -> String {
+    match receiver.get("borrowed_ref") {
+        Some(reference) if reference["is_mutable"] == true => "&mut self".to_string(),
+        Some(_) => "&self".to_string(),
+        None => "self".to_string(),
+    }
+}
+
+#[test]
+fn prelude_matches_snapshot() {
+    let json = rustdoc_json();
+    let index = &json["index"];
+    let prelude = index
+        .as_object()
+        .unwrap()
+        .values()
+        .find(|item| item["name"] == "prelude")
+        .expect("the crate should have a prelude");
+
+    let mut api = Api {
+        index,
+        paths: &json["paths"],
+        lines: vec![],
+    };
+    for id in array(&prelude["inner"]["module"]["items"]) {
+        let export = &api.item(id)["inner"]["use"];
+        let name = export["name"].as_str().unwrap();
+        let item = api.item(&export["id"]);
+        api.add(name, item);
+    }
+    api.lines.sort();
+    api.lines.dedup();
+    insta::assert_snapshot!("prelude", api.lines.join("\n"));
+}